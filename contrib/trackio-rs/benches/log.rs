@@ -0,0 +1,46 @@
+use serde_json::json;
+use std::hint::black_box;
+use std::time::Instant;
+use trackio::Client;
+
+const ROWS: i64 = 200_000;
+
+fn client() -> Client {
+    Client::builder()
+        .project("bench")
+        .run("log")
+        .max_batch(10_000)
+        .build()
+        .with_dry_run(true)
+}
+
+fn per_row(name: &str, log: impl Fn(&Client, i64)) {
+    let client = client();
+    for step in 0..ROWS / 10 {
+        log(&client, step);
+    }
+    let started = Instant::now();
+    for step in 0..ROWS {
+        log(&client, black_box(step));
+    }
+    let elapsed = started.elapsed();
+    println!(
+        "{name:<12} {:>8.0} ns/row",
+        elapsed.as_nanos() as f64 / ROWS as f64
+    );
+}
+
+fn main() {
+    per_row("json!", |client, step| {
+        let loss = 1.0 / (step + 1) as f64;
+        client.log(
+            json!({ "loss": loss, "lr": 3e-4, "epoch": step / 1000 }),
+            Some(step),
+            None,
+        );
+    });
+    per_row("log!", |client, step| {
+        let loss = 1.0 / (step + 1) as f64;
+        trackio::log!(client, step = step, "loss" => loss, "lr" => 3e-4, "epoch" => step / 1000);
+    });
+}
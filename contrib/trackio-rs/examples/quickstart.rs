@@ -1,6 +1,6 @@
 // quickstart.rs
 use serde_json::{json, Value};
-use std::{env, thread, time::{Duration, Instant}};
+use std::env;
 
 fn env_or(name: &str, default: &str) -> String {
     env::var(name).unwrap_or_else(|_| default.to_string())
@@ -18,8 +18,8 @@ fn main() {
     let base = env_or("TRACKIO_SERVER_URL", "http://127.0.0.1:7860");
     let base = base.trim_end_matches('/').to_string();
 
-    let hf_token = env::var("HF_TOKEN")
-        .expect("HF_TOKEN is required (write token for your HF Space)");
+    let hf_token =
+        env::var("HF_TOKEN").expect("HF_TOKEN is required (write token for your HF Space)");
 
     // Trackio bulk_log schema: project, run, metrics, step, config
     let logs = json!([
@@ -63,4 +63,4 @@ fn main() {
         "Open dashboard:\n  {}/?selected_project=rs-quickstart&selected_run=rs-run-1",
         base
     );
-}
\ No newline at end of file
+}
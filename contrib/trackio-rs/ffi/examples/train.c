@@ -0,0 +1,20 @@
+#include <stdio.h>
+#include "trackio.h"
+
+int main(void) {
+    TrackioClient *run = trackio_init("c-demo", "run-1", "{\"lr\": 0.001}");
+    if (!run) {
+        fprintf(stderr, "trackio: %s\n", trackio_last_error());
+        return 1;
+    }
+    char metrics[64];
+    for (int step = 0; step < 100; step++) {
+        snprintf(metrics, sizeof metrics, "{\"loss\": %f}", 1.0 / (step + 1));
+        trackio_log_json(run, metrics, step);
+    }
+    if (trackio_finish(run) != 0) {
+        fprintf(stderr, "trackio: %s\n", trackio_last_error());
+        return 1;
+    }
+    return 0;
+}
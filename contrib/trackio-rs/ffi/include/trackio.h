@@ -0,0 +1,39 @@
+#ifndef TRACKIO_H
+#define TRACKIO_H
+
+#include <stdint.h>
+
+// A client logging to one run, created by `trackio_init`.
+typedef struct TrackioClient TrackioClient;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Start logging to `run` in `project`. The server URL, write token and batching come
+// from the `TRACKIO_*` environment variables and the config file, as for the Rust
+// client. `config_json` is a JSON object of hyperparameters, or NULL.
+//
+// Returns NULL on failure.
+TrackioClient *trackio_init(const char *project, const char *run, const char *config_json);
+
+// Buffer a row of metrics given as a JSON object, at `step`, or at the next step when
+// `step` is negative. Rows are sent in batches; a full batch is sent right away.
+int trackio_log_json(TrackioClient *client, const char *metrics_json, int64_t step);
+
+// Send every buffered row now.
+int trackio_flush(TrackioClient *client);
+
+// Flush, close the run and free the client, which must not be used afterwards. The
+// client is freed even when the final flush fails.
+int trackio_finish(TrackioClient *client);
+
+// The message of the last failure on this thread, or NULL. Valid until the next call
+// into this library on the same thread.
+const char *trackio_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TRACKIO_H */
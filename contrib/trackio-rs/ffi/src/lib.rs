@@ -0,0 +1,146 @@
+//! C API for the trackio client, for C and C++ training code.
+//!
+//! [`trackio_init`] returns a client pointer, or NULL on failure; the other functions
+//! return `0` on success and `-1` on failure. Either way the message is available from
+//! [`trackio_last_error`] on the same thread. The header is `include/trackio.h`,
+//! regenerated with `cbindgen --config cbindgen.toml --output include/trackio.h`.
+
+use serde_json::Value;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use trackio::Client;
+
+/// A client logging to one run, created by [`trackio_init`].
+pub struct TrackioClient {
+    client: Client,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(message).ok());
+}
+
+/// Runs `f`, turning errors and panics into `-1` and the thread's last error.
+fn status(f: impl FnOnce() -> Result<(), String>) -> c_int {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => 0,
+        Ok(Err(message)) => {
+            set_error(message);
+            -1
+        }
+        Err(_) => {
+            set_error("trackio panicked");
+            -1
+        }
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str, String> {
+    if ptr.is_null() {
+        return Err(format!("{name} is NULL"));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| format!("{name} is not valid UTF-8"))
+}
+
+unsafe fn json_arg(ptr: *const c_char, name: &str) -> Result<Value, String> {
+    serde_json::from_str(str_arg(ptr, name)?).map_err(|e| format!("{name}: {e}"))
+}
+
+/// Start logging to `run` in `project`. The server URL, write token and batching come
+/// from the `TRACKIO_*` environment variables and the config file, as for the Rust
+/// client. `config_json` is a JSON object of hyperparameters, or NULL.
+///
+/// Returns NULL on failure.
+///
+/// # Safety
+///
+/// `project` and `run` must be NUL-terminated strings, and `config_json` one or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn trackio_init(
+    project: *const c_char,
+    run: *const c_char,
+    config_json: *const c_char,
+) -> *mut TrackioClient {
+    let mut out = ptr::null_mut();
+    status(|| {
+        let client = Client::new()
+            .with_project(str_arg(project, "project")?)
+            .with_run(str_arg(run, "run")?);
+        if !config_json.is_null() {
+            client.set_config(json_arg(config_json, "config_json")?);
+        }
+        out = Box::into_raw(Box::new(TrackioClient { client }));
+        Ok(())
+    });
+    out
+}
+
+/// Buffer a row of metrics given as a JSON object, at `step`, or at the next step when
+/// `step` is negative. Rows are sent in batches; a full batch is sent right away.
+///
+/// # Safety
+///
+/// `client` must come from [`trackio_init`] and not be finished; `metrics_json` must be a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn trackio_log_json(
+    client: *mut TrackioClient,
+    metrics_json: *const c_char,
+    step: i64,
+) -> c_int {
+    status(|| {
+        let client = client.as_ref().ok_or("client is NULL")?;
+        let metrics = json_arg(metrics_json, "metrics_json")?;
+        if !metrics.is_object() {
+            return Err("metrics_json must be a JSON object".into());
+        }
+        client
+            .client
+            .log(metrics, (step >= 0).then_some(step), None);
+        Ok(())
+    })
+}
+
+/// Send every buffered row now.
+///
+/// # Safety
+///
+/// `client` must come from [`trackio_init`] and not be finished.
+#[no_mangle]
+pub unsafe extern "C" fn trackio_flush(client: *mut TrackioClient) -> c_int {
+    status(|| {
+        let client = client.as_ref().ok_or("client is NULL")?;
+        client.client.flush().map(|_| ()).map_err(|e| e.to_string())
+    })
+}
+
+/// Flush, close the run and free the client, which must not be used afterwards. The
+/// client is freed even when the final flush fails.
+///
+/// # Safety
+///
+/// `client` must come from [`trackio_init`] and not be finished already.
+#[no_mangle]
+pub unsafe extern "C" fn trackio_finish(client: *mut TrackioClient) -> c_int {
+    if client.is_null() {
+        set_error("client is NULL");
+        return -1;
+    }
+    let client = Box::from_raw(client);
+    status(|| client.client.close().map_err(|e| e.to_string()))
+}
+
+/// The message of the last failure on this thread, or NULL. Valid until the next call
+/// into this library on the same thread.
+#[no_mangle]
+pub extern "C" fn trackio_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |m| m.as_ptr()))
+}
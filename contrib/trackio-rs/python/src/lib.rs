@@ -0,0 +1,416 @@
+//! Python bindings for the trackio Rust client, built with maturin as the `trackio_rs`
+//! module.
+//!
+//! The module mirrors the module-level API of the Python package, so code logging at
+//! extreme rates (per-token metrics, per-sample losses) can swap in the Rust batcher by
+//! changing an import:
+//!
+//! ```python
+//! import trackio_rs as trackio
+//!
+//! trackio.init(project="llm", name="run-1", config={"lr": 3e-4})
+//! for step, loss in enumerate(losses):
+//!     trackio.log({"loss": loss}, step=step)
+//! trackio.finish()
+//! ```
+//!
+//! `log` converts the dict and queues it without holding the GIL for any I/O; batches
+//! are sent from the client's background thread. With `agent=True` rows go to the
+//! node's `trackio agent` instead, which keeps them in its write-ahead log until sent.
+
+use parking_lot::Mutex;
+use pyo3::exceptions::{PyRuntimeError, PyRuntimeWarning, PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+use serde_json::{Map, Number, Value};
+use std::ffi::CString;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use trackio::client::TrackioError;
+use trackio::Client;
+
+static CURRENT: Mutex<Option<Current>> = Mutex::new(None);
+
+/// The run the module-level functions act on.
+#[derive(Clone)]
+struct Current {
+    project: String,
+    name: String,
+    sink: Arc<Sink>,
+}
+
+enum Sink {
+    Client(Box<Client>),
+    #[cfg(unix)]
+    Agent(trackio::agent::IpcClient),
+}
+
+impl Sink {
+    fn log(&self, metrics: Value, step: Option<i64>) {
+        match self {
+            Sink::Client(client) => client.log(metrics, step, None),
+            #[cfg(unix)]
+            Sink::Agent(client) => client.log(metrics, step, None),
+        }
+    }
+
+    fn set_config(&self, config: Value) {
+        match self {
+            Sink::Client(client) => client.set_config(config),
+            #[cfg(unix)]
+            Sink::Agent(client) => client.set_config(config),
+        }
+    }
+
+    fn flush(&self) -> Result<(), TrackioError> {
+        match self {
+            Sink::Client(client) => client.flush().map(|_| ()),
+            #[cfg(unix)]
+            Sink::Agent(client) => client.flush(),
+        }
+    }
+
+    fn close(&self) -> Result<(), TrackioError> {
+        match self {
+            Sink::Client(client) => client.close(),
+            #[cfg(unix)]
+            Sink::Agent(client) => client.close(),
+        }
+    }
+}
+
+/// A run started by [`init`]; the module-level `log` and `finish` act on the latest one.
+#[pyclass(module = "trackio_rs", frozen)]
+struct Run {
+    #[pyo3(get)]
+    project: String,
+    #[pyo3(get)]
+    name: String,
+    sink: Arc<Sink>,
+}
+
+#[pymethods]
+impl Run {
+    #[pyo3(signature = (metrics, step=None))]
+    fn log(&self, metrics: &Bound<'_, PyDict>, step: Option<i64>) -> PyResult<()> {
+        self.sink.log(to_json(metrics.as_any())?, step);
+        Ok(())
+    }
+
+    /// Send everything queued and wait for the server to accept it.
+    fn flush(&self, py: Python<'_>) -> PyResult<()> {
+        py.allow_threads(|| self.sink.flush()).map_err(to_py_err)
+    }
+
+    /// Send everything queued and close the run.
+    fn finish(&self, py: Python<'_>) -> PyResult<()> {
+        let mut current = CURRENT.lock();
+        if current
+            .as_ref()
+            .is_some_and(|c| Arc::ptr_eq(&c.sink, &self.sink))
+        {
+            *current = None;
+        }
+        drop(current);
+        py.allow_threads(|| self.sink.close()).map_err(to_py_err)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Run(project={:?}, name={:?})", self.project, self.name)
+    }
+}
+
+/// Start a run. The server, write token and batching come from `TRACKIO_*` variables
+/// and the config file as for the Rust client; `space_id` or `server_url` override the
+/// server. With `agent=True`, rows go to the node's `trackio agent` socket.
+///
+/// As in wandb, calling `init` again for the current run's project, with its name or
+/// none, returns the current run and ignores the other arguments, so two handles never
+/// log interleaved steps to one run. With `reinit=True` the current run is finished and
+/// a new one started. Any other `init` finishes the current run once the new one is
+/// current; a failure to close it is reported as a `RuntimeWarning`.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+#[pyo3(signature = (project, name=None, group=None, space_id=None, server_url=None, config=None, agent=false, reinit=false))]
+fn init(
+    py: Python<'_>,
+    project: &str,
+    name: Option<String>,
+    group: Option<&str>,
+    space_id: Option<&str>,
+    server_url: Option<&str>,
+    config: Option<&Bound<'_, PyDict>>,
+    agent: bool,
+    reinit: bool,
+) -> PyResult<Run> {
+    if !reinit {
+        if let Some(run) = resume(&CURRENT.lock(), project, name.as_deref()) {
+            return Ok(run);
+        }
+    }
+    let requested = name.clone();
+    let name = name.unwrap_or_else(default_run_name);
+    let sink = if agent {
+        connect_agent(project, &name)?
+    } else {
+        let mut client = Client::new().with_project(project).with_run(&name);
+        if let Some(url) = server_url.map(String::from).or(space_id.map(space_url)) {
+            client = client.with_base_url(&url);
+        }
+        if let Some(group) = group {
+            client = client.with_group(group);
+        }
+        Sink::Client(Box::new(client))
+    };
+    if let Some(config) = config {
+        sink.set_config(to_json(config.as_any())?);
+    }
+    let sink = Arc::new(sink);
+    let previous = {
+        let mut current = CURRENT.lock();
+        if !reinit {
+            if let Some(run) = resume(&current, project, requested.as_deref()) {
+                return Ok(run);
+            }
+        }
+        current.replace(Current {
+            project: project.into(),
+            name: name.clone(),
+            sink: sink.clone(),
+        })
+    };
+    if let Some(previous) = previous {
+        if let Err(e) = py.allow_threads(|| previous.sink.close()) {
+            let message = format!("trackio_rs: closing run {:?} failed: {e}", previous.name);
+            let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+            PyErr::warn(py, &py.get_type::<PyRuntimeWarning>(), &message, 1)?;
+        }
+    }
+    Ok(Run {
+        project: project.into(),
+        name,
+        sink,
+    })
+}
+
+/// The current run, if it is `name` of `project` or any run of `project` when `name` is
+/// `None`.
+fn resume(current: &Option<Current>, project: &str, name: Option<&str>) -> Option<Run> {
+    let current = current.as_ref()?;
+    if current.project != project || name.is_some_and(|n| n != current.name) {
+        return None;
+    }
+    Some(Run {
+        project: current.project.clone(),
+        name: current.name.clone(),
+        sink: current.sink.clone(),
+    })
+}
+
+/// Queue a dict of metrics on the current run, at `step` or the run's next step.
+#[pyfunction]
+#[pyo3(signature = (metrics, step=None))]
+fn log(metrics: &Bound<'_, PyDict>, step: Option<i64>) -> PyResult<()> {
+    let metrics = to_json(metrics.as_any())?;
+    current()?.log(metrics, step);
+    Ok(())
+}
+
+/// Send everything queued on the current run.
+#[pyfunction]
+fn flush(py: Python<'_>) -> PyResult<()> {
+    let sink = current()?;
+    py.allow_threads(|| sink.flush()).map_err(to_py_err)
+}
+
+/// Send everything queued and close the current run.
+#[pyfunction]
+fn finish(py: Python<'_>) -> PyResult<()> {
+    let current = CURRENT
+        .lock()
+        .take()
+        .ok_or_else(|| PyRuntimeError::new_err("call trackio_rs.init() before finish()"))?;
+    py.allow_threads(|| current.sink.close()).map_err(to_py_err)
+}
+
+#[pymodule]
+fn trackio_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Run>()?;
+    m.add_function(wrap_pyfunction!(init, m)?)?;
+    m.add_function(wrap_pyfunction!(log, m)?)?;
+    m.add_function(wrap_pyfunction!(flush, m)?)?;
+    m.add_function(wrap_pyfunction!(finish, m)?)?;
+    Ok(())
+}
+
+fn current() -> PyResult<Arc<Sink>> {
+    CURRENT
+        .lock()
+        .as_ref()
+        .map(|current| current.sink.clone())
+        .ok_or_else(|| PyRuntimeError::new_err("call trackio_rs.init() before log()"))
+}
+
+#[cfg(unix)]
+fn connect_agent(project: &str, name: &str) -> PyResult<Sink> {
+    let client = trackio::agent::IpcClient::connect_default().map_err(to_py_err)?;
+    Ok(Sink::Agent(client.with_project(project).with_run(name)))
+}
+
+#[cfg(not(unix))]
+fn connect_agent(_project: &str, _name: &str) -> PyResult<Sink> {
+    Err(PyValueError::new_err("agent=True needs a Unix socket"))
+}
+
+fn default_run_name() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    format!("run-{secs}")
+}
+
+/// `user/my_space` -> `https://user-my-space.hf.space`, as the Python client does.
+fn space_url(space_id: &str) -> String {
+    let subdomain = space_id
+        .replacen('/', "-", 1)
+        .to_lowercase()
+        .replace(['_', '.'], "-");
+    format!("https://{subdomain}.hf.space")
+}
+
+/// Converts a metrics value. Anything else with `__float__`, such as numpy scalars and
+/// 0-d tensors, is logged as a float.
+fn to_json(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if obj.is_none() {
+        return Ok(Value::Null);
+    }
+    if let Ok(b) = obj.downcast::<PyBool>() {
+        return Ok(Value::Bool(b.is_true()));
+    }
+    if let Ok(i) = obj.downcast::<PyInt>() {
+        return Ok(match i.extract::<i64>() {
+            Ok(i) => Value::from(i),
+            Err(_) => float(i.extract::<f64>()?),
+        });
+    }
+    if let Ok(f) = obj.downcast::<PyFloat>() {
+        return Ok(float(f.value()));
+    }
+    if let Ok(s) = obj.downcast::<PyString>() {
+        return Ok(Value::String(s.to_cow()?.into_owned()));
+    }
+    if let Ok(dict) = obj.downcast::<PyDict>() {
+        let mut map = Map::with_capacity(dict.len());
+        for (key, value) in dict.iter() {
+            let key = match key.downcast::<PyString>() {
+                Ok(key) => key.to_cow()?.into_owned(),
+                Err(_) => key.str()?.to_cow()?.into_owned(),
+            };
+            map.insert(key, to_json(&value)?);
+        }
+        return Ok(Value::Object(map));
+    }
+    if let Ok(list) = obj.downcast::<PyList>() {
+        return list.iter().map(|v| to_json(&v)).collect();
+    }
+    if let Ok(tuple) = obj.downcast::<PyTuple>() {
+        return tuple.iter().map(|v| to_json(&v)).collect();
+    }
+    match obj.extract::<f64>() {
+        Ok(f) => Ok(float(f)),
+        Err(_) => Err(PyTypeError::new_err(format!(
+            "cannot log a value of type {}",
+            obj.get_type().name()?
+        ))),
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
+
+fn to_py_err(e: TrackioError) -> PyErr {
+    match e {
+        TrackioError::InvalidArgument(_) => PyValueError::new_err(e.to_string()),
+        _ => PyRuntimeError::new_err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::ffi::c_str;
+    use serde_json::json;
+    use trackio::testing::MockServer;
+
+    fn start(
+        py: Python<'_>,
+        project: &str,
+        name: Option<&str>,
+        url: &str,
+        reinit: bool,
+    ) -> PyResult<Run> {
+        init(
+            py,
+            project,
+            name.map(String::from),
+            None,
+            None,
+            Some(url),
+            None,
+            false,
+            reinit,
+        )
+    }
+
+    #[test]
+    fn init_resumes_reinits_and_closes_the_previous_run() {
+        pyo3::prepare_freethreaded_python();
+        let server = MockServer::start();
+        let down = MockServer::start();
+        down.fail_next(100, 401);
+        Python::with_gil(|py| {
+            let a = start(py, "p", Some("a"), &server.url(), false).unwrap();
+            assert!(Arc::ptr_eq(
+                &start(py, "p", None, "unused", false).unwrap().sink,
+                &a.sink
+            ));
+            assert!(Arc::ptr_eq(
+                &start(py, "p", Some("a"), "unused", false).unwrap().sink,
+                &a.sink
+            ));
+            a.sink.log(json!({ "loss": 0.5 }), Some(0));
+
+            let b = start(py, "p", Some("b"), &server.url(), false).unwrap();
+            assert!(!Arc::ptr_eq(&b.sink, &a.sink));
+            server.assert_logged_value("loss", 0, 0.5);
+            assert_eq!(server.rows()[0].run, "a");
+
+            let other = start(py, "q", None, &server.url(), false).unwrap();
+            assert_eq!(other.project, "q");
+            assert!(Arc::ptr_eq(
+                &start(py, "q", None, "unused", false).unwrap().sink,
+                &other.sink
+            ));
+
+            let again = start(py, "q", Some(&other.name), &server.url(), true).unwrap();
+            assert!(!Arc::ptr_eq(&again.sink, &other.sink));
+            assert_eq!(again.name, other.name);
+
+            let failing = start(py, "r", Some("x"), &down.url(), false).unwrap();
+            failing.sink.log(json!({ "loss": 1.0 }), Some(0));
+            py.run(
+                c_str!("import warnings; warnings.simplefilter('error')"),
+                None,
+                None,
+            )
+            .unwrap();
+            let err = start(py, "r", Some("y"), &server.url(), false)
+                .err()
+                .unwrap();
+            assert!(err.is_instance_of::<PyRuntimeWarning>(py));
+            assert!(err.to_string().contains("closing run \"x\" failed"));
+            assert_eq!(CURRENT.lock().as_ref().unwrap().name, "y");
+        });
+    }
+}
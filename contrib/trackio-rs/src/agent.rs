@@ -0,0 +1,602 @@
+use crate::bandwidth::Bandwidth;
+use crate::client::{Client, ErrorKind, LogItem, TrackioError};
+use crate::pacing::{FlushOutcome, FlushPacer};
+use crate::time::now_iso8601;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Socket the agent listens on: `$TRACKIO_AGENT_SOCKET`, or `trackio-agent.sock` in the
+/// temporary directory.
+pub fn default_socket_path() -> PathBuf {
+    env::var_os("TRACKIO_AGENT_SOCKET")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| env::temp_dir().join("trackio-agent.sock"))
+}
+
+/// One line of the agent protocol, as newline-delimited JSON.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Message {
+    Log {
+        project: String,
+        run: String,
+        metrics: Value,
+        #[serde(default)]
+        step: Option<i64>,
+        #[serde(default)]
+        timestamp: Option<String>,
+    },
+    Config {
+        project: String,
+        run: String,
+        config: Value,
+    },
+    Flush,
+}
+
+type RunKey = (String, String);
+
+type DrainCallback = Arc<dyn Fn(&DrainProgress) + Send + Sync>;
+
+/// How far the [`Agent`] got in sending the backlog it built up while the server was
+/// unreachable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrainProgress {
+    /// Messages of the backlog handled so far: sent, or dropped because the server
+    /// rejected them.
+    pub sent: usize,
+    /// Messages in the backlog when the server came back.
+    pub total: usize,
+    pub elapsed: Duration,
+}
+
+/// How the backlog is sent once the server is reachable again.
+#[derive(Clone)]
+struct Drain {
+    rows_per_sec: u64,
+    concurrency: usize,
+    progress: Option<DrainCallback>,
+}
+
+impl fmt::Debug for Drain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Drain")
+            .field("rows_per_sec", &self.rows_per_sec)
+            .field("concurrency", &self.concurrency)
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// A long-lived uploader shared by the training processes of a node.
+///
+/// Workers connect with an [`IpcClient`] over a unix socket and hand their rows to the
+/// agent, which owns the connection to the server. Received rows are appended to a
+/// write-ahead log before they are acknowledged, sent every flush interval, and kept
+/// (and retried with backoff) until the server accepts them, so short-lived workers
+/// can exit right after logging and a restarted agent resumes from its log.
+///
+/// When a flush fails because the server can't be reached, the agent stops sending and
+/// only checks, with backoff, whether the server answers again; rows pile up in the
+/// log meanwhile. Once it does, the backlog is drained oldest run first, a few runs at
+/// a time and at a capped row rate, so the server isn't flattened by every node
+/// reconnecting at once; see [`with_drain_rate`](Agent::with_drain_rate) and
+/// [`on_drain_progress`](Agent::on_drain_progress).
+///
+/// ```no_run
+/// use trackio::agent::Agent;
+///
+/// Agent::new(trackio::Client::new()).run()?;
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug)]
+pub struct Agent {
+    client: Client,
+    socket: PathBuf,
+    wal: Option<PathBuf>,
+    flush_interval: Duration,
+    drain: Drain,
+}
+
+#[derive(Debug)]
+struct Shared {
+    client: Client,
+    wal: Option<PathBuf>,
+    drain: Drain,
+    online: AtomicBool,
+    state: Mutex<State>,
+    flushing: Mutex<()>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    pending: BTreeMap<RunKey, Vec<Message>>,
+    wal: Option<File>,
+}
+
+impl Agent {
+    /// Upload through `client`'s server, credentials and backend. Its project and run
+    /// are ignored: every row names its own.
+    pub fn new(client: Client) -> Self {
+        let socket = default_socket_path();
+        let wal = match client.spool_dir() {
+            Some(dir) => dir.join("agent.wal"),
+            None => {
+                let mut wal = socket.clone().into_os_string();
+                wal.push(".wal");
+                wal.into()
+            }
+        };
+        Self {
+            client,
+            socket,
+            wal: Some(wal),
+            flush_interval: Duration::from_secs(1),
+            drain: Drain {
+                rows_per_sec: 5000,
+                concurrency: 4,
+                progress: None,
+            },
+        }
+    }
+
+    /// Listen on `path` instead of [`default_socket_path`].
+    pub fn with_socket<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.socket = path.into();
+        self
+    }
+
+    /// Where unsent rows are logged (default: `agent.wal` in the client's spool
+    /// directory, else the socket path with a `.wal` suffix);
+    /// `None` keeps them in memory only.
+    pub fn with_wal<P: Into<PathBuf>>(mut self, path: impl Into<Option<P>>) -> Self {
+        self.wal = path.into().map(Into::into);
+        self
+    }
+
+    /// How often pending rows are sent under steady load (default: 1s). As with
+    /// [`Client::spawn_flusher`], the agent sends sooner while rows pile up and less
+    /// often while idle or while the server is slow.
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// Send at most `rows` rows per second while draining the backlog after an outage
+    /// (default: 5000).
+    pub fn with_drain_rate(mut self, rows: u64) -> Self {
+        self.drain.rows_per_sec = rows;
+        self
+    }
+
+    /// Send the backlogs of at most `runs` runs at the same time while draining
+    /// (default: 4).
+    pub fn with_drain_concurrency(mut self, runs: usize) -> Self {
+        self.drain.concurrency = runs.max(1);
+        self
+    }
+
+    /// Call `callback` after each batch sent while draining the backlog after an
+    /// outage, e.g. to report how much is left.
+    pub fn on_drain_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&DrainProgress) + Send + Sync + 'static,
+    {
+        self.drain.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Replay the write-ahead log, then serve workers until the socket fails.
+    ///
+    /// A stale socket file left by a crashed agent is replaced; if another agent is
+    /// still answering on it, this fails instead. So does a write token the server
+    /// refuses; an unreachable server is fine, rows wait in the log until it is up.
+    pub fn run(self) -> Result<(), TrackioError> {
+        if let Err(e) = self.client.validate() {
+            if e.kind() == ErrorKind::Auth {
+                return Err(e);
+            }
+        }
+        if self.socket.exists() {
+            if UnixStream::connect(&self.socket).is_ok() {
+                return Err(TrackioError::InvalidArgument(format!(
+                    "an agent is already listening on {}",
+                    self.socket.display()
+                )));
+            }
+            fs::remove_file(&self.socket)?;
+        }
+        let listener = UnixListener::bind(&self.socket)?;
+
+        let shared = Arc::new(Shared {
+            client: self.client,
+            wal: self.wal,
+            drain: self.drain,
+            online: AtomicBool::new(true),
+            state: Mutex::new(State::default()),
+            flushing: Mutex::new(()),
+        });
+        shared.replay()?;
+
+        let flusher = shared.clone();
+        let mut pacer = FlushPacer::new(self.flush_interval, flusher.client.max_batch());
+        let mut delay = self.flush_interval;
+        thread::spawn(move || loop {
+            thread::sleep(delay);
+            let started = Instant::now();
+            let flushed = if flusher.online.load(Ordering::Relaxed) {
+                flusher.flush()
+            } else {
+                flusher.reconnect()
+            };
+            if let Err(e) = &flushed {
+                if e.is_retryable() && flusher.online.swap(false, Ordering::Relaxed) {
+                    log::warn!("trackio agent: server unreachable, keeping rows in the log: {e}");
+                }
+            }
+            delay = pacer.next(match flushed {
+                Ok(rows) => FlushOutcome::Sent {
+                    rows,
+                    took: started.elapsed(),
+                },
+                Err(e) => FlushOutcome::Failed {
+                    retry_after: e.retry_after(),
+                },
+            });
+        });
+
+        for stream in listener.incoming() {
+            let stream = stream?;
+            let shared = shared.clone();
+            thread::spawn(move || shared.serve(stream));
+        }
+        Ok(())
+    }
+}
+
+impl Shared {
+    fn replay(&self) -> Result<(), TrackioError> {
+        let Some(path) = &self.wal else {
+            return Ok(());
+        };
+        let mut state = self.state.lock();
+        match File::open(path) {
+            Ok(file) => {
+                for line in BufReader::new(file).lines() {
+                    if let Ok(msg) = serde_json::from_str::<Message>(&line?) {
+                        if let Some(key) = run_key(&msg) {
+                            state.pending.entry(key).or_default().push(msg);
+                        }
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        self.rewrite_wal(&mut state)
+    }
+
+    fn serve(&self, stream: UnixStream) -> Result<(), TrackioError> {
+        let mut reply = stream.try_clone()?;
+        for line in BufReader::new(stream).lines() {
+            let Ok(mut msg) = serde_json::from_str::<Message>(&line?) else {
+                continue;
+            };
+            if let Message::Log { timestamp, .. } = &mut msg {
+                timestamp.get_or_insert_with(now_iso8601);
+            }
+            let Some(key) = run_key(&msg) else {
+                let answer = match self.flush() {
+                    Ok(_) => json!({ "ok": true }),
+                    Err(e) => json!({ "ok": false, "error": e.to_string() }),
+                };
+                writeln!(reply, "{answer}")?;
+                continue;
+            };
+            let mut state = self.state.lock();
+            if let Some(wal) = &mut state.wal {
+                writeln!(wal, "{}", serde_json::to_string(&msg).unwrap_or_default())?;
+            }
+            state.pending.entry(key).or_default().push(msg);
+        }
+        Ok(())
+    }
+
+    /// Send everything pending, returning how many messages that was. Rows the server
+    /// did not accept stay queued, ahead of rows received meanwhile, and the write-ahead
+    /// log is rewritten to match.
+    fn flush(&self) -> Result<usize, TrackioError> {
+        let _flushing = self.flushing.lock();
+        let pending = std::mem::take(&mut self.state.lock().pending);
+        if pending.is_empty() {
+            return Ok(0);
+        }
+        let messages = pending.values().map(Vec::len).sum();
+
+        let mut failed = BTreeMap::new();
+        let mut first_error = None;
+        for ((project, run), messages) in pending {
+            let client = self.client.for_run(&project, &run);
+            if let Err((e, unsent)) = send(&client, messages, None, &|_| {}) {
+                first_error.get_or_insert(e);
+                failed.insert((project, run), unsent);
+            }
+        }
+        self.requeue(failed)?;
+        first_error.map_or(Ok(messages), Err)
+    }
+
+    /// Check whether the server answers again and, if so, drain the backlog.
+    fn reconnect(&self) -> Result<usize, TrackioError> {
+        self.client.ping()?;
+        self.online.store(true, Ordering::Relaxed);
+        log::info!("trackio agent: server reachable again, sending the backlog");
+        self.drain()
+    }
+
+    /// Send everything pending as [`flush`](Shared::flush) does, but oldest run first,
+    /// `concurrency` runs at a time and at the capped row rate, reporting progress
+    /// after each batch. Once a run fails, the runs not started yet stay queued.
+    fn drain(&self) -> Result<usize, TrackioError> {
+        let _flushing = self.flushing.lock();
+        let pending = std::mem::take(&mut self.state.lock().pending);
+        let total = pending.values().map(Vec::len).sum();
+        if total == 0 {
+            return Ok(0);
+        }
+        let mut runs: Vec<_> = pending.into_iter().collect();
+        runs.sort_by(|(_, a), (_, b)| first_timestamp(a).cmp(&first_timestamp(b)));
+        let workers = self.drain.concurrency.min(runs.len());
+        let queue = Mutex::new(runs.into_iter());
+        let failed = Mutex::new(BTreeMap::new());
+        let first_error = Mutex::new(None);
+        let limiter = Bandwidth::new(self.drain.rows_per_sec);
+        let sent = AtomicUsize::new(0);
+        let started = Instant::now();
+        let report = |handled: usize| {
+            let sent = sent.fetch_add(handled, Ordering::Relaxed) + handled;
+            if let Some(progress) = &self.drain.progress {
+                progress(&DrainProgress {
+                    sent,
+                    total,
+                    elapsed: started.elapsed(),
+                });
+            }
+        };
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    let Some(((project, run), messages)) = queue.lock().next() else {
+                        return;
+                    };
+                    if first_error.lock().is_some() {
+                        failed.lock().insert((project, run), messages);
+                        continue;
+                    }
+                    let client = self.client.for_run(&project, &run);
+                    if let Err((e, unsent)) = send(&client, messages, Some(&limiter), &report) {
+                        first_error.lock().get_or_insert(e);
+                        failed.lock().insert((project, run), unsent);
+                    }
+                });
+            }
+        });
+        self.requeue(failed.into_inner())?;
+        first_error.into_inner().map_or(Ok(total), Err)
+    }
+
+    /// Put the messages that could not be sent back, ahead of those received meanwhile,
+    /// and rewrite the write-ahead log to match.
+    fn requeue(&self, mut failed: BTreeMap<RunKey, Vec<Message>>) -> Result<(), TrackioError> {
+        let mut state = self.state.lock();
+        for (key, newer) in std::mem::take(&mut state.pending) {
+            failed.entry(key).or_default().extend(newer);
+        }
+        state.pending = failed;
+        self.rewrite_wal(&mut state)
+    }
+
+    fn rewrite_wal(&self, state: &mut State) -> Result<(), TrackioError> {
+        let Some(path) = &self.wal else {
+            return Ok(());
+        };
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        for msg in state.pending.values().flatten() {
+            writeln!(out, "{}", serde_json::to_string(msg).unwrap_or_default())?;
+        }
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&tmp, path)?;
+        state.wal = Some(OpenOptions::new().append(true).open(path)?);
+        Ok(())
+    }
+}
+
+fn run_key(msg: &Message) -> Option<RunKey> {
+    match msg {
+        Message::Log { project, run, .. } | Message::Config { project, run, .. } => {
+            Some((project.clone(), run.clone()))
+        }
+        Message::Flush => None,
+    }
+}
+
+/// When the first of `messages` was received; configs, which have no timestamp, come
+/// first.
+fn first_timestamp(messages: &[Message]) -> Option<&str> {
+    messages.iter().find_map(|msg| match msg {
+        Message::Log { timestamp, .. } => Some(timestamp.as_deref()),
+        _ => None,
+    })?
+}
+
+/// Sends the messages of one run in batches, returning the error and the messages from
+/// the first batch that failed onwards. Batches the server rejects outright are dropped.
+/// Each batch first waits for its rows to fit under `limiter`, and `handled` is told
+/// how many messages it covered once it is through.
+fn send(
+    client: &Client,
+    messages: Vec<Message>,
+    limiter: Option<&Bandwidth>,
+    handled: &(dyn Fn(usize) + Sync),
+) -> Result<(), (TrackioError, Vec<Message>)> {
+    let mut start = 0;
+    let mut items = Vec::new();
+    let mut config = None;
+    for (i, msg) in messages.iter().enumerate() {
+        match msg {
+            Message::Log {
+                metrics,
+                step,
+                timestamp,
+                ..
+            } => items.push(LogItem {
+                metrics: metrics.clone(),
+                step: *step,
+                timestamp: timestamp.clone(),
+            }),
+            Message::Config { config: c, .. } => config = Some(c.clone()),
+            Message::Flush => {}
+        }
+        if items.len() >= client.max_batch() || i + 1 == messages.len() {
+            if let Some(limiter) = limiter {
+                limiter.acquire(items.len());
+            }
+            match client.send_batch(std::mem::take(&mut items), config.take()) {
+                Err(e) if e.kind() == ErrorKind::Rejected => {
+                    log::warn!("trackio agent: dropping a batch the server rejected: {e}");
+                }
+                Err(e) => return Err((e, messages[start..].to_vec())),
+                Ok(_) => {}
+            }
+            handled(i + 1 - start);
+            start = i + 1;
+        }
+    }
+    Ok(())
+}
+
+/// A thin client that hands rows to a local [`Agent`] instead of talking to the server.
+///
+/// Mirrors [`Client`]'s logging methods: rows are buffered and written to the socket in
+/// bulk, and [`flush`](IpcClient::flush) waits until the agent has sent everything
+/// pending to the server.
+#[derive(Debug)]
+pub struct IpcClient {
+    project: String,
+    run: String,
+    writer: Mutex<BufWriter<UnixStream>>,
+    reader: Mutex<BufReader<UnixStream>>,
+    error: Mutex<Option<io::Error>>,
+}
+
+impl IpcClient {
+    /// Connect to the agent at [`default_socket_path`], logging to `$TRACKIO_PROJECT` /
+    /// `$TRACKIO_RUN` unless overridden.
+    pub fn connect_default() -> Result<Self, TrackioError> {
+        Self::connect(default_socket_path())
+    }
+
+    /// Connect to the agent listening on `path`.
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self, TrackioError> {
+        let stream = UnixStream::connect(path)?;
+        Ok(Self {
+            project: env::var("TRACKIO_PROJECT").unwrap_or_default(),
+            run: env::var("TRACKIO_RUN").unwrap_or_default(),
+            reader: Mutex::new(BufReader::new(stream.try_clone()?)),
+            writer: Mutex::new(BufWriter::new(stream)),
+            error: Mutex::new(None),
+        })
+    }
+
+    pub fn with_project(mut self, p: &str) -> Self {
+        self.project = p.into();
+        self
+    }
+
+    pub fn with_run(mut self, r: &str) -> Self {
+        self.run = r.into();
+        self
+    }
+
+    /// Queue one metric dictionary. Write errors are reported by the next flush.
+    pub fn log(&self, metrics: Value, step: Option<i64>, ts: Option<String>) {
+        self.write(&Message::Log {
+            project: self.project.clone(),
+            run: self.run.clone(),
+            metrics,
+            step,
+            timestamp: ts.or_else(|| Some(now_iso8601())),
+        });
+    }
+
+    /// Set the run's config, replacing any config stored for it.
+    pub fn set_config(&self, config: Value) {
+        self.write(&Message::Config {
+            project: self.project.clone(),
+            run: self.run.clone(),
+            config,
+        });
+    }
+
+    fn write(&self, msg: &Message) {
+        let line = serde_json::to_string(msg).unwrap_or_default();
+        if let Err(e) = writeln!(self.writer.lock(), "{line}") {
+            self.error.lock().get_or_insert(e);
+        }
+    }
+
+    /// Wait until the agent has sent everything it holds, including other workers' rows.
+    pub fn flush(&self) -> Result<(), TrackioError> {
+        if let Some(e) = self.error.lock().take() {
+            return Err(e.into());
+        }
+        {
+            let mut writer = self.writer.lock();
+            writeln!(
+                writer,
+                "{}",
+                serde_json::to_string(&Message::Flush).unwrap_or_default()
+            )?;
+            writer.flush()?;
+        }
+        let mut line = String::new();
+        if self.reader.lock().read_line(&mut line)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let answer: Value = serde_json::from_str(&line)
+            .map_err(|e| TrackioError::Agent(format!("bad reply: {e}")))?;
+        match answer.get("error").and_then(Value::as_str) {
+            Some(error) => Err(TrackioError::Agent(error.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Hand the remaining rows to the agent without waiting for them to be uploaded;
+    /// the agent keeps retrying after this process exits.
+    pub fn close(&self) -> Result<(), TrackioError> {
+        if let Some(e) = self.error.lock().take() {
+            return Err(e.into());
+        }
+        Ok(self.writer.lock().flush()?)
+    }
+}
+
+impl Drop for IpcClient {
+    fn drop(&mut self) {
+        let _ = self.writer.lock().flush();
+    }
+}
@@ -0,0 +1,385 @@
+use crate::client::{Client, LogItem, TrackioError};
+use crate::notify::RunEvent;
+use crate::time::now_iso8601;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub use Condition::{Above, Below, IsNaN};
+
+/// Severity of an alert, as shown by the dashboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+impl AlertLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AlertLevel::Info => "info",
+            AlertLevel::Warn => "warn",
+            AlertLevel::Error => "error",
+        }
+    }
+}
+
+/// An alert raised on a run, either explicitly with [`Client::alert`] or by a rule.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub title: String,
+    pub text: Option<String>,
+    pub level: AlertLevel,
+    pub step: Option<i64>,
+    pub timestamp: String,
+    /// Unique id of the alert, sent again with every retry so the server can drop
+    /// duplicates.
+    pub id: String,
+}
+
+/// A condition on the values of one metric.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    /// The value is NaN or infinite. `serde_json` stores both as `null`, so a logged
+    /// `null` matches as well.
+    IsNaN,
+    Above(f64),
+    Below(f64),
+}
+
+impl Condition {
+    /// Only fire once the condition has held for `steps` consecutive steps.
+    pub fn for_steps(self, steps: i64) -> Trigger {
+        Trigger {
+            condition: self,
+            for_steps: steps.max(0),
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match (self, value.as_f64()) {
+            (IsNaN, v) => value.is_null() || v.is_some_and(|v| !v.is_finite()),
+            (Above(limit), Some(v)) => v > limit,
+            (Below(limit), Some(v)) => v < limit,
+            _ => false,
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            IsNaN => "is NaN".into(),
+            Above(limit) => format!("is above {limit}"),
+            Below(limit) => format!("is below {limit}"),
+        }
+    }
+}
+
+/// A [`Condition`] and how long it must hold before the rule fires.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trigger {
+    pub condition: Condition,
+    pub for_steps: i64,
+}
+
+impl From<Condition> for Trigger {
+    fn from(condition: Condition) -> Self {
+        condition.for_steps(0)
+    }
+}
+
+type LastLog = (Instant, Option<i64>);
+
+pub(crate) type AlertCallback = Arc<dyn Fn(&Alert) + Send + Sync>;
+
+#[derive(Debug)]
+struct Rule {
+    metric: String,
+    trigger: Trigger,
+    since: Option<i64>,
+    fired: bool,
+}
+
+/// Alert rules of a client and the alerts waiting to be sent.
+#[derive(Default)]
+pub(crate) struct Alerting {
+    rules: Mutex<Vec<Rule>>,
+    stall: Option<Duration>,
+    stall_metric: bool,
+    callbacks: Vec<AlertCallback>,
+    pending: Arc<Mutex<Vec<Alert>>>,
+    points: AtomicU64,
+    last_log: Arc<Mutex<Option<LastLog>>>,
+    watcher: Mutex<Option<Arc<()>>>,
+}
+
+impl fmt::Debug for Alerting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Alerting")
+            .field("rules", &self.rules)
+            .field("stall", &self.stall)
+            .field("callbacks", &self.callbacks.len())
+            .finish()
+    }
+}
+
+impl Alerting {
+    pub(crate) fn add_rule(&mut self, metric: &str, trigger: Trigger) {
+        self.rules.get_mut().push(Rule {
+            metric: metric.into(),
+            trigger,
+            since: None,
+            fired: false,
+        });
+    }
+
+    pub(crate) fn set_stall(&mut self, after: Duration) {
+        self.stall = Some(after);
+    }
+
+    /// Also log `system/stalled` when a stall starts (1) and ends (0).
+    pub(crate) fn log_stalls(&mut self) {
+        self.stall_metric = true;
+    }
+
+    pub(crate) fn since_last_log(&self) -> Option<Duration> {
+        self.last_log.lock().map(|(at, _)| at.elapsed())
+    }
+
+    pub(crate) fn last_step(&self) -> Option<i64> {
+        self.last_log.lock().and_then(|(_, step)| step)
+    }
+
+    pub(crate) fn add_callback(&mut self, callback: AlertCallback) {
+        self.callbacks.push(callback);
+    }
+
+    pub(crate) fn raise(&self, alert: Alert) {
+        raise(&self.callbacks, &self.pending, alert);
+    }
+
+    /// Checks a logged row against the rules, raising an alert for each rule that
+    /// starts firing.
+    pub(crate) fn observe(&self, metrics: &Value, step: Option<i64>) {
+        *self.last_log.lock() = Some((Instant::now(), step));
+        let mut rules = self.rules.lock();
+        if rules.is_empty() {
+            return;
+        }
+        let point = self.points.fetch_add(1, Ordering::Relaxed) as i64;
+        let at = step.unwrap_or(point);
+        for rule in rules.iter_mut() {
+            let Some(value) = metrics.get(&rule.metric) else {
+                continue;
+            };
+            if !rule.trigger.condition.matches(value) {
+                rule.since = None;
+                rule.fired = false;
+                continue;
+            }
+            let since = *rule.since.get_or_insert(at);
+            if rule.fired || at - since < rule.trigger.for_steps {
+                continue;
+            }
+            rule.fired = true;
+            let condition = rule.trigger.condition;
+            let level = match condition {
+                IsNaN => AlertLevel::Error,
+                _ => AlertLevel::Warn,
+            };
+            let value = match value {
+                Value::Null => "NaN".to_string(),
+                v => v.to_string(),
+            };
+            let text = match rule.trigger.for_steps {
+                0 => format!("{} was {value} at step {at}", rule.metric),
+                n => format!(
+                    "{} {} for {n} steps, since step {since}; now {value} at step {at}",
+                    rule.metric,
+                    condition.describe()
+                ),
+            };
+            self.raise(Alert {
+                title: format!("{} {}", rule.metric, condition.describe()),
+                text: Some(text),
+                level,
+                step,
+                timestamp: now_iso8601(),
+                id: alert_id(),
+            });
+        }
+    }
+
+    /// Starts the thread that watches for stalls, once, on the first logged row.
+    pub(crate) fn start_watcher(&self, client: &Client) {
+        let Some(after) = self.stall else {
+            return;
+        };
+        let mut watcher = self.watcher.lock();
+        if watcher.is_some() {
+            return;
+        }
+        let alive = Arc::new(());
+        let token = Arc::downgrade(&alive);
+        *watcher = Some(alive);
+        let watch = StallWatch {
+            alive: token,
+            after,
+            log_metric: self.stall_metric,
+            last_log: self.last_log.clone(),
+            pending: self.pending.clone(),
+            callbacks: self.callbacks.clone(),
+            sender: client.for_run(client.project(), client.run()),
+        };
+        thread::spawn(move || watch.run());
+    }
+
+    pub(crate) fn take_pending(&self) -> Vec<Alert> {
+        std::mem::take(&mut *self.pending.lock())
+    }
+
+    pub(crate) fn requeue(&self, mut alerts: Vec<Alert>) {
+        let mut pending = self.pending.lock();
+        alerts.append(&mut pending);
+        *pending = alerts;
+    }
+}
+
+fn raise(callbacks: &[AlertCallback], pending: &Mutex<Vec<Alert>>, alert: Alert) {
+    for callback in callbacks {
+        callback(&alert);
+    }
+    pending.lock().push(alert);
+}
+
+struct StallWatch {
+    alive: Weak<()>,
+    after: Duration,
+    log_metric: bool,
+    last_log: Arc<Mutex<Option<LastLog>>>,
+    pending: Arc<Mutex<Vec<Alert>>>,
+    callbacks: Vec<AlertCallback>,
+    sender: Client,
+}
+
+impl StallWatch {
+    fn run(self) {
+        let tick = (self.after / 4).clamp(Duration::from_millis(50), Duration::from_secs(5));
+        let mut fired = false;
+        while self.alive.strong_count() > 0 {
+            thread::sleep(tick);
+            let Some((last, step)) = *self.last_log.lock() else {
+                continue;
+            };
+            let silent = last.elapsed();
+            if silent < self.after {
+                if fired {
+                    fired = false;
+                    self.log_stalled(0, step);
+                }
+                continue;
+            }
+            if fired {
+                continue;
+            }
+            fired = true;
+            self.log_stalled(1, step);
+            raise(
+                &self.callbacks,
+                &self.pending,
+                Alert {
+                    title: "No metrics logged".into(),
+                    text: Some(format!(
+                        "nothing was logged to {} for {:.1}s",
+                        self.sender.run_name(),
+                        silent.as_secs_f64()
+                    )),
+                    level: AlertLevel::Warn,
+                    step,
+                    timestamp: now_iso8601(),
+                    id: alert_id(),
+                },
+            );
+            let alerts = std::mem::take(&mut *self.pending.lock());
+            if let Err((_, alerts)) = send_alerts(&self.sender, alerts) {
+                let mut pending = self.pending.lock();
+                let newer = std::mem::replace(&mut *pending, alerts);
+                pending.extend(newer);
+            }
+        }
+    }
+
+    fn log_stalled(&self, value: i64, step: Option<i64>) {
+        if !self.log_metric {
+            return;
+        }
+        let item = LogItem {
+            metrics: json!({ "system/stalled": value }),
+            step,
+            timestamp: Some(now_iso8601()),
+        };
+        let _ = self.sender.send_batch(vec![item], None);
+    }
+}
+
+/// Sends alerts to the server's `bulk_alert` endpoint, or to the client's backend.
+/// Gives the alerts back if they could not be delivered.
+pub(crate) fn send_alerts(
+    client: &Client,
+    alerts: Vec<Alert>,
+) -> Result<(), (TrackioError, Vec<Alert>)> {
+    if alerts.is_empty() {
+        return Ok(());
+    }
+    if let Err(e) = deliver(client, &alerts) {
+        return Err((e, alerts));
+    }
+    for alert in alerts {
+        client.notify(RunEvent::Alert(alert));
+    }
+    Ok(())
+}
+
+/// Sends alerts without notifying or requeueing them.
+pub(crate) fn deliver(client: &Client, alerts: &[Alert]) -> Result<(), TrackioError> {
+    if client.dry_run_alerts(alerts) {
+        return Ok(());
+    }
+    let run = client.run_name();
+    if let Some(backend) = client.backend() {
+        return backend.send_alerts(client.project(), &run, alerts);
+    }
+    let entries: Vec<Value> = alerts
+        .iter()
+        .map(|a| {
+            json!({
+                "project": client.project(),
+                "run": run,
+                "title": a.title,
+                "text": a.text,
+                "level": a.level.as_str(),
+                "step": a.step,
+                "timestamp": a.timestamp,
+                "alert_id": a.id,
+            })
+        })
+        .collect();
+    client.call_api(
+        "bulk_alert",
+        &json!({ "alerts": entries, "hf_token": Value::Null }),
+    )?;
+    Ok(())
+}
+
+pub(crate) fn alert_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{nanos:016x}{:08x}{n:08x}", std::process::id())
+}
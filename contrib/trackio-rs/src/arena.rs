@@ -0,0 +1,120 @@
+use crate::log_item::LogItem;
+use crate::time::write_iso8601;
+use parking_lot::Mutex;
+use std::time::SystemTime;
+
+/// Allocations of sent batches kept for the rows that follow: the vector of the last
+/// batch sent and up to a batch's worth of its timestamp strings, which the next batch
+/// and the rows' timestamps are built in instead of new ones.
+///
+/// Only those two are pooled. Metric maps are allocated for every row, since
+/// `serde_json::Map` frees its nodes when cleared, and a batch that fails to send is
+/// requeued rather than recycled.
+#[derive(Debug)]
+pub(crate) struct RowArena {
+    batches: Mutex<Vec<Vec<LogItem>>>,
+    stamps: Mutex<Vec<String>>,
+    max_stamps: usize,
+}
+
+impl RowArena {
+    /// An arena keeping up to `rows` timestamps, a batch's worth.
+    pub(crate) fn new(rows: usize) -> Self {
+        Self {
+            batches: Mutex::default(),
+            stamps: Mutex::default(),
+            max_stamps: rows,
+        }
+    }
+
+    /// An empty vector for the next batch, with room for `rows`.
+    pub(crate) fn batch(&self, rows: usize) -> Vec<LogItem> {
+        self.batches
+            .lock()
+            .pop()
+            .unwrap_or_else(|| Vec::with_capacity(rows))
+    }
+
+    /// The current time as an ISO 8601 string, written into a recycled one if any.
+    pub(crate) fn timestamp(&self) -> String {
+        let mut stamp = self.stamps.lock().pop().unwrap_or_default();
+        stamp.clear();
+        write_iso8601(&mut stamp, SystemTime::now());
+        stamp
+    }
+
+    /// Takes back a sent batch, keeping its timestamps while there is room and its vector
+    /// unless one is kept already.
+    pub(crate) fn recycle(&self, mut items: Vec<LogItem>) {
+        {
+            let mut stamps = self.stamps.lock();
+            let room = self.max_stamps.saturating_sub(stamps.len());
+            stamps.extend(items.drain(..).filter_map(|item| item.timestamp).take(room));
+        }
+        let mut batches = self.batches.lock();
+        if batches.is_empty() {
+            batches.push(items);
+        }
+    }
+
+    /// Drops the pools after a `fork()`, whose locks may belong to a thread that does
+    /// not exist in the child.
+    pub(crate) fn after_fork(&self) {
+        unsafe {
+            if self.batches.is_locked() {
+                self.batches.force_unlock();
+            }
+            if self.stamps.is_locked() {
+                self.stamps.force_unlock();
+            }
+        }
+        self.batches.lock().clear();
+        self.stamps.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn batch(arena: &RowArena, rows: usize) -> Vec<LogItem> {
+        let mut items = arena.batch(rows);
+        for step in 0..rows as i64 {
+            items.push(LogItem {
+                metrics: json!({ "loss": 0.5 }),
+                step: Some(step),
+                timestamp: Some(arena.timestamp()),
+            });
+        }
+        items
+    }
+
+    #[test]
+    fn a_recycled_batch_and_its_timestamps_are_reused() {
+        let arena = RowArena::new(4);
+        let items = batch(&arena, 4);
+        let vector = items.as_ptr();
+        let stamps: Vec<_> = items.iter().map(|item| item.timestamp.as_ref().unwrap().as_ptr()).collect();
+        arena.recycle(items);
+
+        let items = batch(&arena, 4);
+        assert_eq!(items.as_ptr(), vector);
+        for item in &items {
+            assert!(stamps.contains(&item.timestamp.as_ref().unwrap().as_ptr()));
+        }
+    }
+
+    #[test]
+    fn the_pools_are_bounded() {
+        let arena = RowArena::new(2);
+        let first = batch(&arena, 3);
+        let second = batch(&arena, 3);
+        let kept = first.as_ptr();
+        arena.recycle(first);
+        arena.recycle(second);
+        assert_eq!(arena.batches.lock().len(), 1);
+        assert_eq!(arena.batches.lock()[0].as_ptr(), kept);
+        assert_eq!(arena.stamps.lock().len(), 2);
+    }
+}
@@ -0,0 +1,150 @@
+//! Command-line arguments as run config, with the `clap` feature.
+//!
+//! [`Client::with_args`] records every argument of a parsed command line in the run's
+//! config, defaults included, with the types clap parsed them to: numbers stay numbers,
+//! flags are booleans and repeated options are arrays. The arguments set on the command
+//! line or from the environment, as opposed to left at their defaults, are listed under
+//! `_ExplicitArgs`. A subcommand's arguments are nested under its name.
+//!
+//! ```no_run
+//! use clap::Parser;
+//!
+//! #[derive(Parser)]
+//! struct Args {
+//!     #[arg(long, default_value_t = 3e-4)]
+//!     lr: f64,
+//!     #[arg(long, default_value_t = 32)]
+//!     batch_size: u32,
+//! }
+//!
+//! let args = Args::parse();
+//! let client = trackio::Client::new().with_args_of::<Args>();
+//! ```
+
+use crate::client::Client;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command, CommandFactory, Id};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::any::Any;
+use std::path::PathBuf;
+
+impl Client {
+    /// Record the arguments in `matches` in the run's config. They are sent with the
+    /// first flush; keys set with [`set_config`](Client::set_config) take precedence.
+    pub fn with_args(self, matches: &ArgMatches) -> Self {
+        self.with_default_config(args_config(matches))
+    }
+
+    /// Record the process's command-line arguments, parsed again with the command of
+    /// `C`, usually a `#[derive(Parser)]` struct. Knowing the command, options that can
+    /// repeat are recorded as arrays even when given once. Arguments `C` can't parse are
+    /// skipped with a warning.
+    pub fn with_args_of<C: CommandFactory>(self) -> Self {
+        let mut command = C::command();
+        match command.try_get_matches_from_mut(std::env::args_os()) {
+            Ok(matches) => self.with_default_config(config_of(&matches, Some(&command))),
+            Err(e) => {
+                log::warn!("trackio: could not record the command-line arguments: {e}");
+                self
+            }
+        }
+    }
+}
+
+/// The config [`Client::with_args`] records for `matches`. Without the command, an
+/// option is an array only when it was given more than one value.
+pub fn args_config(matches: &ArgMatches) -> Map<String, Value> {
+    config_of(matches, None)
+}
+
+fn config_of(matches: &ArgMatches, command: Option<&Command>) -> Map<String, Value> {
+    let mut config = Map::new();
+    let mut explicit = Vec::new();
+    collect(matches, command, "", &mut config, &mut explicit);
+    if !explicit.is_empty() {
+        config.insert("_ExplicitArgs".into(), explicit.into());
+    }
+    config
+}
+
+fn collect(
+    matches: &ArgMatches,
+    command: Option<&Command>,
+    prefix: &str,
+    config: &mut Map<String, Value>,
+    explicit: &mut Vec<String>,
+) {
+    for id in matches.ids() {
+        let name = id.as_str();
+        let Some(mut values) = values_of(matches, name) else {
+            continue;
+        };
+        let arg = command.and_then(|c| c.get_arguments().find(|a| a.get_id() == id));
+        let value = match arg.map_or(values.len() > 1, takes_many) {
+            true => Value::Array(values),
+            false if values.is_empty() => Value::Null,
+            false => values.swap_remove(0),
+        };
+        if matches
+            .value_source(name)
+            .is_some_and(|source| source != ValueSource::DefaultValue)
+        {
+            explicit.push(format!("{prefix}{name}"));
+        }
+        config.insert(name.into(), value);
+    }
+    if let Some((name, sub)) = matches.subcommand() {
+        let mut nested = Map::new();
+        let sub_command = command.and_then(|c| c.find_subcommand(name));
+        collect(sub, sub_command, &format!("{prefix}{name}."), &mut nested, explicit);
+        config.insert(name.into(), Value::Object(nested));
+    }
+}
+
+fn takes_many(arg: &Arg) -> bool {
+    matches!(arg.get_action(), ArgAction::Append)
+        || arg.get_num_args().is_some_and(|n| n.max_values() > 1)
+}
+
+/// The values of the argument `id` as the type clap parsed them to, falling back to
+/// the raw strings for other types such as `ValueEnum`s. `None` for argument groups
+/// and arguments without a value.
+fn values_of(matches: &ArgMatches, id: &str) -> Option<Vec<Value>> {
+    if matches.try_get_many::<Id>(id).is_ok() {
+        return None;
+    }
+    let raw = matches.try_get_raw(id).ok()??;
+    typed::<bool>(matches, id)
+        .or_else(|| typed::<String>(matches, id))
+        .or_else(|| typed::<i64>(matches, id))
+        .or_else(|| typed::<u64>(matches, id))
+        .or_else(|| typed::<i32>(matches, id))
+        .or_else(|| typed::<u32>(matches, id))
+        .or_else(|| typed::<i16>(matches, id))
+        .or_else(|| typed::<u16>(matches, id))
+        .or_else(|| typed::<i8>(matches, id))
+        .or_else(|| typed::<u8>(matches, id))
+        .or_else(|| typed::<usize>(matches, id))
+        .or_else(|| typed::<isize>(matches, id))
+        .or_else(|| typed::<f64>(matches, id))
+        .or_else(|| typed::<PathBuf>(matches, id))
+        .or_else(|| {
+            let values = matches.try_get_many::<f32>(id).ok()??;
+            Some(values.map(|&f| float(f.to_string().parse().ok())).collect())
+        })
+        .or_else(|| Some(raw.map(|v| v.to_string_lossy().into()).collect()))
+}
+
+fn typed<T: Any + Clone + Send + Sync + Serialize>(matches: &ArgMatches, id: &str) -> Option<Vec<Value>> {
+    let values = matches.try_get_many::<T>(id).ok()??;
+    Some(
+        values
+            .map(|v| serde_json::to_value(v).unwrap_or(Value::Null))
+            .collect(),
+    )
+}
+
+fn float(f: Option<f64>) -> Value {
+    f.and_then(Number::from_f64).map_or(Value::Null, Value::Number)
+}
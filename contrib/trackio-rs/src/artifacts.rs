@@ -0,0 +1,146 @@
+//! Files a run depends on or produces, such as a tokenizer, eval fixtures or a
+//! checkpoint, stored by content so unchanged files are uploaded once across runs.
+//!
+//! [`Client::log_artifact`] hashes every file with SHA-256 and keeps it in the client's
+//! [`MediaStore`](crate::media::MediaStore) as the blob `artifacts/blobs/<sha256>`,
+//! skipping blobs the store already has. A manifest listing each file's path, hash, size
+//! and URL is stored next to them, and recorded as the `artifact/<name>` key of the run's
+//! [metadata](Client::set_meta), so it needs a server with the run metadata endpoints,
+//! such as the one in `trackio::server`. A run logging the same tokenizer as the last
+//! one uploads nothing but the manifest.
+//!
+//! ```no_run
+//! use trackio::media::DirStore;
+//!
+//! let client = trackio::Client::new()
+//!     .with_project("p")
+//!     .with_run("r")
+//!     .with_media_store(DirStore::new("/mnt/ml-artifacts"));
+//! let manifest = client.log_artifact("tokenizer", &["tokenizer/"])?;
+//! println!("{} bytes uploaded, {} reused", manifest.uploaded_bytes, manifest.reused_bytes);
+//! # Ok::<(), trackio::client::TrackioError>(())
+//! ```
+
+use crate::client::{Client, TrackioError};
+use crate::datasets::collect_files;
+use crate::media::key_part;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const META_PREFIX: &str = "artifact/";
+
+/// A file of an artifact.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactFile {
+    /// The path relative to the path given, with `/` separators.
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+    /// Where the store keeps the blob.
+    pub url: String,
+}
+
+/// What [`Client::log_artifact`] stored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    pub name: String,
+    /// SHA-256 over the paths and hashes of the files, the same for the same content.
+    pub digest: String,
+    pub files: Vec<ArtifactFile>,
+    /// Bytes of the blobs uploaded by this call.
+    #[serde(skip)]
+    pub uploaded_bytes: u64,
+    /// Bytes of the blobs the store had already.
+    #[serde(skip)]
+    pub reused_bytes: u64,
+}
+
+impl Client {
+    /// Store the files at `paths`, directories included recursively, as the artifact
+    /// `name` of the run, uploading only the content the client's media store doesn't
+    /// have yet; see the [module docs](crate::artifacts). Logging the artifact again
+    /// replaces the version recorded on the run.
+    pub fn log_artifact<P: AsRef<Path>>(&self, name: &str, paths: &[P]) -> Result<ArtifactManifest, TrackioError> {
+        let store = self.media_store().ok_or_else(|| {
+            TrackioError::InvalidArgument("log_artifact needs Client::with_media_store".into())
+        })?;
+        let mut manifest = ArtifactManifest {
+            name: name.into(),
+            digest: String::new(),
+            files: Vec::new(),
+            uploaded_bytes: 0,
+            reused_bytes: 0,
+        };
+        for root in paths {
+            let root = root.as_ref();
+            let mut files = Vec::new();
+            collect_files(root, &mut files)?;
+            files.sort();
+            for file in files {
+                let relative = match file.strip_prefix(root) {
+                    Ok(relative) if !relative.as_os_str().is_empty() => relative,
+                    _ => Path::new(file.file_name().unwrap_or(file.as_os_str())),
+                };
+                let (sha256, size) = hash_file(&file)?;
+                let key = format!("artifacts/blobs/{sha256}");
+                let url = match store.lookup(&key)? {
+                    Some(url) => {
+                        manifest.reused_bytes += size;
+                        url
+                    }
+                    None => {
+                        manifest.uploaded_bytes += size;
+                        store.put(&key, &fs::read(&file)?, "application/octet-stream")?
+                    }
+                };
+                manifest.files.push(ArtifactFile {
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    sha256,
+                    size,
+                    url,
+                });
+            }
+        }
+        let mut digest = Sha256::new();
+        for file in &manifest.files {
+            digest.update(file.path.as_bytes());
+            digest.update([0]);
+            digest.update(file.sha256.as_bytes());
+            digest.update([0]);
+        }
+        manifest.digest = format!("{:x}", digest.finalize());
+        let key = format!("artifacts/manifests/{}/{}.json", key_part(name), manifest.digest);
+        let url = match store.lookup(&key)? {
+            Some(url) => url,
+            None => {
+                let body = serde_json::to_vec(&manifest).expect("manifest serializes");
+                store.put(&key, &body, "application/json")?
+            }
+        };
+        self.set_meta(
+            &format!("{META_PREFIX}{name}"),
+            json!({ "digest": manifest.digest, "manifest": url }),
+        )?;
+        Ok(manifest)
+    }
+}
+
+fn hash_file(path: &Path) -> Result<(String, u64), TrackioError> {
+    let mut reader = fs::File::open(path)?;
+    let mut hash = Sha256::new();
+    let mut chunk = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let n = reader.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        hash.update(&chunk[..n]);
+        size += n as u64;
+    }
+    Ok((format!("{:x}", hash.finalize()), size))
+}
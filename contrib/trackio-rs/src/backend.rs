@@ -0,0 +1,42 @@
+use crate::alerts::Alert;
+use crate::events::Event;
+use crate::client::{LogItem, TrackioError};
+use std::fmt::Debug;
+
+/// Destination for flushed batches when metrics should not go to a Trackio server over HTTP.
+///
+/// Install one with [`Client::with_backend`](crate::Client::with_backend).
+pub trait Backend: Debug + Send + Sync {
+    /// Persist one batch of items logged to `project`/`run`.
+    fn send(&self, project: &str, run: &str, items: &[LogItem]) -> Result<(), TrackioError>;
+
+    /// Store the config of `project`/`run`, replacing any previous one. Ignored by default.
+    fn set_config(
+        &self,
+        _project: &str,
+        _run: &str,
+        _config: &serde_json::Value,
+    ) -> Result<(), TrackioError> {
+        Ok(())
+    }
+
+    /// Store alerts raised on `project`/`run`. Ignored by default.
+    fn send_alerts(
+        &self,
+        _project: &str,
+        _run: &str,
+        _alerts: &[Alert],
+    ) -> Result<(), TrackioError> {
+        Ok(())
+    }
+
+    /// Store events logged on `project`/`run`. Ignored by default.
+    fn send_events(
+        &self,
+        _project: &str,
+        _run: &str,
+        _events: &[Event],
+    ) -> Result<(), TrackioError> {
+        Ok(())
+    }
+}
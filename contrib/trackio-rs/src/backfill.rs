@@ -0,0 +1,104 @@
+use crate::client::{Client, LogItem, TrackioError};
+use crate::time::{format_iso8601, parse_iso8601};
+use std::time::{Duration, SystemTime};
+
+/// Rows per request when backfilling, larger than live batches since nothing waits on
+/// them.
+pub(crate) const BACKFILL_CHUNK: usize = 1000;
+
+/// How far ahead of this machine's clock a timestamp may be, for points recorded on
+/// machines whose clocks run slightly ahead.
+const CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+impl Client {
+    /// Upload historical rows, each with its own step and timestamp, e.g. results of runs
+    /// computed before the training code was instrumented.
+    ///
+    /// Steps go through the client's [step transforms](Client::with_step_transform).
+    /// Every point is checked before anything is sent, against the client's
+    /// [`schema`](Client::with_schema) if it has one: metrics must be a JSON object with
+    /// non-empty names, the step must be set and non-negative, and the timestamp must be
+    /// set, parse as ISO 8601 and not lie in the future (beyond five minutes of clock
+    /// skew). Timestamps are sent normalized to UTC. The rows go out in large requests
+    /// straight away, bypassing the buffer, so rows logged live with
+    /// [`log`](Client::log) are neither delayed nor interleaved, and no alerts are
+    /// evaluated.
+    ///
+    /// Returns the number of rows sent. If a request fails, the error is a
+    /// [`TrackioError::Batch`] holding that request's rows and all later ones, which can
+    /// be passed back to `log_backfill` to resume.
+    ///
+    /// ```no_run
+    /// use trackio::client::LogItem;
+    ///
+    /// let client = trackio::Client::new().with_project("p").with_run("baseline");
+    /// let points = vec![LogItem {
+    ///     metrics: serde_json::json!({ "val/acc": 0.71 }),
+    ///     step: Some(1000),
+    ///     timestamp: Some("2024-03-01T12:00:00Z".into()),
+    /// }];
+    /// client.log_backfill(points)?;
+    /// # Ok::<(), trackio::client::TrackioError>(())
+    /// ```
+    pub fn log_backfill<I>(&self, points: I) -> Result<usize, TrackioError>
+    where
+        I: IntoIterator<Item = LogItem>,
+    {
+        let latest = SystemTime::now() + CLOCK_SKEW;
+        let mut items = Vec::new();
+        for (i, mut point) in points.into_iter().enumerate() {
+            let Some(metrics) = self.prepare_metrics(point.metrics) else {
+                continue;
+            };
+            point.metrics = self.apply_schema(metrics);
+            point.step = self.map_step(point.step);
+            items.push(validate(i, point, latest)?);
+        }
+        let total = items.len();
+        let mut rest = items.into_iter();
+        loop {
+            let chunk: Vec<LogItem> = rest.by_ref().take(BACKFILL_CHUNK).collect();
+            if chunk.is_empty() {
+                return Ok(total);
+            }
+            if let Err(e) = self.send_batch(chunk, None) {
+                return Err(match e {
+                    TrackioError::Batch(mut batch) => {
+                        batch.items.extend(rest);
+                        TrackioError::Batch(batch)
+                    }
+                    e => e,
+                });
+            }
+        }
+    }
+}
+
+fn validate(i: usize, point: LogItem, latest: SystemTime) -> Result<LogItem, TrackioError> {
+    let invalid = |why: String| TrackioError::InvalidArgument(format!("backfill point {i}: {why}"));
+    let Some(row) = point.metrics.as_object() else {
+        return Err(invalid(format!("metrics must be a JSON object, got {}", point.metrics)));
+    };
+    if row.keys().any(|k| k.is_empty()) {
+        return Err(invalid("metric names must not be empty".into()));
+    }
+    let step = match point.step {
+        Some(step) if step >= 0 => step,
+        Some(step) => return Err(invalid(format!("negative step {step}"))),
+        None => return Err(invalid("no step".into())),
+    };
+    let Some(timestamp) = &point.timestamp else {
+        return Err(invalid("no timestamp".into()));
+    };
+    let Some(time) = parse_iso8601(timestamp) else {
+        return Err(invalid(format!("unparsable timestamp {timestamp:?}")));
+    };
+    if time > latest {
+        return Err(invalid(format!("timestamp {timestamp:?} is in the future")));
+    }
+    Ok(LogItem {
+        metrics: point.metrics,
+        step: Some(step),
+        timestamp: Some(format_iso8601(time)),
+    })
+}
@@ -0,0 +1,44 @@
+use parking_lot::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A token bucket capping how many request bytes a client uploads per second, with
+/// bursts of up to one second's worth.
+///
+/// A request waits until its body fits in the budget. A body larger than a second's
+/// worth goes out once the bucket is full and leaves it in debt, so the requests after
+/// it wait in proportion.
+#[derive(Debug)]
+pub(crate) struct Bandwidth {
+    bytes_per_sec: u64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl Bandwidth {
+    pub(crate) fn new(bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+        Self {
+            bytes_per_sec,
+            bucket: Mutex::new((bytes_per_sec as f64, Instant::now())),
+        }
+    }
+
+    /// Blocks until `bytes` may be sent.
+    pub(crate) fn acquire(&self, bytes: usize) {
+        let rate = self.bytes_per_sec as f64;
+        let wait = {
+            let mut bucket = self.bucket.lock();
+            let (tokens, at) = &mut *bucket;
+            let now = Instant::now();
+            *tokens = (*tokens + now.duration_since(*at).as_secs_f64() * rate).min(rate);
+            *at = now;
+            let needed = (bytes as f64).min(rate);
+            let wait = (needed - *tokens).max(0.0) / rate;
+            *tokens -= bytes as f64;
+            wait
+        };
+        if wait > 0.0 {
+            thread::sleep(Duration::from_secs_f64(wait));
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::client::Client;
+use crate::diff::{diff_maps, flatten_config, ValueChange, CONFIG_DIFF_PREFIX};
+use crate::query::glob_match;
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+pub(crate) type DiffCallback = Arc<dyn Fn(&[ValueChange]) + Send + Sync>;
+
+/// The baseline run set with
+/// [`Client::with_config_baseline`](crate::Client::with_config_baseline), compared
+/// against the first config the run sets.
+#[derive(Default)]
+pub(crate) struct ConfigBaseline {
+    run: Option<String>,
+    expected: Vec<String>,
+    callbacks: Vec<DiffCallback>,
+    checked: AtomicBool,
+}
+
+impl fmt::Debug for ConfigBaseline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConfigBaseline")
+            .field("run", &self.run)
+            .field("expected", &self.expected)
+            .field("callbacks", &self.callbacks.len())
+            .finish()
+    }
+}
+
+impl ConfigBaseline {
+    pub(crate) fn set(&mut self, run: &str, expected: &[&str]) {
+        self.run = Some(run.into());
+        self.expected = expected.iter().map(|&key| key.into()).collect();
+    }
+
+    pub(crate) fn add_callback(&mut self, callback: DiffCallback) {
+        self.callbacks.push(callback);
+    }
+
+    /// Adds a `config_diff/<key>` entry to `config` for every key that differs from the
+    /// baseline's config, and reports the unexpected ones. Only the first config is
+    /// compared, together with the default config; a baseline that can't be fetched is
+    /// skipped with a warning.
+    pub(crate) fn check(&self, client: &Client, config: &mut Value) {
+        let Some(run) = &self.run else {
+            return;
+        };
+        if !config.is_object() || self.checked.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        let baseline = match client.get_config(client.project(), run) {
+            Ok(Some(baseline)) => flatten_config(Some(baseline)),
+            Ok(None) => {
+                log::warn!("trackio: baseline run {run:?} has no config to compare with");
+                return;
+            }
+            Err(e) => {
+                log::warn!("trackio: could not fetch the config of baseline run {run:?}: {e}");
+                return;
+            }
+        };
+        let merged = client.with_reserved_keys(Some(config.clone()));
+        let changes = diff_maps(&baseline, &flatten_config(Some(merged)));
+        let Value::Object(map) = config else {
+            return;
+        };
+        for change in &changes {
+            map.insert(
+                format!("{CONFIG_DIFF_PREFIX}{}", change.key),
+                json!({ "baseline": change.a, "run": change.b }),
+            );
+        }
+        let unexpected: Vec<ValueChange> = changes
+            .into_iter()
+            .filter(|change| !self.expected.iter().any(|p| glob_match(p, &change.key)))
+            .collect();
+        if unexpected.is_empty() {
+            return;
+        }
+        for change in &unexpected {
+            log::warn!(
+                "trackio: config {:?} is {} but {} in baseline run {run:?}",
+                change.key,
+                shown(&change.b),
+                shown(&change.a)
+            );
+        }
+        for callback in &self.callbacks {
+            callback(&unexpected);
+        }
+    }
+}
+
+fn shown(value: &Option<Value>) -> String {
+    value.as_ref().map_or_else(|| "unset".into(), Value::to_string)
+}
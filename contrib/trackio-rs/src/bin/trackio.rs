@@ -0,0 +1,447 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::{Map, Value};
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+#[cfg(unix)]
+use trackio::agent::Agent;
+use trackio::client::TrackioError;
+use trackio::import::{tail_jsonl, JsonlMapping};
+use trackio::report::{ReportFormat, ReportTemplate};
+#[cfg(feature = "shm")]
+use trackio::shm::ShmDrainer;
+use trackio::statsd::StatsdListener;
+use trackio::{Client, ExportFormat, SqliteBackend};
+
+/// Log metrics to a Trackio dashboard from the shell.
+#[derive(Parser)]
+#[command(name = "trackio", version)]
+struct Cli {
+    #[command(flatten)]
+    target: Target,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Target {
+    /// Server URL [default: $TRACKIO_SERVER_URL or http://127.0.0.1:7860]
+    #[arg(long, global = true)]
+    url: Option<String>,
+    /// Project [default: $TRACKIO_PROJECT]
+    #[arg(long, short, global = true)]
+    project: Option<String>,
+    /// Run [default: $TRACKIO_RUN]
+    #[arg(long, short, global = true)]
+    run: Option<String>,
+    /// Write token [default: $TRACKIO_WRITE_TOKEN]
+    #[arg(long, global = true)]
+    write_token: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Log one row given as `key=value` pairs, or one JSON object per stdin line.
+    Log {
+        /// Metrics as `key=value`; values are parsed as JSON, falling back to strings
+        metrics: Vec<String>,
+        /// Step of the row given as arguments
+        #[arg(long)]
+        step: Option<i64>,
+    },
+    /// Follow a JSON-lines metrics file and log every new line to the run.
+    ///
+    /// The offset of the last imported line is checkpointed, so restarting resumes
+    /// where the previous invocation stopped.
+    Tail {
+        path: PathBuf,
+        /// Without a checkpoint, skip the lines already in the file
+        #[arg(long)]
+        from_end: bool,
+        /// Checkpoint file [default: <path>.trackio-offset]
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+        /// Field holding the step
+        #[arg(long, default_value = "step")]
+        step_key: String,
+        /// Only import fields matching these glob patterns
+        #[arg(long = "metric")]
+        metrics: Vec<String>,
+        /// Prepended to every metric name
+        #[arg(long, default_value = "")]
+        prefix: String,
+        /// Poll interval in milliseconds
+        #[arg(long, default_value_t = 500)]
+        interval_ms: u64,
+    },
+    /// Push runs recorded offline in the local SQLite store to the server.
+    Sync {
+        /// Store directory [default: the Python trackio directory]
+        #[arg(long)]
+        dir: Option<PathBuf>,
+    },
+    /// Export the metric histories of runs to a file.
+    Export {
+        /// Runs to export [default: --run]
+        runs: Vec<String>,
+        #[arg(long, short)]
+        output: PathBuf,
+        #[arg(long, value_enum, default_value_t = Format::Csv)]
+        format: Format,
+    },
+    /// Render runs as a self-contained HTML or Markdown report.
+    Report {
+        /// Runs to include [default: --run]
+        runs: Vec<String>,
+        #[arg(long, short)]
+        output: PathBuf,
+        /// Report format [default: from the output extension, else html]
+        #[arg(long, value_enum)]
+        format: Option<ReportKind>,
+        #[arg(long)]
+        title: Option<String>,
+        /// Only include metrics matching these glob patterns
+        #[arg(long = "metric")]
+        metrics: Vec<String>,
+    },
+    /// Import runs recorded by another tracker into the project.
+    #[command(subcommand)]
+    Import(Import),
+    /// Run the local upload agent that `IpcClient`s on this node log through.
+    #[cfg(unix)]
+    Agent {
+        /// Socket to listen on [default: $TRACKIO_AGENT_SOCKET or <tmp>/trackio-agent.sock]
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        /// Write-ahead log of unsent rows [default: <spool dir>/agent.wal or <socket>.wal]
+        #[arg(long)]
+        wal: Option<PathBuf>,
+        /// Flush interval in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+        /// Rows per second sent while draining the backlog after an outage
+        #[arg(long, default_value_t = 5000)]
+        drain_rate: u64,
+        /// Runs drained at the same time after an outage
+        #[arg(long, default_value_t = 4)]
+        drain_concurrency: usize,
+    },
+    /// Send the rows a training process writes to a shared-memory ring.
+    #[cfg(feature = "shm")]
+    DrainShm {
+        /// Ring file created by `ShmProducer::create`
+        path: PathBuf,
+        /// Drain interval in milliseconds
+        #[arg(long, default_value_t = 200)]
+        interval_ms: u64,
+    },
+    /// Listen for StatsD packets over UDP and log each flush interval as a row.
+    Statsd {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8125")]
+        bind: String,
+        /// Flush interval in milliseconds
+        #[arg(long, default_value_t = 10_000)]
+        interval_ms: u64,
+    },
+    /// Print the points of a run as they are logged.
+    Watch {
+        /// Show a live terminal dashboard instead of printing points
+        #[cfg(feature = "tui")]
+        #[arg(long)]
+        tui: bool,
+        /// Runs to show with --tui [default: --run]
+        #[cfg(feature = "tui")]
+        runs: Vec<String>,
+        /// Only show metrics matching these glob patterns with --tui
+        #[cfg(feature = "tui")]
+        #[arg(long = "metric")]
+        metrics: Vec<String>,
+        /// Poll interval in milliseconds
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u64,
+    },
+}
+
+#[derive(Subcommand)]
+enum Import {
+    /// Replay the scalars of a TensorBoard log directory, one run per event directory.
+    Tensorboard { logdir: PathBuf },
+    /// Import W&B runs from an export directory or from the API (`entity/project[/run_id]`).
+    Wandb { source: String },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Csv,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportKind {
+    Html,
+    Markdown,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("trackio: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), TrackioError> {
+    let client = client(&cli.target);
+    let project = cli
+        .target
+        .project
+        .clone()
+        .or_else(|| std::env::var("TRACKIO_PROJECT").ok())
+        .unwrap_or_default();
+    let run = cli
+        .target
+        .run
+        .clone()
+        .or_else(|| std::env::var("TRACKIO_RUN").ok())
+        .unwrap_or_default();
+
+    match cli.command {
+        Command::Log { metrics, step } => {
+            if metrics.is_empty() {
+                for line in io::stdin().lock().lines() {
+                    let line = line?;
+                    if let Some((metrics, step, ts)) = parse_record(&line) {
+                        client.log(metrics, step, ts);
+                    }
+                }
+            } else {
+                client.log(parse_pairs(&metrics), step, None);
+            }
+            for rejected in client.flush()?.rejected {
+                eprintln!("rejected {}: {}", rejected.item.metrics, rejected.reason);
+            }
+            Ok(())
+        }
+        Command::Tail {
+            path,
+            from_end,
+            checkpoint,
+            step_key,
+            metrics,
+            prefix,
+            interval_ms,
+        } => {
+            let mut mapping = JsonlMapping::new()
+                .step_key(step_key)
+                .metrics(metrics)
+                .prefix(&prefix)
+                .from_end(from_end)
+                .poll_interval(Duration::from_millis(interval_ms));
+            if let Some(checkpoint) = checkpoint {
+                mapping = mapping.checkpoint(checkpoint);
+            }
+            tail_jsonl(&client, &path, &mapping)
+        }
+        Command::Sync { dir } => {
+            let store = match dir {
+                Some(dir) => SqliteBackend::with_dir(dir),
+                None => SqliteBackend::new(),
+            };
+            let projects = if project.is_empty() {
+                store.projects()?
+            } else {
+                vec![project]
+            };
+            for project in projects {
+                for run_name in store.runs(&project)? {
+                    if !run.is_empty() && run_name != run {
+                        continue;
+                    }
+                    let run_client = self::client(&cli.target)
+                        .with_project(&project)
+                        .with_run(&run_name);
+                    let logs = store.logs(&project, &run_name)?;
+                    for mut row in logs.iter().cloned() {
+                        let step = row.remove("step").and_then(|s| s.as_i64());
+                        let ts = row
+                            .remove("timestamp")
+                            .and_then(|t| t.as_str().map(String::from));
+                        run_client.log(Value::Object(row), step, ts);
+                    }
+                    run_client.flush()?;
+                    eprintln!("synced {project}/{run_name}: {} rows", logs.len());
+                }
+            }
+            Ok(())
+        }
+        Command::Export {
+            runs,
+            output,
+            format,
+        } => {
+            let runs = if runs.is_empty() { vec![run] } else { runs };
+            let runs: Vec<&str> = runs.iter().map(String::as_str).collect();
+            let format = match format {
+                Format::Csv => ExportFormat::Csv,
+                #[cfg(feature = "parquet")]
+                Format::Parquet => ExportFormat::Parquet,
+            };
+            let n = client.export(&project, &runs, format, &output)?;
+            eprintln!("wrote {n} rows to {}", output.display());
+            Ok(())
+        }
+        Command::Report {
+            runs,
+            output,
+            format,
+            title,
+            metrics,
+        } => {
+            let runs = if runs.is_empty() { vec![run] } else { runs };
+            let runs: Vec<&str> = runs.iter().map(String::as_str).collect();
+            let is_md = output
+                .extension()
+                .is_some_and(|e| e == "md" || e == "markdown");
+            let format = match format {
+                Some(ReportKind::Markdown) => ReportFormat::Markdown,
+                Some(ReportKind::Html) => ReportFormat::Html,
+                None if is_md => ReportFormat::Markdown,
+                None => ReportFormat::Html,
+            };
+            let mut template = ReportTemplate::new(format).metrics(metrics);
+            if let Some(title) = &title {
+                template = template.title(title);
+            }
+            std::fs::write(&output, client.render_report(&project, &runs, &template)?)?;
+            eprintln!("wrote report to {}", output.display());
+            Ok(())
+        }
+        Command::Import(Import::Tensorboard { logdir }) => {
+            let n = client.import_tensorboard(&project, &logdir)?;
+            eprintln!("imported {n} rows into {project}");
+            Ok(())
+        }
+        Command::Import(Import::Wandb { source }) => {
+            let n = client.import_wandb(&project, &source)?;
+            eprintln!("imported {n} rows into {project}");
+            Ok(())
+        }
+        #[cfg(unix)]
+        Command::Agent {
+            socket,
+            wal,
+            interval_ms,
+            drain_rate,
+            drain_concurrency,
+        } => {
+            let spooled = client.spool_dir().is_some();
+            let mut agent = Agent::new(client)
+                .with_flush_interval(Duration::from_millis(interval_ms))
+                .with_drain_rate(drain_rate)
+                .with_drain_concurrency(drain_concurrency);
+            if let Some(socket) = socket {
+                if !spooled {
+                    let mut default_wal = socket.clone().into_os_string();
+                    default_wal.push(".wal");
+                    agent = agent.with_wal(PathBuf::from(default_wal));
+                }
+                agent = agent.with_socket(socket);
+            }
+            if let Some(wal) = wal {
+                agent = agent.with_wal(wal);
+            }
+            agent.run()
+        }
+        #[cfg(feature = "shm")]
+        Command::DrainShm { path, interval_ms } => {
+            ShmDrainer::open(&path, client)?.run(Duration::from_millis(interval_ms))
+        }
+        Command::Statsd { bind, interval_ms } => {
+            let listener = StatsdListener::bind(client, &bind)?
+                .with_flush_interval(Duration::from_millis(interval_ms));
+            eprintln!("listening for StatsD packets on {}", listener.local_addr()?);
+            listener.run()
+        }
+        Command::Watch {
+            #[cfg(feature = "tui")]
+            tui,
+            #[cfg(feature = "tui")]
+            runs,
+            #[cfg(feature = "tui")]
+            metrics,
+            interval_ms,
+        } => {
+            #[cfg(feature = "tui")]
+            if tui {
+                let runs = if runs.is_empty() { vec![run] } else { runs };
+                let runs: Vec<&str> = runs.iter().map(String::as_str).collect();
+                return client
+                    .dashboard(&project, &runs)
+                    .with_metrics(metrics)
+                    .with_poll_interval(Duration::from_millis(interval_ms))
+                    .run();
+            }
+            let watch = client
+                .watch(&project, &run)
+                .with_poll_interval(Duration::from_millis(interval_ms));
+            for point in watch {
+                let point = point?;
+                println!(
+                    "{}\t{}\t{}",
+                    point.point.step, point.metric, point.point.value
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+fn client(target: &Target) -> Client {
+    let mut client = Client::new();
+    if let Some(url) = &target.url {
+        client = client.with_base_url(url);
+    }
+    if let Some(project) = &target.project {
+        client = client.with_project(project);
+    }
+    if let Some(run) = &target.run {
+        client = client.with_run(run);
+    }
+    if let Some(token) = &target.write_token {
+        client = client.with_write_token(token);
+    }
+    client
+}
+
+/// A JSON object per line; `step` and `timestamp` keys are taken out of the metrics.
+fn parse_record(line: &str) -> Option<(Value, Option<i64>, Option<String>)> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let Ok(Value::Object(mut metrics)) = serde_json::from_str(line) else {
+        eprintln!("trackio: skipping line that is not a JSON object: {line}");
+        return None;
+    };
+    let step = metrics.remove("step").and_then(|s| s.as_i64());
+    let ts = metrics
+        .remove("timestamp")
+        .and_then(|t| t.as_str().map(String::from));
+    Some((Value::Object(metrics), step, ts))
+}
+
+fn parse_pairs(pairs: &[String]) -> Value {
+    let mut metrics = Map::new();
+    for pair in pairs {
+        let (key, raw) = pair.split_once('=').unwrap_or((pair, "true"));
+        let value = serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.into()));
+        metrics.insert(key.to_string(), value);
+    }
+    Value::Object(metrics)
+}
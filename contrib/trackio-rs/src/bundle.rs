@@ -0,0 +1,356 @@
+//! A whole project in one portable file, for moving experiments between a private
+//! server and a public Space, or keeping a backup.
+//!
+//! [`Client::export_project`] reads every run of a project from the server, with its
+//! rows, config, metadata and events, and the project's settings, and writes them to a
+//! single SQLite database in the schema of trackio's own project databases. Steps and
+//! timestamps are kept as logged. The bytes of media logged by reference to a local
+//! file, such as the `file://` URLs of a [`DirStore`](crate::media::DirStore), are
+//! embedded in the bundle too; other URLs are kept as references.
+//!
+//! [`Client::import_project`] replays a bundle into the client's server or backend under
+//! a project name of your choice, with the original steps and timestamps. Embedded media
+//! is uploaded to the client's [`MediaStore`](crate::media::MediaStore), if it has one,
+//! and the rows are rewritten to reference the new copies. Copied into a trackio
+//! directory as `<project>.db`, a bundle is also served as is by the Python dashboard or
+//! `trackio::server`.
+//!
+//! ```no_run
+//! let private = trackio::Client::new().with_base_url("http://trackio.internal:7860");
+//! let stats = private.export_project("llama-sweep", "llama-sweep.trackio")?;
+//! println!("{} runs, {} rows, {} media files", stats.runs, stats.rows, stats.media);
+//!
+//! let space = trackio::Client::new()
+//!     .with_base_url("https://me-trackio.hf.space")
+//!     .with_write_token("hf_...");
+//! space.import_project("llama-sweep.trackio", "llama-sweep-public")?;
+//! # Ok::<(), trackio::client::TrackioError>(())
+//! ```
+
+use crate::backfill::BACKFILL_CHUNK;
+use crate::checksum;
+use crate::client::{Client, LogItem, TrackioError};
+use crate::media::key_part;
+use crate::projects::ProjectSettings;
+use crate::sqlite::SqliteBackend;
+use crate::time::now_iso8601;
+use rusqlite::{params, OptionalExtension};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// Version of the bundle layout, stored under `format` in `bundle_info`.
+const FORMAT: i64 = 1;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS bundle_info (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS bundle_media (
+        url TEXT PRIMARY KEY,
+        data BLOB NOT NULL
+    );
+";
+
+/// What [`Client::export_project`] wrote, or [`Client::import_project`] replayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BundleStats {
+    pub runs: usize,
+    pub rows: usize,
+    pub events: usize,
+    /// Media files embedded, or uploaded to the media store on import.
+    pub media: usize,
+}
+
+impl Client {
+    /// Write `project`, with all its runs and the media they reference locally, to the
+    /// bundle `path`, replacing any file there; see the [module docs](crate::bundle). The
+    /// file only appears once it is complete. Metadata and events are left out on
+    /// servers without their endpoints.
+    pub fn export_project<P: AsRef<Path>>(&self, project: &str, path: P) -> Result<BundleStats, TrackioError> {
+        let path = path.as_ref();
+        let partial = path.with_extension("partial");
+        match fs::remove_file(&partial) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+        match self.write_bundle(project, &partial) {
+            Ok(stats) => {
+                fs::rename(&partial, path)?;
+                Ok(stats)
+            }
+            Err(e) => {
+                let _ = fs::remove_file(&partial);
+                Err(e)
+            }
+        }
+    }
+
+    fn write_bundle(&self, project: &str, path: &Path) -> Result<BundleStats, TrackioError> {
+        let bundle = SqliteBackend::single_file(path);
+        bundle.with_conn(project, |conn| conn.execute_batch(SCHEMA))?;
+        let mut stats = BundleStats::default();
+        let settings = match self.call_extension("get_project_settings", &json!({ "project": project })) {
+            Ok(Value::Object(settings)) => settings,
+            Ok(_) | Err(TrackioError::Unsupported(_)) => Map::new(),
+            Err(e) => return Err(e),
+        };
+        bundle.update_project_settings(project, &settings)?;
+
+        let mut runs: Vec<String> = Vec::new();
+        let records = self.call_api("get_runs_for_project", &json!({ "project": project }))?;
+        for record in records.as_array().into_iter().flatten() {
+            if let Some(name) = record.get("name").and_then(Value::as_str) {
+                if !runs.iter().any(|r| r == name) {
+                    runs.push(name.into());
+                }
+            }
+        }
+        let mut media = BTreeSet::new();
+        for run in &runs {
+            let logs = self.call_api("get_logs", &json!({ "project": project, "run": run }))?;
+            let items: Vec<LogItem> = logs
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|row| row.as_object().cloned())
+                .map(log_item)
+                .collect();
+            media.extend(items.iter().flat_map(media_urls).filter(|url| url.starts_with("file://")));
+            bundle.insert(project, run, &items)?;
+            stats.rows += items.len();
+            if let Some(config) = self.get_config(project, run)? {
+                bundle.set_config(project, run, &config)?;
+            }
+            match self.get_meta(project, run) {
+                Ok(meta) => bundle.update_run_meta(project, run, &meta)?,
+                Err(TrackioError::Unsupported(_)) => {}
+                Err(e) => return Err(e),
+            }
+            match self.events(project, run) {
+                Ok(events) => {
+                    bundle.insert_events(project, run, &events)?;
+                    stats.events += events.len();
+                }
+                Err(TrackioError::Unsupported(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        stats.runs = runs.len();
+
+        for url in media {
+            let file = url.trim_start_matches("file://");
+            let data = match fs::read(file) {
+                Ok(data) => data,
+                Err(e) => {
+                    log::warn!("trackio: not embedding {url} in the bundle: {e}");
+                    continue;
+                }
+            };
+            bundle.with_conn(project, |conn| {
+                conn.execute(
+                    "INSERT OR REPLACE INTO bundle_media (url, data) VALUES (?1, ?2)",
+                    params![url, data],
+                )
+            })?;
+            stats.media += 1;
+        }
+        let info = [
+            ("format", json!(FORMAT)),
+            ("project", json!(project)),
+            ("exported_at", json!(now_iso8601())),
+        ];
+        bundle.with_conn(project, |conn| {
+            for (key, value) in &info {
+                conn.execute(
+                    "INSERT OR REPLACE INTO bundle_info (key, value) VALUES (?1, ?2)",
+                    params![key, value.to_string()],
+                )?;
+            }
+            Ok(())
+        })?;
+        Ok(stats)
+    }
+
+    /// Replay the bundle at `path` into the project `project` of the client's server or
+    /// backend, keeping the steps and timestamps of the rows; see the
+    /// [module docs](crate::bundle). Runs are merged into runs of the same name that
+    /// exist already. Project settings, metadata and events are left out on servers
+    /// without their endpoints. Needs the write token.
+    pub fn import_project<P: AsRef<Path>>(&self, path: P, project: &str) -> Result<BundleStats, TrackioError> {
+        let path = path.as_ref();
+        if project.is_empty() {
+            return Err(TrackioError::InvalidArgument("import_project needs a project name".into()));
+        }
+        fs::metadata(path)?;
+        let bundle = SqliteBackend::single_file(path);
+        let format = bundle.with_conn(project, |conn| {
+            let exists: bool = conn.query_row(
+                "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'bundle_info'",
+                [],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                return Ok(None);
+            }
+            conn.query_row("SELECT value FROM bundle_info WHERE key = 'format'", [], |row| {
+                row.get::<_, String>(0)
+            })
+            .optional()
+        })?;
+        match format.and_then(|f| f.parse::<i64>().ok()) {
+            None => {
+                return Err(TrackioError::InvalidArgument(format!(
+                    "{} is not a trackio project bundle",
+                    path.display()
+                )))
+            }
+            Some(format) if format > FORMAT => {
+                return Err(TrackioError::InvalidArgument(format!(
+                    "{} has bundle format {format}, this version reads up to {FORMAT}",
+                    path.display()
+                )))
+            }
+            Some(_) => {}
+        }
+        let mut stats = BundleStats::default();
+
+        let settings = bundle.project_settings(project)?;
+        let text = |key| settings.get(key).and_then(Value::as_str).map(String::from);
+        let project_settings = ProjectSettings {
+            description: text("description"),
+            x_axis: text("x_axis"),
+        };
+        let mut updated = Ok(());
+        if project_settings.description.is_some() || project_settings.x_axis.is_some() {
+            updated = self.create_project(project, &project_settings);
+        }
+        if updated.is_ok() && settings.get("archived") == Some(&Value::Bool(true)) {
+            updated = self.archive_project(project);
+        }
+        skip_unsupported(updated, "project settings")?;
+
+        let mut runs = bundle.runs(project)?;
+        let others: Vec<String> = bundle.with_conn(project, |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT run_id FROM configs UNION SELECT run_id FROM run_meta
+                 UNION SELECT run_id FROM events",
+            )?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect()
+        })?;
+        for run in others {
+            if !runs.contains(&run) {
+                runs.push(run);
+            }
+        }
+        for run in &runs {
+            let target = self.for_run(project, run);
+            let mut items: Vec<LogItem> = bundle
+                .logs(project, run)?
+                .into_iter()
+                .map(log_item)
+                .collect();
+            if let Some(store) = self.media_store() {
+                for item in &mut items {
+                    let Some(row) = item.metrics.as_object_mut() else {
+                        continue;
+                    };
+                    for (metric, value) in row.iter_mut() {
+                        let Some(url) = media_url(value) else {
+                            continue;
+                        };
+                        let data: Option<Vec<u8>> = bundle.with_conn(project, |conn| {
+                            conn.query_row(
+                                "SELECT data FROM bundle_media WHERE url = ?1",
+                                params![url],
+                                |row| row.get(0),
+                            )
+                            .optional()
+                        })?;
+                        let Some(data) = data else {
+                            continue;
+                        };
+                        let extension = url.rsplit_once('.').map_or("", |(_, ext)| ext);
+                        let key = format!(
+                            "{}/{}/{}/{:08x}.{}",
+                            key_part(project),
+                            key_part(run),
+                            metric.split('/').map(key_part).collect::<Vec<_>>().join("/"),
+                            checksum::crc32(&data),
+                            key_part(extension)
+                        );
+                        value["url"] = store.put(&key, &data, "application/octet-stream")?.into();
+                        stats.media += 1;
+                    }
+                }
+            }
+            stats.rows += items.len();
+            let mut config = bundle.config(project, run)?;
+            let mut rest = items.into_iter();
+            loop {
+                let chunk: Vec<LogItem> = rest.by_ref().take(BACKFILL_CHUNK).collect();
+                if chunk.is_empty() && config.is_none() {
+                    break;
+                }
+                target.send_batch(chunk, config.take())?;
+            }
+            let meta = bundle.run_meta(project, run)?;
+            if !meta.is_empty() {
+                let sent = self.call_extension(
+                    "set_run_meta",
+                    &json!({ "project": project, "run": run, "meta": meta }),
+                );
+                skip_unsupported(sent.map(|_| ()), "run metadata")?;
+            }
+            let events = bundle.events(project, run)?;
+            if !events.is_empty() {
+                skip_unsupported(target.send_events(&events), "events")?;
+                stats.events += events.len();
+            }
+        }
+        stats.runs = runs.len();
+        Ok(stats)
+    }
+}
+
+/// `row` of `get_logs` as the item that logged it.
+fn log_item(mut row: Map<String, Value>) -> LogItem {
+    let step = row.remove("step").and_then(|s| s.as_i64());
+    let timestamp = row
+        .remove("timestamp")
+        .and_then(|t| t.as_str().map(String::from));
+    LogItem {
+        metrics: Value::Object(row),
+        step,
+        timestamp,
+    }
+}
+
+/// The URLs of the media references logged in `item`.
+fn media_urls(item: &LogItem) -> impl Iterator<Item = String> + '_ {
+    item.metrics
+        .as_object()
+        .into_iter()
+        .flat_map(|row| row.values())
+        .filter_map(|value| media_url(value).map(String::from))
+}
+
+/// The URL of a logged media reference, such as `{"_type": "trackio.image", "url":
+/// "file:///mnt/media/p/r/samples/7c1e09a2.png"}`.
+fn media_url(value: &Value) -> Option<&str> {
+    let url = value.get("url")?.as_str()?;
+    value.get("_type")?.as_str()?.starts_with("trackio.").then_some(url)
+}
+
+fn skip_unsupported(result: Result<(), TrackioError>, what: &str) -> Result<(), TrackioError> {
+    match result {
+        Err(TrackioError::Unsupported(_)) => {
+            log::warn!("trackio: the server doesn't store {what}, leaving them out of the import");
+            Ok(())
+        }
+        result => result,
+    }
+}
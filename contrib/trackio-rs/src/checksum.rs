@@ -0,0 +1,122 @@
+//! CRC-32 of request bodies, so a proxy that mangles or truncates a batch is caught
+//! instead of storing metric values nobody logged.
+//!
+//! Clients send `X-Trackio-Checksum: crc32:<hex>` of the JSON body before compression.
+//! Servers that support it (such as the one in `trackio::server`) refuse a body that
+//! doesn't match with a "checksum mismatch" error and echo the checksum they verified as
+//! `checksum` next to `data` in the response; others ignore the header.
+
+use crate::error::TrackioError;
+use crate::transport::Response;
+use serde::Deserialize;
+
+pub(crate) const HEADER: &str = "X-Trackio-Checksum";
+
+/// The start of the error servers answer when the body doesn't match its checksum.
+pub(crate) const MISMATCH: &str = "checksum mismatch";
+
+const TABLE: [u32; 256] = table();
+
+const fn table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// CRC-32 (IEEE, as in zlib and gzip) of `data`.
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// The header value for `body`.
+pub(crate) fn of(body: &[u8]) -> String {
+    format!("crc32:{:08x}", crc32(body))
+}
+
+/// Whether `body` matches the header value `checksum`. Algorithms other than CRC-32 are
+/// not checked.
+#[cfg(feature = "server")]
+pub(crate) fn matches(checksum: &str, body: &[u8]) -> bool {
+    match checksum.trim().strip_prefix("crc32:") {
+        Some(hex) => u32::from_str_radix(hex, 16) == Ok(crc32(body)),
+        None => true,
+    }
+}
+
+#[derive(Deserialize)]
+struct Echo {
+    checksum: Option<String>,
+}
+
+/// `resp`, unless the server echoed a checksum other than `sent`.
+pub(crate) fn verify_echo(sent: &str, resp: Response) -> Result<Response, TrackioError> {
+    match serde_json::from_slice::<Echo>(&resp.body) {
+        Ok(Echo {
+            checksum: Some(echoed),
+        }) if echoed != sent => Err(TrackioError::Corrupted(format!(
+            "sent {sent}, the server received {echoed}"
+        ))),
+        _ => Ok(resp),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(body: &str) -> Response {
+        Response {
+            status: 200,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn crc32_matches_the_standard_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(of(b"123456789"), "crc32:cbf43926");
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn a_header_matches_only_its_own_body() {
+        let body = br#"{"project":"p","run":"r","metrics_list":[{"loss":0.5}]}"#;
+        let header = of(body);
+        assert!(matches(&header, body));
+        assert!(matches(&format!("  {header} "), body));
+        let mut mangled = body.to_vec();
+        mangled[40] ^= 0x01;
+        assert!(!matches(&header, &mangled));
+        assert!(!matches(&header, &body[..body.len() - 1]));
+        assert!(matches("sha256:abc", &mangled));
+    }
+
+    #[test]
+    fn an_echo_of_another_checksum_is_corruption() {
+        let sent = of(b"body");
+        let echoed = format!(r#"{{"data": null, "checksum": "{sent}"}}"#);
+        assert!(verify_echo(&sent, response(&echoed)).is_ok());
+        assert!(verify_echo(&sent, response(r#"{"data": null}"#)).is_ok());
+        assert!(verify_echo(&sent, response("not json")).is_ok());
+        let err = verify_echo(&sent, response(r#"{"checksum": "crc32:00000000"}"#)).unwrap_err();
+        assert!(matches!(err, TrackioError::Corrupted(_)));
+        assert!(err.is_retryable());
+    }
+}
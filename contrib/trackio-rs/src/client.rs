@@ -1,46 +1,112 @@
+use crate::arena::RowArena;
+use crate::alerts::{alert_id, send_alerts, Alert, AlertLevel, Alerting, Trigger};
+use crate::backend::Backend;
+use crate::bandwidth::Bandwidth;
+use crate::baseline::ConfigBaseline;
+use crate::checksum;
+use crate::config::{ClientBuilder, ClientConfig, Compression};
+use crate::crash::Breadcrumbs;
+use crate::distributed::DistributedConfig;
+use crate::dump::PayloadDump;
+use crate::events::{Event, PendingEvents};
+use crate::filter::MetricFilter;
+use crate::handles::RunBuffer;
+use crate::hooks::MetricHooks;
+use crate::layout::Layout;
+use crate::media::MediaStore;
+use crate::memory::{self, MemorySampler};
+use crate::notify::{Notifier, RunEvent};
+use crate::pacing::{FlushOutcome, FlushPacer, Wakeup};
+use crate::phase::PhaseState;
+use crate::schema::MetricSchema;
+use crate::signing::RequestSigner;
+use crate::stagger::NodeStagger;
+use crate::step_timer::{StepTimer, QUANTILES};
+use crate::steps::StepTransforms;
+use crate::telemetry::Telemetry;
+use crate::value::Units;
+pub use crate::error::{ErrorKind, FailedBatch, TrackioError};
+use crate::log_item::BulkPayload;
+pub use crate::log_item::{FlushReceipt, LogItem, RejectedItem};
+use crate::time::now_iso8601;
+use crate::transport::{default_transport, user_agent, Method, Request, Response, Transport};
 use once_cell::sync::OnceCell;
 use parking_lot::Mutex;
-use reqwest::blocking::Client as Http;
-use reqwest::StatusCode;
 use serde::Serialize;
-use std::env;
-use std::time::Duration;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 /// A lightweight Trackio REST client for posting metrics to local or remote Trackio dashboards.
 #[derive(Debug)]
 pub struct Client {
     base_url: String,
     project: String,
+    project_prefix: String,
     run: String,
     write_token: Option<String>,
+    user_agent: String,
+    signer: Option<RequestSigner>,
 
-    http: Http,
+    timeout: Duration,
+    resolve: Vec<(String, SocketAddr)>,
+    transport: Option<Arc<dyn Transport>>,
+    max_retries: u32,
+    retry_backoff: Duration,
+    compression: Compression,
+    spool_dir: Option<PathBuf>,
+    bandwidth: Option<Arc<Bandwidth>>,
+    stagger: Option<Arc<NodeStagger>>,
+    pid: AtomicU32,
     cached_bulk_path: OnceCell<String>,
+    unsupported: Arc<Mutex<BTreeSet<String>>>,
+    backend: Option<Arc<dyn Backend>>,
+    media: Option<Arc<dyn MediaStore>>,
+    dry_run: Option<Mutex<DryRunStats>>,
+    distributed: Option<DistributedConfig>,
+    config: Mutex<Option<serde_json::Value>>,
+    reserved: serde_json::Map<String, serde_json::Value>,
+    config_defaults: serde_json::Map<String, serde_json::Value>,
+    baseline: ConfigBaseline,
+    reserved_sent: AtomicBool,
+    units: Units,
+    last_config: Mutex<Option<serde_json::Value>>,
+    alerting: Alerting,
+    events: PendingEvents,
+    notifier: Option<Arc<Notifier>>,
+    dump: Option<Arc<PayloadDump>>,
+    schema: Option<Arc<MetricSchema>>,
+    filter: Option<Arc<MetricFilter>>,
+    hooks: MetricHooks,
+    steps: StepTransforms,
+    telemetry: Option<Telemetry>,
+    step_timer: Option<StepTimer>,
+    memory: Option<MemorySampler>,
+    phases: Mutex<Vec<Arc<PhaseState>>>,
+    started: AtomicBool,
+    summary: Mutex<serde_json::Map<String, serde_json::Value>>,
+    breadcrumbs: Option<Breadcrumbs>,
 
     // batching
     buf: Mutex<Vec<LogItem>>,
+    handles: Mutex<BTreeMap<String, RunBuffer>>,
+    arena: RowArena,
     max_batch: usize,
     #[allow(dead_code)]
     flush_interval: Duration,
+    wakeup: Arc<Wakeup>,
 }
 
-#[derive(Debug, Clone, Serialize)]
-struct BulkPayload<'a> {
-    project: &'a str,
-    run: &'a str,
-    #[serde(rename = "metrics_list")]
-    metrics_list: Vec<serde_json::Value>,
-    steps: Vec<i64>,
-    timestamps: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    config: Option<serde_json::Value>,
-}
-
-#[derive(Debug, Clone)]
-pub struct LogItem {
-    pub metrics: serde_json::Value,
-    pub step: Option<i64>,
-    pub timestamp: Option<String>,
+impl Default for Client {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Client {
@@ -54,46 +120,88 @@ impl Client {
     /// - `TRACKIO_TIMEOUT_MS`
     /// - `TRACKIO_MAX_BATCH`
     /// - `TRACKIO_FLUSH_INTERVAL_MS`
+    /// - `TRACKIO_MAX_RETRIES`, `TRACKIO_RETRY_BACKOFF_MS`, `TRACKIO_SERVER_FLAVOR`,
+    ///   `TRACKIO_COMPRESSION`, `TRACKIO_MAX_BYTES_PER_SEC` (see [`ClientConfig`])
+    /// - `TRACKIO_WEBHOOK_URL`, `TRACKIO_WEBHOOK_MIN_LEVEL` (see [`Notifier::from_env`])
+    /// - `TRACKIO_DUMP_PAYLOADS` (see [`PayloadDump::from_env`])
     pub fn new() -> Self {
-        let base = env::var("TRACKIO_SERVER_URL").unwrap_or_else(|_| "http://127.0.0.1:7860".into());
-        let project = env::var("TRACKIO_PROJECT").unwrap_or_default();
-        let run = env::var("TRACKIO_RUN").unwrap_or_default();
-        let write_token = env::var("TRACKIO_WRITE_TOKEN").ok();
-
-        let timeout_ms = env::var("TRACKIO_TIMEOUT_MS")
-            .ok()
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(5000);
-
-        let max_batch = env::var("TRACKIO_MAX_BATCH")
-            .ok()
-            .and_then(|s| s.parse::<usize>().ok())
-            .unwrap_or(128);
-
-        let flush_interval = env::var("TRACKIO_FLUSH_INTERVAL_MS")
-            .ok()
-            .and_then(|s| s.parse::<u64>().ok())
-            .map(Duration::from_millis)
-            .unwrap_or(Duration::from_millis(200));
+        Self::from_config(ClientConfig::from_env())
+    }
+
+    /// A builder for setting up the client in code; its settings take precedence over
+    /// the environment.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
 
+    /// A client with exactly `config`. Notifier and payload dump still come from the
+    /// environment.
+    pub fn from_config(config: ClientConfig) -> Self {
+        let cached_bulk_path = OnceCell::new();
+        if let Some(path) = config.flavor.bulk_path() {
+            let _ = cached_bulk_path.set(path.to_string());
+        }
+        let stagger = config.node_stagger.map(|spacing| {
+            Arc::new(NodeStagger::new(config.spool_dir.as_deref(), spacing, config.timeout))
+        });
         Self {
-            base_url: base,
-            project,
-            run,
-            write_token,
-            http: Http::builder()
-                .timeout(Duration::from_millis(timeout_ms))
-                .build()
-                .expect("failed to build HTTP client"),
-            cached_bulk_path: OnceCell::new(),
-            buf: Mutex::new(Vec::with_capacity(max_batch)),
-            max_batch,
-            flush_interval,
+            base_url: config.base_url,
+            project: prefixed(&config.project_prefix, &config.project),
+            project_prefix: config.project_prefix,
+            run: config.run,
+            user_agent: user_agent(config.app_id.as_deref()),
+            signer: None,
+            write_token: config.write_token,
+            transport: default_transport(config.timeout, &config.resolve),
+            timeout: config.timeout,
+            resolve: config.resolve,
+            max_retries: config.max_retries,
+            retry_backoff: config.retry_backoff,
+            compression: config.compression,
+            spool_dir: config.spool_dir,
+            bandwidth: config.max_bytes_per_sec.map(|b| Arc::new(Bandwidth::new(b))),
+            stagger,
+            pid: AtomicU32::new(process::id()),
+            cached_bulk_path,
+            unsupported: Arc::default(),
+            backend: None,
+            media: None,
+            dry_run: None,
+            distributed: None,
+            config: Mutex::new(None),
+            reserved: serde_json::Map::new(),
+            config_defaults: serde_json::Map::new(),
+            baseline: ConfigBaseline::default(),
+            reserved_sent: AtomicBool::new(false),
+            units: Units::default(),
+            last_config: Mutex::new(None),
+            alerting: Alerting::default(),
+            events: PendingEvents::default(),
+            notifier: Notifier::from_env().map(Arc::new),
+            dump: PayloadDump::from_env().map(Arc::new),
+            schema: None,
+            filter: None,
+            hooks: MetricHooks::default(),
+            steps: StepTransforms::default(),
+            telemetry: None,
+            step_timer: None,
+            memory: None,
+            phases: Mutex::default(),
+            started: AtomicBool::new(false),
+            summary: Mutex::new(serde_json::Map::new()),
+            breadcrumbs: None,
+            buf: Mutex::new(Vec::with_capacity(config.max_batch)),
+            handles: Mutex::default(),
+            arena: RowArena::new(config.max_batch),
+            max_batch: config.max_batch,
+            flush_interval: config.flush_interval,
+            wakeup: Arc::default(),
         }
     }
 
+    /// Log to project `p`, with the configured project prefix if it lacks it.
     pub fn with_project(mut self, p: &str) -> Self {
-        self.project = p.into();
+        self.project = prefixed(&self.project_prefix, p);
         self
     }
 
@@ -102,69 +210,1057 @@ impl Client {
         self
     }
 
+    /// The project this client logs to.
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    /// Where unsent rows may be kept on disk, if configured.
+    pub fn spool_dir(&self) -> Option<&Path> {
+        self.spool_dir.as_deref()
+    }
+
+    /// The run this client logs to.
+    pub fn run(&self) -> &str {
+        &self.run
+    }
+
     pub fn with_base_url(mut self, u: &str) -> Self {
         self.base_url = u.into();
         self
     }
 
+    pub fn with_write_token(mut self, t: &str) -> Self {
+        self.write_token = Some(t.into());
+        self
+    }
+
+    /// Identify the application sending the metrics, such as `my-trainer/2.3`, after the
+    /// client's own `trackio-rs/<version> (rustc <version>; <os> <arch>)` in the
+    /// `User-Agent` of every request, so server operators can tell the senders apart in
+    /// their logs and scope rate limits to them. Also set by `TRACKIO_APP_ID`.
+    pub fn with_app_id(mut self, app: &str) -> Self {
+        self.user_agent = user_agent(Some(app));
+        self
+    }
+
+    /// Send `agent` as the whole `User-Agent` instead of the one described in
+    /// [`with_app_id`](Client::with_app_id).
+    pub fn with_user_agent(mut self, agent: &str) -> Self {
+        self.user_agent = agent.into();
+        self
+    }
+
+    /// Call `signer` on every request to the server, including each retry, and add the
+    /// headers it returns, so deployments behind an AWS SigV4 or HMAC gateway can
+    /// authenticate. The request it sees is final: its URL, its other headers and its
+    /// body, compressed if compression is on.
+    ///
+    /// ```no_run
+    /// # fn hmac_hex(_key: &[u8], _data: &[u8]) -> String { String::new() }
+    /// let key = std::env::var("GATEWAY_KEY").unwrap_or_default();
+    /// let client = trackio::Client::new().with_request_signer(move |request| {
+    ///     let signature = hmac_hex(key.as_bytes(), &request.body);
+    ///     vec![("X-Gateway-Signature".into(), signature)]
+    /// });
+    /// ```
+    pub fn with_request_signer<F>(mut self, signer: F) -> Self
+    where
+        F: Fn(&Request<'_>) -> Vec<(String, String)> + Send + Sync + 'static,
+    {
+        self.signer = Some(RequestSigner::new(Arc::new(signer)));
+        self
+    }
+
+    /// Connect to `addr` for URLs on `host` instead of resolving it through DNS, for
+    /// networks where the dashboard is only reachable at hard-coded addresses. `addr`
+    /// can be IPv4 or IPv6; a port in the URL takes precedence over its port. Replaces a
+    /// transport set earlier with [`with_transport`](Client::with_transport), since
+    /// custom transports resolve names themselves. Also set by `TRACKIO_RESOLVE`.
+    ///
+    /// ```no_run
+    /// let client = trackio::Client::new()
+    ///     .with_base_url("https://dash.internal")
+    ///     .with_resolve("dash.internal", "[fd00::5]:443".parse().unwrap());
+    /// ```
+    pub fn with_resolve(mut self, host: &str, addr: SocketAddr) -> Self {
+        self.resolve.push((host.into(), addr));
+        if let Some(transport) = default_transport(self.timeout, &self.resolve) {
+            self.transport = Some(transport);
+        }
+        self
+    }
+
+    /// Reach the server through `transport` instead of the default one; see
+    /// [`Transport`].
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Send flushed batches to `backend` instead of posting them to the Trackio server.
+    pub fn with_backend<B: Backend + 'static>(mut self, backend: B) -> Self {
+        self.backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// Store the bytes of [`log_media`](Client::log_media) in `store`, such as a bucket
+    /// of your own, and log only their URLs to the run.
+    pub fn with_media_store<S: MediaStore + 'static>(mut self, store: S) -> Self {
+        self.media = Some(Arc::new(store));
+        self
+    }
+
+    /// Serialize and validate every batch and alert, count them and log them at debug
+    /// level (via the `log` crate), but never send them. Reads such as
+    /// [`history`](Client::history) still go to the server.
+    ///
+    /// Useful to check instrumentation in CI and to benchmark logging overhead in
+    /// isolation; see [`dry_run_stats`](Client::dry_run_stats).
+    pub fn with_dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = enabled.then(Mutex::default);
+        self
+    }
+
+    /// Upload at most `bytes` per second, so metric uploads from hundreds of workers
+    /// behind one NAT don't saturate the link the training data also needs. Requests
+    /// wait until their body fits in the budget, with bursts of up to a second's worth;
+    /// the background flusher then sends fewer, larger batches. Clients derived from this
+    /// one, such as [`child`](Client::child) runs and sweep trials, share the cap.
+    ///
+    /// Also set by `TRACKIO_MAX_BYTES_PER_SEC`.
+    pub fn with_max_bytes_per_sec(mut self, bytes: u64) -> Self {
+        self.bandwidth = Some(Arc::new(Bandwidth::new(bytes)));
+        self
+    }
+
+    /// Take turns with the other processes on this host that set it, starting bulk posts
+    /// at least `spacing` apart, so 64 workers flushing every 200ms don't fall into step
+    /// and hit the server in bursts. Turns are taken through a lock file in the
+    /// [spool directory](ClientConfig::spool_dir), or in the temporary directory, so
+    /// containers sharing a node need a shared spool directory. A post waits for its
+    /// turn at most the request timeout; retries wait outside it.
+    ///
+    /// Also set by `TRACKIO_NODE_STAGGER_MS`. Routing every process through one
+    /// [`trackio agent`](crate::agent) is the alternative when they can reach a socket.
+    pub fn with_node_stagger(mut self, spacing: Duration) -> Self {
+        self.stagger = Some(Arc::new(NodeStagger::new(
+            self.spool_dir.as_deref(),
+            spacing,
+            self.timeout,
+        )));
+        self
+    }
+
+    /// Log the client's own health to the run once a minute, so delivery problems show
+    /// up in the dashboard they affect. Each report is a row of `trackio/*` metrics at
+    /// the latest step logged: `queue_depth` (rows waiting to be sent), and for the past
+    /// minute `flushes`, `failed_flushes`, `flush_latency_ms` and `max_flush_latency_ms`,
+    /// `retries` (requests sent again after a retryable failure) and `dropped` (rows the
+    /// server rejected or that a failed background flush lost).
+    ///
+    /// Reports go out with the next flush after the minute is up, and bypass hooks,
+    /// filters and the schema.
+    pub fn with_telemetry(mut self, enabled: bool) -> Self {
+        self.telemetry = enabled.then(Telemetry::default);
+        self
+    }
+
+    /// Time the steps of the training loop from the rows it logs: whenever a row comes
+    /// with a higher step than the last, the wall time since that one, per step advanced,
+    /// goes into a sketch (rows without a step count one each). Every flush that has new
+    /// timings adds a row at the latest step with `trackio/step_time/p50`, `p95` and
+    /// `p99` in seconds, within 1% of the true quantiles, and `trackio/step_time/count`,
+    /// so stragglers and slow data loading show up without instrumenting the loop.
+    ///
+    /// The rows bypass hooks, filters and the schema.
+    pub fn with_step_timer(mut self, enabled: bool) -> Self {
+        self.step_timer = enabled.then(StepTimer::default);
+        if enabled {
+            for (key, _) in QUANTILES {
+                self.units.set(key, "s");
+            }
+        }
+        self
+    }
+
+    /// Log the memory use of the process, under `system/memory/*`, at most every
+    /// `interval` with the flushes; see the [module docs](crate::memory). The rows go at
+    /// the latest step logged and bypass hooks, filters and the schema. With
+    /// [`spawn_flusher`](Client::spawn_flusher), memory keeps being sampled while the
+    /// run logs nothing.
+    pub fn with_memory_tracking(mut self, interval: Duration) -> Self {
+        for key in memory::BYTE_KEYS {
+            self.units.set(key, "bytes");
+        }
+        self.memory = Some(MemorySampler::new(interval));
+        self
+    }
+
+    /// What a dry-run client would have sent so far, `None` unless dry run is enabled.
+    pub fn dry_run_stats(&self) -> Option<DryRunStats> {
+        self.dry_run.as_ref().map(|stats| stats.lock().clone())
+    }
+
+    /// Log according to a rank-aware policy, so every rank of a distributed job can call
+    /// [`log`](Client::log) without `if rank == 0` guards or duplicate runs.
+    pub fn with_distributed(mut self, config: DistributedConfig) -> Self {
+        self.distributed = Some(config);
+        self
+    }
+
+    /// Put the run in a group, like the `group` argument of Python's `trackio.init`. The
+    /// dashboard can show or hide a whole group at once.
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.reserved.insert("_Group".into(), group.into());
+        self
+    }
+
+    /// Tag the run, e.g. `["baseline", "vision"]`, for filtering runs across a project.
+    /// Tags are kept in the run's config as `_Tags`, next to `_Group`.
+    pub fn with_tags(mut self, tags: &[&str]) -> Self {
+        self.reserved.insert("_Tags".into(), tags.into());
+        self
+    }
+
+    /// Lay out the run's dashboard: which metrics are plotted together, on which scale
+    /// and with what smoothing; see the [module docs](crate::layout). The layout is kept
+    /// in the run's config as `_Layout`.
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        let layout = serde_json::to_value(layout).expect("layout serializes");
+        self.reserved.insert("_Layout".into(), layout);
+        self
+    }
+
+    /// Config keys every config of the run starts from, such as a team's standard
+    /// settings; keys set with [`set_config`](Client::set_config) take precedence.
+    /// Sent with the first flush even if no config is set.
+    pub fn with_default_config(
+        mut self,
+        defaults: serde_json::Map<String, serde_json::Value>,
+    ) -> Self {
+        self.config_defaults.extend(defaults);
+        self
+    }
+
+    /// Compare the run's config with the config of `run`, a baseline in the same
+    /// project, when it is first set. Every key that differs is recorded in the config
+    /// as `config_diff/<key>`, holding both values, so the dashboard shows what changed.
+    /// Differences in keys other than those matching the `expected` globs are logged as
+    /// warnings and passed to the [`on_config_diff`](Client::on_config_diff) callbacks,
+    /// catching a forgotten learning rate change before the run burns GPU hours.
+    pub fn with_config_baseline(mut self, run: &str, expected: &[&str]) -> Self {
+        self.baseline.set(run, expected);
+        self
+    }
+
+    /// Call `callback` with the unexpected differences from the baseline run set with
+    /// [`with_config_baseline`](Client::with_config_baseline), if there are any. In each
+    /// [`ValueChange`](crate::diff::ValueChange), `a` is the baseline's value and `b`
+    /// this run's.
+    pub fn on_config_diff<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&[crate::diff::ValueChange]) + Send + Sync + 'static,
+    {
+        self.baseline.add_callback(Arc::new(callback));
+        self
+    }
+
+    /// Record `parent` (in the same project) as the run that launched this one. Unless
+    /// a group is set, the run is also grouped under the parent's name.
+    pub fn with_parent(mut self, parent: &str) -> Self {
+        self.reserved.insert("_Parent".into(), parent.into());
+        self.reserved
+            .entry("_Group")
+            .or_insert_with(|| parent.into());
+        self
+    }
+
+    /// Raise an alert when `metric` meets `trigger`, e.g. `alert_if("train/loss", IsNaN)`
+    /// or `alert_if("val/acc", Below(0.1).for_steps(500))`. A rule fires once when its
+    /// condition starts holding and again only after it has cleared.
+    pub fn alert_if(mut self, metric: &str, trigger: impl Into<Trigger>) -> Self {
+        self.alerting.add_rule(metric, trigger.into());
+        self
+    }
+
+    /// Raise an alert when nothing has been logged for `after`, counted from the last
+    /// logged row.
+    pub fn alert_if_no_log_for(mut self, after: Duration) -> Self {
+        self.alerting.set_stall(after);
+        self
+    }
+
+    /// Watch for stalled training: if nothing is logged for `window`, log
+    /// `system/stalled = 1` at the last logged step and raise a "No metrics logged"
+    /// alert, then log `system/stalled = 0` once rows arrive again. Catches dataloader
+    /// deadlocks and hung collectives on unattended runs.
+    pub fn with_watchdog(mut self, window: Duration) -> Self {
+        self.alerting.set_stall(window);
+        self.alerting.log_stalls();
+        self
+    }
+
+    /// Keep the last `rows` logged rows in memory, to include them in the crash report
+    /// of [`install_panic_hook`](Client::install_panic_hook).
+    pub fn with_crash_breadcrumbs(mut self, rows: usize) -> Self {
+        self.breadcrumbs = Some(Breadcrumbs::new(rows));
+        self
+    }
+
+    /// Time since the last row was logged, `None` before the first one.
+    pub fn since_last_log(&self) -> Option<Duration> {
+        self.alerting.since_last_log()
+    }
+
+    /// Call `callback` for every alert as it is raised, before it is sent.
+    pub fn on_alert<F: Fn(&Alert) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.alerting.add_callback(Arc::new(callback));
+        self
+    }
+
+    /// Post to `notifier` when the run starts, finishes ([`close`](Client::close)), crashes
+    /// ([`fail`](Client::fail)) or raises an alert. Replaces the notifier configured from
+    /// `TRACKIO_WEBHOOK_URL`.
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = Some(Arc::new(notifier));
+        self
+    }
+
+    /// Add `offset` to every explicit step, e.g. to continue a resumed run where the
+    /// previous attempt stopped while the training loop counts from zero again.
+    /// Stacks with other offsets and [`with_step_transform`](Client::with_step_transform).
+    pub fn with_step_offset(self, offset: i64) -> Self {
+        self.with_step_transform(move |step| step.saturating_add(offset))
+    }
+
+    /// Rewrite every explicit step with `transform` before it is used, by alerts and
+    /// distributed sampling as well as on the server. Applies to [`log`](Client::log),
+    /// [`alert`](Client::alert) and [`log_backfill`](Client::log_backfill); rows logged
+    /// without a step are numbered by the server as before. Transforms run in the order
+    /// they were added and are not inherited by [`child`](Client::child) runs.
+    ///
+    /// ```no_run
+    /// let phase = 2;
+    /// let client = trackio::Client::new()
+    ///     .with_project("curriculum")
+    ///     .with_run("r")
+    ///     .with_step_transform(move |step| phase * 1_000_000 + step);
+    /// ```
+    pub fn with_step_transform<F>(mut self, transform: F) -> Self
+    where
+        F: Fn(i64) -> i64 + Send + Sync + 'static,
+    {
+        self.steps.push(Arc::new(transform));
+        self
+    }
+
+    /// Check every logged row against `schema`, coercing or dropping values of the
+    /// wrong type before they are sent; see [`MetricSchema`].
+    pub fn with_schema(mut self, schema: MetricSchema) -> Self {
+        self.schema = Some(Arc::new(schema));
+        self
+    }
+
+    /// Log only the metrics whose names match one of the `allow` globs (all of them when
+    /// `allow` is empty) and none of the `deny` globs, e.g. to silence `debug/*` in
+    /// production while keeping the logging calls. `*` matches any run of characters.
+    /// Rows left without metrics are not logged.
+    ///
+    /// ```no_run
+    /// let client = trackio::Client::new()
+    ///     .with_project("p")
+    ///     .with_run("r")
+    ///     .with_metric_filter(&[], &["debug/*", "*/grad_norm_*"]);
+    /// ```
+    pub fn with_metric_filter(mut self, allow: &[&str], deny: &[&str]) -> Self {
+        self.filter = Some(Arc::new(MetricFilter::new(allow, deny)));
+        self
+    }
+
+    /// Run `hook` on every logged row before it is filtered, checked and buffered, to
+    /// keep conversions in one place instead of at every call site. Hooks run in the
+    /// order they are added.
+    ///
+    /// ```no_run
+    /// let client = trackio::Client::new()
+    ///     .with_project("p")
+    ///     .with_run("r")
+    ///     .with_metrics_hook(|row| {
+    ///         if let Some(loss) = row.remove("loss") {
+    ///             row.insert("train/loss".into(), loss);
+    ///         }
+    ///     })
+    ///     .with_metrics_hook(|row| {
+    ///         for value in row.values_mut() {
+    ///             if let Some(f) = value.as_f64() {
+    ///                 *value = format!("{f:.3e}").parse::<f64>().unwrap_or(f).into();
+    ///             }
+    ///         }
+    ///     });
+    /// ```
+    pub fn with_metrics_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut serde_json::Map<String, serde_json::Value>) + Send + Sync + 'static,
+    {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    /// Write every request sent to the server, and its outcome, to `dump`. Replaces the
+    /// dump configured from `TRACKIO_DUMP_PAYLOADS`.
+    pub fn with_payload_dump(mut self, dump: PayloadDump) -> Self {
+        self.dump = Some(Arc::new(dump));
+        self
+    }
+
+    /// Raise an alert on the run. It is sent with the next flush.
+    pub fn alert(&self, title: &str, text: Option<&str>, level: AlertLevel, step: Option<i64>) {
+        self.alerting.raise(Alert {
+            title: title.into(),
+            text: text.map(Into::into),
+            level,
+            step: self.steps.apply(step),
+            timestamp: now_iso8601(),
+            id: alert_id(),
+        });
+    }
+
+    /// A client for a run launched by this one (a worker, an eval job), recorded on the
+    /// server as its child.
+    pub fn child(&self, run: &str) -> Client {
+        self.for_run(&self.project, run).with_parent(&self.run)
+    }
+
+    /// A client with the same server, credentials and backend, logging to another run.
+    pub(crate) fn for_run(&self, project: &str, run: &str) -> Client {
+        Client {
+            base_url: self.base_url.clone(),
+            project: project.into(),
+            project_prefix: self.project_prefix.clone(),
+            run: run.into(),
+            write_token: self.write_token.clone(),
+            user_agent: self.user_agent.clone(),
+            signer: self.signer.clone(),
+            timeout: self.timeout,
+            resolve: self.resolve.clone(),
+            transport: self.transport.clone(),
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+            compression: self.compression,
+            spool_dir: self.spool_dir.clone(),
+            bandwidth: self.bandwidth.clone(),
+            stagger: self.stagger.clone(),
+            pid: AtomicU32::new(process::id()),
+            cached_bulk_path: self.cached_bulk_path.clone(),
+            unsupported: self.unsupported.clone(),
+            backend: self.backend.clone(),
+            media: self.media.clone(),
+            dry_run: self.dry_run.as_ref().map(|_| Mutex::default()),
+            distributed: self.distributed.clone(),
+            config: Mutex::new(None),
+            reserved: serde_json::Map::new(),
+            config_defaults: serde_json::Map::new(),
+            baseline: ConfigBaseline::default(),
+            reserved_sent: AtomicBool::new(false),
+            units: Units::default(),
+            last_config: Mutex::new(None),
+            alerting: Alerting::default(),
+            events: PendingEvents::default(),
+            notifier: self.notifier.clone(),
+            dump: self.dump.clone(),
+            schema: self.schema.clone(),
+            filter: self.filter.clone(),
+            hooks: self.hooks.clone(),
+            steps: StepTransforms::default(),
+            telemetry: self.telemetry.as_ref().map(|_| Telemetry::default()),
+            step_timer: self.step_timer.as_ref().map(|_| StepTimer::default()),
+            memory: None,
+            phases: Mutex::default(),
+            started: AtomicBool::new(false),
+            summary: Mutex::new(serde_json::Map::new()),
+            breadcrumbs: None,
+            buf: Mutex::new(Vec::with_capacity(self.max_batch)),
+            handles: Mutex::default(),
+            arena: RowArena::new(self.max_batch),
+            max_batch: self.max_batch,
+            flush_interval: self.flush_interval,
+            wakeup: Arc::default(),
+        }
+    }
+
+    /// Set the run's config (hyperparameters). It is sent with the next flush and
+    /// replaces any config stored for the run.
+    pub fn set_config(&self, mut config: serde_json::Value) {
+        if !self.sends_config() {
+            return;
+        }
+        self.baseline.check(self, &mut config);
+        *self.config.lock() = Some(config);
+    }
+
+    /// Record that `metric` is in `unit`, such as `s` or `bytes`. Units are sent in the
+    /// run's config under `_Units`, with the next flush when one is new, so the dashboard
+    /// and code reading the run back know what the numbers mean. [`log!`](crate::log)
+    /// records the units of [`MetricValue`](crate::value::MetricValue)s by itself.
+    pub fn set_unit(&self, metric: &str, unit: &str) {
+        self.units.set(metric, unit);
+    }
+
     /// Logs a single metric dictionary into the in-memory buffer.
     /// Auto-flushes when `max_batch` is reached.
     pub fn log(&self, metrics: serde_json::Value, step: Option<i64>, ts: Option<String>) {
+        if self.stage(metrics, step, ts) {
+            if let Err(e) = self.flush() {
+                self.dropped(&e);
+            }
+        }
+    }
+
+    /// [`log`](Client::log) without the flush a full buffer triggers, for callers that
+    /// flush, and keep what fails to send, themselves. Returns whether the buffer is full.
+    pub(crate) fn stage(&self, metrics: serde_json::Value, step: Option<i64>, ts: Option<String>) -> bool {
+        self.detect_fork();
+        let step = self.steps.apply(step);
+        if let Some(telemetry) = &self.telemetry {
+            telemetry.observe_step(step);
+        }
+        if let Some(timer) = &self.step_timer {
+            timer.observe(step);
+        }
+        let mut full = self.collect_reduced();
+        let routed = match &self.distributed {
+            Some(d) => d.route(step, metrics),
+            None => Some(metrics),
+        };
+        if let Some(metrics) = routed {
+            full |= self.push_row(metrics, step, ts);
+        }
+        full
+    }
+
+    /// Buffer the rows combined across ranks since the last call, as if logged. Returns
+    /// whether the buffer is full.
+    fn collect_reduced(&self) -> bool {
+        let Some(distributed) = &self.distributed else {
+            return false;
+        };
+        let mut full = false;
+        for item in distributed.reduced() {
+            full |= self.push_row(item.metrics, item.step, item.timestamp);
+        }
+        full
+    }
+
+    /// Buffer a row that passed the distributed policy. Returns whether the buffer is full.
+    fn push_row(&self, metrics: serde_json::Value, step: Option<i64>, ts: Option<String>) -> bool {
+        let Some(metrics) = self.prepare_metrics(metrics) else {
+            return false;
+        };
+        let metrics = self.apply_schema(metrics);
+        if let Some(breadcrumbs) = &self.breadcrumbs {
+            breadcrumbs.push(step, &metrics);
+        }
+        self.alerting.observe(&metrics, step);
+        self.alerting.start_watcher(self);
+        if self.notifier.is_some() {
+            if let Some(row) = metrics.as_object() {
+                let mut summary = self.summary.lock();
+                for (key, value) in row {
+                    summary.insert(key.clone(), value.clone());
+                }
+            }
+        }
         let mut buf = self.buf.lock();
         buf.push(LogItem {
             metrics,
             step,
-            timestamp: ts,
+            timestamp: ts.or_else(|| Some(self.timestamp())),
+        });
+        if buf.len() == self.hot_rows() {
+            self.wakeup.wake();
+        }
+        buf.len() >= self.max_batch
+    }
+
+    /// Flush from a background thread until the client is dropped, so rows reach the
+    /// dashboard without waiting for a full batch.
+    ///
+    /// The pace adapts to the load: every `flush_interval` under steady logging, right
+    /// away once half a batch is waiting, backing off to a few seconds while nothing is
+    /// logged, and slower while the server takes longer than the interval to answer, so
+    /// rows are coalesced into fewer requests. Failed batches are put back when the error
+    /// is retryable and dropped with a warning otherwise.
+    ///
+    /// ```no_run
+    /// let client = std::sync::Arc::new(trackio::Client::new().with_project("p").with_run("r"));
+    /// client.spawn_flusher();
+    /// ```
+    pub fn spawn_flusher(self: &Arc<Self>) {
+        let client = Arc::downgrade(self);
+        let wakeup = self.wakeup.clone();
+        let mut pacer = FlushPacer::new(self.flush_interval, self.hot_rows());
+        let mut delay = self.flush_interval;
+        thread::spawn(move || loop {
+            wakeup.wait(delay);
+            let Some(client) = client.upgrade() else {
+                return;
+            };
+            let started = Instant::now();
+            let outcome = match client.flush() {
+                Ok(receipt) => FlushOutcome::Sent {
+                    rows: receipt.sent,
+                    took: started.elapsed(),
+                },
+                Err(e) => {
+                    let retry_after = e.retry_after();
+                    if !e.is_retryable() {
+                        log::warn!("trackio: dropping batch: {e}");
+                        client.dropped(&e);
+                    } else if let Some(batch) = e.into_batch() {
+                        client.requeue(batch);
+                    }
+                    FlushOutcome::Failed { retry_after }
+                }
+            };
+            delay = pacer.next(outcome);
         });
-        if buf.len() >= self.max_batch {
-            drop(buf);
-            let _ = self.flush(); // best-effort flush
+    }
+
+    /// Counts the rows of a failed batch that are not put back.
+    fn dropped(&self, e: &TrackioError) {
+        if let (Some(telemetry), Some(batch)) = (&self.telemetry, e.batch()) {
+            telemetry.dropped(batch.items.len());
         }
     }
 
-    /// Flush all buffered metrics to the Trackio server.
-    pub fn flush(&self) -> Result<(), TrackioError> {
-        let items = {
+    /// Rows waiting that make the background flusher send right away.
+    fn hot_rows(&self) -> usize {
+        (self.max_batch / 2).max(1)
+    }
+
+    /// Flush all buffered metrics to the Trackio server, returning what the server
+    /// reported about them.
+    pub fn flush(&self) -> Result<FlushReceipt, TrackioError> {
+        self.detect_fork();
+        self.collect_reduced();
+        let (items, config) = {
             let mut buf = self.buf.lock();
-            if buf.is_empty() {
-                return Ok(());
+            if let Some(telemetry) = &self.telemetry {
+                if let Some(mut report) = telemetry.report(buf.len()) {
+                    report.timestamp = Some(self.timestamp());
+                    buf.push(report);
+                }
+            }
+            if let Some(mut report) = self.step_timer.as_ref().and_then(StepTimer::report) {
+                report.timestamp = Some(self.timestamp());
+                buf.push(report);
+            }
+            if let Some(mut report) = self.memory.as_ref().and_then(|m| m.report(self.last_step())) {
+                report.timestamp = Some(self.timestamp());
+                buf.push(report);
+            }
+            let mut config = self.config.lock().take();
+            let units_changed = self.units.take_changed();
+            if config.is_some() {
+                self.last_config.lock().clone_from(&config);
+            } else if units_changed {
+                config = self.last_config.lock().clone();
+            }
+            let reserved_pending = config.is_some()
+                || units_changed
+                || !self.reserved_sent.load(Ordering::Relaxed);
+            if self.has_reserved_keys() && reserved_pending {
+                config = Some(self.with_reserved_keys(config));
             }
-            let out = buf.clone();
-            buf.clear();
-            out
+            let out = if buf.is_empty() {
+                Vec::new()
+            } else {
+                std::mem::replace(&mut *buf, self.arena.batch(self.max_batch))
+            };
+            (out, config)
         };
+        let handles = std::mem::take(&mut *self.handles.lock());
+        let mut receipt = FlushReceipt::default();
+        if !items.is_empty() || config.is_some() || !handles.is_empty() {
+            let sends_reserved = config.is_some() && self.has_reserved_keys();
+            let sends_rows = !items.is_empty();
+            let started = Instant::now();
+            let sent = if handles.is_empty() {
+                self.send_batch(items, config)
+            } else {
+                self.send_coalesced(RunBuffer { items, config }, handles)
+            };
+            if let Some(telemetry) = &self.telemetry {
+                telemetry.flushed(started.elapsed(), sent.is_ok());
+                if let Ok(receipt) = &sent {
+                    telemetry.dropped(receipt.rejected.len());
+                }
+            }
+            receipt = sent?;
+            if sends_reserved {
+                self.reserved_sent.store(true, Ordering::Relaxed);
+            }
+            if sends_rows && !self.started.swap(true, Ordering::Relaxed) {
+                self.notify(RunEvent::Started);
+            }
+        }
+        if let Err((e, alerts)) = send_alerts(self, self.alerting.take_pending()) {
+            self.alerting.requeue(alerts);
+            return Err(e);
+        }
+        self.events.send(self)?;
+        Ok(receipt)
+    }
 
-        let mut metrics_list = Vec::with_capacity(items.len());
-        let mut steps = Vec::with_capacity(items.len());
-        let mut timestamps = Vec::with_capacity(items.len());
+    fn has_reserved_keys(&self) -> bool {
+        !self.reserved.is_empty()
+            || !self.config_defaults.is_empty()
+            || self.units.to_json().is_some()
+    }
 
-        for it in items {
-            metrics_list.push(it.metrics);
-            steps.push(it.step.unwrap_or(-1));
-            timestamps.push(it.timestamp.unwrap_or_else(|| "".into()));
+    pub(crate) fn with_reserved_keys(&self, config: Option<serde_json::Value>) -> serde_json::Value {
+        let mut config = match config {
+            Some(serde_json::Value::Object(map)) => map,
+            _ => serde_json::Map::new(),
+        };
+        for (key, value) in &self.config_defaults {
+            config.entry(key).or_insert_with(|| value.clone());
+        }
+        for (key, value) in &self.reserved {
+            config.insert(key.clone(), value.clone());
         }
+        if let Some(units) = self.units.to_json() {
+            config.insert("_Units".into(), units);
+        }
+        serde_json::Value::Object(config)
+    }
 
-        let payload = BulkPayload {
-            project: &self.project,
-            run: &self.run,
-            metrics_list,
-            steps,
-            timestamps,
-            config: None,
+    /// Posts `event` to the notifier, if any and not in dry run. Notifications are
+    /// best-effort.
+    pub(crate) fn notify(&self, event: RunEvent) {
+        let (Some(notifier), None) = (&self.notifier, &self.dry_run) else {
+            return;
         };
+        let dashboard = format!(
+            "{}/?project={}",
+            self.base_url.trim_end_matches('/'),
+            urlencoding::encode(&self.project)
+        );
+        let _ = notifier.notify(&self.project, &self.run_name(), Some(&dashboard), &event);
+    }
 
-        // Discover a working bulk endpoint once.
-        let path = self.cached_bulk_path.get_or_try_init(|| {
-            if self.try_post("/api/bulk_log", &payload).is_ok() {
-                return Ok("/api/bulk_log".to_string());
+    pub(crate) fn run_name(&self) -> Cow<'_, str> {
+        match &self.distributed {
+            Some(d) => Cow::Owned(d.run_name(&self.run)),
+            None => Cow::Borrowed(self.run.as_str()),
+        }
+    }
+
+    /// Sends `items` and `config` to the backend or server right away, bypassing the buffer.
+    /// Failures come back as [`TrackioError::Batch`], carrying what wasn't sent.
+    ///
+    /// When the server rejects the batch as a whole and says which rows it refused, the
+    /// rows are sent again in halves, down to single rows, so one malformed row doesn't
+    /// lose the others; the rows it still rejects on their own, or in halves after
+    /// [`MAX_BISECT_DEPTH`] splits, are reported in the receipt and dropped. A rejection
+    /// naming no row, such as an unknown project, fails the batch at once.
+    pub(crate) fn send_batch(
+        &self,
+        items: Vec<LogItem>,
+        config: Option<serde_json::Value>,
+    ) -> Result<FlushReceipt, TrackioError> {
+        self.send_run(&self.run_name(), items, config)
+    }
+
+    /// [`send_batch`](Client::send_batch) for `run` of the client's project.
+    pub(crate) fn send_run(
+        &self,
+        run: &str,
+        mut items: Vec<LogItem>,
+        config: Option<serde_json::Value>,
+    ) -> Result<FlushReceipt, TrackioError> {
+        let failed = |items, config, error| {
+            TrackioError::Batch(Box::new(FailedBatch {
+                project: self.project.clone(),
+                run: run.to_string(),
+                items,
+                config,
+                error,
+            }))
+        };
+        match self.send_rows(run, &items, config.as_ref()) {
+            Ok(receipt) => {
+                self.recycle(items);
+                Ok(receipt)
             }
-            if self.try_post("/gradio_api/bulk_log", &payload).is_ok() {
-                return Ok("/gradio_api/bulk_log".to_string());
+            Err(e) if e.kind() == ErrorKind::Rejected && items.len() > 1 && names_rows(&e) => {
+                if let Some(c) = &config {
+                    if let Err(e) = self.send_rows(run, &[], Some(c)) {
+                        return Err(failed(items, config, e));
+                    }
+                }
+                let mut receipt = FlushReceipt::default();
+                match self.bisect(run, &items, 1, &mut receipt) {
+                    Ok(()) => {
+                        self.recycle(items);
+                        Ok(receipt)
+                    }
+                    Err((e, sent)) => Err(failed(items.split_off(sent), None, e)),
+                }
             }
-            Err(TrackioError::NoBulkEndpoint)
-        })?;
+            Err(e) => Err(failed(items, config, e)),
+        }
+    }
 
-        self.try_post(path, &payload)
+    fn send_rows(
+        &self,
+        run: &str,
+        items: &[LogItem],
+        config: Option<&serde_json::Value>,
+    ) -> Result<FlushReceipt, TrackioError> {
+        match (&self.dry_run, &self.backend) {
+            (Some(stats), _) => dry_run_batch(stats, &self.project, run, items, config)
+                .map(|()| FlushReceipt::unconfirmed(items.len())),
+            (None, Some(backend)) => {
+                send_to_backend(backend.as_ref(), &self.project, run, items, config)
+                    .map(|()| FlushReceipt::unconfirmed(items.len()))
+            }
+            (None, None) => self.post_bulk(&self.project, run, items, config),
+        }
+    }
+
+    /// Sends the two halves of `items` separately, splitting those the server rejects
+    /// again until `depth` reaches [`MAX_BISECT_DEPTH`]. On another failure, returns it
+    /// with the number of rows already handled.
+    fn bisect(
+        &self,
+        run: &str,
+        items: &[LogItem],
+        depth: u32,
+        receipt: &mut FlushReceipt,
+    ) -> Result<(), (TrackioError, usize)> {
+        let (left, right) = items.split_at(items.len() / 2);
+        for half in [left, right] {
+            match self.send_rows(run, half, None) {
+                Ok(sent) => receipt.append(sent),
+                Err(e) if e.kind() == ErrorKind::Rejected && half.len() > 1 && depth < MAX_BISECT_DEPTH => {
+                    self.bisect(run, half, depth + 1, receipt)?
+                }
+                Err(e) if e.kind() == ErrorKind::Rejected => {
+                    log::warn!("trackio: dropping {} row(s) the server rejected: {e}", half.len());
+                    for item in half {
+                        receipt.append(FlushReceipt::rejected(item.clone(), e.to_string()));
+                    }
+                }
+                Err(e) => return Err((e, receipt.sent)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Put the rows and config of a failed [`flush`](Client::flush) back at the front of
+    /// the buffer, to be sent with the next one.
+    ///
+    /// ```no_run
+    /// # let client = trackio::Client::new();
+    /// if let Err(e) = client.flush() {
+    ///     if e.is_retryable() {
+    ///         if let Some(batch) = e.into_batch() {
+    ///             client.requeue(batch);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn requeue(&self, batch: FailedBatch) {
+        if batch.run != self.run_name() {
+            let buffer = RunBuffer {
+                items: batch.items,
+                config: batch.config,
+            };
+            return self.requeue_handle(batch.run, buffer);
+        }
+        let mut buf = self.buf.lock();
+        buf.splice(0..0, batch.items);
+        if let Some(config) = batch.config {
+            self.config.lock().get_or_insert(config);
+        }
+    }
+
+    /// Puts the rows and config of a [`RunHandle`](crate::handles::RunHandle) back at the
+    /// front of its buffer.
+    pub(crate) fn requeue_handle(&self, run: String, buffer: RunBuffer) {
+        let mut handles = self.handles.lock();
+        let pending = handles.entry(run).or_default();
+        pending.items.splice(0..0, buffer.items);
+        if let Some(config) = buffer.config {
+            pending.config.get_or_insert(config);
+        }
+    }
+
+    /// Buffers `item` for the run handle `run`, returning whether the rows buffered for
+    /// all runs make a full batch.
+    pub(crate) fn buffer_handle_row(&self, run: &str, item: LogItem) -> bool {
+        self.detect_fork();
+        let mut handles = self.handles.lock();
+        handles.entry(run.to_string()).or_default().items.push(item);
+        let rows = handles.values().map(|b| b.items.len()).sum::<usize>() + self.buf.lock().len();
+        if rows == self.hot_rows() {
+            self.wakeup.wake();
+        }
+        rows >= self.max_batch
+    }
+
+    /// Buffers `items` for this client's run behind the rows logged with
+    /// [`log`](Client::log), without waking the flusher; they go out with the next flush.
+    pub(crate) fn buffer_background(&self, items: Vec<LogItem>) {
+        self.detect_fork();
+        let run = self.run_name().into_owned();
+        self.handles.lock().entry(run).or_default().items.extend(items);
+    }
+
+    /// Buffers a row the client produced itself, such as a throughput report, with the
+    /// rows logged with [`log`](Client::log), bypassing hooks, filters and the schema.
+    pub(crate) fn buffer_report(&self, item: LogItem) {
+        self.buf.lock().push(item);
+    }
+
+    /// Whether this rank sends the run's config, and other rows logged once per run.
+    pub(crate) fn sends_config(&self) -> bool {
+        self.distributed.as_ref().is_none_or(|d| d.logs_config())
+    }
+
+    pub(crate) fn set_handle_config(&self, run: &str, config: serde_json::Value) {
+        self.handles.lock().entry(run.to_string()).or_default().config = Some(config);
+    }
+
+    /// The current time for a row's timestamp, reusing the string of a sent row.
+    pub(crate) fn timestamp(&self) -> String {
+        self.arena.timestamp()
+    }
+
+    /// Takes back the rows of a sent batch for their allocations.
+    pub(crate) fn recycle(&self, items: Vec<LogItem>) {
+        self.arena.recycle(items);
+    }
+
+    /// Whether batches go to a server, rather than a backend or a dry run.
+    pub(crate) fn posts_to_server(&self) -> bool {
+        self.dry_run.is_none() && self.backend.is_none()
+    }
+
+    /// Posts one bulk request for `project`/`run` to the server, discovering the bulk
+    /// endpoint on first use.
+    pub(crate) fn post_bulk(
+        &self,
+        project: &str,
+        run: &str,
+        items: &[LogItem],
+        config: Option<&serde_json::Value>,
+    ) -> Result<FlushReceipt, TrackioError> {
+        let resp = self.post_to_bulk_endpoint(&BulkPayload::new(project, run, items, config))?;
+        Ok(FlushReceipt::from_body(items, &resp.body))
+    }
+
+    /// Check up front that the server is reachable and accepts this client's write token,
+    /// so a wrong or missing token fails at startup rather than on every flush while the
+    /// run logs nothing.
+    ///
+    /// Posts the bulk log a flush would, without rows, which writes nothing. Fails with
+    /// [`TrackioError::Unauthorized`] when the server refuses the token (401 for a
+    /// missing or wrong one, 403 for one without write access). Always succeeds with a
+    /// backend or in dry run.
+    ///
+    /// ```no_run
+    /// let client = trackio::Client::new().with_project("p").with_run("r");
+    /// client.validate()?;
+    /// # Ok::<(), trackio::client::TrackioError>(())
+    /// ```
+    pub fn validate(&self) -> Result<(), TrackioError> {
+        if self.backend.is_some() || self.dry_run.is_some() {
+            return Ok(());
+        }
+        self.post_bulk(&self.project, &self.run_name(), &[], None)
+            .map(|_| ())
+    }
+
+    /// Post `payload` to the server's bulk endpoint, discovering it on first use. Only a
+    /// 404 moves on to the next candidate, and only a successful post is cached, so a
+    /// server that is still waking up can't pin the wrong path.
+    pub(crate) fn post_to_bulk_endpoint<T: Serialize>(&self, payload: &T) -> Result<Response, TrackioError> {
+        if let Some(path) = self.cached_bulk_path.get() {
+            return self.try_post(path, payload);
+        }
+
+        for path in ["/api/bulk_log", "/gradio_api/bulk_log"] {
+            match self.try_post(path, payload) {
+                Ok(resp) => {
+                    let _ = self.cached_bulk_path.set(path.to_string());
+                    return Ok(resp);
+                }
+                Err(TrackioError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(TrackioError::NoBulkEndpoint)
+    }
+
+    /// Make the client usable in a process forked from the one that created it, e.g. a
+    /// data-loader worker.
+    ///
+    /// The HTTP client's connection thread does not survive `fork()`, and rows buffered
+    /// in the parent would otherwise be sent twice, so the child gets a fresh HTTP client,
+    /// an empty buffer and an empty run summary; the parent still sends its own rows. The
+    /// client does this by itself when it sees the process id change, but calling it
+    /// right after forking, before the child starts threads that use the client, is the
+    /// safe option.
+    pub fn after_fork(&self) {
+        self.pid.store(process::id(), Ordering::Relaxed);
+        self.reset_after_fork();
+    }
+
+    /// Locks held at the time of the fork belong to threads that do not exist in the
+    /// child, so they are released before the state they guard is replaced.
+    fn reset_after_fork(&self) {
+        unsafe {
+            if self.buf.is_locked() {
+                self.buf.force_unlock();
+            }
+            if self.config.is_locked() {
+                self.config.force_unlock();
+            }
+            if self.summary.is_locked() {
+                self.summary.force_unlock();
+            }
+            if self.handles.is_locked() {
+                self.handles.force_unlock();
+            }
+        }
+        self.buf.lock().clear();
+        self.summary.lock().clear();
+        self.handles.lock().clear();
+        self.config.lock().take();
+        self.arena.after_fork();
+        if let Some(transport) = &self.transport {
+            transport.after_fork();
+        }
+    }
+
+    fn detect_fork(&self) {
+        let (old, pid) = (self.pid.load(Ordering::Relaxed), process::id());
+        if old != pid
+            && self
+                .pid
+                .compare_exchange(old, pid, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        {
+            self.reset_after_fork();
+        }
+    }
+
+    fn transport(&self) -> Result<&dyn Transport, TrackioError> {
+        self.detect_fork();
+        self.transport.as_deref().ok_or(TrackioError::NoTransport)
     }
 
     /// Internal helper to send JSON POST and map non-2xx responses.
@@ -172,38 +1268,407 @@ impl Client {
         &self,
         path: P,
         payload: &T,
-    ) -> Result<(), TrackioError> {
+    ) -> Result<Response, TrackioError> {
         let url = format!("{}{}", self.base_url, path.as_ref());
-        let mut req = self.http.post(url).json(payload);
+        let mut attempt = 0;
+        loop {
+            let sent = match &self.stagger {
+                Some(stagger) => stagger.run(|| self.post_json(&url, payload)),
+                None => self.post_json(&url, payload),
+            };
+            match sent {
+                Ok(resp) => return Ok(resp),
+                Err(e) if e.is_retryable() && attempt < self.max_retries => {
+                    if let Some(telemetry) = &self.telemetry {
+                        telemetry.retried();
+                    }
+                    let backoff = self.retry_backoff.saturating_mul(1 << attempt.min(16));
+                    thread::sleep(
+                        e.retry_after()
+                            .unwrap_or(backoff)
+                            .min(Duration::from_secs(60)),
+                    );
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Posts `payload` with the write token, dumping the request and its outcome if a
+    /// payload dump is set.
+    fn post_json<T: Serialize>(
+        &self,
+        url: &str,
+        payload: &T,
+    ) -> Result<Response, TrackioError> {
+        let transport = self.transport()?;
+        let body = serde_json::to_vec(payload)
+            .map_err(|e| TrackioError::InvalidArgument(e.to_string()))?;
+        let checksum = checksum::of(&body);
+        let mut headers = vec![
+            ("Content-Type", "application/json"),
+            ("User-Agent", self.user_agent.as_str()),
+            (checksum::HEADER, checksum.as_str()),
+        ];
+        let body = match self.compression {
+            Compression::None => body,
+            #[cfg(feature = "gzip")]
+            Compression::Gzip => {
+                headers.push(("Content-Encoding", "gzip"));
+                gzip(&body)?
+            }
+        };
         if let Some(tok) = &self.write_token {
-            req = req.header("X-Trackio-Write-Token", tok);
+            headers.push(("X-Trackio-Write-Token", tok));
+        }
+        if let Some(bandwidth) = &self.bandwidth {
+            bandwidth.acquire(body.len());
+        }
+        if let Some(dump) = &self.dump {
+            dump.request(url, self.write_token.is_some(), payload);
         }
-        let resp = req.send().map_err(TrackioError::Http)?;
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let body = resp.text().unwrap_or_default();
-            if status == StatusCode::NOT_FOUND {
-                return Err(TrackioError::NotFound(body));
+        let started = Instant::now();
+        let result = self.send_signed(
+            transport,
+            Request {
+                method: Method::Post,
+                url,
+                headers,
+                body,
+            },
+        )
+        .and_then(|resp| checksum::verify_echo(&checksum, resp));
+        if let Some(dump) = &self.dump {
+            let outcome = match &result {
+                Ok(resp) => resp.status.to_string(),
+                Err(e) => format!("{:?}: {e}", e.kind()),
+            };
+            dump.response(url, &outcome, started.elapsed());
+        }
+        result
+    }
+
+    /// Block until the server answers its health endpoint, polling with backoff.
+    ///
+    /// Call this before the first flush when the dashboard may still be starting, e.g. a
+    /// Hugging Face Space waking from sleep. Fails with [`TrackioError::NotReady`] once
+    /// `timeout` has elapsed.
+    pub fn wait_until_ready(&self, timeout: Duration) -> Result<(), TrackioError> {
+        let deadline = Instant::now() + timeout;
+        let mut delay = Duration::from_millis(100);
+        loop {
+            if self.ping().is_ok() {
+                return Ok(());
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(TrackioError::NotReady(timeout));
             }
-            return Err(TrackioError::Status(status.as_u16(), body));
+            thread::sleep(delay.min(deadline - now));
+            delay = (delay * 2).min(Duration::from_secs(5));
         }
+    }
+
+    /// Checks that the server answers `GET /version` with a success status.
+    pub(crate) fn ping(&self) -> Result<(), TrackioError> {
+        self.send_signed(
+            self.transport()?,
+            Request {
+                method: Method::Get,
+                url: &format!("{}/version", self.base_url),
+                headers: vec![("User-Agent", &self.user_agent)],
+                body: Vec::new(),
+            },
+        )?;
         Ok(())
     }
 
-    /// Flush remaining metrics and stop background tasks (if any).
+    /// Sends `request` with the headers of the [request
+    /// signer](Client::with_request_signer), if any.
+    fn send_signed(
+        &self,
+        transport: &dyn Transport,
+        request: Request<'_>,
+    ) -> Result<Response, TrackioError> {
+        let Some(signer) = &self.signer else {
+            return transport.send(request);
+        };
+        let signed = signer.headers(&request);
+        let mut headers = request.headers;
+        headers.extend(signed.iter().map(|(name, value)| (name.as_str(), value.as_str())));
+        transport.send(Request { headers, ..request })
+    }
+
+    /// Counts `alerts` instead of sending them when dry run is enabled. Returns whether it
+    /// did.
+    pub(crate) fn dry_run_alerts(&self, alerts: &[Alert]) -> bool {
+        let Some(stats) = &self.dry_run else {
+            return false;
+        };
+        for alert in alerts {
+            log::debug!(
+                "trackio dry run: alert {:?} on {}/{}: {}",
+                alert.title,
+                self.project,
+                self.run,
+                alert.text.as_deref().unwrap_or("")
+            );
+        }
+        stats.lock().alerts += alerts.len() as u64;
+        true
+    }
+
+    pub(crate) fn dry_run_events(&self, events: &[Event]) -> bool {
+        let Some(stats) = &self.dry_run else {
+            return false;
+        };
+        for event in events {
+            log::debug!(
+                "trackio dry run: event {:?} on {}/{}: {}",
+                event.name,
+                self.project,
+                self.run,
+                serde_json::Value::Object(event.fields.clone())
+            );
+        }
+        stats.lock().events += events.len() as u64;
+        true
+    }
+
+    pub(crate) fn map_step(&self, step: Option<i64>) -> Option<i64> {
+        self.steps.apply(step)
+    }
+
+    pub(crate) fn breadcrumbs(&self) -> Option<&Breadcrumbs> {
+        self.breadcrumbs.as_ref()
+    }
+
+    pub(crate) fn pending_events(&self) -> &PendingEvents {
+        &self.events
+    }
+
+    /// Whether a thread holds the buffer, config or events right now, so logging or
+    /// flushing from a panic hook on that thread would deadlock.
+    pub(crate) fn buffers_in_use(&self) -> bool {
+        self.buf.is_locked() || self.config.is_locked() || self.events.in_use()
+    }
+
+    /// The step of the last row logged, as sent.
+    pub(crate) fn last_step(&self) -> Option<i64> {
+        self.alerting.last_step()
+    }
+
+    pub(crate) fn phases(&self) -> &Mutex<Vec<Arc<PhaseState>>> {
+        &self.phases
+    }
+
+    /// `metrics` after the [hooks](Client::with_metrics_hook), without those excluded by
+    /// [`with_metric_filter`](Client::with_metric_filter), or `None` if the row is left
+    /// empty.
+    pub(crate) fn prepare_metrics(&self, metrics: serde_json::Value) -> Option<serde_json::Value> {
+        let metrics = self.hooks.apply(metrics);
+        match &self.filter {
+            Some(filter) => filter.apply(metrics),
+            None => Some(metrics),
+        }
+    }
+
+    /// `metrics` checked against the schema set with [`with_schema`](Client::with_schema).
+    pub(crate) fn apply_schema(&self, metrics: serde_json::Value) -> serde_json::Value {
+        match &self.schema {
+            Some(schema) => schema.apply(metrics),
+            None => metrics,
+        }
+    }
+
+    pub(crate) fn media_store(&self) -> Option<&Arc<dyn MediaStore>> {
+        self.media.as_ref()
+    }
+
+    pub(crate) fn backend(&self) -> Option<&Arc<dyn Backend>> {
+        self.backend.as_ref()
+    }
+
+    pub(crate) fn max_batch(&self) -> usize {
+        self.max_batch
+    }
+
+    #[cfg(feature = "http")]
+    pub(crate) fn write_token(&self) -> Option<&str> {
+        self.write_token.as_deref()
+    }
+
+    /// Calls a named endpoint of the Trackio HTTP API (`/api/<name>`) with keyword
+    /// arguments and returns the unwrapped `data` field of the response.
+    pub(crate) fn call_api(
+        &self,
+        name: &str,
+        kwargs: &serde_json::Value,
+    ) -> Result<serde_json::Value, TrackioError> {
+        let resp = self.post_json(&format!("{}/api/{}", self.base_url, name), kwargs)?;
+        let mut body: serde_json::Value = serde_json::from_slice(&resp.body)
+            .map_err(|e| TrackioError::Status(resp.status, format!("invalid JSON: {e}")))?;
+        Ok(body
+            .get_mut("data")
+            .map(serde_json::Value::take)
+            .unwrap_or(serde_json::Value::Null))
+    }
+
+    /// Calls an endpoint only some servers have, such as those `trackio::server` adds to
+    /// the Python dashboard's API. A 404 means the server lacks it, which is remembered
+    /// so later calls fail with [`TrackioError::Unsupported`] without a request.
+    pub(crate) fn call_extension(
+        &self,
+        name: &str,
+        kwargs: &serde_json::Value,
+    ) -> Result<serde_json::Value, TrackioError> {
+        if self.unsupported.lock().contains(name) {
+            return Err(TrackioError::Unsupported(name.into()));
+        }
+        match self.call_api(name, kwargs) {
+            Err(TrackioError::NotFound(_)) => {
+                self.unsupported.lock().insert(name.into());
+                Err(TrackioError::Unsupported(name.into()))
+            }
+            result => result,
+        }
+    }
+
+    /// Flush remaining metrics and stop background tasks (if any). Notifies that the run
+    /// finished, with the last value of each metric, if a notifier is set.
     pub fn close(&self) -> Result<(), TrackioError> {
-        self.flush()
+        if let Some(distributed) = &self.distributed {
+            distributed.finish();
+        }
+        self.flush()?;
+        let summary = std::mem::take(&mut *self.summary.lock());
+        self.notify(RunEvent::Finished { summary });
+        Ok(())
+    }
+
+    /// Flush remaining metrics and notify that the run crashed with `error`. Call it from
+    /// the error path of a training loop instead of [`close`](Client::close).
+    pub fn fail(&self, error: &str) -> Result<(), TrackioError> {
+        let flushed = self.flush();
+        self.notify(RunEvent::Crashed {
+            error: error.into(),
+        });
+        flushed.map(|_| ())
+    }
+}
+
+/// Payloads a client in dry-run mode would have sent. See [`Client::with_dry_run`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DryRunStats {
+    /// Bulk requests, including config-only ones.
+    pub batches: u64,
+    pub rows: u64,
+    /// Serialized size of the bulk requests' JSON bodies.
+    pub bytes: u64,
+    pub configs: u64,
+    pub alerts: u64,
+    pub events: u64,
+}
+
+/// How many times [`Client::send_batch`] halves a rejected batch, bounding the requests
+/// one flush makes to a few hundred.
+pub(crate) const MAX_BISECT_DEPTH: u32 = 8;
+
+/// Whether a rejection says which rows were refused, with a `rejected` list or by naming
+/// a row, so the other rows may go through on their own.
+fn names_rows(e: &TrackioError) -> bool {
+    let TrackioError::Rejected(_, body) = e else {
+        return false;
+    };
+    let listed = serde_json::from_str::<serde_json::Value>(body).is_ok_and(|body| {
+        ["/rejected", "/data/rejected", "/detail/rejected", "/error/rejected"]
+            .iter()
+            .any(|path| body.pointer(path).and_then(serde_json::Value::as_array).is_some_and(|r| !r.is_empty()))
+    });
+    let lower = body.to_lowercase();
+    listed
+        || ["row", "index", "item"].iter().any(|word| {
+            lower.match_indices(word).any(|(at, _)| {
+                lower[at + word.len()..]
+                    .trim_start_matches([' ', '#', '[', ':'])
+                    .starts_with(|c: char| c.is_ascii_digit())
+            })
+        })
+}
+
+fn dry_run_batch(
+    stats: &Mutex<DryRunStats>,
+    project: &str,
+    run: &str,
+    items: &[LogItem],
+    config: Option<&serde_json::Value>,
+) -> Result<(), TrackioError> {
+    if project.is_empty() || run.is_empty() {
+        return Err(TrackioError::InvalidArgument(
+            "project and run must be set".into(),
+        ));
+    }
+    for item in items {
+        let Some(row) = item.metrics.as_object() else {
+            return Err(TrackioError::InvalidArgument(format!(
+                "logged metrics must be a JSON object, got {}",
+                item.metrics
+            )));
+        };
+        if row.keys().any(|k| k.is_empty()) {
+            return Err(TrackioError::InvalidArgument(
+                "metric names must not be empty".into(),
+            ));
+        }
+    }
+    if config.is_some_and(|c| !c.is_object()) {
+        return Err(TrackioError::InvalidArgument(
+            "config must be a JSON object".into(),
+        ));
     }
+    let rows = items.len() as u64;
+    let has_config = config.is_some();
+    let payload = BulkPayload::new(project, run, items, config);
+    let body = serde_json::to_string(&payload)
+        .map_err(|e| TrackioError::InvalidArgument(e.to_string()))?;
+    log::debug!("trackio dry run: bulk_log {body}");
+    let mut stats = stats.lock();
+    stats.batches += 1;
+    stats.rows += rows;
+    stats.bytes += body.len() as u64;
+    stats.configs += u64::from(has_config);
+    Ok(())
 }
 
-#[derive(thiserror::Error, Debug)]
-pub enum TrackioError {
-    #[error("no Trackio bulk endpoint found")]
-    NoBulkEndpoint,
-    #[error("HTTP error: {0}")]
-    Http(#[from] reqwest::Error),
-    #[error("404 Not Found: {0}")]
-    NotFound(String),
-    #[error("HTTP {0}: {1}")]
-    Status(u16, String),
-}
\ No newline at end of file
+fn send_to_backend(
+    backend: &dyn Backend,
+    project: &str,
+    run: &str,
+    items: &[LogItem],
+    config: Option<&serde_json::Value>,
+) -> Result<(), TrackioError> {
+    if let Some(config) = config {
+        backend.set_config(project, run, config)?;
+    }
+    if items.is_empty() {
+        return Ok(());
+    }
+    backend.send(project, run, items)
+}
+
+fn prefixed(prefix: &str, project: &str) -> String {
+    if project.is_empty() || project.starts_with(prefix) {
+        project.to_string()
+    } else {
+        format!("{prefix}{project}")
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip(body: &[u8]) -> Result<Vec<u8>, TrackioError> {
+    use std::io::Write;
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(body)?;
+    Ok(encoder.finish()?)
+}
@@ -0,0 +1,543 @@
+use crate::client::{Client, TrackioError};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Every setting of a [`Client`], resolved.
+///
+/// [`from_env`](ClientConfig::from_env) starts from the defaults, applies the config file
+/// (see [`from_file`](ClientConfig::from_file)), then the `TRACKIO_*` environment
+/// variables; [`ClientBuilder`] applies settings made in code on top. Libraries that log on behalf of their users can build a `ClientConfig` by hand
+/// (starting from `ClientConfig::default()`) to ignore the environment entirely.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfig {
+    /// `TRACKIO_SERVER_URL` (default: `http://127.0.0.1:7860`).
+    pub base_url: String,
+    /// `TRACKIO_PROJECT`.
+    pub project: String,
+    /// `TRACKIO_PROJECT_PREFIX`, put in front of every project name that doesn't
+    /// already start with it, so a team's projects share a namespace.
+    pub project_prefix: String,
+    /// `TRACKIO_RUN`.
+    pub run: String,
+    /// `TRACKIO_WRITE_TOKEN`.
+    pub write_token: Option<String>,
+    /// `TRACKIO_TIMEOUT_MS`, per request (default: 5s).
+    pub timeout: Duration,
+    /// `TRACKIO_MAX_BATCH`, rows buffered before a flush (default: 128).
+    pub max_batch: usize,
+    /// `TRACKIO_FLUSH_INTERVAL_MS` (default: 200ms).
+    pub flush_interval: Duration,
+    /// `TRACKIO_MAX_RETRIES`, extra attempts for a bulk post that failed with a
+    /// retryable error (default: 0).
+    pub max_retries: u32,
+    /// `TRACKIO_RETRY_BACKOFF_MS`, wait before the first retry, doubled for each one
+    /// after (default: 500ms). A `Retry-After` from the server takes precedence.
+    pub retry_backoff: Duration,
+    /// `TRACKIO_SERVER_FLAVOR`: `auto`, `trackio` or `gradio`.
+    pub flavor: ServerFlavor,
+    /// `TRACKIO_COMPRESSION`: `none` or `gzip`.
+    pub compression: Compression,
+    /// `TRACKIO_SPOOL_DIR`, where unsent rows are kept on disk, e.g. by the
+    /// [`Agent`](crate::agent::Agent).
+    pub spool_dir: Option<PathBuf>,
+    /// `TRACKIO_MAX_BYTES_PER_SEC`, a cap on the bytes the client uploads per second,
+    /// so metric uploads from many workers behind one link leave bandwidth for the
+    /// training data (default: none).
+    pub max_bytes_per_sec: Option<u64>,
+    /// `TRACKIO_APP_ID`, the application sending the metrics, such as `my-trainer/2.3`,
+    /// appended to the `User-Agent` of every request.
+    pub app_id: Option<String>,
+    /// `TRACKIO_RESOLVE`, host names to connect to at a fixed address instead of
+    /// resolving them, as `host=addr` pairs separated by commas
+    /// (`dash.internal=10.0.0.5:443,dash6.internal=[fd00::5]:443`).
+    pub resolve: Vec<(String, SocketAddr)>,
+    /// `TRACKIO_NODE_STAGGER_MS`, the least time between the starts of two bulk posts
+    /// from the processes on this host; see
+    /// [`Client::with_node_stagger`](crate::Client::with_node_stagger) (default: off).
+    pub node_stagger: Option<Duration>,
+}
+
+/// Which bulk endpoint the server has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ServerFlavor {
+    /// Try `/api/bulk_log`, then `/gradio_api/bulk_log`, on the first post.
+    #[default]
+    Auto,
+    /// `/api/bulk_log`, as served by Trackio and the `server` feature.
+    Trackio,
+    /// `/gradio_api/bulk_log`, as served by Gradio apps.
+    Gradio,
+}
+
+/// How request bodies are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    /// Gzip bodies with `Content-Encoding: gzip`. Only for servers that accept them,
+    /// such as the `server` feature built with `gzip`.
+    #[cfg(feature = "gzip")]
+    Gzip,
+}
+
+impl ServerFlavor {
+    pub(crate) fn bulk_path(self) -> Option<&'static str> {
+        match self {
+            ServerFlavor::Auto => None,
+            ServerFlavor::Trackio => Some("/api/bulk_log"),
+            ServerFlavor::Gradio => Some("/gradio_api/bulk_log"),
+        }
+    }
+}
+
+impl FromStr for ServerFlavor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(ServerFlavor::Auto),
+            "trackio" => Ok(ServerFlavor::Trackio),
+            "gradio" => Ok(ServerFlavor::Gradio),
+            other => Err(format!("unknown server flavor {other:?}")),
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" | "" => Ok(Compression::None),
+            #[cfg(feature = "gzip")]
+            "gzip" => Ok(Compression::Gzip),
+            other => Err(format!("unsupported compression {other:?}")),
+        }
+    }
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            base_url: "http://127.0.0.1:7860".into(),
+            project: String::new(),
+            project_prefix: String::new(),
+            run: String::new(),
+            write_token: None,
+            timeout: Duration::from_millis(5000),
+            max_batch: 128,
+            flush_interval: Duration::from_millis(200),
+            max_retries: 0,
+            retry_backoff: Duration::from_millis(500),
+            flavor: ServerFlavor::Auto,
+            compression: Compression::None,
+            spool_dir: None,
+            max_bytes_per_sec: None,
+            app_id: None,
+            resolve: Vec::new(),
+            node_stagger: None,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// The defaults, overridden by the config file, then by whichever `TRACKIO_*`
+    /// variables are set. Unparsable values are ignored, and so is a config file that
+    /// can't be read, with a warning through the `log` crate.
+    pub fn from_env() -> Self {
+        let profile = env::var("TRACKIO_PROFILE").ok();
+        let mut config = match Self::from_file(default_config_path(), profile.as_deref()) {
+            Ok(config) => config,
+            Err(e) => {
+                log::warn!("trackio: ignoring config file: {e}");
+                Self::default()
+            }
+        };
+        config.apply_env();
+        config
+    }
+
+    /// The defaults, overridden by the TOML file at `path`: its top-level settings, then
+    /// those of the `[profile.<name>]` section if a profile is given. A missing file
+    /// gives the defaults; a missing profile is an error.
+    ///
+    /// ```toml
+    /// server_url = "https://team-trackio.hf.space"
+    /// token_path = "~/.config/trackio/token"
+    /// project_prefix = "vision-"
+    /// spool_dir = "/scratch/trackio"
+    /// max_retries = 3
+    ///
+    /// [profile.staging]
+    /// server_url = "https://team-trackio-staging.hf.space"
+    /// ```
+    ///
+    /// Keys are those of [`ClientConfig`], with `server_url` for `base_url`, durations as
+    /// `timeout_ms`, `flush_interval_ms` and `retry_backoff_ms`, and `token_path` naming a
+    /// file that holds the write token.
+    pub fn from_file<P: AsRef<Path>>(path: P, profile: Option<&str>) -> Result<Self, TrackioError> {
+        let path = path.as_ref();
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound && profile.is_none() => {
+                return Ok(Self::default())
+            }
+            Err(e) => return Err(TrackioError::Config(format!("{}: {e}", path.display()))),
+        };
+        let mut file: ConfigFile = toml::from_str(&text)
+            .map_err(|e| TrackioError::Config(format!("{}: {e}", path.display())))?;
+        let mut config = Self::default();
+        file.settings.apply(&mut config)?;
+        if let Some(name) = profile {
+            let settings = file.profile.remove(name).ok_or_else(|| {
+                TrackioError::Config(format!("{}: no [profile.{name}]", path.display()))
+            })?;
+            settings.apply(&mut config)?;
+        }
+        Ok(config)
+    }
+
+    fn apply_env(&mut self) {
+        if let Ok(url) = env::var("TRACKIO_SERVER_URL") {
+            self.base_url = url;
+        }
+        if let Ok(project) = env::var("TRACKIO_PROJECT") {
+            self.project = project;
+        }
+        if let Ok(prefix) = env::var("TRACKIO_PROJECT_PREFIX") {
+            self.project_prefix = prefix;
+        }
+        if let Ok(run) = env::var("TRACKIO_RUN") {
+            self.run = run;
+        }
+        if let Ok(token) = env::var("TRACKIO_WRITE_TOKEN") {
+            self.write_token = Some(token);
+        }
+        if let Some(ms) = parsed("TRACKIO_TIMEOUT_MS") {
+            self.timeout = Duration::from_millis(ms);
+        }
+        if let Some(rows) = parsed("TRACKIO_MAX_BATCH") {
+            self.max_batch = rows;
+        }
+        if let Some(ms) = parsed("TRACKIO_FLUSH_INTERVAL_MS") {
+            self.flush_interval = Duration::from_millis(ms);
+        }
+        if let Some(retries) = parsed("TRACKIO_MAX_RETRIES") {
+            self.max_retries = retries;
+        }
+        if let Some(ms) = parsed("TRACKIO_RETRY_BACKOFF_MS") {
+            self.retry_backoff = Duration::from_millis(ms);
+        }
+        if let Some(flavor) = parsed("TRACKIO_SERVER_FLAVOR") {
+            self.flavor = flavor;
+        }
+        if let Some(compression) = parsed("TRACKIO_COMPRESSION") {
+            self.compression = compression;
+        }
+        if let Some(dir) = env::var_os("TRACKIO_SPOOL_DIR") {
+            self.spool_dir = Some(dir.into());
+        }
+        if let Some(bytes) = parsed("TRACKIO_MAX_BYTES_PER_SEC") {
+            self.max_bytes_per_sec = Some(bytes);
+        }
+        if let Ok(app) = env::var("TRACKIO_APP_ID") {
+            self.app_id = Some(app);
+        }
+        if let Ok(resolve) = env::var("TRACKIO_RESOLVE") {
+            match parse_resolve(&resolve) {
+                Ok(resolve) => self.resolve = resolve,
+                Err(e) => log::warn!("trackio: ignoring TRACKIO_RESOLVE: {e}"),
+            }
+        }
+        if let Some(ms) = parsed("TRACKIO_NODE_STAGGER_MS") {
+            self.node_stagger = Some(Duration::from_millis(ms));
+        }
+    }
+}
+
+/// `$TRACKIO_CONFIG`, else `config.toml` under `$XDG_CONFIG_HOME/trackio` or
+/// `~/.config/trackio`.
+pub fn default_config_path() -> PathBuf {
+    if let Some(path) = env::var_os("TRACKIO_CONFIG") {
+        return path.into();
+    }
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home().join(".config"))
+        .join("trackio")
+        .join("config.toml")
+}
+
+fn home() -> PathBuf {
+    PathBuf::from(env::var_os("HOME").unwrap_or_else(|| ".".into()))
+}
+
+fn expand_home(path: PathBuf) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home().join(rest),
+        Err(_) => path,
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(flatten)]
+    settings: FileSettings,
+    #[serde(default)]
+    profile: BTreeMap<String, FileSettings>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FileSettings {
+    server_url: Option<String>,
+    project: Option<String>,
+    project_prefix: Option<String>,
+    write_token: Option<String>,
+    token_path: Option<PathBuf>,
+    timeout_ms: Option<u64>,
+    max_batch: Option<usize>,
+    flush_interval_ms: Option<u64>,
+    max_retries: Option<u32>,
+    retry_backoff_ms: Option<u64>,
+    flavor: Option<ServerFlavor>,
+    compression: Option<Compression>,
+    spool_dir: Option<PathBuf>,
+    max_bytes_per_sec: Option<u64>,
+    app_id: Option<String>,
+    resolve: Option<BTreeMap<String, SocketAddr>>,
+    node_stagger_ms: Option<u64>,
+}
+
+impl FileSettings {
+    fn apply(self, config: &mut ClientConfig) -> Result<(), TrackioError> {
+        if let Some(url) = self.server_url {
+            config.base_url = url;
+        }
+        if let Some(project) = self.project {
+            config.project = project;
+        }
+        if let Some(prefix) = self.project_prefix {
+            config.project_prefix = prefix;
+        }
+        if let Some(token) = self.write_token {
+            config.write_token = Some(token);
+        }
+        if let Some(path) = self.token_path {
+            let path = expand_home(path);
+            let token = fs::read_to_string(&path)
+                .map_err(|e| TrackioError::Config(format!("{}: {e}", path.display())))?;
+            config.write_token = Some(token.trim().to_string());
+        }
+        if let Some(ms) = self.timeout_ms {
+            config.timeout = Duration::from_millis(ms);
+        }
+        if let Some(rows) = self.max_batch {
+            config.max_batch = rows.max(1);
+        }
+        if let Some(ms) = self.flush_interval_ms {
+            config.flush_interval = Duration::from_millis(ms);
+        }
+        if let Some(retries) = self.max_retries {
+            config.max_retries = retries;
+        }
+        if let Some(ms) = self.retry_backoff_ms {
+            config.retry_backoff = Duration::from_millis(ms);
+        }
+        if let Some(flavor) = self.flavor {
+            config.flavor = flavor;
+        }
+        if let Some(compression) = self.compression {
+            config.compression = compression;
+        }
+        if let Some(dir) = self.spool_dir {
+            config.spool_dir = Some(expand_home(dir));
+        }
+        if let Some(bytes) = self.max_bytes_per_sec {
+            config.max_bytes_per_sec = Some(bytes);
+        }
+        if let Some(app) = self.app_id {
+            config.app_id = Some(app);
+        }
+        if let Some(resolve) = self.resolve {
+            config.resolve = resolve.into_iter().collect();
+        }
+        if let Some(ms) = self.node_stagger_ms {
+            config.node_stagger = Some(Duration::from_millis(ms));
+        }
+        Ok(())
+    }
+}
+
+fn parsed<T: FromStr>(var: &str) -> Option<T> {
+    env::var(var).ok()?.trim().parse().ok()
+}
+
+fn parse_resolve(value: &str) -> Result<Vec<(String, SocketAddr)>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (host, addr) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("{pair:?} is not host=addr"))?;
+            let addr = addr
+                .trim()
+                .parse()
+                .map_err(|e| format!("{pair:?}: {e}"))?;
+            Ok((host.trim().to_string(), addr))
+        })
+        .collect()
+}
+
+/// Builds a [`Client`] from settings made in code, which take precedence over the
+/// environment and the config file.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use trackio::config::ServerFlavor;
+///
+/// let client = trackio::Client::builder()
+///     .base_url("https://me-trackio.hf.space")
+///     .project("p")
+///     .run("r")
+///     .timeout(Duration::from_secs(30))
+///     .max_retries(3)
+///     .flavor(ServerFlavor::Gradio)
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    config: ClientConfig,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClientBuilder {
+    /// Start from [`ClientConfig::from_env`].
+    pub fn new() -> Self {
+        Self::from_config(ClientConfig::from_env())
+    }
+
+    /// Start from `config` instead of the environment.
+    pub fn from_config(config: ClientConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn base_url(mut self, url: &str) -> Self {
+        self.config.base_url = url.into();
+        self
+    }
+
+    pub fn project(mut self, project: &str) -> Self {
+        self.config.project = project.into();
+        self
+    }
+
+    pub fn project_prefix(mut self, prefix: &str) -> Self {
+        self.config.project_prefix = prefix.into();
+        self
+    }
+
+    pub fn run(mut self, run: &str) -> Self {
+        self.config.run = run.into();
+        self
+    }
+
+    pub fn write_token(mut self, token: &str) -> Self {
+        self.config.write_token = Some(token.into());
+        self
+    }
+
+    /// Per-request timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    /// Rows buffered before a flush.
+    pub fn max_batch(mut self, rows: usize) -> Self {
+        self.config.max_batch = rows.max(1);
+        self
+    }
+
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.config.flush_interval = interval;
+        self
+    }
+
+    /// Extra attempts for a bulk post that failed with a retryable error.
+    pub fn max_retries(mut self, retries: u32) -> Self {
+        self.config.max_retries = retries;
+        self
+    }
+
+    /// Wait before the first retry, doubled for each one after.
+    pub fn retry_backoff(mut self, backoff: Duration) -> Self {
+        self.config.retry_backoff = backoff;
+        self
+    }
+
+    pub fn flavor(mut self, flavor: ServerFlavor) -> Self {
+        self.config.flavor = flavor;
+        self
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.config.compression = compression;
+        self
+    }
+
+    pub fn spool_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.config.spool_dir = Some(dir.into());
+        self
+    }
+
+    /// Cap uploads at `bytes` per second.
+    pub fn max_bytes_per_sec(mut self, bytes: u64) -> Self {
+        self.config.max_bytes_per_sec = Some(bytes);
+        self
+    }
+
+    /// Identify the application in the `User-Agent`, e.g. `my-trainer/2.3`.
+    pub fn app_id(mut self, app: &str) -> Self {
+        self.config.app_id = Some(app.into());
+        self
+    }
+
+    /// Connect to `addr` for `host` instead of resolving it; see
+    /// [`Client::with_resolve`](crate::Client::with_resolve).
+    pub fn resolve(mut self, host: &str, addr: SocketAddr) -> Self {
+        self.config.resolve.push((host.into(), addr));
+        self
+    }
+
+    /// Start bulk posts from this host at least `spacing` apart; see
+    /// [`Client::with_node_stagger`](crate::Client::with_node_stagger).
+    pub fn node_stagger(mut self, spacing: Duration) -> Self {
+        self.config.node_stagger = Some(spacing);
+        self
+    }
+
+    /// The configuration the client would get.
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    pub fn build(self) -> Client {
+        Client::from_config(self.config)
+    }
+}
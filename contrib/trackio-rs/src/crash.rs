@@ -0,0 +1,114 @@
+use crate::alerts::AlertLevel;
+use crate::client::Client;
+use crate::notify::RunEvent;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::panic::{self, PanicHookInfo};
+use std::sync::Arc;
+
+/// The last rows logged by a client, kept for the crash report of
+/// [`Client::install_panic_hook`].
+#[derive(Debug)]
+pub(crate) struct Breadcrumbs {
+    capacity: usize,
+    rows: Mutex<VecDeque<(Option<i64>, Value)>>,
+}
+
+impl Breadcrumbs {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            rows: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    pub(crate) fn push(&self, step: Option<i64>, metrics: &Value) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut rows = self.rows.lock();
+        if rows.len() == self.capacity {
+            rows.pop_front();
+        }
+        rows.push_back((step, metrics.clone()));
+    }
+
+    fn to_json(&self) -> Value {
+        let Some(rows) = self.rows.try_lock() else {
+            return Value::Array(Vec::new());
+        };
+        rows.iter()
+            .map(|(step, metrics)| json!({ "step": step, "metrics": metrics }))
+            .collect()
+    }
+}
+
+impl Client {
+    /// Report panics anywhere in the process as a crash of this run: after the panic
+    /// message is printed, an error event `crash` is logged with the message, its
+    /// location, the last rows logged (see
+    /// [`with_crash_breadcrumbs`](Client::with_crash_breadcrumbs)) and, with the
+    /// `backtrace` feature, a backtrace; then the buffer is flushed and the notifier told
+    /// that the run crashed, as [`fail`](Client::fail) does. Post-mortems start from the
+    /// state at the panic rather than from the last row that happened to be sent.
+    ///
+    /// A panic while a thread is logging or flushing on the client only notifies: the
+    /// event and the flush would wait on locks that may never be released.
+    ///
+    /// The hook holds the client weakly and chains to the hook installed before it.
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    ///
+    /// let client = Arc::new(
+    ///     trackio::Client::new()
+    ///         .with_project("p")
+    ///         .with_run("r")
+    ///         .with_crash_breadcrumbs(20),
+    /// );
+    /// client.install_panic_hook();
+    /// ```
+    pub fn install_panic_hook(self: &Arc<Self>) {
+        let client = Arc::downgrade(self);
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            previous(info);
+            if let Some(client) = client.upgrade() {
+                client.crashed(info);
+            }
+        }));
+    }
+
+    fn crashed(&self, info: &PanicHookInfo<'_>) {
+        let message = match info.payload().downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match info.payload().downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "panic with a non-string payload".into(),
+            },
+        };
+        if self.buffers_in_use() {
+            log::warn!("trackio: the client was busy when the run crashed, not sending the crash report");
+            self.notify(RunEvent::Crashed { error: message });
+            return;
+        }
+        #[cfg(feature = "backtrace")]
+        let backtrace = Value::from(std::backtrace::Backtrace::force_capture().to_string());
+        #[cfg(not(feature = "backtrace"))]
+        let backtrace = Value::Null;
+        self.log_event(
+            AlertLevel::Error,
+            "crash",
+            json!({
+                "message": message,
+                "location": info.location().map(ToString::to_string),
+                "rows": self.breadcrumbs().map_or_else(|| json!([]), Breadcrumbs::to_json),
+                "backtrace": backtrace,
+            }),
+        );
+        if let Err(e) = self.fail(&message) {
+            log::warn!("trackio: could not send the crash report: {e}");
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! Which dataset versions runs consumed.
+//!
+//! A run records each dataset it reads with [`Client::use_dataset`], under a name and a
+//! fingerprint: a hash of the files from [`fingerprint_files`], or the revision of a
+//! Hugging Face dataset from [`hf_revision`]. [`Client::runs_using_dataset`] then finds
+//! every run trained on a given version, e.g. to retrain or re-evaluate after a labeling
+//! fix. Versions are stored as `dataset/<name>` keys of the run's
+//! [metadata](Client::set_meta), so they need a server with the run metadata endpoints,
+//! such as the one in `trackio::server`.
+//!
+//! ```no_run
+//! use trackio::datasets::{fingerprint_files, hf_revision};
+//!
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! client.use_dataset("train", &fingerprint_files(&["data/train"])?)?;
+//! client.use_dataset("eval", &hf_revision("org/eval-set", "9f1c2e7"))?;
+//! let affected = client.runs_using_dataset("p", "eval", &hf_revision("org/eval-set", "9f1c2e7"))?;
+//! # Ok::<(), trackio::client::TrackioError>(())
+//! ```
+
+use crate::client::{Client, TrackioError};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+const META_PREFIX: &str = "dataset/";
+
+impl Client {
+    /// Record that this run consumed version `fingerprint` of the dataset `name`,
+    /// replacing a version recorded before under the same name.
+    pub fn use_dataset(&self, name: &str, fingerprint: &str) -> Result<(), TrackioError> {
+        self.set_meta(&format!("{META_PREFIX}{name}"), json!(fingerprint))
+    }
+
+    /// The datasets `run` recorded, by name, with their fingerprints.
+    pub fn datasets(&self, project: &str, run: &str) -> Result<BTreeMap<String, String>, TrackioError> {
+        Ok(dataset_versions(&self.get_meta(project, run)?))
+    }
+
+    /// The runs of `project` that recorded version `fingerprint` of the dataset `name`,
+    /// sorted by name.
+    pub fn runs_using_dataset(
+        &self,
+        project: &str,
+        name: &str,
+        fingerprint: &str,
+    ) -> Result<Vec<String>, TrackioError> {
+        Ok(self
+            .get_all_meta(project)?
+            .into_iter()
+            .filter(|(_, meta)| {
+                dataset_versions(meta).get(name).map(String::as_str) == Some(fingerprint)
+            })
+            .map(|(run, _)| run)
+            .collect())
+    }
+}
+
+fn dataset_versions(meta: &serde_json::Map<String, Value>) -> BTreeMap<String, String> {
+    meta.iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(META_PREFIX)?;
+            Some((name.to_string(), value.as_str()?.to_string()))
+        })
+        .collect()
+}
+
+/// A fingerprint of the files at `paths`, directories included recursively: a hash of
+/// every file's path relative to the path given and its contents, stable across
+/// machines and platforms. Not a cryptographic hash.
+pub fn fingerprint_files<P: AsRef<Path>>(paths: &[P]) -> Result<String, TrackioError> {
+    let mut hash = Fnv::default();
+    for root in paths {
+        let root = root.as_ref();
+        let mut files = Vec::new();
+        collect_files(root, &mut files)?;
+        files.sort();
+        for file in files {
+            let relative = file.strip_prefix(root).unwrap_or(&file);
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            hash.write(relative.as_bytes());
+            hash.write(&[0]);
+            let mut reader = fs::File::open(&file)?;
+            let mut chunk = vec![0; 64 * 1024];
+            loop {
+                let n = reader.read(&mut chunk)?;
+                if n == 0 {
+                    break;
+                }
+                hash.write(&chunk[..n]);
+            }
+            hash.write(&[0]);
+        }
+    }
+    Ok(format!("fnv1a:{:016x}", hash.0))
+}
+
+/// The fingerprint of revision `revision` (a commit hash or tag) of the Hugging Face
+/// dataset `repo`.
+pub fn hf_revision(repo: &str, revision: &str) -> String {
+    format!("hf://datasets/{repo}@{revision}")
+}
+
+pub(crate) fn collect_files(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), TrackioError> {
+    if !path.is_dir() {
+        files.push(path.to_path_buf());
+        return Ok(());
+    }
+    for entry in fs::read_dir(path)? {
+        collect_files(&entry?.path(), files)?;
+    }
+    Ok(())
+}
+
+/// 64-bit FNV-1a, which unlike `std`'s hasher is the same in every Rust release.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::client::{Client, TrackioError};
+use crate::query::HistoryQuery;
+use crate::runs::MetricPoint;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Prefix of the config keys recording how a run's config differs from its baseline,
+/// see [`Client::with_config_baseline`].
+pub(crate) const CONFIG_DIFF_PREFIX: &str = "config_diff/";
+
+/// A key whose value differs between two runs. A side is `None` when the key is missing there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub key: String,
+    pub a: Option<Value>,
+    pub b: Option<Value>,
+}
+
+/// The values of one metric at a given step in both runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignedPoint {
+    pub step: i64,
+    pub a: Option<Value>,
+    pub b: Option<Value>,
+}
+
+/// Structured difference between two runs of the same project.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunDiff {
+    pub run_a: String,
+    pub run_b: String,
+    /// Config keys that differ, flattened with `.` separators. Keys starting with `_` are
+    /// bookkeeping written by trackio itself (creation time, user) and are ignored, as
+    /// are the `config_diff/*` keys recorded against a baseline run.
+    pub config: Vec<ValueChange>,
+    /// Final metric values that differ.
+    pub summary: Vec<ValueChange>,
+    /// Metric histories of both runs aligned on step.
+    pub histories: BTreeMap<String, Vec<AlignedPoint>>,
+}
+
+impl RunDiff {
+    /// Returns true when neither the configs nor the final metrics differ.
+    pub fn is_empty(&self) -> bool {
+        self.config.is_empty() && self.summary.is_empty()
+    }
+}
+
+impl Client {
+    /// Compare two runs: config changes, summary metric changes and step-aligned histories.
+    pub fn diff_runs(
+        &self,
+        project: &str,
+        run_a: &str,
+        run_b: &str,
+    ) -> Result<RunDiff, TrackioError> {
+        let config_a = flatten_config(self.get_config(project, run_a)?);
+        let config_b = flatten_config(self.get_config(project, run_b)?);
+        let summary_a = self.get_summary(project, run_a)?.metrics;
+        let summary_b = self.get_summary(project, run_b)?.metrics;
+        let history_a = self.history(project, run_a, &HistoryQuery::default())?;
+        let history_b = self.history(project, run_b, &HistoryQuery::default())?;
+
+        Ok(RunDiff {
+            run_a: run_a.to_string(),
+            run_b: run_b.to_string(),
+            config: diff_maps(&config_a, &config_b),
+            summary: diff_maps(&summary_a, &summary_b),
+            histories: align_histories(&history_a, &history_b),
+        })
+    }
+}
+
+pub(crate) fn flatten_config(config: Option<Value>) -> Map<String, Value> {
+    fn walk(prefix: &str, value: Value, out: &mut Map<String, Value>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(&key, value, out);
+                }
+            }
+            other => {
+                out.insert(prefix.to_string(), other);
+            }
+        }
+    }
+
+    let mut out = Map::new();
+    if let Some(Value::Object(map)) = config {
+        for (key, value) in map {
+            if !key.starts_with('_') && !key.starts_with(CONFIG_DIFF_PREFIX) {
+                walk(&key, value, &mut out);
+            }
+        }
+    }
+    out
+}
+
+pub(crate) fn diff_maps(a: &Map<String, Value>, b: &Map<String, Value>) -> Vec<ValueChange> {
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    keys.into_iter()
+        .filter(|key| a.get(*key) != b.get(*key))
+        .map(|key| ValueChange {
+            key: key.clone(),
+            a: a.get(key).cloned(),
+            b: b.get(key).cloned(),
+        })
+        .collect()
+}
+
+fn align_histories(
+    a: &BTreeMap<String, Vec<MetricPoint>>,
+    b: &BTreeMap<String, Vec<MetricPoint>>,
+) -> BTreeMap<String, Vec<AlignedPoint>> {
+    let metrics: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    metrics
+        .into_iter()
+        .map(|metric| {
+            let mut steps: BTreeMap<i64, AlignedPoint> = BTreeMap::new();
+            for p in a.get(metric).into_iter().flatten() {
+                steps
+                    .entry(p.step)
+                    .or_insert_with(|| AlignedPoint {
+                        step: p.step,
+                        a: None,
+                        b: None,
+                    })
+                    .a = Some(p.value.clone());
+            }
+            for p in b.get(metric).into_iter().flatten() {
+                steps
+                    .entry(p.step)
+                    .or_insert_with(|| AlignedPoint {
+                        step: p.step,
+                        a: None,
+                        b: None,
+                    })
+                    .b = Some(p.value.clone());
+            }
+            (metric.clone(), steps.into_values().collect())
+        })
+        .collect()
+}
@@ -0,0 +1,574 @@
+use crate::client::{LogItem, TrackioError};
+use crate::time::now_iso8601;
+use parking_lot::{Condvar, Mutex};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fmt::Debug;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv6Addr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How per-rank values are combined under [`Policy::Reduce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction {
+    Mean,
+    Sum,
+}
+
+/// What each rank of a distributed job does with the rows it logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    /// Only rank 0 logs; other ranks drop their rows and config.
+    Rank0Only,
+    /// Every rank logs to its own run, named after the client's run plus this suffix.
+    /// `{rank}` in the suffix is replaced by the rank, and the rank is appended if the
+    /// suffix has no placeholder.
+    AllRanksSeparateRuns(String),
+    /// Rows logged at the same step are combined across ranks and rank 0 logs the
+    /// result. Combining needs a [`Reducer`] such as [`TcpReducer`]; without one, rank 0
+    /// logs its own values.
+    Reduce(Reduction),
+}
+
+/// Combines the rows logged by every rank for [`Policy::Reduce`].
+pub trait Reducer: Debug + Send + Sync {
+    /// Contribute this rank's row for `step`. A reducer that combines rows right away
+    /// returns the result on the rank that logs it (rank 0); otherwise `None`, and rank 0
+    /// gets the result from [`ready`](Reducer::ready) later.
+    fn reduce(&self, step: Option<i64>, metrics: Value, op: Reduction) -> Option<Value>;
+
+    /// Rows combined in the background since the last call, with their steps and
+    /// timestamps, for rank 0 to log. None by default.
+    fn ready(&self) -> Vec<LogItem> {
+        Vec::new()
+    }
+
+    /// Wait for the rows contributed so far to be combined, before the run closes. Does
+    /// nothing by default.
+    fn finish(&self) {}
+}
+
+/// Rank-aware logging for multi-GPU and multi-node jobs, so every rank can log
+/// unconditionally without duplicating runs.
+///
+/// ```no_run
+/// use trackio::distributed::{DistributedConfig, Policy};
+///
+/// let client = trackio::Client::new()
+///     .with_project("p")
+///     .with_run("ddp")
+///     .with_distributed(DistributedConfig::from_env().with_policy(Policy::Rank0Only));
+/// client.log(serde_json::json!({"loss": 0.5}), Some(1), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct DistributedConfig {
+    pub rank: usize,
+    pub world_size: usize,
+    pub policy: Policy,
+    pub reducer: Option<Arc<dyn Reducer>>,
+}
+
+impl DistributedConfig {
+    /// `rank` of `world_size`, logging from rank 0 only.
+    pub fn new(rank: usize, world_size: usize) -> Self {
+        Self {
+            rank,
+            world_size: world_size.max(1),
+            policy: Policy::Rank0Only,
+            reducer: None,
+        }
+    }
+
+    /// Rank and world size from the launcher's environment: `RANK`/`WORLD_SIZE`
+    /// (torchrun, accelerate, deepspeed), `SLURM_PROCID`/`SLURM_NTASKS` or
+    /// `OMPI_COMM_WORLD_RANK`/`OMPI_COMM_WORLD_SIZE`. A single process if none are set.
+    pub fn from_env() -> Self {
+        let var = |name: &str| env::var(name).ok().and_then(|v| v.trim().parse().ok());
+        let pairs = [
+            ("RANK", "WORLD_SIZE"),
+            ("SLURM_PROCID", "SLURM_NTASKS"),
+            ("OMPI_COMM_WORLD_RANK", "OMPI_COMM_WORLD_SIZE"),
+        ];
+        for (rank, size) in pairs {
+            if let (Some(rank), Some(size)) = (var(rank), var(size)) {
+                return Self::new(rank, size);
+            }
+        }
+        Self::new(0, 1)
+    }
+
+    pub fn with_policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Combine rows across ranks with `reducer` under [`Policy::Reduce`].
+    pub fn with_reducer<R: Reducer + 'static>(mut self, reducer: R) -> Self {
+        self.reducer = Some(Arc::new(reducer));
+        self
+    }
+
+    /// Whether this rank is the one that logs shared rows and config.
+    pub fn is_primary(&self) -> bool {
+        self.rank == 0
+    }
+
+    /// The run this rank logs to, given the client's run name.
+    pub fn run_name(&self, run: &str) -> String {
+        match &self.policy {
+            Policy::AllRanksSeparateRuns(suffix) if suffix.contains("{rank}") => {
+                format!("{run}{}", suffix.replace("{rank}", &self.rank.to_string()))
+            }
+            Policy::AllRanksSeparateRuns(suffix) => format!("{run}{suffix}{}", self.rank),
+            _ => run.to_string(),
+        }
+    }
+
+    /// Applies the policy to a row logged on this rank, returning the row to log here.
+    pub(crate) fn route(&self, step: Option<i64>, metrics: Value) -> Option<Value> {
+        match &self.policy {
+            Policy::AllRanksSeparateRuns(_) => Some(metrics),
+            Policy::Rank0Only => self.is_primary().then_some(metrics),
+            Policy::Reduce(op) => match &self.reducer {
+                Some(reducer) if self.world_size > 1 => reducer.reduce(step, metrics, *op),
+                _ => self.is_primary().then_some(metrics),
+            },
+        }
+    }
+
+    /// Rows the reducer combined in the background since the last call.
+    pub(crate) fn reduced(&self) -> Vec<LogItem> {
+        match (&self.policy, &self.reducer) {
+            (Policy::Reduce(_), Some(reducer)) => reducer.ready(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Wait for the reducer to combine the rows logged so far.
+    pub(crate) fn finish(&self) {
+        if let (Policy::Reduce(_), Some(reducer)) = (&self.policy, &self.reducer) {
+            reducer.finish();
+        }
+    }
+
+    /// Whether this rank sends the run's config.
+    pub(crate) fn logs_config(&self) -> bool {
+        matches!(self.policy, Policy::AllRanksSeparateRuns(_)) || self.is_primary()
+    }
+}
+
+/// Combine rows from several ranks key by key: numeric values are summed or averaged
+/// over the ranks that logged them, other values are taken from the first row that has
+/// them.
+pub fn reduce_rows(rows: &[Value], op: Reduction) -> Value {
+    let mut out = Map::new();
+    let mut numeric: HashMap<String, (f64, usize)> = HashMap::new();
+    for row in rows {
+        for (key, value) in row.as_object().into_iter().flatten() {
+            match value.as_f64() {
+                Some(v) if !out.contains_key(key) || numeric.contains_key(key) => {
+                    let (sum, n) = numeric.entry(key.clone()).or_default();
+                    *sum += v;
+                    *n += 1;
+                    out.insert(key.clone(), Value::Null);
+                }
+                _ => {
+                    out.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+        }
+    }
+    for (key, (sum, n)) in numeric {
+        let value = match op {
+            Reduction::Mean => sum / n as f64,
+            Reduction::Sum => sum,
+        };
+        out.insert(key, Value::from(value));
+    }
+    Value::Object(out)
+}
+
+/// Which rows of the ranks are combined together: the `n`th row each rank logged at a
+/// step or, for rows without a step, the `n`th such row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Key {
+    Seq(u64),
+    Step(i64, u64),
+}
+
+impl Key {
+    fn new(step: Option<i64>, n: u64) -> Self {
+        match step {
+            Some(step) => Key::Step(step, n),
+            None => Key::Seq(n),
+        }
+    }
+
+    fn step(self) -> Option<i64> {
+        match self {
+            Key::Step(step, _) => Some(step),
+            Key::Seq(_) => None,
+        }
+    }
+
+    fn n(self) -> u64 {
+        match self {
+            Key::Step(_, n) | Key::Seq(n) => n,
+        }
+    }
+}
+
+/// Counts a rank's rows to give each its [`Key`].
+#[derive(Debug, Default)]
+struct Position {
+    unstepped: u64,
+    step: Option<i64>,
+    at_step: u64,
+}
+
+impl Position {
+    fn next(&mut self, step: Option<i64>) -> Key {
+        let n = match step {
+            None => {
+                self.unstepped += 1;
+                self.unstepped - 1
+            }
+            Some(_) if self.step == step => {
+                self.at_step += 1;
+                self.at_step
+            }
+            Some(_) => {
+                self.step = step;
+                self.at_step = 0;
+                0
+            }
+        };
+        Key::new(step, n)
+    }
+}
+
+/// The rows gathered on rank 0 for one of its rows, matched across ranks by [`Key`].
+#[derive(Debug, Default)]
+struct Bucket {
+    rows: Vec<Value>,
+    own: Option<Own>,
+}
+
+/// Rank 0's contribution to a [`Bucket`].
+#[derive(Debug)]
+struct Own {
+    /// Position among rank 0's rows, the order results are logged in.
+    seq: u64,
+    op: Reduction,
+    timestamp: String,
+    deadline: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Gather {
+    buckets: BTreeMap<Key, Bucket>,
+    /// The row of rank 0 combined next.
+    next: u64,
+    /// The last keys combined, without and with a step; rows up to them arrived too late.
+    last_seq: Option<Key>,
+    last_step: Option<Key>,
+    ready: Vec<LogItem>,
+}
+
+impl Gather {
+    fn late(&self, key: Key) -> bool {
+        is_late(key, self.last_seq, self.last_step)
+    }
+}
+
+fn is_late(key: Key, last_seq: Option<Key>, last_step: Option<Key>) -> bool {
+    let last = match key {
+        Key::Seq(_) => last_seq,
+        Key::Step(..) => last_step,
+    };
+    last.is_some_and(|last| key <= last)
+}
+
+type Shared = (Mutex<Gather>, Condvar);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(10);
+const OUTBOX: usize = 4096;
+
+/// A [`Reducer`] that gathers rows on rank 0 over TCP, without NCCL or MPI.
+///
+/// Rank 0 listens on the given address; the other ranks connect to it and send each row
+/// as one JSON line. Rows logged at a step are matched across ranks by that step and,
+/// when several share it, such as the loss and then the learning rate, by their order
+/// within it; rows without a step by their order among such rows. So a rank that logs a
+/// row the others don't only delays that row.
+///
+/// Nothing waits on the network in [`reduce`](Reducer::reduce). The other ranks queue
+/// rows for a background thread that sends them, reconnecting with backoff, and drop
+/// them while rank 0 can't be reached or the queue is full. On rank 0 a background
+/// thread combines each of its rows once every rank's row has arrived, or once the
+/// timeout passes, with the rows received so far, and the client logs the result with
+/// its next row or flush. Rows arriving after theirs was combined are dropped.
+///
+/// ```no_run
+/// use trackio::distributed::{DistributedConfig, Policy, Reduction, TcpReducer};
+///
+/// let dist = DistributedConfig::from_env();
+/// let reducer = TcpReducer::from_env(&dist)?;
+/// let client = trackio::Client::new()
+///     .with_distributed(dist.with_policy(Policy::Reduce(Reduction::Mean)).with_reducer(reducer));
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug)]
+pub struct TcpReducer {
+    rank: usize,
+    timeout: Duration,
+    position: Mutex<Position>,
+    seq: AtomicU64,
+    gathered: Arc<Shared>,
+    outbox: Option<SyncSender<String>>,
+    queued: Arc<AtomicUsize>,
+}
+
+impl TcpReducer {
+    /// Rank 0 listens on `addr` (e.g. `0.0.0.0:29501`); other ranks connect to it.
+    pub fn new(rank: usize, world_size: usize, addr: &str) -> Result<Self, TrackioError> {
+        let mut reducer = Self {
+            rank,
+            timeout: Duration::from_secs(30),
+            position: Mutex::default(),
+            seq: AtomicU64::new(0),
+            gathered: Arc::default(),
+            outbox: None,
+            queued: Arc::default(),
+        };
+        if rank == 0 {
+            let listener = TcpListener::bind(addr)?;
+            let gathered = Arc::downgrade(&reducer.gathered);
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    let gathered = gathered.clone();
+                    thread::spawn(move || receive(stream, &gathered));
+                }
+            });
+            let gathered = Arc::downgrade(&reducer.gathered);
+            let world_size = world_size.max(1);
+            thread::spawn(move || combine(&gathered, world_size));
+        } else {
+            let (outbox, rows) = mpsc::sync_channel(OUTBOX);
+            let (addr, queued) = (addr.to_string(), reducer.queued.clone());
+            thread::spawn(move || forward(&addr, &rows, &queued));
+            reducer.outbox = Some(outbox);
+        }
+        Ok(reducer)
+    }
+
+    /// Gather on `$MASTER_ADDR` (rank 0 binds all interfaces, IPv6 ones too when
+    /// `$MASTER_ADDR` is an IPv6 address) at `$TRACKIO_REDUCE_PORT`, or one above
+    /// `$MASTER_PORT` (default 29501).
+    pub fn from_env(dist: &DistributedConfig) -> Result<Self, TrackioError> {
+        let port = env::var("TRACKIO_REDUCE_PORT")
+            .ok()
+            .and_then(|p| p.parse::<u16>().ok())
+            .or_else(|| {
+                env::var("MASTER_PORT")
+                    .ok()
+                    .and_then(|p| p.parse::<u16>().ok())
+                    .map(|p| p.wrapping_add(1))
+            })
+            .unwrap_or(29501);
+        let master = env::var("MASTER_ADDR").unwrap_or_else(|_| "127.0.0.1".into());
+        let master = master.trim_start_matches('[').trim_end_matches(']');
+        let ipv6 = master.parse::<Ipv6Addr>().is_ok();
+        let addr = match (dist.rank, ipv6) {
+            (0, false) => format!("0.0.0.0:{port}"),
+            (0, true) => format!("[::]:{port}"),
+            (_, false) => format!("{master}:{port}"),
+            (_, true) => format!("[{master}]:{port}"),
+        };
+        Self::new(dist.rank, dist.world_size, &addr)
+    }
+
+    /// How long rank 0 waits for the other ranks' rows of one of its rows (default: 30s).
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn send(&self, key: Key, metrics: Value) {
+        let Some(outbox) = &self.outbox else {
+            return;
+        };
+        let line = json!({ "step": key.step(), "seq": key.n(), "metrics": metrics }).to_string();
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        if outbox.try_send(line).is_err() {
+            self.queued.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Reducer for TcpReducer {
+    fn reduce(&self, step: Option<i64>, metrics: Value, op: Reduction) -> Option<Value> {
+        let mut key = self.position.lock().next(step);
+        if self.rank != 0 {
+            self.send(key, metrics);
+            return None;
+        }
+        let (gathered, arrived) = &*self.gathered;
+        let mut gather = gathered.lock();
+        while gather.buckets.get(&key).is_some_and(|b| b.own.is_some()) {
+            key = Key::new(key.step(), key.n() + 1);
+        }
+        let bucket = gather.buckets.entry(key).or_default();
+        bucket.rows.insert(0, metrics);
+        bucket.own = Some(Own {
+            seq: self.seq.fetch_add(1, Ordering::Relaxed),
+            op,
+            timestamp: now_iso8601(),
+            deadline: Instant::now() + self.timeout,
+        });
+        arrived.notify_all();
+        None
+    }
+
+    fn ready(&self) -> Vec<LogItem> {
+        std::mem::take(&mut self.gathered.0.lock().ready)
+    }
+
+    fn finish(&self) {
+        if self.rank != 0 {
+            let deadline = Instant::now() + self.timeout;
+            while self.queued.load(Ordering::Relaxed) > 0 && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(5));
+            }
+            return;
+        }
+        let (gathered, arrived) = &*self.gathered;
+        let mut gather = gathered.lock();
+        let Some(last) = gather
+            .buckets
+            .values()
+            .filter_map(|b| b.own.as_ref().map(|o| o.deadline))
+            .max()
+        else {
+            return;
+        };
+        let deadline = last + Duration::from_secs(1);
+        while gather.buckets.values().any(|b| b.own.is_some()) {
+            if arrived.wait_until(&mut gather, deadline).timed_out() {
+                return;
+            }
+        }
+    }
+}
+
+/// Combine rank 0's rows in the order it logged them, each once every rank's row has
+/// arrived or its timeout has passed, until the reducer is dropped.
+fn combine(gathered: &Weak<Shared>, world_size: usize) {
+    loop {
+        let Some(shared) = gathered.upgrade() else {
+            return;
+        };
+        let (gathered, arrived) = &*shared;
+        let mut gather = gathered.lock();
+        let next = gather.next;
+        let front = gather.buckets.iter().find_map(|(key, bucket)| {
+            let own = bucket.own.as_ref().filter(|o| o.seq == next)?;
+            Some((*key, bucket.rows.len() >= world_size, Some(own.deadline)))
+        });
+        let deadline = match front {
+            Some((key, complete, Some(deadline))) if complete || Instant::now() >= deadline => {
+                let bucket = gather.buckets.remove(&key).unwrap_or_default();
+                let own = bucket.own.expect("front bucket has rank 0's row");
+                let metrics = reduce_rows(&bucket.rows, own.op);
+                gather.ready.push(LogItem {
+                    metrics,
+                    step: key.step(),
+                    timestamp: Some(own.timestamp),
+                });
+                gather.next += 1;
+                match key {
+                    Key::Seq(_) => gather.last_seq = Some(key),
+                    Key::Step(..) => gather.last_step = Some(key),
+                }
+                let (last_seq, last_step) = (gather.last_seq, gather.last_step);
+                gather
+                    .buckets
+                    .retain(|key, bucket| bucket.own.is_some() || !is_late(*key, last_seq, last_step));
+                arrived.notify_all();
+                continue;
+            }
+            Some((_, _, Some(deadline))) => deadline,
+            _ => Instant::now() + Duration::from_secs(1),
+        };
+        arrived.wait_until(&mut gather, deadline.min(Instant::now() + Duration::from_secs(1)));
+    }
+}
+
+fn receive(stream: TcpStream, gathered: &Weak<Shared>) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let Ok(mut msg) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        let Some(shared) = gathered.upgrade() else {
+            return;
+        };
+        let n = msg.get("seq").and_then(Value::as_u64).unwrap_or(0);
+        let key = Key::new(msg.get("step").and_then(Value::as_i64), n);
+        let metrics = msg.get_mut("metrics").map(Value::take).unwrap_or_default();
+        let mut gather = shared.0.lock();
+        if gather.late(key) {
+            continue;
+        }
+        gather.buckets.entry(key).or_default().rows.push(metrics);
+        shared.1.notify_all();
+    }
+}
+
+/// Send the rows queued in `rows` to rank 0 at `addr` until the reducer is dropped,
+/// dropping those that can't be sent and waiting longer after each failed connect.
+fn forward(addr: &str, rows: &Receiver<String>, queued: &AtomicUsize) {
+    let mut conn: Option<TcpStream> = None;
+    let mut backoff = Duration::from_millis(100);
+    let mut retry_at = Instant::now();
+    for line in rows {
+        for _ in 0..2 {
+            if conn.is_none() && Instant::now() >= retry_at {
+                conn = connect(addr);
+                if conn.is_some() {
+                    backoff = Duration::from_millis(100);
+                } else {
+                    retry_at = Instant::now() + backoff;
+                    backoff = (backoff * 2).min(MAX_RECONNECT_BACKOFF);
+                }
+            }
+            let Some(stream) = conn.as_mut() else {
+                break;
+            };
+            if writeln!(stream, "{line}").is_ok() {
+                break;
+            }
+            conn = None;
+        }
+        queued.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn connect(addr: &str) -> Option<TcpStream> {
+    let stream = addr
+        .to_socket_addrs()
+        .ok()?
+        .find_map(|addr| TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).ok())?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT)).ok()?;
+    let _ = stream.set_nodelay(true);
+    Some(stream)
+}
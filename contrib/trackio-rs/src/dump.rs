@@ -0,0 +1,143 @@
+use parking_lot::Mutex;
+use serde::Serialize;
+use serde_json::Value;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SECRET_WORDS: [&str; 5] = ["token", "secret", "password", "apikey", "authorization"];
+
+/// Writes every request the client sends, with its outcome, for diagnosing rejected
+/// batches without a packet capture.
+///
+/// Payloads are pretty-printed and cut at [`max_bytes`](PayloadDump::max_bytes). Values
+/// under keys that look like credentials (`hf_token`, `db_password`, `api_key`, ...)
+/// are replaced with `"[redacted]"`, and the write token header is only noted as
+/// present. Install with [`Client::with_payload_dump`](crate::Client::with_payload_dump),
+/// or set `TRACKIO_DUMP_PAYLOADS` to a file path, or to `log` for the `log` crate.
+///
+/// ```no_run
+/// use trackio::dump::PayloadDump;
+///
+/// let client = trackio::Client::new()
+///     .with_project("p")
+///     .with_run("r")
+///     .with_payload_dump(PayloadDump::file("trackio-requests.log").max_bytes(16 * 1024));
+/// ```
+#[derive(Debug)]
+pub struct PayloadDump {
+    target: Target,
+    max_bytes: usize,
+}
+
+#[derive(Debug)]
+enum Target {
+    Log,
+    File {
+        path: PathBuf,
+        file: Mutex<Option<File>>,
+    },
+}
+
+impl PayloadDump {
+    /// Dump at debug level to the `trackio::payload` target of the `log` crate.
+    pub fn log() -> Self {
+        Self {
+            target: Target::Log,
+            max_bytes: 64 * 1024,
+        }
+    }
+
+    /// Append dumps to the file at `path`, created on the first request.
+    pub fn file<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            target: Target::File {
+                path: path.as_ref().to_path_buf(),
+                file: Mutex::new(None),
+            },
+            max_bytes: 64 * 1024,
+        }
+    }
+
+    /// Longest payload written, in bytes; longer ones are cut (default: 64 KiB).
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// From `TRACKIO_DUMP_PAYLOADS`: `log` for [`log`](PayloadDump::log), anything else
+    /// is a file path.
+    pub fn from_env() -> Option<Self> {
+        match env::var("TRACKIO_DUMP_PAYLOADS").ok()?.as_str() {
+            "" => None,
+            "log" => Some(Self::log()),
+            path => Some(Self::file(path)),
+        }
+    }
+
+    pub(crate) fn request<T: Serialize>(&self, url: &str, with_token: bool, payload: &T) {
+        let mut body = serde_json::to_value(payload).unwrap_or(Value::Null);
+        redact(&mut body);
+        let mut text = serde_json::to_string_pretty(&body).unwrap_or_default();
+        let size = text.len();
+        if size > self.max_bytes {
+            let mut end = self.max_bytes;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+            text.push_str(&format!("\n... ({size} bytes in total)"));
+        }
+        let token = if with_token {
+            "X-Trackio-Write-Token: [redacted]"
+        } else {
+            "no write token"
+        };
+        self.write(&format!("POST {url} ({token}, {size} bytes)\n{text}"));
+    }
+
+    pub(crate) fn response(&self, url: &str, outcome: &str, elapsed: Duration) {
+        self.write(&format!("{url} -> {outcome} in {elapsed:.1?}"));
+    }
+
+    fn write(&self, entry: &str) {
+        match &self.target {
+            Target::Log => log::debug!(target: "trackio::payload", "{entry}"),
+            Target::File { path, file } => {
+                let mut file = file.lock();
+                if file.is_none() {
+                    *file = OpenOptions::new().create(true).append(true).open(path).ok();
+                }
+                if let Some(file) = file.as_mut() {
+                    let _ = writeln!(file, "[{}] {entry}", crate::time::now_iso8601());
+                }
+            }
+        }
+    }
+}
+
+fn redact(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_secret(key) {
+                    *value = Value::String("[redacted]".into());
+                } else {
+                    redact(value);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact),
+        _ => {}
+    }
+}
+
+/// Whether the last word of `key` names a credential, so `hf_token` is redacted but
+/// `tokens_per_sec` is not.
+fn is_secret(key: &str) -> bool {
+    let key = key.to_lowercase();
+    let last = key.rsplit(['_', '-', '/', '.']).next().unwrap_or("");
+    SECRET_WORDS.contains(&last) || key.ends_with("api_key")
+}
@@ -0,0 +1,106 @@
+use crate::client::Client;
+use crate::sweep::Goal;
+use serde_json::Value;
+
+/// Stops training once a logged metric has not improved for `patience` points.
+///
+/// Feed it the rows you log, preferably through [`log`](EarlyStopper::log) so the stopper
+/// sees exactly the values the dashboard shows. Rows without the metric are ignored and
+/// a non-finite value counts as no improvement.
+///
+/// ```no_run
+/// use trackio::early_stop::EarlyStopper;
+/// use trackio::sweep::Goal;
+///
+/// let client = trackio::Client::new().with_project("p").with_run("r");
+/// let mut stopper = EarlyStopper::new("val_loss", 3, Goal::Minimize);
+/// for epoch in 0..100 {
+///     let val_loss = 1.0 / (epoch as f64 + 1.0);
+///     if stopper.log(&client, serde_json::json!({ "val_loss": val_loss }), Some(epoch)) {
+///         break;
+///     }
+/// }
+/// println!("best {:?} at step {:?}", stopper.best(), stopper.best_step());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct EarlyStopper {
+    metric: String,
+    patience: usize,
+    goal: Goal,
+    min_delta: f64,
+    best: Option<f64>,
+    best_step: Option<i64>,
+    since_best: usize,
+}
+
+impl EarlyStopper {
+    pub fn new(metric: &str, patience: usize, goal: Goal) -> Self {
+        Self {
+            metric: metric.into(),
+            patience,
+            goal,
+            min_delta: 0.0,
+            best: None,
+            best_step: None,
+            since_best: 0,
+        }
+    }
+
+    /// How much better than the best value a point must be to count as an improvement
+    /// (default: 0).
+    pub fn with_min_delta(mut self, min_delta: f64) -> Self {
+        self.min_delta = min_delta.abs();
+        self
+    }
+
+    /// Log `metrics` to `client` and observe them. Returns whether training should stop.
+    pub fn log(&mut self, client: &Client, metrics: Value, step: Option<i64>) -> bool {
+        self.observe(&metrics, step);
+        client.log(metrics, step, None);
+        self.should_stop()
+    }
+
+    /// Observe a row logged elsewhere. Returns whether training should stop.
+    pub fn observe(&mut self, metrics: &Value, step: Option<i64>) -> bool {
+        let Some(value) = metrics.get(&self.metric) else {
+            return self.should_stop();
+        };
+        let value = value.as_f64().filter(|v| v.is_finite());
+        let improved = match (value, self.best) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(v), Some(best)) => match self.goal {
+                Goal::Minimize => v < best - self.min_delta,
+                Goal::Maximize => v > best + self.min_delta,
+            },
+        };
+        if improved {
+            self.best = value;
+            self.best_step = step;
+            self.since_best = 0;
+        } else {
+            self.since_best += 1;
+        }
+        self.should_stop()
+    }
+
+    /// Whether the metric has gone `patience` points without improving.
+    pub fn should_stop(&self) -> bool {
+        self.since_best >= self.patience && self.since_best > 0
+    }
+
+    /// Best value observed so far.
+    pub fn best(&self) -> Option<f64> {
+        self.best
+    }
+
+    /// Step of the best value, if it was logged with one.
+    pub fn best_step(&self) -> Option<i64> {
+        self.best_step
+    }
+
+    /// Points observed since the last improvement.
+    pub fn since_best(&self) -> usize {
+        self.since_best
+    }
+}
@@ -0,0 +1,259 @@
+use crate::checksum;
+use crate::log_item::LogItem;
+use std::time::Duration;
+
+#[derive(thiserror::Error, Debug)]
+pub enum TrackioError {
+    #[error("no Trackio bulk endpoint found")]
+    NoBulkEndpoint,
+    #[cfg(feature = "http")]
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Talking to a server without a transport: built without the `http` feature and
+    /// none set with [`Client::with_transport`](crate::Client::with_transport).
+    #[error("no transport to reach the server; enable the `http` feature or set one")]
+    NoTransport,
+    #[error("404 Not Found: {0}")]
+    NotFound(String),
+    /// The server has no such endpoint. The Python dashboard lacks the ones only
+    /// `trackio::server` provides, such as run metadata or pruning.
+    #[error("the server has no `{0}` endpoint; it is provided by trackio::server")]
+    Unsupported(String),
+    /// The server refused the credentials (HTTP 401 or 403, or the Python server's
+    /// equivalent).
+    #[error("HTTP {0}, check the write token: {1}")]
+    Unauthorized(u16, String),
+    /// The server asked to slow down (HTTP 429), possibly saying for how long.
+    #[error("rate limited: {body}")]
+    RateLimited {
+        retry_after: Option<Duration>,
+        body: String,
+    },
+    /// The server refused the payload itself (HTTP 400, 413 or 422).
+    #[error("HTTP {0}, payload rejected: {1}")]
+    Rejected(u16, String),
+    #[error("HTTP {0}: {1}")]
+    Status(u16, String),
+    /// The payload didn't arrive as sent: the server found its checksum wrong, or echoed
+    /// a different one. Sending it again may work.
+    #[error("payload corrupted in transit: {0}")]
+    Corrupted(String),
+    /// A batch that could not be sent, returned by [`Client::flush`](crate::Client::flush).
+    #[error("{0}")]
+    Batch(Box<FailedBatch>),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("export failed: {0}")]
+    Export(String),
+    #[error("SQLite error: {0}")]
+    Sqlite(String),
+    #[error("server not ready after {0:?}")]
+    NotReady(Duration),
+    #[error("failed to launch local trackio server: {0}")]
+    Launch(String),
+    #[error("Hugging Face Hub error: {0}")]
+    Hub(String),
+    /// A [`MediaStore`](crate::media::MediaStore) failed to store media.
+    #[error("media storage: {0}")]
+    Media(String),
+    #[error("import failed: {0}")]
+    Import(String),
+    #[error("trackio agent: {0}")]
+    Agent(String),
+    #[error("cassette: {0}")]
+    Cassette(String),
+    #[error("config file {0}")]
+    Config(String),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+}
+
+/// What kind of failure a [`TrackioError`] is, for deciding whether to try again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The server's host name did not resolve.
+    Dns,
+    /// The connection was refused or dropped.
+    Connect,
+    /// The request timed out.
+    Timeout,
+    /// The credentials were missing or refused.
+    Auth,
+    /// The server asked to slow down.
+    RateLimit,
+    /// The server refused the payload; sending it again won't help.
+    Rejected,
+    /// The endpoint, project or run does not exist.
+    NotFound,
+    /// The server failed or isn't up yet.
+    Server,
+    /// The payload was damaged on the way, e.g. by a proxy.
+    Corrupted,
+    /// A local problem: bad arguments, I/O, a missing endpoint or feature.
+    Other,
+}
+
+impl ErrorKind {
+    /// Whether the same request may succeed later without changes.
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            Self::Dns
+                | Self::Connect
+                | Self::Timeout
+                | Self::RateLimit
+                | Self::Server
+                | Self::Corrupted
+        )
+    }
+}
+
+/// The rows and config of a send that failed, with the reason.
+#[derive(Debug)]
+pub struct FailedBatch {
+    pub project: String,
+    pub run: String,
+    pub items: Vec<LogItem>,
+    pub config: Option<serde_json::Value>,
+    pub error: TrackioError,
+}
+
+impl std::fmt::Display for FailedBatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} rows of {}/{} not sent)",
+            self.error,
+            self.items.len(),
+            self.project,
+            self.run
+        )
+    }
+}
+
+impl TrackioError {
+    /// The class of this error; for [`Batch`](TrackioError::Batch), that of the failure.
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            #[cfg(feature = "http")]
+            Self::Http(e) => http_kind(e),
+            Self::NotFound(_) => ErrorKind::NotFound,
+            Self::Unauthorized(..) => ErrorKind::Auth,
+            Self::RateLimited { .. } => ErrorKind::RateLimit,
+            Self::Rejected(..) => ErrorKind::Rejected,
+            Self::Status(status, _) => status_kind(*status),
+            Self::Corrupted(_) => ErrorKind::Corrupted,
+            Self::Batch(batch) => batch.error.kind(),
+            Self::NotReady(_) => ErrorKind::Server,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Whether retrying the same request may succeed, as for network failures, timeouts,
+    /// rate limits and server errors.
+    pub fn is_retryable(&self) -> bool {
+        self.kind().is_retryable()
+    }
+
+    /// How long the server asked to wait before retrying, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => *retry_after,
+            Self::Batch(batch) => batch.error.retry_after(),
+            _ => None,
+        }
+    }
+
+    /// The batch that failed to send, if this error carries one.
+    pub fn batch(&self) -> Option<&FailedBatch> {
+        match self {
+            Self::Batch(batch) => Some(batch),
+            _ => None,
+        }
+    }
+
+    /// Takes the batch that failed to send, e.g. to [`requeue`](crate::Client::requeue) it.
+    pub fn into_batch(self) -> Option<FailedBatch> {
+        match self {
+            Self::Batch(batch) => Some(*batch),
+            _ => None,
+        }
+    }
+
+    /// Classifies a non-success response by its status.
+    #[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+    pub(crate) fn from_response(resp: reqwest::blocking::Response) -> Self {
+        let status = resp.status().as_u16();
+        let retry_after = retry_after(resp.headers());
+        Self::from_status(status, retry_after, resp.text().unwrap_or_default())
+    }
+
+    /// Classifies a failure status with the response body and the `Retry-After` delay,
+    /// for [`Transport`](crate::transport::Transport) implementations.
+    pub fn from_status(status: u16, retry_after: Option<Duration>, body: String) -> Self {
+        let lower = body.to_lowercase();
+        match status {
+            404 => Self::NotFound(body),
+            401 | 403 => Self::Unauthorized(status, body),
+            _ if AUTH_MESSAGES.iter().any(|m| lower.contains(m)) => {
+                Self::Unauthorized(status, body)
+            }
+            429 => Self::RateLimited { retry_after, body },
+            400 | 422 if lower.contains(checksum::MISMATCH) => Self::Corrupted(body),
+            400 | 413 | 422 => Self::Rejected(status, body),
+            _ => Self::Status(status, body),
+        }
+    }
+}
+
+/// `Retry-After` in seconds; HTTP dates are not supported.
+#[cfg(feature = "http")]
+pub(crate) fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok()?.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// The Python server reports refused tokens as generic API errors, recognisable only by
+/// their message.
+const AUTH_MESSAGES: [&str; 3] = ["write_token is required", "hf_token", "permissionerror"];
+
+fn status_kind(status: u16) -> ErrorKind {
+    match status {
+        401 | 403 => ErrorKind::Auth,
+        404 => ErrorKind::NotFound,
+        408 => ErrorKind::Timeout,
+        429 => ErrorKind::RateLimit,
+        400..=499 => ErrorKind::Rejected,
+        _ => ErrorKind::Server,
+    }
+}
+
+/// reqwest only says "connect error" for failed lookups, so the cause chain is checked
+/// for the resolver's message.
+#[cfg(feature = "http")]
+fn http_kind(e: &reqwest::Error) -> ErrorKind {
+    if e.is_timeout() {
+        return ErrorKind::Timeout;
+    }
+    if let Some(status) = e.status() {
+        return status_kind(status.as_u16());
+    }
+    if e.is_builder() {
+        return ErrorKind::Other;
+    }
+    if e.is_decode() {
+        return ErrorKind::Server;
+    }
+    let mut source = std::error::Error::source(e);
+    while let Some(cause) = source {
+        let msg = cause.to_string();
+        if msg.contains("dns error") || msg.contains("failed to lookup address") {
+            return ErrorKind::Dns;
+        }
+        source = cause.source();
+    }
+    ErrorKind::Connect
+}
@@ -0,0 +1,155 @@
+//! Benchmark results logged at a checkpoint, from lm-evaluation-harness output or any
+//! `task -> metric -> value` structure.
+//!
+//! [`Client::log_eval`] logs every score as a scalar `eval/<task>/<metric>`, so
+//! checkpoints chart against each other, and the whole set as a table under
+//! `eval/results`, with one row per score and its standard error when known.
+//! lm-evaluation-harness names metrics `<metric>,<filter>`; the default `none` filter
+//! is dropped and others become `<metric>/<filter>`.
+//!
+//! ```no_run
+//! use trackio::evals::EvalResults;
+//!
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! let results = EvalResults::from_file("results.json")?;
+//! client.log_eval(&results, Some(20_000));
+//!
+//! let custom = EvalResults::new()
+//!     .score("gsm8k", "exact_match", 0.412)
+//!     .score("mmlu", "acc", 0.634);
+//! client.log_eval(&custom, Some(20_000));
+//! # Ok::<(), trackio::client::TrackioError>(())
+//! ```
+
+use crate::client::{Client, TrackioError};
+use serde_json::{json, Map, Number, Value};
+use std::fs;
+use std::path::Path;
+
+/// One score of an evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalScore {
+    pub task: String,
+    pub metric: String,
+    pub value: f64,
+    pub stderr: Option<f64>,
+}
+
+/// The scores of one evaluation of a checkpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvalResults {
+    pub scores: Vec<EvalScore>,
+}
+
+impl EvalResults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the score `value` of `metric` on `task`.
+    pub fn score(mut self, task: &str, metric: &str, value: f64) -> Self {
+        self.scores.push(EvalScore {
+            task: task.into(),
+            metric: metric.into(),
+            value,
+            stderr: None,
+        });
+        self
+    }
+
+    /// Read results in the shape lm-evaluation-harness writes: an object whose
+    /// `results` (or the object itself) maps each task to its metrics. Non-numeric
+    /// values, such as task aliases and `"N/A"` standard errors, are skipped.
+    pub fn from_json(json: &Value) -> Result<Self, TrackioError> {
+        let tasks = json
+            .get("results")
+            .unwrap_or(json)
+            .as_object()
+            .ok_or_else(|| TrackioError::Config("eval results must map tasks to metrics".into()))?;
+        let mut scores = Vec::new();
+        for (task, metrics) in tasks {
+            let Some(metrics) = metrics.as_object() else {
+                return Err(TrackioError::Config(format!(
+                    "eval results: task {task:?} must map metrics to values"
+                )));
+            };
+            for (key, value) in metrics {
+                let (name, filter) = match key.split_once(',') {
+                    Some((name, filter)) => (name, Some(filter)),
+                    None => (key.as_str(), None),
+                };
+                let Some(value) = value.as_f64() else {
+                    continue;
+                };
+                if name.ends_with("_stderr") {
+                    continue;
+                }
+                let stderr_key = match filter {
+                    Some(filter) => format!("{name}_stderr,{filter}"),
+                    None => format!("{name}_stderr"),
+                };
+                scores.push(EvalScore {
+                    task: task.clone(),
+                    metric: metric_name(name, filter),
+                    value,
+                    stderr: metrics.get(&stderr_key).and_then(Value::as_f64),
+                });
+            }
+        }
+        Ok(Self { scores })
+    }
+
+    /// [`from_json`](EvalResults::from_json) for the JSON file at `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, TrackioError> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()))
+            .map_err(|e| TrackioError::Config(format!("{}: {e}", path.display())))?;
+        Self::from_json(&json)
+    }
+
+    /// The row [`Client::log_eval`] logs: a scalar per score and the `eval/results`
+    /// table.
+    pub fn to_row(&self) -> Map<String, Value> {
+        let mut row = Map::new();
+        let mut table = Vec::with_capacity(self.scores.len());
+        for score in &self.scores {
+            row.insert(
+                format!("eval/{}/{}", score.task, score.metric),
+                float(score.value),
+            );
+            table.push(json!({
+                "task": score.task,
+                "metric": score.metric,
+                "value": float(score.value),
+                "stderr": score.stderr.map_or(Value::Null, float),
+            }));
+        }
+        row.insert(
+            "eval/results".into(),
+            json!({ "_type": "trackio.table", "_value": table }),
+        );
+        row
+    }
+}
+
+impl Client {
+    /// Log `results` as one row at `step`, usually the step of the evaluated checkpoint.
+    pub fn log_eval(&self, results: &EvalResults, step: Option<i64>) {
+        if !results.scores.is_empty() {
+            self.log(Value::Object(results.to_row()), step, None);
+        }
+    }
+}
+
+fn metric_name(name: &str, filter: Option<&str>) -> String {
+    match filter {
+        None | Some("none") => name.into(),
+        Some(filter) => format!("{name}/{filter}"),
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,232 @@
+//! Discrete events of a run, such as a checkpoint saved, a data loader restarted or an
+//! out-of-memory error recovered from, stored apart from the metrics.
+//!
+//! An event has a level, a name, free-form fields, the step of the last row logged
+//! before it and a timestamp. Events are sent with the next flush to the `log_events`
+//! endpoint of the server in `trackio::server`, or to the [`Backend`](crate::Backend)
+//! installed on the client, and read back with [`Client::events`]. The Python
+//! dashboard has no event endpoints: events logged against it are dropped with a
+//! warning, and reading them fails with [`TrackioError::Unsupported`].
+//!
+//! ```no_run
+//! use serde_json::json;
+//! use trackio::alerts::AlertLevel;
+//!
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! client.log_event(
+//!     AlertLevel::Info,
+//!     "checkpoint_saved",
+//!     json!({ "path": "ckpt/step-2000", "size_mb": 812 }),
+//! );
+//! client.log_event(AlertLevel::Warn, "oom_recovered", json!({ "batch_size": 16 }));
+//! ```
+//!
+//! An [annotation](Client::annotate) is an event marking a given step with a label,
+//! such as a learning rate drop, a switch of dataset or the point a run resumed from:
+//!
+//! ```no_run
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! client.annotate(12_000, "lr drop to 3e-5", "#d62728");
+//! client.annotate(20_000, "switched to the long-context mix", "");
+//! ```
+
+use crate::alerts::AlertLevel;
+use crate::client::{Client, TrackioError};
+use crate::time::now_iso8601;
+use parking_lot::Mutex;
+use serde_json::{json, Map, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The name of the events written by [`Client::annotate`].
+pub const ANNOTATION: &str = "annotation";
+
+/// An event logged with [`Client::log_event`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub level: AlertLevel,
+    pub name: String,
+    pub fields: Map<String, Value>,
+    /// The step of the last row logged before the event, if any.
+    pub step: Option<i64>,
+    pub timestamp: String,
+}
+
+impl Event {
+    /// The event as sent to and returned by the server.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "level": self.level.as_str(),
+            "name": self.name,
+            "fields": self.fields,
+            "step": self.step,
+            "timestamp": self.timestamp,
+        })
+    }
+
+    /// Read an event in the shape of [`to_json`](Event::to_json). Unknown levels are
+    /// read as info.
+    pub fn from_json(json: &Value) -> Option<Self> {
+        let level = match json.get("level").and_then(Value::as_str) {
+            Some("warn") => AlertLevel::Warn,
+            Some("error") => AlertLevel::Error,
+            _ => AlertLevel::Info,
+        };
+        Some(Self {
+            level,
+            name: json.get("name")?.as_str()?.into(),
+            fields: json
+                .get("fields")
+                .and_then(Value::as_object)
+                .cloned()
+                .unwrap_or_default(),
+            step: json.get("step").and_then(Value::as_i64),
+            timestamp: json
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .into(),
+        })
+    }
+}
+
+/// A label on a step of a run, from [`Client::annotate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Annotation {
+    pub step: i64,
+    pub text: String,
+    /// A CSS color for tools that draw the annotation, or `None` for their default.
+    pub color: Option<String>,
+}
+
+impl Annotation {
+    /// The annotation an event carries, if it is one.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        if event.name != ANNOTATION {
+            return None;
+        }
+        let text = |key: &str| event.fields.get(key).and_then(Value::as_str).map(String::from);
+        Some(Self {
+            step: event.step?,
+            text: text("text").unwrap_or_default(),
+            color: text("color"),
+        })
+    }
+}
+
+/// Events logged by a client and not sent yet.
+#[derive(Debug, Default)]
+pub(crate) struct PendingEvents {
+    events: Mutex<Vec<Event>>,
+    unsupported: AtomicBool,
+}
+
+impl PendingEvents {
+    /// Whether a thread is adding or sending events right now.
+    pub(crate) fn in_use(&self) -> bool {
+        self.events.is_locked()
+    }
+
+    /// Send the pending events, putting them back if that fails.
+    pub(crate) fn send(&self, client: &Client) -> Result<(), TrackioError> {
+        let events = std::mem::take(&mut *self.events.lock());
+        if events.is_empty() || client.dry_run_events(&events) {
+            return Ok(());
+        }
+        match client.send_events(&events) {
+            Ok(()) => Ok(()),
+            Err(TrackioError::Unsupported(_)) => {
+                self.unsupported.store(true, Ordering::Relaxed);
+                log::warn!(
+                    "trackio: the server has no log_events endpoint, dropping {} event(s) and any logged later",
+                    events.len()
+                );
+                Ok(())
+            }
+            Err(e) => {
+                self.events.lock().splice(0..0, events);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Log a discrete event on the run, sent with the next flush; see the
+    /// [module docs](crate::events). `fields` is an object of details; any other value
+    /// is stored under `value`, and `null` as no fields.
+    pub fn log_event(&self, level: AlertLevel, name: &str, fields: Value) {
+        let fields = match fields {
+            Value::Object(fields) => fields,
+            Value::Null => Map::new(),
+            value => Map::from_iter([("value".to_string(), value)]),
+        };
+        self.push_event(level, name, fields, self.last_step());
+    }
+
+    /// Label `step` of the run with `text` and a `color` for tools that draw it (any CSS
+    /// color, or `""` for none). Annotations are events named [`ANNOTATION`], sent with
+    /// the next flush and stored only where events are; the step goes through the
+    /// client's step transform like the steps of logged rows. The Python dashboard does
+    /// not store or draw them.
+    pub fn annotate(&self, step: i64, text: &str, color: &str) {
+        let mut fields = Map::from_iter([("text".to_string(), Value::from(text))]);
+        if !color.is_empty() {
+            fields.insert("color".into(), color.into());
+        }
+        self.push_event(AlertLevel::Info, ANNOTATION, fields, self.map_step(Some(step)));
+    }
+
+    /// Send `events` of the run to the backend or server right away.
+    pub(crate) fn send_events(&self, events: &[Event]) -> Result<(), TrackioError> {
+        let run = self.run_name();
+        match self.backend() {
+            Some(backend) => backend.send_events(self.project(), &run, events),
+            None => self
+                .call_extension(
+                    "log_events",
+                    &json!({
+                        "project": self.project(),
+                        "run": run,
+                        "events": events.iter().map(Event::to_json).collect::<Vec<_>>(),
+                    }),
+                )
+                .map(|_| ()),
+        }
+    }
+
+    fn push_event(&self, level: AlertLevel, name: &str, fields: Map<String, Value>, step: Option<i64>) {
+        let pending = self.pending_events();
+        if pending.unsupported.load(Ordering::Relaxed) {
+            return;
+        }
+        pending.events.lock().push(Event {
+            level,
+            name: name.into(),
+            fields,
+            step,
+            timestamp: now_iso8601(),
+        });
+    }
+
+    /// The events logged on `run` of `project`, oldest first. Needs a server with the
+    /// event endpoints, such as the one in `trackio::server`; with others it fails with
+    /// [`TrackioError::Unsupported`].
+    pub fn events(&self, project: &str, run: &str) -> Result<Vec<Event>, TrackioError> {
+        let events = self.call_extension("get_events", &json!({ "project": project, "run": run }))?;
+        Ok(events
+            .as_array()
+            .map(|events| events.iter().filter_map(Event::from_json).collect())
+            .unwrap_or_default())
+    }
+
+    /// The annotations of `run` of `project`, by step; see [`events`](Client::events).
+    pub fn annotations(&self, project: &str, run: &str) -> Result<Vec<Annotation>, TrackioError> {
+        let mut annotations: Vec<Annotation> = self
+            .events(project, run)?
+            .iter()
+            .filter_map(Annotation::from_event)
+            .collect();
+        annotations.sort_by_key(|a| a.step);
+        Ok(annotations)
+    }
+}
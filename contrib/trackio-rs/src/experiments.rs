@@ -0,0 +1,124 @@
+//! Online A/B tests of model versions, monitored in the same dashboard as training.
+//!
+//! An [`Experiment`] keeps counters per variant as a serving process records what each
+//! request saw and did, and [`log`](Experiment::log) sends them as one row:
+//!
+//! | key | value |
+//! |-----|-------|
+//! | `ab/<experiment>/<variant>/exposures` | requests served by the variant |
+//! | `ab/<experiment>/<variant>/conversions` | requests that converted |
+//! | `ab/<experiment>/<variant>/conversion_rate` | conversions over exposures |
+//! | `ab/<experiment>/<variant>/<metric>` | mean of the values observed for `metric` |
+//!
+//! Counts and means are over the whole experiment, so the latest row is the running
+//! result.
+//!
+//! ```no_run
+//! # let client = trackio::Client::new().with_project("serving").with_run("ranker-ab");
+//! let ab = client.experiment("ranker");
+//! ab.expose("v2");
+//! ab.observe("v2", "latency_ms", 41.0);
+//! ab.convert("v2");
+//! ab.log(None);
+//! ```
+
+use crate::client::Client;
+use parking_lot::Mutex;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default)]
+struct VariantStats {
+    exposures: u64,
+    conversions: u64,
+    metrics: BTreeMap<String, (f64, u64)>,
+}
+
+/// An A/B test logged to a client's run, from [`Client::experiment`]. Recording takes
+/// `&self`, so one experiment can be shared by the threads serving requests.
+#[derive(Debug)]
+pub struct Experiment<'a> {
+    client: &'a Client,
+    name: String,
+    variants: Mutex<BTreeMap<String, VariantStats>>,
+}
+
+impl Client {
+    /// Start tracking the A/B test `name`; see [`Experiment`].
+    pub fn experiment(&self, name: &str) -> Experiment<'_> {
+        Experiment {
+            client: self,
+            name: name.into(),
+            variants: Mutex::default(),
+        }
+    }
+}
+
+impl Experiment<'_> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Count a request served by `variant`.
+    pub fn expose(&self, variant: &str) {
+        self.with_variant(variant, |stats| stats.exposures += 1);
+    }
+
+    /// Count a conversion (a click, an accepted completion) of a request served by
+    /// `variant`.
+    pub fn convert(&self, variant: &str) {
+        self.with_variant(variant, |stats| stats.conversions += 1);
+    }
+
+    /// Record a value of `metric`, such as a latency or a rating, for a request served
+    /// by `variant`. Non-finite values are ignored.
+    pub fn observe(&self, variant: &str, metric: &str, value: f64) {
+        if !value.is_finite() {
+            return;
+        }
+        self.with_variant(variant, |stats| {
+            let (sum, count) = stats.metrics.entry(metric.into()).or_default();
+            *sum += value;
+            *count += 1;
+        });
+    }
+
+    /// The current counters of every variant, as the row [`log`](Experiment::log)
+    /// sends.
+    pub fn snapshot(&self) -> Map<String, Value> {
+        let mut row = Map::new();
+        for (variant, stats) in self.variants.lock().iter() {
+            let prefix = format!("ab/{}/{variant}", self.name);
+            row.insert(format!("{prefix}/exposures"), stats.exposures.into());
+            row.insert(format!("{prefix}/conversions"), stats.conversions.into());
+            if stats.exposures > 0 {
+                let rate = stats.conversions as f64 / stats.exposures as f64;
+                row.insert(format!("{prefix}/conversion_rate"), float(rate));
+            }
+            for (metric, (sum, count)) in &stats.metrics {
+                row.insert(format!("{prefix}/{metric}"), float(sum / *count as f64));
+            }
+        }
+        row
+    }
+
+    /// Log the counters of every variant as one row at `step`.
+    pub fn log(&self, step: Option<i64>) {
+        let row = self.snapshot();
+        if !row.is_empty() {
+            self.client.log(Value::Object(row), step, None);
+        }
+    }
+
+    fn with_variant(&self, variant: &str, f: impl FnOnce(&mut VariantStats)) {
+        let mut variants = self.variants.lock();
+        match variants.get_mut(variant) {
+            Some(stats) => f(stats),
+            None => f(variants.entry(variant.into()).or_default()),
+        }
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,140 @@
+use crate::client::{Client, TrackioError};
+use crate::query::HistoryQuery;
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Output format for [`Client::export`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+/// One exported row in long format: a single value of a single metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRow {
+    pub run: String,
+    pub metric: String,
+    pub step: i64,
+    pub timestamp: Option<String>,
+    pub value: Value,
+}
+
+impl Client {
+    /// Write the full metric histories of `runs` to `path` in long format
+    /// (`run, metric, step, timestamp, value`). Returns the number of rows written.
+    pub fn export<P: AsRef<Path>>(
+        &self,
+        project: &str,
+        runs: &[&str],
+        format: ExportFormat,
+        path: P,
+    ) -> Result<usize, TrackioError> {
+        let mut rows = Vec::new();
+        for run in runs {
+            for (metric, points) in self.history(project, run, &HistoryQuery::default())? {
+                rows.extend(points.into_iter().map(|p| ExportRow {
+                    run: run.to_string(),
+                    metric: metric.clone(),
+                    step: p.step,
+                    timestamp: p.timestamp,
+                    value: p.value,
+                }));
+            }
+        }
+
+        match format {
+            ExportFormat::Csv => write_csv(&rows, path.as_ref())?,
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => write_parquet(&rows, path.as_ref())?,
+        }
+        Ok(rows.len())
+    }
+}
+
+/// Write rows as RFC 4180 CSV with a header line.
+pub fn write_csv(rows: &[ExportRow], path: &Path) -> Result<(), TrackioError> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "run,metric,step,timestamp,value")?;
+    for row in rows {
+        let value = match &row.value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        writeln!(
+            out,
+            "{},{},{},{},{}",
+            csv_field(&row.run),
+            csv_field(&row.metric),
+            row.step,
+            csv_field(row.timestamp.as_deref().unwrap_or("")),
+            csv_field(&value),
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Write rows as a Parquet file. Numeric values go to the `value` column and
+/// anything else to `text`, so no value is lost.
+#[cfg(feature = "parquet")]
+pub fn write_parquet(rows: &[ExportRow], path: &Path) -> Result<(), TrackioError> {
+    use arrow_array::{ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema};
+    use parquet::arrow::ArrowWriter;
+    use std::sync::Arc;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("run", DataType::Utf8, false),
+        Field::new("metric", DataType::Utf8, false),
+        Field::new("step", DataType::Int64, false),
+        Field::new("timestamp", DataType::Utf8, true),
+        Field::new("value", DataType::Float64, true),
+        Field::new("text", DataType::Utf8, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.run.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.metric.as_str()),
+        )),
+        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.step))),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|r| r.timestamp.as_deref()),
+        )),
+        Arc::new(Float64Array::from_iter(
+            rows.iter().map(|r| r.value.as_f64()),
+        )),
+        Arc::new(StringArray::from_iter(rows.iter().map(
+            |r| match &r.value {
+                Value::Number(_) => None,
+                Value::String(s) => Some(s.clone()),
+                other => Some(other.to_string()),
+            },
+        ))),
+    ];
+
+    let batch = RecordBatch::try_new(schema.clone(), columns)
+        .map_err(|e| TrackioError::Export(e.to_string()))?;
+    let mut writer = ArrowWriter::try_new(File::create(path)?, schema, None)
+        .map_err(|e| TrackioError::Export(e.to_string()))?;
+    writer
+        .write(&batch)
+        .map_err(|e| TrackioError::Export(e.to_string()))?;
+    writer
+        .close()
+        .map_err(|e| TrackioError::Export(e.to_string()))?;
+    Ok(())
+}
@@ -0,0 +1,66 @@
+use crate::query::glob_match;
+use serde_json::Value;
+
+/// The allow and deny globs installed with
+/// [`Client::with_metric_filter`](crate::Client::with_metric_filter).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MetricFilter {
+    allow: Vec<String>,
+    deny: Vec<String>,
+}
+
+impl MetricFilter {
+    pub(crate) fn new(allow: &[&str], deny: &[&str]) -> Self {
+        Self {
+            allow: allow.iter().map(|p| p.to_string()).collect(),
+            deny: deny.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    /// Whether a metric called `name` is logged: it matches an allow glob, or there are
+    /// none, and no deny glob.
+    fn allows(&self, name: &str) -> bool {
+        (self.allow.is_empty() || self.allow.iter().any(|p| glob_match(p, name)))
+            && !self.deny.iter().any(|p| glob_match(p, name))
+    }
+
+    /// `metrics` without the filtered-out metrics, or `None` if that leaves nothing of
+    /// a row that had some. Anything other than an object is returned unchanged.
+    pub(crate) fn apply(&self, metrics: Value) -> Option<Value> {
+        let Value::Object(row) = metrics else {
+            return Some(metrics);
+        };
+        let had_metrics = !row.is_empty();
+        let row: serde_json::Map<String, Value> =
+            row.into_iter().filter(|(name, _)| self.allows(name)).collect();
+        (!row.is_empty() || !had_metrics).then_some(Value::Object(row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn deny_globs_drop_matching_metrics() {
+        let filter = MetricFilter::new(&[], &["debug/*", "*/grad_norm_*"]);
+        let row = json!({ "loss": 1, "debug/lr": 2, "layer1/grad_norm_w": 3, "eval/acc": 4 });
+        assert_eq!(filter.apply(row), Some(json!({ "loss": 1, "eval/acc": 4 })));
+    }
+
+    #[test]
+    fn allow_globs_keep_only_matching_metrics_unless_denied() {
+        let filter = MetricFilter::new(&["train/*", "eval/*"], &["eval/debug_*"]);
+        let row = json!({ "train/loss": 1, "eval/acc": 2, "eval/debug_x": 3, "lr": 4 });
+        assert_eq!(filter.apply(row), Some(json!({ "train/loss": 1, "eval/acc": 2 })));
+    }
+
+    #[test]
+    fn a_row_left_empty_is_dropped_but_an_empty_row_is_not() {
+        let filter = MetricFilter::new(&[], &["debug/*"]);
+        assert_eq!(filter.apply(json!({ "debug/x": 1 })), None);
+        assert_eq!(filter.apply(json!({})), Some(json!({})));
+        assert_eq!(filter.apply(json!(3)), Some(json!(3)));
+    }
+}
@@ -0,0 +1,146 @@
+//! Aggregates across the runs of a group, such as the seeds of one configuration or the
+//! trials of a sweep.
+//!
+//! The dashboard plots each run on its own and can't combine runs. When one process sees
+//! the values of every member, such as a sweep driver or a launcher collecting the
+//! results of its workers, a [`GroupAggregate`] computes, for each step and numeric
+//! metric, the statistics over the members and logs them as one row of a synthetic run
+//! `<group>-aggregate`, in the same group:
+//!
+//! | key | value |
+//! |-----|-------|
+//! | `<metric>/mean` | mean over the members that logged the metric at the step |
+//! | `<metric>/std` | their sample standard deviation, 0 for a single member |
+//! | `<metric>/min`, `<metric>/max` | their smallest and largest value |
+//! | `<metric>/count` | how many members logged it |
+//!
+//! A step is logged as soon as [`with_members`](GroupAggregate::with_members) runs have
+//! reported it, and [`finish`](GroupAggregate::finish) logs the steps still open with
+//! the members that reported them.
+//!
+//! ```no_run
+//! use serde_json::json;
+//!
+//! # let client = trackio::Client::new().with_project("p");
+//! let aggregate = client.group_aggregate("lr-3e-4").with_members(3);
+//! for seed in 0..3 {
+//!     let run = format!("lr-3e-4-seed{seed}");
+//!     for step in 0..100 {
+//!         aggregate.observe(&run, &json!({ "loss": 1.0 / (step + seed + 1) as f64 }), step);
+//!     }
+//! }
+//! aggregate.finish()?;
+//! # Ok::<(), trackio::client::TrackioError>(())
+//! ```
+
+use crate::client::{Client, TrackioError};
+use parking_lot::Mutex;
+use serde_json::{Map, Number, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Default)]
+struct StepValues {
+    runs: BTreeSet<String>,
+    metrics: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+/// Statistics over the runs of a group, logged to `<group>-aggregate`; see the
+/// [module docs](crate::groups). Observing takes `&self`, so one aggregate can be
+/// shared by the threads collecting the members' values.
+#[derive(Debug)]
+pub struct GroupAggregate {
+    client: Client,
+    members: Option<usize>,
+    steps: Mutex<BTreeMap<i64, StepValues>>,
+}
+
+impl Client {
+    /// Aggregate the runs of `group` in the client's project; see [`GroupAggregate`].
+    pub fn group_aggregate(&self, group: &str) -> GroupAggregate {
+        GroupAggregate {
+            client: self
+                .for_run(self.project(), &format!("{group}-aggregate"))
+                .with_group(group),
+            members: None,
+            steps: Mutex::default(),
+        }
+    }
+}
+
+impl GroupAggregate {
+    /// Log a step as soon as `n` runs have reported it, rather than only on
+    /// [`finish`](GroupAggregate::finish).
+    pub fn with_members(mut self, n: usize) -> Self {
+        self.members = Some(n.max(1));
+        self
+    }
+
+    /// The client logging to the aggregate run.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Record the numeric values of `metrics`, a row `run` logged at `step`. A run that
+    /// reports a metric again at the same step replaces its value; non-finite values are
+    /// ignored.
+    pub fn observe(&self, run: &str, metrics: &Value, step: i64) {
+        let Some(metrics) = metrics.as_object() else {
+            return;
+        };
+        let mut steps = self.steps.lock();
+        let values = steps.entry(step).or_default();
+        values.runs.insert(run.into());
+        for (key, value) in metrics {
+            if let Some(v) = value.as_f64().filter(|v| v.is_finite()) {
+                values
+                    .metrics
+                    .entry(key.clone())
+                    .or_default()
+                    .insert(run.into(), v);
+            }
+        }
+        if self.members.is_some_and(|n| values.runs.len() >= n) {
+            if let Some(values) = steps.remove(&step) {
+                drop(steps);
+                self.log(step, &values);
+            }
+        }
+    }
+
+    /// Log every step still open with the runs that reported it, and flush the
+    /// aggregate run.
+    pub fn finish(&self) -> Result<(), TrackioError> {
+        let steps = std::mem::take(&mut *self.steps.lock());
+        for (step, values) in &steps {
+            self.log(*step, values);
+        }
+        self.client.flush().map(|_| ())
+    }
+
+    fn log(&self, step: i64, values: &StepValues) {
+        let mut row = Map::new();
+        for (metric, runs) in &values.metrics {
+            let n = runs.len() as f64;
+            let mean = runs.values().sum::<f64>() / n;
+            let var = if runs.len() > 1 {
+                runs.values().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+            } else {
+                0.0
+            };
+            let min = runs.values().copied().fold(f64::INFINITY, f64::min);
+            let max = runs.values().copied().fold(f64::NEG_INFINITY, f64::max);
+            row.insert(format!("{metric}/mean"), float(mean));
+            row.insert(format!("{metric}/std"), float(var.sqrt()));
+            row.insert(format!("{metric}/min"), float(min));
+            row.insert(format!("{metric}/max"), float(max));
+            row.insert(format!("{metric}/count"), runs.len().into());
+        }
+        if !row.is_empty() {
+            self.client.log(Value::Object(row), Some(step), None);
+        }
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,176 @@
+use crate::client::{Client, FailedBatch, FlushReceipt, LogItem, TrackioError};
+use crate::error::ErrorKind;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Rows and config buffered for a [`RunHandle`].
+#[derive(Debug, Default)]
+pub(crate) struct RunBuffer {
+    pub(crate) items: Vec<LogItem>,
+    pub(crate) config: Option<Value>,
+}
+
+/// Another run of the client's project logged through the client's buffer, from
+/// [`Client::run_handle`].
+///
+/// Rows of every handle are sent with the client's own on each flush, in one request
+/// for all runs when the server takes multi-run bulk logs (the Python server and the
+/// one in `trackio::server` do), which cuts request volume when dozens of trials log at
+/// once. Step transforms, hooks, filters and the schema of the client apply; alerts and
+/// distributed routing only watch the client's own run.
+///
+/// ```no_run
+/// # let client = trackio::Client::new().with_project("pbt").with_run("controller");
+/// let names: Vec<String> = (0..32).map(|i| format!("trial-{i}")).collect();
+/// let trials: Vec<_> = names.iter().map(|name| client.run_handle(name)).collect();
+/// for (i, trial) in trials.iter().enumerate() {
+///     trial.log(serde_json::json!({ "reward": i }), Some(0), None);
+/// }
+/// client.flush()?;
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct RunHandle<'a> {
+    client: &'a Client,
+    run: &'a str,
+}
+
+impl Client {
+    /// A handle logging to `run` through this client; see [`RunHandle`].
+    pub fn run_handle<'a>(&'a self, run: &'a str) -> RunHandle<'a> {
+        RunHandle { client: self, run }
+    }
+
+    /// Sends the client's own rows and those of its run handles, in one request if the
+    /// server allows. On a failure other than a rejection, the handles' rows stay
+    /// buffered and the error carries the client's own.
+    pub(crate) fn send_coalesced(
+        &self,
+        own: RunBuffer,
+        handles: BTreeMap<String, RunBuffer>,
+    ) -> Result<FlushReceipt, TrackioError> {
+        let mut runs: Vec<(String, RunBuffer)> = Vec::with_capacity(handles.len() + 1);
+        runs.push((self.run_name().into_owned(), own));
+        runs.extend(handles);
+        runs.retain(|(_, b)| !b.items.is_empty() || b.config.is_some());
+        if self.posts_to_server() && runs.iter().all(|(_, b)| !b.items.is_empty()) {
+            let payload = MultiRunPayload::new(self.project(), &runs);
+            match self.post_to_bulk_endpoint(&payload) {
+                Ok(resp) => {
+                    let items: Vec<LogItem> = runs
+                        .into_iter()
+                        .flat_map(|(_, b)| b.items)
+                        .collect();
+                    let receipt = FlushReceipt::from_body(&items, &resp.body);
+                    self.recycle(items);
+                    return Ok(receipt);
+                }
+                Err(e) if e.kind() != ErrorKind::Rejected => {
+                    let own_run = self.run_name().into_owned();
+                    let mut own = RunBuffer::default();
+                    for (run, buffer) in runs {
+                        if run == own_run {
+                            own = buffer;
+                        } else {
+                            self.requeue_handle(run, buffer);
+                        }
+                    }
+                    return Err(TrackioError::Batch(Box::new(FailedBatch {
+                        project: self.project().to_string(),
+                        run: own_run,
+                        items: own.items,
+                        config: own.config,
+                        error: e,
+                    })));
+                }
+                Err(_) => {}
+            }
+        }
+        let mut receipt = FlushReceipt::default();
+        let mut runs = runs.into_iter();
+        while let Some((run, buffer)) = runs.next() {
+            match self.send_run(&run, buffer.items, buffer.config) {
+                Ok(sent) => receipt.append(sent),
+                Err(e) => {
+                    for (run, buffer) in runs {
+                        self.requeue_handle(run, buffer);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(receipt)
+    }
+}
+
+impl RunHandle<'_> {
+    pub fn run(&self) -> &str {
+        self.run
+    }
+
+    /// Buffer a row for this run, as [`Client::log`] does for the client's own.
+    pub fn log(&self, metrics: Value, step: Option<i64>, ts: Option<String>) {
+        let step = self.client.map_step(step);
+        let Some(metrics) = self.client.prepare_metrics(metrics) else {
+            return;
+        };
+        let item = LogItem {
+            metrics: self.client.apply_schema(metrics),
+            step,
+            timestamp: ts.or_else(|| Some(self.client.timestamp())),
+        };
+        if self.client.buffer_handle_row(self.run, item) {
+            let _ = self.client.flush();
+        }
+    }
+
+    /// Set this run's config, sent with the next flush.
+    pub fn set_config(&self, config: Value) {
+        self.client.set_handle_config(self.run, config);
+    }
+
+    /// Record the unit of `metric`. Units are kept per client, so they go to the config
+    /// of the client's own run; see [`Client::set_unit`].
+    pub fn set_unit(&self, metric: &str, unit: &str) {
+        self.client.set_unit(metric, unit);
+    }
+}
+
+/// The multi-run shape of a bulk log: one entry per row, each naming its run.
+#[derive(Debug, Serialize)]
+struct MultiRunPayload<'a> {
+    logs: Vec<MultiRunEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct MultiRunEntry<'a> {
+    project: &'a str,
+    run: &'a str,
+    metrics: &'a Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    step: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<&'a Value>,
+}
+
+impl<'a> MultiRunPayload<'a> {
+    fn new(project: &'a str, runs: &'a [(String, RunBuffer)]) -> Self {
+        let mut logs = Vec::new();
+        for (run, buffer) in runs {
+            for (i, item) in buffer.items.iter().enumerate() {
+                logs.push(MultiRunEntry {
+                    project,
+                    run,
+                    metrics: &item.metrics,
+                    step: item.step,
+                    timestamp: item.timestamp.as_deref(),
+                    config: if i == 0 { buffer.config.as_ref() } else { None },
+                });
+            }
+        }
+        Self { logs }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::client::TrackioError;
+use crate::transport::user_agent;
+use base64::Engine;
+use reqwest::blocking::{Client as Http, Response};
+use reqwest::StatusCode;
+use serde_json::{json, Value};
+use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Kind of Hugging Face Hub repository.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RepoType {
+    #[cfg(feature = "backend-hub")]
+    Dataset,
+    Space,
+}
+
+impl RepoType {
+    fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "backend-hub")]
+            RepoType::Dataset => "dataset",
+            RepoType::Space => "space",
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            #[cfg(feature = "backend-hub")]
+            RepoType::Dataset => "datasets",
+            RepoType::Space => "spaces",
+        }
+    }
+}
+
+/// Minimal client for the Hub REST endpoints this crate needs: repo creation,
+/// commits (inline and LFS), file downloads and Space secrets/variables.
+#[derive(Debug, Clone)]
+pub(crate) struct HfApi {
+    endpoint: String,
+    token: Option<String>,
+    http: Http,
+}
+
+impl HfApi {
+    /// Talk to `$HF_ENDPOINT` (default: `https://huggingface.co`) with the default token.
+    pub(crate) fn new() -> Self {
+        Self {
+            endpoint: env::var("HF_ENDPOINT")
+                .map(|e| e.trim_end_matches('/').to_string())
+                .unwrap_or_else(|_| "https://huggingface.co".into()),
+            token: default_hf_token(),
+            http: Http::builder()
+                .timeout(Duration::from_secs(120))
+                .user_agent(user_agent(None))
+                .build()
+                .expect("failed to build HTTP client"),
+        }
+    }
+
+    #[cfg(feature = "backend-hub")]
+    pub(crate) fn set_endpoint(&mut self, endpoint: &str) {
+        self.endpoint = endpoint.trim_end_matches('/').into();
+    }
+
+    #[cfg(feature = "backend-hub")]
+    pub(crate) fn set_token(&mut self, token: &str) {
+        self.token = Some(token.into());
+    }
+
+    fn token(&self) -> Result<&str, TrackioError> {
+        self.token.as_deref().ok_or_else(|| {
+            TrackioError::Hub(
+                "no Hugging Face token; set HF_TOKEN or log in with the hf CLI".into(),
+            )
+        })
+    }
+
+    pub(crate) fn repo_exists(&self, kind: RepoType, repo_id: &str) -> Result<bool, TrackioError> {
+        let resp = self
+            .http
+            .get(format!(
+                "{}/api/{}/{}",
+                self.endpoint,
+                kind.prefix(),
+                repo_id
+            ))
+            .bearer_auth(self.token()?)
+            .send()?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+        check(resp).map(|_| true)
+    }
+
+    /// Create a repo, succeeding if it already exists. `private` is always sent so the
+    /// visibility never depends on an organization default.
+    pub(crate) fn create_repo(
+        &self,
+        kind: RepoType,
+        repo_id: &str,
+        private: bool,
+        sdk: Option<&str>,
+    ) -> Result<(), TrackioError> {
+        let (organization, name) = match repo_id.split_once('/') {
+            Some((org, name)) => (Some(org), name),
+            None => (None, repo_id),
+        };
+        let mut body = json!({
+            "type": kind.name(),
+            "name": name,
+            "organization": organization,
+            "private": private,
+        });
+        if let Some(sdk) = sdk {
+            body["sdk"] = json!(sdk);
+        }
+        let resp = self
+            .http
+            .post(format!("{}/api/repos/create", self.endpoint))
+            .bearer_auth(self.token()?)
+            .json(&body)
+            .send()?;
+        if resp.status() == StatusCode::CONFLICT {
+            return Ok(());
+        }
+        check(resp).map(|_| ())
+    }
+
+    /// Download a file from the main branch, or `None` if it does not exist.
+    #[cfg(feature = "backend-hub")]
+    pub(crate) fn get_file(
+        &self,
+        kind: RepoType,
+        repo_id: &str,
+        path: &str,
+    ) -> Result<Option<Response>, TrackioError> {
+        let resp = self
+            .http
+            .get(format!(
+                "{}/{}/{}/resolve/main/{}",
+                self.endpoint,
+                kind.prefix(),
+                repo_id,
+                path
+            ))
+            .bearer_auth(self.token()?)
+            .send()?;
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        check(resp).map(Some)
+    }
+
+    /// Whether the Hub wants `data` stored at `path` through LFS rather than inline.
+    #[cfg(feature = "backend-hub")]
+    pub(crate) fn needs_lfs(
+        &self,
+        kind: RepoType,
+        repo_id: &str,
+        path: &str,
+        data: &[u8],
+    ) -> Result<bool, TrackioError> {
+        let sample = base64::engine::general_purpose::STANDARD.encode(&data[..data.len().min(512)]);
+        let preupload: Value = check(
+            self.http
+                .post(format!(
+                    "{}/api/{}/{}/preupload/main",
+                    self.endpoint,
+                    kind.prefix(),
+                    repo_id
+                ))
+                .bearer_auth(self.token()?)
+                .json(&json!({ "files": [{ "path": path, "size": data.len(), "sample": sample }] }))
+                .send()?,
+        )?
+        .json()?;
+        Ok(preupload
+            .pointer("/files/0/uploadMode")
+            .and_then(Value::as_str)
+            == Some("lfs"))
+    }
+
+    /// Upload an LFS object with the basic transfer adapter, skipping it if the Hub
+    /// already has it.
+    #[cfg(feature = "backend-hub")]
+    pub(crate) fn upload_lfs(
+        &self,
+        kind: RepoType,
+        repo_id: &str,
+        oid: &str,
+        data: Vec<u8>,
+    ) -> Result<(), TrackioError> {
+        let token = self.token()?;
+        let size = data.len();
+        let batch: Value = check(
+            self.http
+                .post(format!(
+                    "{}/{}/{}.git/info/lfs/objects/batch",
+                    self.endpoint,
+                    kind.prefix(),
+                    repo_id
+                ))
+                .bearer_auth(token)
+                .header("Accept", "application/vnd.git-lfs+json")
+                .header("Content-Type", "application/vnd.git-lfs+json")
+                .json(&json!({
+                    "operation": "upload",
+                    "transfers": ["basic"],
+                    "hash_algo": "sha256",
+                    "objects": [{ "oid": oid, "size": size }],
+                }))
+                .send()?,
+        )?
+        .json()?;
+        let object = batch.pointer("/objects/0").cloned().unwrap_or(Value::Null);
+        if let Some(error) = object.get("error") {
+            return Err(TrackioError::Hub(format!("LFS upload refused: {error}")));
+        }
+        let Some(upload) = object.pointer("/actions/upload") else {
+            return Ok(());
+        };
+        let href = upload
+            .get("href")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let mut req = self.http.put(href).body(data);
+        for (name, value) in upload
+            .get("header")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(value) = value.as_str() {
+                req = req.header(name.as_str(), value);
+            }
+        }
+        check(req.send()?)?;
+
+        if let Some(verify) = object.pointer("/actions/verify") {
+            let href = verify
+                .get("href")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            check(
+                self.http
+                    .post(href)
+                    .bearer_auth(token)
+                    .json(&json!({ "oid": oid, "size": size }))
+                    .send()?,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Create one commit on the main branch from operations built with [`file_op`]
+    /// and [`lfs_file_op`].
+    pub(crate) fn commit(
+        &self,
+        kind: RepoType,
+        repo_id: &str,
+        summary: &str,
+        ops: &[Value],
+    ) -> Result<(), TrackioError> {
+        let mut body =
+            json!({ "key": "header", "value": { "summary": summary, "description": "" } })
+                .to_string();
+        body.push('\n');
+        for op in ops {
+            body.push_str(&op.to_string());
+            body.push('\n');
+        }
+        check(
+            self.http
+                .post(format!(
+                    "{}/api/{}/{}/commit/main",
+                    self.endpoint,
+                    kind.prefix(),
+                    repo_id
+                ))
+                .bearer_auth(self.token()?)
+                .header("Content-Type", "application/x-ndjson")
+                .body(body)
+                .send()?,
+        )
+        .map(|_| ())
+    }
+
+    pub(crate) fn add_space_secret(
+        &self,
+        space_id: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), TrackioError> {
+        self.space_setting(space_id, "secrets", key, value)
+    }
+
+    pub(crate) fn add_space_variable(
+        &self,
+        space_id: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), TrackioError> {
+        self.space_setting(space_id, "variables", key, value)
+    }
+
+    fn space_setting(
+        &self,
+        space_id: &str,
+        kind: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<(), TrackioError> {
+        check(
+            self.http
+                .post(format!(
+                    "{}/api/spaces/{}/{}",
+                    self.endpoint, space_id, kind
+                ))
+                .bearer_auth(self.token()?)
+                .json(&json!({ "key": key, "value": value }))
+                .send()?,
+        )
+        .map(|_| ())
+    }
+
+    pub(crate) fn token_value(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+}
+
+/// Commit operation adding `data` at `path` inline.
+pub(crate) fn file_op(path: &str, data: &[u8]) -> Value {
+    json!({
+        "key": "file",
+        "value": {
+            "path": path,
+            "content": base64::engine::general_purpose::STANDARD.encode(data),
+            "encoding": "base64",
+        }
+    })
+}
+
+/// Commit operation pointing `path` at an already uploaded LFS object.
+#[cfg(feature = "backend-hub")]
+pub(crate) fn lfs_file_op(path: &str, oid: &str, size: usize) -> Value {
+    json!({
+        "key": "lfsFile",
+        "value": { "path": path, "algo": "sha256", "oid": oid, "size": size }
+    })
+}
+
+pub(crate) fn check(resp: Response) -> Result<Response, TrackioError> {
+    if resp.status().is_success() {
+        return Ok(resp);
+    }
+    Err(TrackioError::from_response(resp))
+}
+
+/// `$HF_TOKEN`, then the token file written by `hf auth login`.
+pub(crate) fn default_hf_token() -> Option<String> {
+    if let Ok(token) = env::var("HF_TOKEN") {
+        return Some(token);
+    }
+    let path = match env::var("HF_TOKEN_PATH") {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => env::var("HF_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                env::var("XDG_CACHE_HOME")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| {
+                        PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".into()))
+                            .join(".cache")
+                    })
+                    .join("huggingface")
+            })
+            .join("token"),
+    };
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
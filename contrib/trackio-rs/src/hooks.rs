@@ -0,0 +1,36 @@
+use serde_json::{Map, Value};
+use std::fmt;
+use std::sync::Arc;
+
+type HookFn = Arc<dyn Fn(&mut Map<String, Value>) + Send + Sync>;
+
+/// The row rewrites installed with
+/// [`Client::with_metrics_hook`](crate::Client::with_metrics_hook), run in the order
+/// they were added.
+#[derive(Clone, Default)]
+pub(crate) struct MetricHooks {
+    hooks: Vec<HookFn>,
+}
+
+impl fmt::Debug for MetricHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MetricHooks")
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+impl MetricHooks {
+    pub(crate) fn push(&mut self, hook: HookFn) {
+        self.hooks.push(hook);
+    }
+
+    pub(crate) fn apply(&self, mut metrics: Value) -> Value {
+        if let Value::Object(row) = &mut metrics {
+            for hook in &self.hooks {
+                hook(row);
+            }
+        }
+        metrics
+    }
+}
@@ -0,0 +1,349 @@
+use crate::backend::Backend;
+use crate::client::{LogItem, TrackioError};
+use crate::hf::{file_op, lfs_file_op, HfApi, RepoType};
+use crate::time::now_iso8601;
+use arrow_array::{
+    Array, ArrayRef, BooleanArray, Float32Array, Float64Array, Int32Array, Int64Array,
+    LargeStringArray, RecordBatch, StringArray,
+};
+use arrow_schema::{DataType, Field, Schema};
+use parking_lot::Mutex;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const STRUCTURAL_COLUMNS: [&str; 7] = [
+    "id",
+    "run_id",
+    "timestamp",
+    "run_name",
+    "step",
+    "log_id",
+    "space_id",
+];
+
+/// Persists metrics to a Hugging Face Dataset repo, the way the Python client's
+/// dataset sync does, so runs survive even if the dashboard Space is never up.
+///
+/// Rows are kept in memory and every `sync_interval` (default: 5 minutes, checked on
+/// each send) the full history of every changed project is written to
+/// `<project>.parquet` at the root of the dataset and committed. The file layout is
+/// the one `trackio` imports when a Space starts from a dataset, so a dashboard
+/// created later picks the runs up. Existing rows in the dataset are downloaded
+/// before the first commit of a project and kept.
+///
+/// Remaining rows are committed on drop; call [`HubBackend::sync`] to commit
+/// explicitly and see errors.
+#[derive(Debug)]
+pub struct HubBackend {
+    repo_id: String,
+    api: HfApi,
+    private: bool,
+    sync_interval: Duration,
+    state: Mutex<HubState>,
+}
+
+#[derive(Debug)]
+struct HubState {
+    projects: BTreeMap<String, ProjectRows>,
+    repo_ready: bool,
+    last_sync: Instant,
+}
+
+#[derive(Debug, Default)]
+struct ProjectRows {
+    rows: Vec<HubRow>,
+    next_step: HashMap<String, i64>,
+    loaded: bool,
+    dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+struct HubRow {
+    run: String,
+    timestamp: String,
+    step: i64,
+    metrics: String,
+    log_id: Option<String>,
+    space_id: Option<String>,
+}
+
+impl HubBackend {
+    /// Commit to the dataset `repo_id` (`user/name`), authenticating with `$HF_TOKEN`
+    /// or the token saved by `huggingface-cli login`.
+    pub fn new(repo_id: &str) -> Self {
+        Self {
+            repo_id: repo_id.into(),
+            api: HfApi::new(),
+            private: true,
+            sync_interval: Duration::from_secs(300),
+            state: Mutex::new(HubState {
+                projects: BTreeMap::new(),
+                repo_ready: false,
+                last_sync: Instant::now(),
+            }),
+        }
+    }
+
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.api.set_token(token);
+        self
+    }
+
+    /// Hub URL to talk to (default: `$HF_ENDPOINT`, then `https://huggingface.co`).
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.api.set_endpoint(endpoint);
+        self
+    }
+
+    /// Whether the dataset is created private if it does not exist yet (default: true).
+    pub fn with_private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    /// Minimum time between automatic commits (default: 5 minutes).
+    pub fn with_sync_interval(mut self, interval: Duration) -> Self {
+        self.sync_interval = interval;
+        self
+    }
+
+    /// Commit every project with rows that have not been pushed yet.
+    pub fn sync(&self) -> Result<(), TrackioError> {
+        let mut state = self.state.lock();
+        state.last_sync = Instant::now();
+        if !state.projects.values().any(|p| p.dirty) {
+            return Ok(());
+        }
+        if !state.repo_ready {
+            self.api
+                .create_repo(RepoType::Dataset, &self.repo_id, self.private, None)?;
+            state.repo_ready = true;
+        }
+        for (project, rows) in state.projects.iter_mut().filter(|(_, p)| p.dirty) {
+            let path = parquet_path(project);
+            if !rows.loaded {
+                let mut remote = self.download_rows(&path)?;
+                remote.append(&mut rows.rows);
+                rows.rows = remote;
+                rows.next_step.clear();
+                for row in &rows.rows {
+                    let next = rows.next_step.entry(row.run.clone()).or_insert(0);
+                    *next = (*next).max(row.step + 1);
+                }
+                rows.loaded = true;
+            }
+            let data = write_rows(&rows.rows)?;
+            self.upload(&path, data, &format!("Sync trackio project {project}"))?;
+            rows.dirty = false;
+        }
+        Ok(())
+    }
+
+    fn download_rows(&self, path: &str) -> Result<Vec<HubRow>, TrackioError> {
+        match self.api.get_file(RepoType::Dataset, &self.repo_id, path)? {
+            Some(resp) => read_rows(resp.bytes()?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn upload(&self, path: &str, data: Vec<u8>, summary: &str) -> Result<(), TrackioError> {
+        let op = if self
+            .api
+            .needs_lfs(RepoType::Dataset, &self.repo_id, path, &data)?
+        {
+            let oid = format!("{:x}", Sha256::digest(&data));
+            let size = data.len();
+            self.api
+                .upload_lfs(RepoType::Dataset, &self.repo_id, &oid, data)?;
+            lfs_file_op(path, &oid, size)
+        } else {
+            file_op(path, &data)
+        };
+        self.api
+            .commit(RepoType::Dataset, &self.repo_id, summary, &[op])
+    }
+}
+
+impl Backend for HubBackend {
+    fn send(&self, project: &str, run: &str, items: &[LogItem]) -> Result<(), TrackioError> {
+        let due = {
+            let mut state = self.state.lock();
+            let rows = state.projects.entry(project.to_string()).or_default();
+            let next_step = rows.next_step.entry(run.to_string()).or_insert(0);
+            let mut new_rows = Vec::with_capacity(items.len());
+            for item in items {
+                let step = match item.step {
+                    Some(step) if step >= 0 => step,
+                    _ => *next_step,
+                };
+                *next_step = (*next_step).max(step + 1);
+                new_rows.push(HubRow {
+                    run: run.to_string(),
+                    timestamp: item
+                        .timestamp
+                        .clone()
+                        .filter(|t| !t.is_empty())
+                        .unwrap_or_else(now_iso8601),
+                    step,
+                    metrics: item.metrics.to_string(),
+                    log_id: None,
+                    space_id: None,
+                });
+            }
+            rows.rows.extend(new_rows);
+            rows.dirty = true;
+            state.last_sync.elapsed() >= self.sync_interval
+        };
+        if due {
+            let _ = self.sync();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for HubBackend {
+    fn drop(&mut self) {
+        let _ = self.sync();
+    }
+}
+
+/// File name of a project's metrics table in the dataset, as written by Python trackio.
+fn parquet_path(project: &str) -> String {
+    let safe: String = project
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if safe.is_empty() {
+        "default.parquet".into()
+    } else {
+        format!("{safe}.parquet")
+    }
+}
+
+fn write_rows(rows: &[HubRow]) -> Result<Vec<u8>, TrackioError> {
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Int64, false),
+        Field::new("run_id", DataType::Utf8, false),
+        Field::new("timestamp", DataType::Utf8, false),
+        Field::new("run_name", DataType::Utf8, false),
+        Field::new("step", DataType::Int64, false),
+        Field::new("metrics", DataType::Utf8, false),
+        Field::new("log_id", DataType::Utf8, true),
+        Field::new("space_id", DataType::Utf8, true),
+    ]));
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from_iter_values(1..=rows.len() as i64)),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.run.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.timestamp.as_str()),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.run.as_str()),
+        )),
+        Arc::new(Int64Array::from_iter_values(rows.iter().map(|r| r.step))),
+        Arc::new(StringArray::from_iter_values(
+            rows.iter().map(|r| r.metrics.as_str()),
+        )),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|r| r.log_id.as_deref()),
+        )),
+        Arc::new(StringArray::from_iter(
+            rows.iter().map(|r| r.space_id.as_deref()),
+        )),
+    ];
+
+    let batch = RecordBatch::try_new(schema.clone(), columns).map_err(hub_err)?;
+    let mut out = Vec::new();
+    let mut writer = ArrowWriter::try_new(&mut out, schema, None).map_err(hub_err)?;
+    writer.write(&batch).map_err(hub_err)?;
+    writer.close().map_err(hub_err)?;
+    Ok(out)
+}
+
+/// Reads a metrics table written either by this backend (a `metrics` JSON column) or
+/// by Python trackio (one column per metric).
+fn read_rows(
+    data: impl parquet::file::reader::ChunkReader + 'static,
+) -> Result<Vec<HubRow>, TrackioError> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(data)
+        .map_err(hub_err)?
+        .build()
+        .map_err(hub_err)?;
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(hub_err)?;
+        let schema = batch.schema();
+        let column = |name: &str| batch.column_by_name(name);
+        for i in 0..batch.num_rows() {
+            let run = column("run_name")
+                .or_else(|| column("run_id"))
+                .map(|c| cell(c, i))
+                .and_then(|v| v.as_str().map(String::from))
+                .unwrap_or_default();
+            let metrics = match column("metrics").map(|c| cell(c, i)) {
+                Some(Value::String(raw)) => raw,
+                _ => {
+                    let mut metrics = Map::new();
+                    for (field, col) in schema.fields().iter().zip(batch.columns()) {
+                        if STRUCTURAL_COLUMNS.contains(&field.name().as_str()) {
+                            continue;
+                        }
+                        let value = cell(col, i);
+                        if !value.is_null() {
+                            metrics.insert(field.name().clone(), value);
+                        }
+                    }
+                    Value::Object(metrics).to_string()
+                }
+            };
+            let text =
+                |name: &str| column(name).and_then(|c| cell(c, i).as_str().map(String::from));
+            rows.push(HubRow {
+                run,
+                timestamp: text("timestamp").unwrap_or_default(),
+                step: column("step")
+                    .and_then(|c| cell(c, i).as_i64())
+                    .unwrap_or(0),
+                metrics,
+                log_id: text("log_id"),
+                space_id: text("space_id"),
+            });
+        }
+    }
+    Ok(rows)
+}
+
+fn cell(col: &ArrayRef, i: usize) -> Value {
+    if col.is_null(i) {
+        return Value::Null;
+    }
+    let any = col.as_any();
+    if let Some(a) = any.downcast_ref::<StringArray>() {
+        json!(a.value(i))
+    } else if let Some(a) = any.downcast_ref::<LargeStringArray>() {
+        json!(a.value(i))
+    } else if let Some(a) = any.downcast_ref::<Int64Array>() {
+        json!(a.value(i))
+    } else if let Some(a) = any.downcast_ref::<Int32Array>() {
+        json!(a.value(i))
+    } else if let Some(a) = any.downcast_ref::<Float64Array>() {
+        json!(a.value(i))
+    } else if let Some(a) = any.downcast_ref::<Float32Array>() {
+        json!(a.value(i))
+    } else if let Some(a) = any.downcast_ref::<BooleanArray>() {
+        json!(a.value(i))
+    } else {
+        Value::Null
+    }
+}
+
+fn hub_err(e: impl std::fmt::Display) -> TrackioError {
+    TrackioError::Hub(e.to_string())
+}
@@ -0,0 +1,229 @@
+pub mod tensorboard;
+#[cfg(feature = "http")]
+pub mod wandb;
+
+use crate::client::{Client, TrackioError};
+use crate::query::glob_match;
+use serde_json::{Map, Value};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// How the records of a JSON-lines file map onto logged rows.
+///
+/// ```no_run
+/// use trackio::import::{tail_jsonl, JsonlMapping};
+///
+/// let client = trackio::Client::new().with_project("p").with_run("r");
+/// let mapping = JsonlMapping::new().step_key("iter").metrics(["train/*"]);
+/// tail_jsonl(&client, "metrics.jsonl", &mapping)?;
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonlMapping {
+    pub step_key: Option<String>,
+    pub timestamp_key: Option<String>,
+    pub metrics: Vec<String>,
+    pub prefix: String,
+    pub checkpoint: Option<PathBuf>,
+    pub from_end: bool,
+    pub poll_interval: Duration,
+}
+
+impl Default for JsonlMapping {
+    fn default() -> Self {
+        Self {
+            step_key: Some("step".into()),
+            timestamp_key: Some("timestamp".into()),
+            metrics: Vec::new(),
+            prefix: String::new(),
+            checkpoint: None,
+            from_end: false,
+            poll_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl JsonlMapping {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Field holding the step (default: `step`); `None` lets the client number rows.
+    pub fn step_key<S: Into<String>>(mut self, key: impl Into<Option<S>>) -> Self {
+        self.step_key = key.into().map(Into::into);
+        self
+    }
+
+    /// Field holding the timestamp (default: `timestamp`).
+    pub fn timestamp_key<S: Into<String>>(mut self, key: impl Into<Option<S>>) -> Self {
+        self.timestamp_key = key.into().map(Into::into);
+        self
+    }
+
+    /// Only import fields matching one of these glob patterns.
+    pub fn metrics<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.metrics = globs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Prepended to every imported metric name, e.g. `"train/"`.
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Where the byte offset of the last imported line is stored
+    /// (default: `<file>.trackio-offset` next to the tailed file).
+    pub fn checkpoint<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// Without a checkpoint, skip the lines already in the file instead of importing them.
+    pub fn from_end(mut self, from_end: bool) -> Self {
+        self.from_end = from_end;
+        self
+    }
+
+    /// How often the file is checked for new lines (default: 500ms).
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Turns one line into `(metrics, step, timestamp)`, or `None` if it is not a JSON object.
+    pub fn parse(&self, line: &str) -> Option<(Value, Option<i64>, Option<String>)> {
+        let Ok(Value::Object(mut record)) = serde_json::from_str(line.trim()) else {
+            return None;
+        };
+        let step = self
+            .step_key
+            .as_ref()
+            .and_then(|k| record.remove(k))
+            .and_then(|s| s.as_i64().or_else(|| s.as_f64().map(|f| f as i64)));
+        let timestamp = self
+            .timestamp_key
+            .as_ref()
+            .and_then(|k| record.remove(k))
+            .and_then(|t| t.as_str().map(String::from));
+        let metrics: Map<String, Value> = record
+            .into_iter()
+            .filter(|(k, _)| {
+                self.metrics.is_empty() || self.metrics.iter().any(|g| glob_match(g, k))
+            })
+            .map(|(k, v)| (format!("{}{k}", self.prefix), v))
+            .collect();
+        Some((Value::Object(metrics), step, timestamp))
+    }
+}
+
+/// Follows a JSON-lines file and forwards new records to a run, remembering how far it
+/// got so a restarted importer resumes where the previous one stopped.
+#[derive(Debug)]
+pub struct JsonlTail<'a> {
+    client: &'a Client,
+    path: PathBuf,
+    checkpoint: PathBuf,
+    mapping: JsonlMapping,
+    offset: u64,
+}
+
+impl<'a> JsonlTail<'a> {
+    /// Resume from the checkpoint if there is one.
+    pub fn new<P: AsRef<Path>>(
+        client: &'a Client,
+        path: P,
+        mapping: &JsonlMapping,
+    ) -> Result<Self, TrackioError> {
+        let path = path.as_ref().to_path_buf();
+        let checkpoint = mapping.checkpoint.clone().unwrap_or_else(|| {
+            let mut name = path.file_name().unwrap_or_default().to_os_string();
+            name.push(".trackio-offset");
+            path.with_file_name(name)
+        });
+        let offset = match fs::read_to_string(&checkpoint) {
+            Ok(raw) => raw.trim().parse().unwrap_or(0),
+            Err(_) if mapping.from_end => fs::metadata(&path).map_or(0, |m| m.len()),
+            Err(_) => 0,
+        };
+        Ok(Self {
+            client,
+            path,
+            checkpoint,
+            mapping: mapping.clone(),
+            offset,
+        })
+    }
+
+    /// Byte offset up to which the file has been imported.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Import the complete lines appended since the last call, flush them and advance the
+    /// checkpoint. Returns the number of rows logged. A file that shrank is assumed to have
+    /// been truncated or replaced and is read again from the start.
+    pub fn poll(&mut self) -> Result<usize, TrackioError> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+        let Some(end) = buf.iter().rposition(|b| *b == b'\n') else {
+            return Ok(0);
+        };
+
+        let mut logged = 0;
+        for line in buf[..end].split(|b| *b == b'\n') {
+            let line = String::from_utf8_lossy(line);
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some((metrics, step, ts)) = self.mapping.parse(&line) {
+                self.client.log(metrics, step, ts);
+                logged += 1;
+            }
+        }
+        self.client.flush()?;
+        self.offset += end as u64 + 1;
+        self.save_checkpoint()?;
+        Ok(logged)
+    }
+
+    fn save_checkpoint(&self) -> Result<(), TrackioError> {
+        let mut tmp = self.checkpoint.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, self.offset.to_string())?;
+        fs::rename(&tmp, &self.checkpoint)?;
+        Ok(())
+    }
+}
+
+/// Follow `path` forever, logging every record to `client`'s run.
+///
+/// Returns on the first error. The checkpoint only advances after a successful flush,
+/// so calling it again resumes without losing or repeating lines.
+pub fn tail_jsonl<P: AsRef<Path>>(
+    client: &Client,
+    path: P,
+    mapping: &JsonlMapping,
+) -> Result<(), TrackioError> {
+    let mut tail = JsonlTail::new(client, path, mapping)?;
+    loop {
+        tail.poll()?;
+        thread::sleep(mapping.poll_interval);
+    }
+}
@@ -0,0 +1,275 @@
+use crate::client::{Client, TrackioError};
+use crate::time::format_iso8601;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+/// One scalar read from a TensorBoard event file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scalar {
+    pub tag: String,
+    pub step: i64,
+    pub wall_time: f64,
+    pub value: f64,
+}
+
+/// Read every scalar of a `tfevents` file, both the legacy `simple_value` summaries
+/// and the scalar tensors written by `tf.summary.scalar` / `torch.utils.tensorboard`.
+/// A record cut short by a writer that is still running ends the file.
+pub fn read_scalars<P: AsRef<Path>>(path: P) -> Result<Vec<Scalar>, TrackioError> {
+    let data = fs::read(path)?;
+    let mut scalars = Vec::new();
+    let mut pos = 0;
+    while pos + 12 <= data.len() {
+        let len = u64::from_le_bytes(data[pos..pos + 8].try_into().expect("8 bytes")) as usize;
+        let start = pos + 12;
+        let Some(end) = start.checked_add(len).filter(|end| end + 4 <= data.len()) else {
+            break;
+        };
+        read_event(&data[start..end], &mut scalars);
+        pos = end + 4;
+    }
+    Ok(scalars)
+}
+
+impl Client {
+    /// Replay the scalars of a TensorBoard log directory into `project`.
+    ///
+    /// Every directory holding `*tfevents*` files becomes a run named after its path
+    /// relative to `logdir` (or after `logdir` itself for files at its root). Scalars
+    /// logged at the same step are merged into one row, timestamped with the event's
+    /// wall time. Returns the number of rows logged.
+    pub fn import_tensorboard<P: AsRef<Path>>(
+        &self,
+        project: &str,
+        logdir: P,
+    ) -> Result<usize, TrackioError> {
+        let logdir = logdir.as_ref();
+        let mut runs: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+        for file in event_files(logdir)? {
+            let dir = file.parent().unwrap_or(logdir);
+            let name = match dir.strip_prefix(logdir) {
+                Ok(rel) if !rel.as_os_str().is_empty() => rel.to_string_lossy().replace('\\', "/"),
+                _ => logdir
+                    .canonicalize()
+                    .ok()
+                    .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+                    .unwrap_or_else(|| "tensorboard".into()),
+            };
+            runs.entry(name).or_default().push(file);
+        }
+
+        let mut logged = 0;
+        for (run, mut files) in runs {
+            files.sort();
+            let mut rows: BTreeMap<i64, (f64, Map<String, Value>)> = BTreeMap::new();
+            for file in files {
+                for scalar in read_scalars(&file)? {
+                    let (wall_time, metrics) = rows.entry(scalar.step).or_default();
+                    *wall_time = wall_time.max(scalar.wall_time);
+                    metrics.insert(scalar.tag, Value::from(scalar.value));
+                }
+            }
+            let client = self.for_run(project, &run);
+            for (step, (wall_time, metrics)) in rows {
+                let ts = UNIX_EPOCH + Duration::from_secs_f64(wall_time.max(0.0));
+                client.log(Value::Object(metrics), Some(step), Some(format_iso8601(ts)));
+                logged += 1;
+            }
+            client.flush()?;
+        }
+        Ok(logged)
+    }
+}
+
+fn event_files(dir: &Path) -> Result<Vec<PathBuf>, TrackioError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files.extend(event_files(&path)?);
+        } else if path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().contains("tfevents"))
+        {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+fn read_event(buf: &[u8], out: &mut Vec<Scalar>) {
+    let mut wall_time = 0.0;
+    let mut step = 0;
+    let mut summaries = Vec::new();
+    for (field, value) in Fields(buf) {
+        match (field, value) {
+            (1, Wire::Fixed64(v)) => wall_time = f64::from_bits(v),
+            (2, Wire::Varint(v)) => step = v as i64,
+            (5, Wire::Bytes(b)) => summaries.push(b),
+            _ => {}
+        }
+    }
+    for summary in summaries {
+        for (field, value) in Fields(summary) {
+            if let (1, Wire::Bytes(b)) = (field, value) {
+                if let Some((tag, value)) = read_value(b) {
+                    out.push(Scalar {
+                        tag,
+                        step,
+                        wall_time,
+                        value,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn read_value(buf: &[u8]) -> Option<(String, f64)> {
+    let mut tag = None;
+    let mut simple = None;
+    let mut tensor = None;
+    let mut plugin = None;
+    for (field, value) in Fields(buf) {
+        match (field, value) {
+            (1, Wire::Bytes(b)) => tag = Some(String::from_utf8_lossy(b).into_owned()),
+            (2, Wire::Fixed32(v)) => simple = Some(widen(f32::from_bits(v))),
+            (8, Wire::Bytes(b)) => tensor = Some(b),
+            (9, Wire::Bytes(b)) => plugin = plugin_name(b),
+            _ => {}
+        }
+    }
+    let value = match (simple, tensor) {
+        (Some(v), _) => v,
+        (None, Some(t)) if plugin.as_deref().is_none_or(|p| p == "scalars") => scalar_tensor(t)?,
+        _ => return None,
+    };
+    Some((tag?, value))
+}
+
+fn plugin_name(metadata: &[u8]) -> Option<String> {
+    let plugin_data = Fields(metadata).find_map(|(f, v)| match (f, v) {
+        (1, Wire::Bytes(b)) => Some(b),
+        _ => None,
+    })?;
+    Fields(plugin_data).find_map(|(f, v)| match (f, v) {
+        (1, Wire::Bytes(b)) => Some(String::from_utf8_lossy(b).into_owned()),
+        _ => None,
+    })
+}
+
+const DT_FLOAT: u64 = 1;
+const DT_DOUBLE: u64 = 2;
+const DT_INT32: u64 = 3;
+const DT_INT64: u64 = 9;
+
+fn scalar_tensor(buf: &[u8]) -> Option<f64> {
+    let mut dtype = 0;
+    let mut scalar_shape = true;
+    let mut content: Option<&[u8]> = None;
+    let mut value = None;
+    for (field, wire) in Fields(buf) {
+        match (field, wire) {
+            (1, Wire::Varint(v)) => dtype = v,
+            (2, Wire::Bytes(shape)) => {
+                scalar_shape = !Fields(shape).any(|(f, _)| f == 2);
+            }
+            (4, Wire::Bytes(b)) => content = Some(b),
+            (5, Wire::Bytes(b)) if b.len() >= 4 => {
+                value = Some(widen(f32::from_le_bytes(b[..4].try_into().ok()?)))
+            }
+            (5, Wire::Fixed32(v)) => value = Some(widen(f32::from_bits(v))),
+            (6, Wire::Bytes(b)) if b.len() >= 8 => {
+                value = Some(f64::from_le_bytes(b[..8].try_into().ok()?))
+            }
+            (6, Wire::Fixed64(v)) => value = Some(f64::from_bits(v)),
+            (7 | 10, Wire::Bytes(b)) => value = varint(b, &mut 0).map(|v| v as i64 as f64),
+            (7 | 10, Wire::Varint(v)) => value = Some(v as i64 as f64),
+            _ => {}
+        }
+    }
+    if !scalar_shape {
+        return None;
+    }
+    if let Some(b) = content {
+        return match (dtype, b.len()) {
+            (DT_FLOAT, 4) => Some(widen(f32::from_le_bytes(b.try_into().ok()?))),
+            (DT_DOUBLE, 8) => Some(f64::from_le_bytes(b.try_into().ok()?)),
+            (DT_INT32, 4) => Some(i32::from_le_bytes(b.try_into().ok()?) as f64),
+            (DT_INT64, 8) => Some(i64::from_le_bytes(b.try_into().ok()?) as f64),
+            _ => None,
+        };
+    }
+    match dtype {
+        DT_FLOAT | DT_DOUBLE | DT_INT32 | DT_INT64 => value,
+        _ => None,
+    }
+}
+
+/// Widens through the shortest decimal form, so a logged `0.1f32` becomes `0.1`
+/// rather than `0.10000000149011612`.
+fn widen(v: f32) -> f64 {
+    v.to_string().parse().unwrap_or(v as f64)
+}
+
+enum Wire<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Iterator over the `(field number, value)` pairs of an encoded protobuf message.
+/// Stops at the first malformed field.
+struct Fields<'a>(&'a [u8]);
+
+fn varint(buf: &[u8], pos: &mut usize) -> Option<u64> {
+    let mut out = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *buf.get(*pos)?;
+        *pos += 1;
+        out |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(out);
+        }
+    }
+    None
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = (u64, Wire<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buf = self.0;
+        let mut pos = 0;
+        let key = varint(buf, &mut pos)?;
+        let wire = match key & 7 {
+            0 => Wire::Varint(varint(buf, &mut pos)?),
+            1 => {
+                let v = u64::from_le_bytes(buf.get(pos..pos + 8)?.try_into().ok()?);
+                pos += 8;
+                Wire::Fixed64(v)
+            }
+            2 => {
+                let len = varint(buf, &mut pos)? as usize;
+                let b = buf.get(pos..pos.checked_add(len)?)?;
+                pos += len;
+                Wire::Bytes(b)
+            }
+            5 => {
+                let v = u32::from_le_bytes(buf.get(pos..pos + 4)?.try_into().ok()?);
+                pos += 4;
+                Wire::Fixed32(v)
+            }
+            _ => {
+                self.0 = &[];
+                return None;
+            }
+        };
+        self.0 = &buf[pos..];
+        Some((key >> 3, wire))
+    }
+}
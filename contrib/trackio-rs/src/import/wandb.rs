@@ -0,0 +1,370 @@
+use crate::client::{Client, TrackioError};
+use crate::time::format_iso8601;
+use reqwest::blocking::Client as Http;
+use serde_json::{json, Map, Value};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+
+const HISTORY_PAGE: i64 = 1000;
+
+const RUNS_QUERY: &str = "
+query Runs($entity: String!, $project: String!, $cursor: String) {
+  project(name: $project, entityName: $entity) {
+    runs(first: 50, after: $cursor) {
+      edges { node { name displayName config lastHistoryStep } }
+      pageInfo { hasNextPage endCursor }
+    }
+  }
+}";
+
+const RUN_QUERY: &str = "
+query Run($entity: String!, $project: String!, $run: String!) {
+  project(name: $project, entityName: $entity) {
+    run(name: $run) { name displayName config lastHistoryStep }
+  }
+}";
+
+const HISTORY_QUERY: &str = "
+query History($entity: String!, $project: String!, $run: String!, $minStep: Int64!, $maxStep: Int64!, $samples: Int!) {
+  project(name: $project, entityName: $entity) {
+    run(name: $run) { history(minStep: $minStep, maxStep: $maxStep, samples: $samples) }
+  }
+}";
+
+/// A run read from Weights & Biases, ready to be replayed into Trackio.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WandbRun {
+    pub name: String,
+    pub config: Map<String, Value>,
+    pub history: Vec<Map<String, Value>>,
+}
+
+impl Client {
+    /// Import Weights & Biases runs into `project`, keeping their steps, timestamps
+    /// and config. Returns the number of rows logged.
+    ///
+    /// `source` is either a local directory or a W&B path:
+    /// - a directory is read with [`read_export_dir`];
+    /// - `entity/project` imports every run of that W&B project and
+    ///   `entity/project/run_id` a single run, through the W&B API authenticated with
+    ///   `$WANDB_API_KEY` (and `$WANDB_BASE_URL` for a self-hosted server).
+    pub fn import_wandb(&self, project: &str, source: &str) -> Result<usize, TrackioError> {
+        let runs = if Path::new(source).is_dir() {
+            read_export_dir(source)?
+        } else {
+            fetch_runs(source)?
+        };
+        let mut logged = 0;
+        for run in runs {
+            let client = self.for_run(project, &run.name);
+            if !run.config.is_empty() {
+                client.set_config(Value::Object(run.config));
+            }
+            for row in run.history {
+                let (metrics, step, ts) = convert_row(row);
+                if metrics.is_empty() {
+                    continue;
+                }
+                client.log(Value::Object(metrics), step, ts);
+                logged += 1;
+            }
+            client.flush()?;
+        }
+        Ok(logged)
+    }
+}
+
+/// Read runs exported to disk. Each run is a directory (either `dir` itself or one of
+/// its subdirectories, such as the `wandb/run-*` folders the W&B client writes) holding:
+/// - the history as JSON lines in `history.jsonl` or `wandb-history.jsonl`, or as a JSON
+///   array in `history.json` (e.g. `run.history().to_json(orient="records")`);
+/// - optionally the config in `config.json`, or the `config.yaml` written by the client
+///   (top-level scalar values only);
+/// - optionally the run name under `name`/`displayName` in `metadata.json`.
+///
+/// The same files are also looked up in a `files/` subdirectory.
+pub fn read_export_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<WandbRun>, TrackioError> {
+    let dir = dir.as_ref();
+    let mut runs = Vec::new();
+    if let Some(run) = read_run_dir(dir)? {
+        runs.push(run);
+        return Ok(runs);
+    }
+    let mut subdirs: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_dir())
+        .collect();
+    subdirs.sort();
+    for sub in subdirs {
+        if let Some(run) = read_run_dir(&sub)? {
+            runs.push(run);
+        }
+    }
+    Ok(runs)
+}
+
+fn read_run_dir(dir: &Path) -> Result<Option<WandbRun>, TrackioError> {
+    let find = |names: &[&str]| {
+        [dir.to_path_buf(), dir.join("files")]
+            .into_iter()
+            .flat_map(|d| names.iter().map(move |n| d.join(n)))
+            .find(|p| p.is_file())
+    };
+
+    let history = if let Some(path) = find(&["history.jsonl", "wandb-history.jsonl"]) {
+        fs::read_to_string(path)?
+            .lines()
+            .filter_map(|l| match serde_json::from_str(l) {
+                Ok(Value::Object(row)) => Some(row),
+                _ => None,
+            })
+            .collect()
+    } else if let Some(path) = find(&["history.json"]) {
+        match serde_json::from_str(&fs::read_to_string(path)?) {
+            Ok(Value::Array(rows)) => rows
+                .into_iter()
+                .filter_map(|r| match r {
+                    Value::Object(row) => Some(row),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    } else {
+        return Ok(None);
+    };
+
+    let config = if let Some(path) = find(&["config.json"]) {
+        match serde_json::from_str(&fs::read_to_string(path)?) {
+            Ok(Value::Object(config)) => unwrap_config(config),
+            _ => Map::new(),
+        }
+    } else if let Some(path) = find(&["config.yaml"]) {
+        unwrap_config(parse_config_yaml(&fs::read_to_string(path)?))
+    } else {
+        Map::new()
+    };
+
+    let metadata_name = find(&["metadata.json"])
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .and_then(|m| {
+            m.get("displayName")
+                .or_else(|| m.get("name"))
+                .and_then(Value::as_str)
+                .map(String::from)
+        });
+    let name = metadata_name.unwrap_or_else(|| {
+        let dir_name = dir
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "wandb".into());
+        match dir_name.strip_prefix("run-") {
+            Some(rest) => rest.rsplit('-').next().unwrap_or(rest).to_string(),
+            None => dir_name,
+        }
+    });
+
+    Ok(Some(WandbRun {
+        name,
+        config,
+        history,
+    }))
+}
+
+/// Read runs through the W&B GraphQL API. `path` is `entity/project[/run_id]`.
+pub fn fetch_runs(path: &str) -> Result<Vec<WandbRun>, TrackioError> {
+    let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let (entity, project, run) = match parts.as_slice() {
+        [entity, project] => (*entity, *project, None),
+        [entity, project, run] => (*entity, *project, Some(*run)),
+        _ => {
+            return Err(TrackioError::Import(format!(
+                "{path:?} is neither a directory nor a W&B path entity/project[/run_id]"
+            )))
+        }
+    };
+    let api = WandbApi::new()?;
+
+    let nodes = match run {
+        Some(run) => {
+            let data = api.query(
+                RUN_QUERY,
+                json!({ "entity": entity, "project": project, "run": run }),
+            )?;
+            match data.pointer("/project/run") {
+                Some(node) if !node.is_null() => vec![node.clone()],
+                _ => return Err(TrackioError::NotFound(format!("W&B run {path}"))),
+            }
+        }
+        None => {
+            let mut nodes = Vec::new();
+            let mut cursor = Value::Null;
+            loop {
+                let data = api.query(
+                    RUNS_QUERY,
+                    json!({ "entity": entity, "project": project, "cursor": cursor }),
+                )?;
+                let runs = data
+                    .pointer("/project/runs")
+                    .ok_or_else(|| TrackioError::NotFound(format!("W&B project {path}")))?;
+                for edge in runs["edges"].as_array().into_iter().flatten() {
+                    nodes.push(edge["node"].clone());
+                }
+                if runs.pointer("/pageInfo/hasNextPage") != Some(&Value::Bool(true)) {
+                    break;
+                }
+                cursor = runs
+                    .pointer("/pageInfo/endCursor")
+                    .cloned()
+                    .unwrap_or_default();
+            }
+            nodes
+        }
+    };
+
+    let mut runs = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let id = node["name"].as_str().unwrap_or_default().to_string();
+        let config = match node["config"].as_str().map(serde_json::from_str) {
+            Some(Ok(Value::Object(config))) => unwrap_config(config),
+            _ => Map::new(),
+        };
+        let last_step = node["lastHistoryStep"].as_i64().unwrap_or(-1);
+        let mut history = Vec::new();
+        let mut min_step = 0;
+        while min_step <= last_step {
+            let data = api.query(
+                HISTORY_QUERY,
+                json!({
+                    "entity": entity,
+                    "project": project,
+                    "run": id,
+                    "minStep": min_step,
+                    "maxStep": min_step + HISTORY_PAGE,
+                    "samples": HISTORY_PAGE,
+                }),
+            )?;
+            for line in data
+                .pointer("/project/run/history")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                if let Some(Ok(Value::Object(row))) = line.as_str().map(serde_json::from_str) {
+                    history.push(row);
+                }
+            }
+            min_step += HISTORY_PAGE;
+        }
+        runs.push(WandbRun {
+            name: node["displayName"].as_str().map(String::from).unwrap_or(id),
+            config,
+            history,
+        });
+    }
+    Ok(runs)
+}
+
+struct WandbApi {
+    url: String,
+    key: String,
+    http: Http,
+}
+
+impl WandbApi {
+    fn new() -> Result<Self, TrackioError> {
+        let key = env::var("WANDB_API_KEY").map_err(|_| {
+            TrackioError::Import("set WANDB_API_KEY to import from the W&B API".into())
+        })?;
+        let base = env::var("WANDB_BASE_URL").unwrap_or_else(|_| "https://api.wandb.ai".into());
+        Ok(Self {
+            url: format!("{}/graphql", base.trim_end_matches('/')),
+            key,
+            http: Http::builder()
+                .timeout(Duration::from_secs(60))
+                .build()
+                .expect("failed to build HTTP client"),
+        })
+    }
+
+    fn query(&self, query: &str, variables: Value) -> Result<Value, TrackioError> {
+        let resp = self
+            .http
+            .post(&self.url)
+            .basic_auth("api", Some(&self.key))
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()?;
+        if !resp.status().is_success() {
+            return Err(TrackioError::from_response(resp));
+        }
+        let mut body: Value = resp.json()?;
+        if let Some(errors) = body.get("errors").filter(|e| !e.is_null()) {
+            return Err(TrackioError::Import(format!("W&B API error: {errors}")));
+        }
+        Ok(body.get_mut("data").map(Value::take).unwrap_or_default())
+    }
+}
+
+/// W&B stores every config entry as `{"desc": ..., "value": ...}` and adds internal
+/// `_wandb` keys; keep only the user's values.
+fn unwrap_config(config: Map<String, Value>) -> Map<String, Value> {
+    config
+        .into_iter()
+        .filter(|(k, _)| !k.starts_with('_'))
+        .map(|(k, v)| match v {
+            Value::Object(mut entry) if entry.contains_key("value") => {
+                (k, entry.remove("value").unwrap_or_default())
+            }
+            other => (k, other),
+        })
+        .collect()
+}
+
+/// Reads the `key:\n  value: <scalar>` entries of a W&B `config.yaml`. Nested values
+/// are skipped since there is no YAML parser in the dependency tree.
+fn parse_config_yaml(raw: &str) -> Map<String, Value> {
+    let mut config = Map::new();
+    let mut key: Option<String> = None;
+    for line in raw.lines() {
+        if !line.starts_with(' ') {
+            key = line.strip_suffix(':').map(|k| k.trim().to_string());
+            continue;
+        }
+        let Some(key) = &key else { continue };
+        let Some(value) = line.strip_prefix("  value:") else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        let parsed = serde_json::from_str(value).unwrap_or_else(|_| match value {
+            "null" | "~" => Value::Null,
+            "true" | "True" => Value::Bool(true),
+            "false" | "False" => Value::Bool(false),
+            _ => Value::String(value.trim_matches('\'').to_string()),
+        });
+        config.insert(key.clone(), json!({ "value": parsed }));
+    }
+    config
+}
+
+/// Splits a W&B history row into metrics, `_step` and `_timestamp`, dropping internal
+/// keys and media/histogram objects, which have no Trackio scalar equivalent.
+fn convert_row(mut row: Map<String, Value>) -> (Map<String, Value>, Option<i64>, Option<String>) {
+    let step = row.get("_step").and_then(Value::as_i64);
+    let ts = row
+        .get("_timestamp")
+        .and_then(Value::as_f64)
+        .filter(|t| *t > 0.0)
+        .map(|t| format_iso8601(UNIX_EPOCH + Duration::from_secs_f64(t)));
+    row.retain(|k, v| {
+        let media = v.get("_type").is_some();
+        !(k.starts_with('_') || v.is_null() || media)
+    });
+    (row, step, ts)
+}
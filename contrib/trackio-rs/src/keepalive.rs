@@ -0,0 +1,56 @@
+use crate::client::Client;
+use std::env;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+impl Client {
+    /// Ping the server from a background thread about every `interval` (at least a
+    /// minute, give or take 10% so many workers don't ping in step), so a free-tier
+    /// Hugging Face Space doesn't go to sleep in the middle of a run that logs nothing
+    /// for a while, such as during a long evaluation. No ping is sent while rows are
+    /// being logged, since those keep the Space awake already.
+    ///
+    /// The pings stop for good after `max_duration`, so a forgotten process can't keep
+    /// a Space up indefinitely, and when the client is dropped. Keepalive is off unless
+    /// this is called, and `TRACKIO_KEEPALIVE=0` turns it off even then, for users who
+    /// want their Space to sleep.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    ///
+    /// let client = std::sync::Arc::new(trackio::Client::new().with_project("p").with_run("r"));
+    /// client.spawn_keepalive(Duration::from_secs(600), Duration::from_secs(24 * 3600));
+    /// ```
+    pub fn spawn_keepalive(self: &Arc<Self>, interval: Duration, max_duration: Duration) {
+        if env::var("TRACKIO_KEEPALIVE").is_ok_and(|v| matches!(v.trim(), "0" | "false")) {
+            log::debug!("trackio: keepalive disabled by TRACKIO_KEEPALIVE");
+            return;
+        }
+        let client = Arc::downgrade(self);
+        let interval = interval.max(MIN_INTERVAL);
+        let deadline = Instant::now() + max_duration;
+        let mut seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        thread::spawn(move || loop {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            let jitter = (seed >> 11) as f64 / (1u64 << 53) as f64 * 0.2 - 0.1;
+            thread::sleep(interval.mul_f64(1.0 + jitter));
+            if Instant::now() >= deadline {
+                return;
+            }
+            let Some(client) = client.upgrade() else {
+                return;
+            };
+            if client.since_last_log().is_some_and(|idle| idle < interval) {
+                continue;
+            }
+            if let Err(e) = client.ping() {
+                log::debug!("trackio: keepalive ping failed: {e}");
+            }
+        });
+    }
+}
@@ -0,0 +1,172 @@
+use crate::client::{Client, TrackioError};
+use std::env;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const WRITE_ACCESS_MARKER: &str = "write access at: ";
+
+/// Options for [`launch_local_with`].
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    /// Python interpreter with the `trackio` package installed
+    /// (default: `$TRACKIO_PYTHON`, then `python3`).
+    pub python: String,
+    /// Port to bind; `None` lets trackio pick the first free port from 7860.
+    pub port: Option<u16>,
+    /// Project preselected in the dashboard and used by [`LocalServer::client`].
+    pub project: Option<String>,
+    /// How long to wait for the server to print its URL and answer requests.
+    pub startup_timeout: Duration,
+}
+
+impl Default for LaunchOptions {
+    fn default() -> Self {
+        Self {
+            python: env::var("TRACKIO_PYTHON").unwrap_or_else(|_| "python3".into()),
+            port: None,
+            project: None,
+            startup_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// A Python trackio dashboard running as a child process. Killed on drop.
+#[derive(Debug)]
+pub struct LocalServer {
+    child: Child,
+    url: String,
+    write_token: Option<String>,
+    project: Option<String>,
+}
+
+impl LocalServer {
+    /// Base URL of the dashboard, without query parameters.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The write token printed by the server at startup.
+    pub fn write_token(&self) -> Option<&str> {
+        self.write_token.as_deref()
+    }
+
+    /// A client pointed at this server, authenticated with its write token.
+    pub fn client(&self) -> Client {
+        let mut client = Client::new().with_base_url(&self.url);
+        if let Some(token) = &self.write_token {
+            client = client.with_write_token(token);
+        }
+        if let Some(project) = &self.project {
+            client = client.with_project(project);
+        }
+        client
+    }
+
+    /// Stop the server now instead of on drop.
+    pub fn shutdown(mut self) {
+        self.kill();
+    }
+
+    fn kill(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for LocalServer {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+/// Spawn a local Python trackio dashboard with default options, like
+/// `trackio.show(open_browser=False)`, and wait until it accepts requests.
+pub fn launch_local() -> Result<LocalServer, TrackioError> {
+    launch_local_with(LaunchOptions::default())
+}
+
+/// Spawn a local Python trackio dashboard and wait until it accepts requests.
+pub fn launch_local_with(opts: LaunchOptions) -> Result<LocalServer, TrackioError> {
+    let mut args = vec!["open_browser=False".to_string(), "block_thread=True".into()];
+    if let Some(port) = opts.port {
+        args.push(format!("server_port={port}"));
+    }
+    if let Some(project) = &opts.project {
+        args.push(format!("project={}", python_str(project)));
+    }
+    let script = format!("import trackio; trackio.show({})", args.join(", "));
+
+    let mut child = Command::new(&opts.python)
+        .args(["-u", "-c", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| TrackioError::Launch(format!("failed to start {}: {e}", opts.python)))?;
+
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut tx = Some(tx);
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if let Some(pos) = line.find(WRITE_ACCESS_MARKER) {
+                if let Some(tx) = tx.take() {
+                    let _ = tx.send(line[pos + WRITE_ACCESS_MARKER.len()..].trim().to_string());
+                }
+            }
+        }
+    });
+
+    let deadline = Instant::now() + opts.startup_timeout;
+    let full_url = match rx.recv_timeout(opts.startup_timeout) {
+        Ok(url) => url,
+        Err(_) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(TrackioError::Launch(
+                "trackio server exited or did not print its URL in time".into(),
+            ));
+        }
+    };
+
+    let (url, write_token) = split_write_token(&full_url);
+    let server = LocalServer {
+        child,
+        url,
+        write_token,
+        project: opts.project,
+    };
+
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    match Client::new()
+        .with_base_url(&server.url)
+        .wait_until_ready(remaining)
+    {
+        Ok(()) => Ok(server),
+        Err(_) => Err(TrackioError::Launch(format!(
+            "trackio server at {} did not become ready in time",
+            server.url
+        ))),
+    }
+}
+
+fn split_write_token(full_url: &str) -> (String, Option<String>) {
+    let (base, query) = full_url.split_once('?').unwrap_or((full_url, ""));
+    let token = query
+        .split('&')
+        .filter_map(|kv| kv.split_once('='))
+        .find(|(k, _)| *k == "write_token")
+        .map(|(_, v)| {
+            urlencoding::decode(v)
+                .map(|v| v.into_owned())
+                .unwrap_or_else(|_| v.to_string())
+        });
+    (base.trim_end_matches('/').to_string(), token)
+}
+
+fn python_str(s: &str) -> String {
+    format!("'{}'", s.replace('\\', "\\\\").replace('\'', "\\'"))
+}
@@ -0,0 +1,158 @@
+//! Layered service configuration as run config, with the `figment` and `config-rs`
+//! features.
+//!
+//! Services often assemble their configuration from defaults, files and environment
+//! variables. [`Client::with_figment`] and [`Client::with_config_rs`] record the merged
+//! result in the run's config, flattened to `.`-separated keys, and under
+//! `_ConfigSources` where each key's value came from: a file, an environment variable
+//! prefix, or the code that set it.
+//!
+//! ```no_run
+//! use figment::providers::Serialized;
+//! use figment::Figment;
+//!
+//! let figment = Figment::from(Serialized::defaults(serde_json::json!({ "workers": 4 })))
+//!     .merge(Serialized::default("model.name", "gpt"));
+//! let client = trackio::Client::new().with_figment(&figment);
+//! ```
+
+use crate::client::{Client, TrackioError};
+use serde_json::{Map, Value};
+
+impl Client {
+    /// Record the configuration merged by `figment` in the run's config. It is sent
+    /// with the first flush; keys set with [`set_config`](Client::set_config) take
+    /// precedence. A configuration that doesn't extract is skipped with a warning.
+    #[cfg(feature = "figment")]
+    pub fn with_figment(self, figment: &figment::Figment) -> Self {
+        match figment_config(figment) {
+            Ok(config) => self.with_default_config(config),
+            Err(e) => {
+                log::warn!("trackio: could not record the figment configuration: {e}");
+                self
+            }
+        }
+    }
+
+    /// [`with_figment`](Client::with_figment) for a configuration built with the
+    /// `config` crate.
+    #[cfg(feature = "config-rs")]
+    pub fn with_config_rs(self, config: &config_rs::Config) -> Self {
+        match config_rs_config(config) {
+            Ok(config) => self.with_default_config(config),
+            Err(e) => {
+                log::warn!("trackio: could not record the configuration: {e}");
+                self
+            }
+        }
+    }
+}
+
+/// The config [`Client::with_figment`] records for `figment`.
+#[cfg(feature = "figment")]
+pub fn figment_config(figment: &figment::Figment) -> Result<Map<String, Value>, TrackioError> {
+    let merged: Value = figment
+        .extract()
+        .map_err(|e| TrackioError::Config(e.to_string()))?;
+    let mut config = Map::new();
+    flatten("", merged, &mut config);
+    let sources = config
+        .keys()
+        .filter_map(|key| {
+            let metadata = figment.find_metadata(key)?;
+            let source = match &metadata.source {
+                Some(source) => format!("{} ({source})", metadata.name),
+                None => metadata.name.to_string(),
+            };
+            Some((key.clone(), Value::String(source)))
+        })
+        .collect();
+    Ok(with_sources(config, sources))
+}
+
+/// The config [`Client::with_config_rs`] records for `config`. Keys set in code, such
+/// as defaults and overrides, have no source.
+#[cfg(feature = "config-rs")]
+pub fn config_rs_config(config: &config_rs::Config) -> Result<Map<String, Value>, TrackioError> {
+    use config_rs::Source;
+
+    fn walk(
+        prefix: &str,
+        table: config_rs::Map<String, config_rs::Value>,
+        config: &mut Map<String, Value>,
+        sources: &mut Map<String, Value>,
+    ) {
+        for (key, value) in table {
+            let key = join(prefix, &key);
+            if let Some(origin) = value.origin() {
+                sources.insert(key.clone(), Value::String(origin.into()));
+            }
+            match value.kind {
+                config_rs::ValueKind::Table(table) => walk(&key, table, config, sources),
+                kind => {
+                    config.insert(key, to_json(kind));
+                }
+            }
+        }
+    }
+
+    let table = config
+        .collect()
+        .map_err(|e| TrackioError::Config(e.to_string()))?;
+    let mut flat = Map::new();
+    let mut sources = Map::new();
+    walk("", table, &mut flat, &mut sources);
+    sources.retain(|key, _| flat.contains_key(key));
+    Ok(with_sources(flat, sources))
+}
+
+#[cfg(feature = "config-rs")]
+fn to_json(kind: config_rs::ValueKind) -> Value {
+    use config_rs::ValueKind;
+    use serde_json::Number;
+
+    match kind {
+        ValueKind::Nil => Value::Null,
+        ValueKind::Boolean(b) => Value::Bool(b),
+        ValueKind::I64(i) => Value::from(i),
+        ValueKind::U64(u) => Value::from(u),
+        ValueKind::I128(i) => i64::try_from(i).map_or_else(|_| i.to_string().into(), Value::from),
+        ValueKind::U128(u) => u64::try_from(u).map_or_else(|_| u.to_string().into(), Value::from),
+        ValueKind::Float(f) => Number::from_f64(f).map_or(Value::Null, Value::Number),
+        ValueKind::String(s) => Value::String(s),
+        ValueKind::Array(values) => values.into_iter().map(|v| to_json(v.kind)).collect(),
+        ValueKind::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| (key, to_json(value.kind)))
+            .collect(),
+    }
+}
+
+#[cfg(feature = "figment")]
+fn flatten(prefix: &str, value: Value, config: &mut Map<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten(&join(prefix, &key), value, config);
+            }
+        }
+        value => {
+            config.insert(prefix.into(), value);
+        }
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.into()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+fn with_sources(mut config: Map<String, Value>, sources: Map<String, Value>) -> Map<String, Value> {
+    if !sources.is_empty() {
+        config.insert("_ConfigSources".into(), Value::Object(sources));
+    }
+    config
+}
@@ -0,0 +1,137 @@
+use crate::client::Client;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+
+/// Per-layer values of a deep model (attention entropy, activation norms, gradient
+/// norms), collected over a step and logged as a few keys instead of one per layer.
+///
+/// Logging every layer of an 80-layer model as its own scalar makes hundreds of metric
+/// keys per step. By default each metric is logged instead as one array with a value
+/// per layer (`null` for layers without one) under its name, next to `<name>/mean`,
+/// `/min` and `/max` over the layers, which the dashboard charts. With
+/// [`scalars`](LayerMetrics::scalars), layers are logged as scalars
+/// `<name>/layer_07` for at most `max_layers` evenly spaced layers, first and last
+/// included, next to the same statistics over all layers.
+///
+/// ```no_run
+/// use trackio::LayerMetrics;
+///
+/// # let client = trackio::Client::new().with_project("p").with_run("r");
+/// # let norms = vec![1.0; 80];
+/// let mut layers = LayerMetrics::new(80);
+/// for step in 0..1_000 {
+///     for (layer, norm) in norms.iter().enumerate() {
+///         layers.record(layer, "act_norm", *norm);
+///     }
+///     client.log_layers(&mut layers, Some(step));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LayerMetrics {
+    n_layers: usize,
+    max_scalars: Option<usize>,
+    values: BTreeMap<String, Vec<Option<f64>>>,
+}
+
+impl LayerMetrics {
+    /// Collect values for layers `0..n_layers`.
+    pub fn new(n_layers: usize) -> Self {
+        Self {
+            n_layers,
+            max_scalars: None,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Log scalars for at most `max_layers` evenly spaced layers instead of arrays.
+    pub fn scalars(mut self, max_layers: usize) -> Self {
+        self.max_scalars = Some(max_layers);
+        self
+    }
+
+    /// Set the value of the metric `name` for `layer`. Layers out of range are ignored.
+    pub fn record(&mut self, layer: usize, name: &str, value: f64) {
+        if layer >= self.n_layers {
+            return;
+        }
+        let n_layers = self.n_layers;
+        let values = match self.values.get_mut(name) {
+            Some(values) => values,
+            None => self
+                .values
+                .entry(name.into())
+                .or_insert_with(|| vec![None; n_layers]),
+        };
+        values[layer] = Some(value);
+    }
+
+    /// Set the value of the metric `name` for every layer, in layer order.
+    pub fn record_all(&mut self, name: &str, values: impl IntoIterator<Item = f64>) {
+        for (layer, value) in values.into_iter().enumerate() {
+            self.record(layer, name, value);
+        }
+    }
+
+    /// The row of everything recorded since the last call, emptying the collector.
+    pub fn take(&mut self) -> Map<String, Value> {
+        let mut row = Map::new();
+        let picked = self.max_scalars.map(|max| spread(self.n_layers, max));
+        let width = self.n_layers.saturating_sub(1).to_string().len();
+        for (name, values) in std::mem::take(&mut self.values) {
+            let recorded: Vec<f64> = values.iter().flatten().copied().collect();
+            if recorded.is_empty() {
+                continue;
+            }
+            let sum: f64 = recorded.iter().sum();
+            let min = recorded.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = recorded.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            row.insert(format!("{name}/mean"), float(sum / recorded.len() as f64));
+            row.insert(format!("{name}/min"), float(min));
+            row.insert(format!("{name}/max"), float(max));
+            match &picked {
+                Some(layers) => {
+                    for &layer in layers {
+                        if let Some(value) = values[layer] {
+                            row.insert(format!("{name}/layer_{layer:0width$}"), float(value));
+                        }
+                    }
+                }
+                None => {
+                    let array = values.into_iter().map(|v| v.map_or(Value::Null, float));
+                    row.insert(name, array.collect());
+                }
+            }
+        }
+        row
+    }
+}
+
+impl Client {
+    /// Log everything `layers` collected since the last call as one row at `step`.
+    pub fn log_layers(&self, layers: &mut LayerMetrics, step: Option<i64>) {
+        let row = layers.take();
+        if !row.is_empty() {
+            self.log(Value::Object(row), step, None);
+        }
+    }
+}
+
+/// At most `max` evenly spaced indices of `0..n`, including the first and last.
+fn spread(n: usize, max: usize) -> Vec<usize> {
+    if n <= max {
+        return (0..n).collect();
+    }
+    match max {
+        0 => Vec::new(),
+        1 => vec![0],
+        _ => {
+            let mut picked: Vec<usize> = (0..max).map(|i| i * (n - 1) / (max - 1)).collect();
+            picked.dedup();
+            picked
+        }
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
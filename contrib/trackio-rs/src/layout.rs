@@ -0,0 +1,104 @@
+//! A dashboard layout pushed with the run, so its charts come up organized instead of
+//! one per metric in alphabetical order.
+//!
+//! A [`Layout`] lists charts, each plotting one or more metrics (names, or globs such as
+//! `train/*`) on a linear or log scale, with a smoothing default for the whole layout
+//! that a chart can override. [`Client::with_layout`](crate::Client::with_layout) stores
+//! it in the run's config as `_Layout`, sent with the first flush:
+//!
+//! ```no_run
+//! use trackio::layout::{Chart, Layout, Scale};
+//!
+//! let layout = Layout::new()
+//!     .with_smoothing(0.6)
+//!     .with_chart(Chart::new("Loss").with_metrics(&["train/loss", "val/loss"]).with_scale(Scale::Log))
+//!     .with_chart(Chart::new("Learning rate").with_metrics(&["lr"]).with_smoothing(0.0))
+//!     .with_chart(Chart::new("System").with_metrics(&["system/*"]).in_section("System"));
+//! let client = trackio::Client::new().with_project("p").with_run("r").with_layout(layout);
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+/// The scale of a chart's y axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scale {
+    #[default]
+    Linear,
+    Log,
+}
+
+/// A chart of a [`Layout`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Chart {
+    pub title: String,
+    /// Metric names or globs, plotted together.
+    pub metrics: Vec<String>,
+    #[serde(default)]
+    pub scale: Scale,
+    /// Smoothing of this chart, from 0 (none) to 1, instead of the layout's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoothing: Option<f64>,
+    /// The collapsible section the chart is shown in, if not the default one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+impl Chart {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.into(),
+            metrics: Vec::new(),
+            scale: Scale::Linear,
+            smoothing: None,
+            section: None,
+        }
+    }
+
+    pub fn with_metrics(mut self, metrics: &[&str]) -> Self {
+        self.metrics.extend(metrics.iter().map(|m| m.to_string()));
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Scale) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Smoothing of this chart, clamped to 0..=1.
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = Some(smoothing.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn in_section(mut self, section: &str) -> Self {
+        self.section = Some(section.into());
+        self
+    }
+}
+
+/// The charts of a run's dashboard, in order; see the [module docs](crate::layout).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Layout {
+    pub charts: Vec<Chart>,
+    /// Smoothing of the charts that don't set their own, from 0 (none) to 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub smoothing: Option<f64>,
+}
+
+impl Layout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_chart(mut self, chart: Chart) -> Self {
+        self.charts.push(chart);
+        self
+    }
+
+    /// Smoothing of the charts that don't set their own, clamped to 0..=1.
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = Some(smoothing.clamp(0.0, 1.0));
+        self
+    }
+}
@@ -1,2 +1,136 @@
-pub mod client;
-pub use client::Client;
\ No newline at end of file
+//! Trackio client for Rust.
+//!
+//! On `wasm32` targets only [`web::WebClient`] is available: the blocking [`Client`] and
+//! everything built on it need threads, sockets and files the browser doesn't have.
+
+/// Items that need threads, sockets or the file system, left out of `wasm32` builds.
+macro_rules! native {
+    ($($item:item)*) => {
+        $(
+            #[cfg(not(target_arch = "wasm32"))]
+            $item
+        )*
+    };
+}
+
+mod checksum;
+mod error;
+mod log_item;
+pub mod runtime;
+mod time;
+pub mod transport;
+pub mod web;
+
+native! {
+    #[cfg(unix)]
+    pub mod agent;
+    pub mod alerts;
+    mod arena;
+    #[cfg(feature = "artifacts")]
+    pub mod artifacts;
+    #[cfg(feature = "clap")]
+    pub mod args;
+    pub mod backend;
+    mod bandwidth;
+    mod backfill;
+    mod baseline;
+    #[cfg(feature = "backend-sqlite")]
+    pub mod bundle;
+    pub mod client;
+    pub mod config;
+    mod crash;
+    pub mod datasets;
+    pub mod diff;
+    pub mod distributed;
+    pub mod dump;
+    pub mod early_stop;
+    pub mod evals;
+    pub mod events;
+    pub mod experiments;
+    pub mod export;
+    mod filter;
+    pub mod groups;
+    mod handles;
+    #[cfg(feature = "http")]
+    mod hf;
+    mod hooks;
+    #[cfg(feature = "backend-hub")]
+    pub mod hub;
+    pub mod import;
+    mod keepalive;
+    pub mod launch;
+    mod layers;
+    pub mod layout;
+    #[cfg(any(feature = "figment", feature = "config-rs"))]
+    pub mod layered;
+    mod macros;
+    pub mod media;
+    pub mod memory;
+    pub mod mlflow_compat;
+    pub mod norms;
+    pub mod notify;
+    #[cfg(feature = "otel")]
+    pub mod otel;
+    mod pacing;
+    mod phase;
+    pub mod pool;
+    pub mod projects;
+    #[cfg(feature = "indicatif")]
+    pub mod progress;
+    pub mod query;
+    pub mod reader;
+    pub mod registry;
+    pub mod report;
+    pub mod runs;
+    mod schedule;
+    pub mod schema;
+    #[cfg(feature = "server")]
+    pub mod server;
+    #[cfg(feature = "shm")]
+    pub mod shm;
+    mod signing;
+    pub mod sinks;
+    #[cfg(feature = "http")]
+    mod space;
+    #[cfg(feature = "backend-sqlite")]
+    pub mod sqlite;
+    mod stagger;
+    pub mod statsd;
+    mod step_timer;
+    mod steps;
+    pub mod sweep;
+    mod telemetry;
+    pub mod template;
+    #[cfg(feature = "testing")]
+    pub mod testing;
+    #[cfg(feature = "tokio")]
+    mod tokio_metrics;
+    #[cfg(feature = "tui")]
+    pub mod tui;
+    pub mod value;
+    pub mod watch;
+    pub use backend::Backend;
+    pub use client::Client;
+    pub use config::{ClientBuilder, ClientConfig};
+    pub use diff::RunDiff;
+    pub use distributed::{DistributedConfig, Policy};
+    pub use export::ExportFormat;
+    pub use handles::RunHandle;
+    #[doc(hidden)]
+    pub use serde_json as __serde_json;
+    #[cfg(feature = "backend-hub")]
+    pub use hub::HubBackend;
+    pub use launch::{launch_local, LocalServer};
+    pub use layers::LayerMetrics;
+    pub use norms::OptimNorms;
+    pub use phase::Phase;
+    pub use projects::{ProjectDescription, ProjectSettings};
+    pub use query::HistoryQuery;
+    pub use runs::{MetricPoint, Note, RunSummary};
+    #[cfg(feature = "backend-sqlite")]
+    pub use sqlite::SqliteBackend;
+    pub use template::RunTemplate;
+    pub use transport::Transport;
+    pub use value::ToMetricValue;
+    pub use watch::{Watch, WatchedPoint};
+}
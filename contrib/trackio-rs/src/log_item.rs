@@ -0,0 +1,264 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone)]
+pub struct LogItem {
+    pub metrics: serde_json::Value,
+    pub step: Option<i64>,
+    pub timestamp: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct BulkPayload<'a> {
+    project: &'a str,
+    run: &'a str,
+    #[serde(rename = "metrics_list")]
+    metrics_list: Vec<&'a serde_json::Value>,
+    steps: Vec<i64>,
+    timestamps: Vec<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<&'a serde_json::Value>,
+}
+
+impl<'a> BulkPayload<'a> {
+    pub(crate) fn new(
+        project: &'a str,
+        run: &'a str,
+        items: &'a [LogItem],
+        config: Option<&'a serde_json::Value>,
+    ) -> Self {
+        Self {
+            project,
+            run,
+            metrics_list: items.iter().map(|it| &it.metrics).collect(),
+            steps: items.iter().map(|it| it.step.unwrap_or(-1)).collect(),
+            timestamps: items
+                .iter()
+                .map(|it| it.timestamp.as_deref().unwrap_or(""))
+                .collect(),
+            config,
+        }
+    }
+}
+
+/// What the server reported about the rows of one [`flush`](crate::Client::flush).
+///
+/// Servers that acknowledge bulk logs (such as the one in `trackio::server`) report how
+/// many rows they stored, the offset (row id) assigned to each and the rows they
+/// rejected. Others, backends and dry runs only confirm the request succeeded, leaving
+/// `accepted` unset and `offsets` empty.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Default)]
+pub struct FlushReceipt {
+    /// Rows sent.
+    pub sent: usize,
+    /// Rows the server stored, if it said.
+    pub accepted: Option<usize>,
+    /// Server-assigned offset of each sent row, in order; `None` for rows it did not
+    /// store.
+    pub offsets: Vec<Option<i64>>,
+    pub rejected: Vec<RejectedItem>,
+}
+
+/// A row the server refused, with its reason.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone)]
+pub struct RejectedItem {
+    /// Position of the row among those sent.
+    pub index: usize,
+    pub reason: String,
+    pub item: LogItem,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FlushReceipt {
+    /// A receipt for `sent` rows without anything reported by the server.
+    pub(crate) fn unconfirmed(sent: usize) -> Self {
+        Self {
+            sent,
+            ..Self::default()
+        }
+    }
+
+    /// A receipt for `item`, sent alone and rejected for `reason`.
+    pub(crate) fn rejected(item: LogItem, reason: String) -> Self {
+        Self {
+            sent: 1,
+            accepted: Some(0),
+            offsets: vec![None],
+            rejected: vec![RejectedItem {
+                index: 0,
+                reason,
+                item,
+            }],
+        }
+    }
+
+    /// Adds `other`, for the rows sent right after these, to this receipt. Counts and
+    /// offsets stay known only if both receipts have them.
+    pub(crate) fn append(&mut self, other: FlushReceipt) {
+        let reported = self.offsets.len() == self.sent && other.offsets.len() == other.sent;
+        self.accepted = match (self.sent, self.accepted, other.accepted) {
+            (0, _, accepted) => accepted,
+            (_, Some(a), Some(b)) => Some(a + b),
+            _ => None,
+        };
+        if reported {
+            self.offsets.extend(other.offsets);
+        } else {
+            self.offsets.clear();
+        }
+        let base = self.sent;
+        self.rejected
+            .extend(other.rejected.into_iter().map(|r| RejectedItem {
+                index: base + r.index,
+                ..r
+            }));
+        self.sent += other.sent;
+    }
+
+    /// Reads the acknowledgement in `body`, a bulk log response, for the rows `items`.
+    pub(crate) fn from_body(items: &[LogItem], body: &[u8]) -> Self {
+        let data = serde_json::from_slice::<serde_json::Value>(body)
+            .ok()
+            .and_then(|mut body| body.get_mut("data").map(serde_json::Value::take))
+            .unwrap_or_default();
+        Self::from_response(items, &data)
+    }
+
+    /// Reads the acknowledgement in `data`, the `data` field of a bulk log response,
+    /// for the rows `items`.
+    fn from_response(items: &[LogItem], data: &serde_json::Value) -> Self {
+        let mut receipt = Self::unconfirmed(items.len());
+        if !data.is_object() {
+            return receipt;
+        }
+        receipt.accepted = data
+            .get("accepted")
+            .and_then(serde_json::Value::as_u64)
+            .map(|n| n as usize);
+        if let Some(offsets) = data.get("offsets").and_then(serde_json::Value::as_array) {
+            receipt.offsets = offsets.iter().map(serde_json::Value::as_i64).collect();
+        }
+        for rejected in data
+            .get("rejected")
+            .and_then(serde_json::Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let index = rejected.get("index").and_then(serde_json::Value::as_u64);
+            let Some((index, item)) =
+                index.and_then(|i| Some((i as usize, items.get(i as usize)?)))
+            else {
+                continue;
+            };
+            receipt.rejected.push(RejectedItem {
+                index,
+                reason: rejected
+                    .get("reason")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                item: item.clone(),
+            });
+        }
+        receipt
+    }
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn items(n: usize) -> Vec<LogItem> {
+        (0..n)
+            .map(|i| LogItem {
+                metrics: json!({ "loss": i }),
+                step: Some(i as i64),
+                timestamp: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn the_payload_lists_rows_column_by_column() {
+        let mut rows = items(2);
+        rows[1].step = None;
+        rows[1].timestamp = Some("2024-01-01T00:00:00".into());
+        let config = json!({ "lr": 0.1 });
+        let payload =
+            serde_json::to_value(BulkPayload::new("p", "r", &rows, Some(&config))).unwrap();
+        assert_eq!(
+            payload,
+            json!({
+                "project": "p",
+                "run": "r",
+                "metrics_list": [{ "loss": 0 }, { "loss": 1 }],
+                "steps": [0, -1],
+                "timestamps": ["", "2024-01-01T00:00:00"],
+                "config": { "lr": 0.1 },
+            })
+        );
+        let empty = serde_json::to_value(BulkPayload::new("p", "r", &[], None)).unwrap();
+        assert_eq!(empty["metrics_list"], json!([]));
+        assert!(empty.get("config").is_none());
+    }
+
+    #[test]
+    fn a_receipt_reads_the_server_acknowledgement() {
+        let rows = items(3);
+        let body = json!({
+            "data": {
+                "accepted": 2,
+                "offsets": [11, null, 12],
+                "rejected": [
+                    { "index": 1, "reason": "NaN step" },
+                    { "index": 9, "reason": "out of range" },
+                ],
+            }
+        });
+        let receipt = FlushReceipt::from_body(&rows, body.to_string().as_bytes());
+        assert_eq!(receipt.sent, 3);
+        assert_eq!(receipt.accepted, Some(2));
+        assert_eq!(receipt.offsets, [Some(11), None, Some(12)]);
+        assert_eq!(receipt.rejected.len(), 1);
+        assert_eq!(receipt.rejected[0].index, 1);
+        assert_eq!(receipt.rejected[0].reason, "NaN step");
+        assert_eq!(receipt.rejected[0].item.step, Some(1));
+    }
+
+    #[test]
+    fn a_receipt_without_an_acknowledgement_only_counts_rows() {
+        for body in [&br#"{"data": null}"#[..], b"[]", b"not json"] {
+            let receipt = FlushReceipt::from_body(&items(4), body);
+            assert_eq!(receipt.sent, 4);
+            assert_eq!(receipt.accepted, None);
+            assert!(receipt.offsets.is_empty());
+            assert!(receipt.rejected.is_empty());
+        }
+    }
+
+    #[test]
+    fn appended_receipts_shift_the_rejected_indices() {
+        let rows = items(4);
+        let ack = |accepted: usize, offsets: serde_json::Value| {
+            json!({ "data": { "accepted": accepted, "offsets": offsets } }).to_string()
+        };
+        let mut receipt = FlushReceipt::from_body(&rows[..2], ack(2, json!([1, 2])).as_bytes());
+        receipt.append(FlushReceipt::rejected(rows[2].clone(), "bad".into()));
+        receipt.append(FlushReceipt::from_body(
+            &rows[3..],
+            ack(1, json!([3])).as_bytes(),
+        ));
+        assert_eq!(receipt.sent, 4);
+        assert_eq!(receipt.accepted, Some(3));
+        assert_eq!(receipt.offsets, [Some(1), Some(2), None, Some(3)]);
+        assert_eq!(receipt.rejected.len(), 1);
+        assert_eq!(receipt.rejected[0].index, 2);
+
+        receipt.append(FlushReceipt::unconfirmed(2));
+        assert_eq!(receipt.sent, 6);
+        assert_eq!(receipt.accepted, None);
+        assert!(receipt.offsets.is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+/// Log a row of metrics given as `name => value` pairs.
+///
+/// The row is built as the map the batch buffer stores, each value converted with
+/// [`ToMetricValue`](crate::ToMetricValue), so domain types that implement the trait are
+/// logged without converting them first, and units they carry are set on the run. It
+/// costs about as much per call as [`Client::log`](crate::Client::log) with a `json!`
+/// row; `cargo bench --bench log` measures both. `run` is anything with a
+/// `log(metrics, step, timestamp)` and a `set_unit(metric, unit)` method, such as a
+/// [`Client`](crate::Client) or a [`RunHandle`](crate::RunHandle). Without `step = ...`
+/// the server picks the step.
+///
+/// ```no_run
+/// # let client = trackio::Client::new().with_project("p").with_run("r");
+/// # let (loss, lr) = (0.5, 3e-4);
+/// for i in 0..1_000_000 {
+///     trackio::log!(client, step = i, "loss" => loss, "lr" => lr);
+/// }
+/// trackio::log!(client, "epoch" => 1);
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($run:expr, step = $step:expr, $($name:expr => $value:expr),+ $(,)?) => {
+        $crate::log!(@row $run, ::core::option::Option::Some($step), $($name => $value),+)
+    };
+    ($run:expr, $($name:expr => $value:expr),+ $(,)?) => {
+        $crate::log!(@row $run, ::core::option::Option::None, $($name => $value),+)
+    };
+    (@row $run:expr, $step:expr, $($name:expr => $value:expr),+) => {{
+        let run = &$run;
+        let mut row = $crate::__serde_json::Map::with_capacity(
+            <[()]>::len(&[$($crate::log!(@unit $name)),+]),
+        );
+        $(
+            let name = ::std::string::String::from($name);
+            let value = $value;
+            if let ::core::option::Option::Some(unit) = $crate::ToMetricValue::unit(&value) {
+                run.set_unit(&name, unit);
+            }
+            row.insert(name, $crate::ToMetricValue::to_metric_value(value));
+        )+
+        run.log($crate::__serde_json::Value::Object(row), $step, ::core::option::Option::None)
+    }};
+    (@unit $name:expr) => {
+        ()
+    };
+}
@@ -0,0 +1,224 @@
+//! Media kept in the user's own object storage, with only a reference logged to the run.
+//!
+//! For organizations that can't upload large binaries to the Hub, a [`MediaStore`]
+//! writes the bytes of images, audio, video and other files wherever they are allowed
+//! to live, and [`Client::log_media`] logs the URL it returns as the metric's value:
+//!
+//! ```json
+//! { "_type": "trackio.image", "url": "s3://ml-media/p/r/samples/7c1e09a2.png", "caption": "step 100", "size": 48213 }
+//! ```
+//!
+//! Objects are named `<project>/<run>/<metric>/<checksum>.<extension>`, so logging the
+//! same bytes twice stores them once. The crate ships one store, [`DirStore`], which
+//! writes to a directory such as a bucket mounted with gcsfuse or s3fs; it has no client
+//! for the object storage APIs themselves. Writing straight to S3, GCS or Azure means
+//! implementing [`MediaStore`] over the provider's SDK.
+//!
+//! ```no_run
+//! use trackio::media::{DirStore, Media};
+//!
+//! # let png: Vec<u8> = Vec::new();
+//! let client = trackio::Client::new()
+//!     .with_project("p")
+//!     .with_run("r")
+//!     .with_media_store(DirStore::new("/mnt/ml-media").with_base_url("s3://ml-media"));
+//! client.log_media("samples", Media::image(&png, "png").with_caption("step 100"), Some(100))?;
+//! # Ok::<(), trackio::client::TrackioError>(())
+//! ```
+
+use crate::checksum;
+use crate::client::{Client, TrackioError};
+use serde_json::{json, Map, Value};
+use std::fmt::Debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where [`Client::log_media`] stores the bytes of media; install one with
+/// [`Client::with_media_store`](crate::Client::with_media_store).
+pub trait MediaStore: Debug + Send + Sync {
+    /// Store `bytes` as the object `key`, replacing any previous one, and return the URL
+    /// the dashboard or a reader of the run can fetch it from.
+    fn put(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<String, TrackioError>;
+
+    /// The URL of the object `key` if it is stored already, so content-addressed objects,
+    /// such as the blobs of artifacts, aren't uploaded again. `None` by default.
+    fn lookup(&self, _key: &str) -> Result<Option<String>, TrackioError> {
+        Ok(None)
+    }
+}
+
+/// Stores media as files under a directory, such as a mounted bucket or a shared file
+/// system, referenced by `file://` URLs or by URLs under a base of your own.
+#[derive(Debug, Clone)]
+pub struct DirStore {
+    dir: PathBuf,
+    base_url: Option<String>,
+}
+
+impl DirStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            base_url: None,
+        }
+    }
+
+    /// Reference the files as `<base_url>/<key>`, e.g. the public URL of the mounted
+    /// bucket, instead of by their `file://` path.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = Some(base_url.trim_end_matches('/').into());
+        self
+    }
+
+    fn url(&self, key: &str, path: &Path) -> String {
+        match &self.base_url {
+            Some(base) => format!("{base}/{key}"),
+            None => {
+                let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+                format!("file://{}", path.display())
+            }
+        }
+    }
+}
+
+impl MediaStore for DirStore {
+    fn put(&self, key: &str, bytes: &[u8], _content_type: &str) -> Result<String, TrackioError> {
+        let path = self.dir.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, bytes)?;
+        Ok(self.url(key, &path))
+    }
+
+    fn lookup(&self, key: &str) -> Result<Option<String>, TrackioError> {
+        let path = self.dir.join(key);
+        Ok(path.is_file().then(|| self.url(key, &path)))
+    }
+}
+
+/// What a piece of media is, which decides how the dashboard shows it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    Audio,
+    Video,
+    /// Anything else, shown as a link.
+    File,
+}
+
+impl MediaKind {
+    /// The `_type` of the logged reference, as the Python client's media objects.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MediaKind::Image => "trackio.image",
+            MediaKind::Audio => "trackio.audio",
+            MediaKind::Video => "trackio.video",
+            MediaKind::File => "trackio.file",
+        }
+    }
+}
+
+/// The bytes of a piece of media to log with [`Client::log_media`].
+#[derive(Debug, Clone, Copy)]
+pub struct Media<'a> {
+    kind: MediaKind,
+    bytes: &'a [u8],
+    extension: &'a str,
+    caption: Option<&'a str>,
+}
+
+impl<'a> Media<'a> {
+    /// Media of `kind` in the format of the file extension `extension`, such as `png`.
+    pub fn new(kind: MediaKind, bytes: &'a [u8], extension: &'a str) -> Self {
+        Self {
+            kind,
+            bytes,
+            extension: extension.trim_start_matches('.'),
+            caption: None,
+        }
+    }
+
+    pub fn image(bytes: &'a [u8], extension: &'a str) -> Self {
+        Self::new(MediaKind::Image, bytes, extension)
+    }
+
+    pub fn audio(bytes: &'a [u8], extension: &'a str) -> Self {
+        Self::new(MediaKind::Audio, bytes, extension)
+    }
+
+    pub fn video(bytes: &'a [u8], extension: &'a str) -> Self {
+        Self::new(MediaKind::Video, bytes, extension)
+    }
+
+    pub fn file(bytes: &'a [u8], extension: &'a str) -> Self {
+        Self::new(MediaKind::File, bytes, extension)
+    }
+
+    pub fn with_caption(mut self, caption: &'a str) -> Self {
+        self.caption = Some(caption);
+        self
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self.extension.to_ascii_lowercase().as_str() {
+            "png" => "image/png",
+            "jpg" | "jpeg" => "image/jpeg",
+            "gif" => "image/gif",
+            "webp" => "image/webp",
+            "svg" => "image/svg+xml",
+            "wav" => "audio/wav",
+            "mp3" => "audio/mpeg",
+            "flac" => "audio/flac",
+            "mp4" => "video/mp4",
+            "webm" => "video/webm",
+            "json" => "application/json",
+            "txt" => "text/plain",
+            _ => "application/octet-stream",
+        }
+    }
+}
+
+impl Client {
+    /// Store `media` in the client's [`MediaStore`] and log its URL as the value of
+    /// `metric` at `step`; see the [module docs](crate::media). Returns the URL. Fails
+    /// without a store, or if the store does.
+    pub fn log_media(&self, metric: &str, media: Media<'_>, step: Option<i64>) -> Result<String, TrackioError> {
+        let store = self.media_store().ok_or_else(|| {
+            TrackioError::InvalidArgument("log_media needs Client::with_media_store".into())
+        })?;
+        let key = format!(
+            "{}/{}/{}/{:08x}.{}",
+            key_part(self.project()),
+            key_part(&self.run_name()),
+            metric.split('/').map(key_part).collect::<Vec<_>>().join("/"),
+            checksum::crc32(media.bytes),
+            key_part(media.extension)
+        );
+        let url = store.put(&key, media.bytes, media.content_type())?;
+        let mut value = json!({
+            "_type": media.kind.as_str(),
+            "url": url,
+            "size": media.bytes.len(),
+        });
+        if let Some(caption) = media.caption {
+            value["caption"] = caption.into();
+        }
+        self.log(Value::Object(Map::from_iter([(metric.to_string(), value)])), step, None);
+        Ok(url)
+    }
+}
+
+/// `part` with anything but ASCII letters, digits, `-` and `_` replaced, and `_` if
+/// empty, so a key can't leave its prefix or need escaping in a URL.
+pub(crate) fn key_part(part: &str) -> String {
+    if part.is_empty() {
+        return "_".into();
+    }
+    part.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
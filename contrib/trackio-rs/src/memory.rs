@@ -0,0 +1,188 @@
+//! Memory use of the training process, logged under `system/memory/*` to catch slow
+//! leaks, such as a data loader holding on to batches, over long runs.
+//!
+//! [`Client::with_memory_tracking`](crate::Client::with_memory_tracking) adds a row to the
+//! flushes at most once per interval:
+//!
+//! | key | value |
+//! |-----|-------|
+//! | `system/memory/rss` | resident set size, in bytes (Linux) |
+//! | `system/memory/peak_rss` | highest resident set size so far (Linux) |
+//! | `system/memory/allocated` | bytes currently allocated through the Rust allocator |
+//! | `system/memory/peak_allocated` | highest `allocated` so far |
+//! | `system/memory/allocations` | live allocations |
+//!
+//! The allocator figures need the [`TrackingAllocator`] installed as the global
+//! allocator. It wraps another one, so they also cover jemalloc or mimalloc:
+//!
+//! ```no_run
+//! use std::alloc::System;
+//! use trackio::memory::TrackingAllocator;
+//!
+//! #[global_allocator]
+//! static ALLOC: TrackingAllocator<System> = TrackingAllocator::new(System);
+//!
+//! # use std::time::Duration;
+//! let client = trackio::Client::new()
+//!     .with_project("p")
+//!     .with_run("r")
+//!     .with_memory_tracking(Duration::from_secs(30));
+//! ```
+
+use crate::log_item::LogItem;
+use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use std::alloc::{GlobalAlloc, Layout};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+pub(crate) const BYTE_KEYS: [&str; 4] = [
+    "system/memory/rss",
+    "system/memory/peak_rss",
+    "system/memory/allocated",
+    "system/memory/peak_allocated",
+];
+
+/// A global allocator counting the bytes and allocations live through `A`, for
+/// [`allocator_stats`]. The counters are a few relaxed atomic operations per call.
+#[derive(Debug, Default)]
+pub struct TrackingAllocator<A> {
+    inner: A,
+}
+
+impl<A> TrackingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+fn allocated(size: usize) {
+    let now = ALLOCATED.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_ALLOCATED.fetch_max(now, Ordering::Relaxed);
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for TrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc(layout) };
+        if !ptr.is_null() {
+            INSTALLED.store(true, Ordering::Relaxed);
+            allocated(layout.size());
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { self.inner.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            INSTALLED.store(true, Ordering::Relaxed);
+            allocated(layout.size());
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { self.inner.dealloc(ptr, layout) };
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = unsafe { self.inner.realloc(ptr, layout, new_size) };
+        if !new.is_null() {
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+            allocated(new_size);
+        }
+        new
+    }
+}
+
+/// What the [`TrackingAllocator`] counted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocatorStats {
+    /// Bytes currently allocated.
+    pub allocated: usize,
+    /// Highest `allocated` so far.
+    pub peak_allocated: usize,
+    /// Allocations not freed yet.
+    pub allocations: usize,
+}
+
+/// The counts of the [`TrackingAllocator`], `None` unless it is the global allocator.
+pub fn allocator_stats() -> Option<AllocatorStats> {
+    INSTALLED.load(Ordering::Relaxed).then(|| AllocatorStats {
+        allocated: ALLOCATED.load(Ordering::Relaxed),
+        peak_allocated: PEAK_ALLOCATED.load(Ordering::Relaxed),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+    })
+}
+
+/// The resident set size of the process and its peak, in bytes, from
+/// `/proc/self/status`; `None` where that doesn't exist.
+pub fn resident_memory() -> Option<(u64, u64)> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kib = |field: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(field))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse::<u64>()
+            .ok()
+            .map(|kib| kib * 1024)
+    };
+    Some((kib("VmRSS:")?, kib("VmHWM:")?))
+}
+
+/// Adds the memory row to a client's flushes; see
+/// [`Client::with_memory_tracking`](crate::Client::with_memory_tracking).
+#[derive(Debug)]
+pub(crate) struct MemorySampler {
+    interval: Duration,
+    last: Mutex<Option<Instant>>,
+}
+
+impl MemorySampler {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: Mutex::new(None),
+        }
+    }
+
+    /// The memory row at `step`, if `interval` has passed since the last one.
+    pub(crate) fn report(&self, step: Option<i64>) -> Option<LogItem> {
+        {
+            let mut last = self.last.lock();
+            if last.is_some_and(|at| at.elapsed() < self.interval) {
+                return None;
+            }
+            *last = Some(Instant::now());
+        }
+        let mut metrics = Map::new();
+        if let Some((rss, peak)) = resident_memory() {
+            metrics.insert("system/memory/rss".into(), rss.into());
+            metrics.insert("system/memory/peak_rss".into(), peak.into());
+        }
+        if let Some(stats) = allocator_stats() {
+            metrics.insert("system/memory/allocated".into(), stats.allocated.into());
+            metrics.insert(
+                "system/memory/peak_allocated".into(),
+                stats.peak_allocated.into(),
+            );
+            metrics.insert("system/memory/allocations".into(), stats.allocations.into());
+        }
+        (!metrics.is_empty()).then_some(LogItem {
+            metrics: Value::Object(metrics),
+            step,
+            timestamp: None,
+        })
+    }
+}
@@ -0,0 +1,209 @@
+use crate::client::{Client, TrackioError};
+use parking_lot::Mutex;
+use serde_json::{json, Map, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Final state of a run, as passed to MLflow's `end_run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunStatus {
+    Finished,
+    Failed,
+    Killed,
+}
+
+impl RunStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            RunStatus::Finished => "FINISHED",
+            RunStatus::Failed => "FAILED",
+            RunStatus::Killed => "KILLED",
+        }
+    }
+}
+
+/// Identity of a started run. Trackio identifies runs by name, so `run_id` and
+/// `run_name` are the same.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunInfo {
+    pub experiment: String,
+    pub run_id: String,
+    pub run_name: String,
+}
+
+#[derive(Debug)]
+struct ActiveRun {
+    info: RunInfo,
+    client: Client,
+    params: Map<String, Value>,
+}
+
+/// MLflow's fluent tracking API (`set_experiment`, `start_run`, `log_param`,
+/// `log_metric`, `end_run`) backed by a Trackio [`Client`], so code written against
+/// MLflow's conventions can switch to Trackio by swapping the tracker type.
+/// Experiments map to projects, params to the run config and metrics to logged rows.
+///
+/// ```no_run
+/// use trackio::mlflow_compat::{RunStatus, Tracker};
+///
+/// let mut mlflow = Tracker::new(trackio::Client::new());
+/// mlflow.set_experiment("my-experiment");
+/// mlflow.start_run(Some("baseline"))?;
+/// mlflow.log_param("lr", 1e-3)?;
+/// mlflow.log_metric("loss", 0.25, Some(10))?;
+/// mlflow.end_run(RunStatus::Finished)?;
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+///
+/// As in MLflow, logging without an active run starts one, metrics logged without a
+/// step are recorded at step 0, params are stored as strings and cannot be changed
+/// once logged, and a run still active when the tracker is dropped is ended.
+#[derive(Debug)]
+pub struct Tracker {
+    client: Client,
+    experiment: String,
+    active: Mutex<Option<ActiveRun>>,
+}
+
+impl Tracker {
+    /// Track into `client`'s server. The experiment defaults to the client's project,
+    /// or `Default` like MLflow's default experiment.
+    pub fn new(client: Client) -> Self {
+        let experiment = match client.project() {
+            "" => "Default".to_string(),
+            project => project.to_string(),
+        };
+        Self {
+            client,
+            experiment,
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Select the experiment (Trackio project) that following runs are created in.
+    pub fn set_experiment(&mut self, name: &str) {
+        self.experiment = name.into();
+    }
+
+    /// The currently active run, if any.
+    pub fn active_run(&self) -> Option<RunInfo> {
+        self.active.lock().as_ref().map(|run| run.info.clone())
+    }
+
+    /// Start a run. Without a name, the client's run name is used if set, otherwise one
+    /// is generated. Fails if a run is already active, as MLflow does.
+    pub fn start_run(&self, run_name: Option<&str>) -> Result<RunInfo, TrackioError> {
+        let mut active = self.active.lock();
+        if let Some(run) = active.as_ref() {
+            return Err(TrackioError::InvalidArgument(format!(
+                "run {} is already active; call end_run() first",
+                run.info.run_id
+            )));
+        }
+        Ok(self.start(&mut active, run_name))
+    }
+
+    fn start(&self, active: &mut Option<ActiveRun>, run_name: Option<&str>) -> RunInfo {
+        let name = match run_name {
+            Some(name) => name.to_string(),
+            None if !self.client.run().is_empty() => self.client.run().to_string(),
+            None => generated_run_name(),
+        };
+        let info = RunInfo {
+            experiment: self.experiment.clone(),
+            run_id: name.clone(),
+            run_name: name.clone(),
+        };
+        *active = Some(ActiveRun {
+            info: info.clone(),
+            client: self.client.for_run(&self.experiment, &name),
+            params: Map::new(),
+        });
+        info
+    }
+
+    fn with_run<T>(&self, f: impl FnOnce(&mut ActiveRun) -> T) -> T {
+        let mut active = self.active.lock();
+        if active.is_none() {
+            self.start(&mut active, None);
+        }
+        f(active.as_mut().expect("a run was just started"))
+    }
+
+    /// Log a param. Logging the same key again with a different value fails.
+    pub fn log_param<V: ToString>(&self, key: &str, value: V) -> Result<(), TrackioError> {
+        self.log_params([(key, value)])
+    }
+
+    /// Log several params at once.
+    pub fn log_params<I, K, V>(&self, params: I) -> Result<(), TrackioError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: ToString,
+    {
+        self.with_run(|run| {
+            for (key, value) in params {
+                let (key, value) = (key.as_ref(), value.to_string());
+                match run.params.get(key).and_then(Value::as_str) {
+                    Some(old) if old != value => {
+                        return Err(TrackioError::InvalidArgument(format!(
+                            "param {key} was already logged as {old:?} and cannot be changed to {value:?}"
+                        )))
+                    }
+                    _ => {
+                        run.params.insert(key.to_string(), Value::String(value));
+                    }
+                }
+            }
+            run.client.set_config(Value::Object(run.params.clone()));
+            Ok(())
+        })
+    }
+
+    /// Log one metric value. `step` defaults to 0, as in MLflow.
+    pub fn log_metric(&self, key: &str, value: f64, step: Option<i64>) -> Result<(), TrackioError> {
+        self.log_metrics([(key, value)], step)
+    }
+
+    /// Log several metrics at the same step.
+    pub fn log_metrics<I, K>(&self, metrics: I, step: Option<i64>) -> Result<(), TrackioError>
+    where
+        I: IntoIterator<Item = (K, f64)>,
+        K: AsRef<str>,
+    {
+        let row: Map<String, Value> = metrics
+            .into_iter()
+            .map(|(k, v)| (k.as_ref().to_string(), json!(v)))
+            .collect();
+        self.with_run(|run| {
+            run.client
+                .log(Value::Object(row), Some(step.unwrap_or(0)), None)
+        });
+        Ok(())
+    }
+
+    /// End the active run, recording `status` under the `mlflow.status` param, and
+    /// flush it. Does nothing without an active run.
+    pub fn end_run(&self, status: RunStatus) -> Result<(), TrackioError> {
+        let Some(mut run) = self.active.lock().take() else {
+            return Ok(());
+        };
+        run.params
+            .insert("mlflow.status".into(), json!(status.as_str()));
+        run.client.set_config(Value::Object(run.params));
+        run.client.flush().map(|_| ())
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        let _ = self.end_run(RunStatus::Finished);
+    }
+}
+
+fn generated_run_name() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    format!("run-{}-{:06}", now.as_secs(), now.subsec_micros())
+}
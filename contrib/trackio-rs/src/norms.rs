@@ -0,0 +1,206 @@
+//! Optimization diagnostics for any framework: gradient and parameter norms, and the
+//! ratio of update to parameter norms, computed from plain `(name, &[f32])` slices.
+//!
+//! burn, candle, tch or a hand-written trainer can all hand over their tensors as
+//! slices, so every run logs the same keys:
+//!
+//! | key | value |
+//! |-----|-------|
+//! | `optim/grad_norm` | L2 norm of all gradients |
+//! | `optim/grad_norm/<group>` | L2 norm of the gradients of a group |
+//! | `optim/grad_nonfinite` | number of NaN or infinite gradient values, left out of the norms |
+//! | `optim/param_norm`, `optim/param_norm/<group>` | the same for the parameters |
+//! | `optim/update_ratio`, `optim/update_ratio/<group>` | update norm over parameter norm |
+//!
+//! Groups are the first [`group_depth`](OptimNorms::group_depth) `.`-separated parts of
+//! the tensor names, so `layers.3.attn.q_proj.weight` belongs to group `layers` by
+//! default and `layers.3` with a depth of 2.
+//!
+//! ```no_run
+//! use trackio::OptimNorms;
+//!
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! # let grads: Vec<(String, Vec<f32>)> = Vec::new();
+//! # let params = grads.clone();
+//! let mut norms = OptimNorms::new().group_depth(2);
+//! norms
+//!     .grads(grads.iter().map(|(name, g)| (name.as_str(), g.as_slice())))
+//!     .params(params.iter().map(|(name, p)| (name.as_str(), p.as_slice())));
+//! client.log_norms(&mut norms, Some(0));
+//! ```
+
+use crate::client::Client;
+use serde_json::{Map, Number, Value};
+use std::collections::BTreeMap;
+
+/// Squared L2 norms summed per group, with the number of non-finite values skipped.
+#[derive(Debug, Clone, Default)]
+struct Sums {
+    groups: BTreeMap<String, f64>,
+    nonfinite: u64,
+    seen: bool,
+}
+
+impl Sums {
+    fn add<'a>(&mut self, depth: usize, tensors: impl IntoIterator<Item = (&'a str, &'a [f32])>) {
+        self.seen = true;
+        for (name, values) in tensors {
+            let (sum, nonfinite) = sum_squares(values);
+            self.nonfinite += nonfinite;
+            *self.groups.entry(group_of(name, depth).into()).or_default() += sum;
+        }
+    }
+
+    fn total(&self) -> f64 {
+        self.groups.values().sum()
+    }
+}
+
+/// Collects the tensors of one optimizer step and turns them into the standard keys
+/// listed in the [module docs](self).
+#[derive(Debug, Clone)]
+pub struct OptimNorms {
+    depth: usize,
+    grads: Sums,
+    params: Sums,
+    updates: Sums,
+}
+
+impl Default for OptimNorms {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimNorms {
+    pub fn new() -> Self {
+        Self {
+            depth: 1,
+            grads: Sums::default(),
+            params: Sums::default(),
+            updates: Sums::default(),
+        }
+    }
+
+    /// How many `.`-separated parts of a tensor name make its group (default 1); 0
+    /// logs only the global norms.
+    pub fn group_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Add gradients.
+    pub fn grads<'a>(&mut self, tensors: impl IntoIterator<Item = (&'a str, &'a [f32])>) -> &mut Self {
+        self.grads.add(self.depth, tensors);
+        self
+    }
+
+    /// Add parameters.
+    pub fn params<'a>(&mut self, tensors: impl IntoIterator<Item = (&'a str, &'a [f32])>) -> &mut Self {
+        self.params.add(self.depth, tensors);
+        self
+    }
+
+    /// Add the updates applied to the parameters in this step (new minus old values).
+    /// Update ratios are logged when parameters are added too.
+    pub fn updates<'a>(&mut self, tensors: impl IntoIterator<Item = (&'a str, &'a [f32])>) -> &mut Self {
+        self.updates.add(self.depth, tensors);
+        self
+    }
+
+    /// The row of everything added since the last call, emptying the collector.
+    pub fn take(&mut self) -> Map<String, Value> {
+        let grads = std::mem::take(&mut self.grads);
+        let params = std::mem::take(&mut self.params);
+        let updates = std::mem::take(&mut self.updates);
+        let mut row = Map::new();
+        for (key, sums) in [("grad_norm", &grads), ("param_norm", &params)] {
+            if !sums.seen {
+                continue;
+            }
+            row.insert(format!("optim/{key}"), float(sums.total().sqrt()));
+            if self.depth > 0 {
+                for (group, sum) in &sums.groups {
+                    row.insert(format!("optim/{key}/{group}"), float(sum.sqrt()));
+                }
+            }
+        }
+        if grads.seen {
+            row.insert("optim/grad_nonfinite".into(), grads.nonfinite.into());
+        }
+        if updates.seen && params.seen {
+            row.insert(
+                "optim/update_ratio".into(),
+                ratio(updates.total(), params.total()),
+            );
+            if self.depth > 0 {
+                for (group, sum) in &updates.groups {
+                    let param = params.groups.get(group).copied().unwrap_or(0.0);
+                    row.insert(format!("optim/update_ratio/{group}"), ratio(*sum, param));
+                }
+            }
+        }
+        row
+    }
+}
+
+impl Client {
+    /// Log everything `norms` collected since the last call as one row at `step`.
+    pub fn log_norms(&self, norms: &mut OptimNorms, step: Option<i64>) {
+        let row = norms.take();
+        if !row.is_empty() {
+            self.log(Value::Object(row), step, None);
+        }
+    }
+}
+
+/// The L2 norm of all values of `tensors` taken together, ignoring non-finite values.
+pub fn global_norm<'a>(tensors: impl IntoIterator<Item = (&'a str, &'a [f32])>) -> f64 {
+    tensors
+        .into_iter()
+        .map(|(_, values)| sum_squares(values).0)
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// The sum of squares of the finite values, and the number of the others. Sums in
+/// `f64`, so large tensors of small gradients don't lose precision.
+fn sum_squares(values: &[f32]) -> (f64, u64) {
+    let sum: f64 = values.iter().map(|&v| f64::from(v) * f64::from(v)).sum();
+    if sum.is_finite() {
+        return (sum, 0);
+    }
+    let mut nonfinite = 0;
+    let sum = values
+        .iter()
+        .filter(|v| {
+            let finite = v.is_finite();
+            nonfinite += u64::from(!finite);
+            finite
+        })
+        .map(|&v| f64::from(v) * f64::from(v))
+        .sum();
+    (sum, nonfinite)
+}
+
+fn group_of(name: &str, depth: usize) -> &str {
+    if depth == 0 {
+        return "";
+    }
+    match name.match_indices('.').nth(depth - 1) {
+        Some((end, _)) => &name[..end],
+        None => name,
+    }
+}
+
+fn ratio(update: f64, param: f64) -> Value {
+    if param > 0.0 {
+        float(update.sqrt() / param.sqrt())
+    } else {
+        Value::Null
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,216 @@
+use crate::alerts::{Alert, AlertLevel};
+use crate::client::TrackioError;
+use crate::transport::{default_transport, user_agent, Method, Request, Transport};
+use serde_json::{json, Map, Value};
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Something that happened to a run that is worth a notification.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunEvent {
+    /// The first rows of the run reached the server.
+    Started,
+    /// The run was closed; `summary` holds the last value of each metric.
+    Finished {
+        summary: Map<String, Value>,
+    },
+    /// The run was ended with [`Client::fail`](crate::Client::fail).
+    Crashed {
+        error: String,
+    },
+    Alert(Alert),
+}
+
+impl RunEvent {
+    fn level(&self) -> AlertLevel {
+        match self {
+            RunEvent::Started | RunEvent::Finished { .. } => AlertLevel::Info,
+            RunEvent::Crashed { .. } => AlertLevel::Error,
+            RunEvent::Alert(alert) => alert.level,
+        }
+    }
+
+    fn title_and_text(&self) -> (String, Option<String>) {
+        match self {
+            RunEvent::Started => ("Run started".into(), None),
+            RunEvent::Finished { summary } => {
+                let lines: Vec<String> = summary.iter().map(|(k, v)| format!("{k}: {v}")).collect();
+                (
+                    "Run finished".into(),
+                    (!lines.is_empty()).then(|| lines.join("\n")),
+                )
+            }
+            RunEvent::Crashed { error } => ("Run crashed".into(), Some(error.clone())),
+            RunEvent::Alert(alert) => (alert.title.clone(), alert.text.clone()),
+        }
+    }
+
+    fn step(&self) -> Option<i64> {
+        match self {
+            RunEvent::Alert(alert) => alert.step,
+            _ => None,
+        }
+    }
+}
+
+/// Posts run events to a webhook, formatted like the Python package's alert webhooks:
+/// Slack and Discord URLs get their native message layout, other URLs a flat JSON
+/// object with `level`, `title`, `text`, `project`, `run`, `step` and `timestamp`.
+///
+/// Install one with [`Client::with_notifier`](crate::Client::with_notifier) to be told
+/// when a run starts, finishes, crashes or raises an alert.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    url: String,
+    min_level: Option<AlertLevel>,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+impl Notifier {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.into(),
+            min_level: None,
+            transport: default_transport(Duration::from_secs(10), &[]),
+        }
+    }
+
+    /// A notifier for `$TRACKIO_WEBHOOK_URL` with `$TRACKIO_WEBHOOK_MIN_LEVEL`, the
+    /// variables the Python package reads, if the URL is set.
+    pub fn from_env() -> Option<Self> {
+        let url = env::var("TRACKIO_WEBHOOK_URL")
+            .ok()
+            .filter(|u| !u.is_empty())?;
+        let min_level = env::var("TRACKIO_WEBHOOK_MIN_LEVEL").ok().and_then(|l| {
+            match l.trim().to_ascii_lowercase().as_str() {
+                "info" => Some(AlertLevel::Info),
+                "warn" => Some(AlertLevel::Warn),
+                "error" => Some(AlertLevel::Error),
+                _ => None,
+            }
+        });
+        let notifier = Self::new(&url);
+        Some(match min_level {
+            Some(level) => notifier.with_min_level(level),
+            None => notifier,
+        })
+    }
+
+    /// Post through `transport` instead of the default one.
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Skip events below `level`. Start and finish events are `info`, crashes `error`.
+    pub fn with_min_level(mut self, level: AlertLevel) -> Self {
+        self.min_level = Some(level);
+        self
+    }
+
+    /// Post `event` for `project`/`run`, linking to `dashboard_url` if given.
+    pub fn notify(
+        &self,
+        project: &str,
+        run: &str,
+        dashboard_url: Option<&str>,
+        event: &RunEvent,
+    ) -> Result<(), TrackioError> {
+        if self.min_level.is_some_and(|min| event.level() < min) {
+            return Ok(());
+        }
+        let transport = self.transport.as_deref().ok_or(TrackioError::NoTransport)?;
+        let payload = self.payload(project, run, dashboard_url, event);
+        transport.send(Request {
+            method: Method::Post,
+            url: &self.url,
+            headers: vec![
+                ("Content-Type", "application/json"),
+                ("User-Agent", &user_agent(None)),
+            ],
+            body: payload.to_string().into_bytes(),
+        })?;
+        Ok(())
+    }
+
+    fn payload(
+        &self,
+        project: &str,
+        run: &str,
+        dashboard_url: Option<&str>,
+        event: &RunEvent,
+    ) -> Value {
+        let level = event.level();
+        let (title, text) = event.title_and_text();
+        let step = event.step();
+        let emoji = match level {
+            AlertLevel::Info => "ℹ️",
+            AlertLevel::Warn => "⚠️",
+            AlertLevel::Error => "🚨",
+        };
+        let step_str = step.map(|s| format!("  •  Step {s}")).unwrap_or_default();
+        let context = format!("Project: {project}  •  Run: {run}{step_str}");
+        let upper = level.as_str().to_uppercase();
+
+        if self.url.contains("hooks.slack.com") {
+            let mut blocks = vec![json!({
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": format!("{emoji} *[{upper}] {title}*") },
+            })];
+            if let Some(text) = &text {
+                blocks
+                    .push(json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } }));
+            }
+            let context = match dashboard_url {
+                Some(url) => format!("{context}  •  <{url}|Dashboard>"),
+                None => context,
+            };
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{ "type": "mrkdwn", "text": context }],
+            }));
+            return json!({ "blocks": blocks });
+        }
+
+        if self.url.contains("discord.com/api/webhooks")
+            || self.url.contains("discordapp.com/api/webhooks")
+        {
+            let color = match level {
+                AlertLevel::Info => 3447003,
+                AlertLevel::Warn => 16776960,
+                AlertLevel::Error => 15158332,
+            };
+            let mut embed = json!({
+                "title": format!("{emoji} [{upper}] {title}"),
+                "color": color,
+                "footer": { "text": context },
+            });
+            if let Some(text) = text {
+                embed["description"] = json!(text);
+            }
+            if let Some(url) = dashboard_url {
+                embed["url"] = json!(url);
+            }
+            return json!({ "embeds": [embed] });
+        }
+
+        let mut payload = json!({
+            "level": level.as_str(),
+            "title": title,
+            "text": text,
+            "project": project,
+            "run": run,
+            "step": step,
+            "timestamp": match event {
+                RunEvent::Alert(alert) => json!(alert.timestamp),
+                _ => json!(crate::time::now_iso8601()),
+            },
+            "dashboard_url": dashboard_url,
+        });
+        if let RunEvent::Finished { summary } = event {
+            payload["summary"] = Value::Object(summary.clone());
+        }
+        payload
+    }
+}
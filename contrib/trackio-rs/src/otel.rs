@@ -0,0 +1,200 @@
+use crate::client::Client;
+use crate::time::format_iso8601;
+use opentelemetry::KeyValue;
+use opentelemetry_sdk::error::{OTelSdkError, OTelSdkResult};
+use opentelemetry_sdk::metrics::data::{AggregatedMetrics, MetricData, ResourceMetrics};
+use opentelemetry_sdk::metrics::exporter::PushMetricExporter;
+use opentelemetry_sdk::metrics::Temporality;
+use serde_json::{Map, Value};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::{Duration, SystemTime};
+
+/// An OpenTelemetry metrics exporter that logs every collection to a Trackio run.
+///
+/// Each export becomes one row at the next step. Sums and gauges are logged under the
+/// instrument name, histograms as `<name>/count`, `/sum`, `/mean`, `/min` and `/max`.
+/// Data points with attributes get them appended as `<name>{key=value,...}`.
+///
+/// ```no_run
+/// use opentelemetry_sdk::metrics::{PeriodicReader, SdkMeterProvider};
+/// use trackio::otel::TrackioExporter;
+///
+/// let client = trackio::Client::new().with_project("infra").with_run("node-1");
+/// let reader = PeriodicReader::builder(TrackioExporter::new(client))
+///     .with_interval(std::time::Duration::from_secs(15))
+///     .build();
+/// let provider = SdkMeterProvider::builder().with_reader(reader).build();
+/// opentelemetry::global::set_meter_provider(provider);
+/// ```
+#[derive(Debug)]
+pub struct TrackioExporter {
+    client: Client,
+    temporality: Temporality,
+    step: AtomicI64,
+    shut_down: AtomicBool,
+}
+
+impl TrackioExporter {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            temporality: Temporality::Cumulative,
+            step: AtomicI64::new(0),
+            shut_down: AtomicBool::new(false),
+        }
+    }
+
+    /// Temporality requested from the SDK (default: cumulative).
+    pub fn with_temporality(mut self, temporality: Temporality) -> Self {
+        self.temporality = temporality;
+        self
+    }
+
+    fn log(&self, metrics: &ResourceMetrics) -> OTelSdkResult {
+        let mut row = Map::new();
+        let mut time = None;
+        for scope in metrics.scope_metrics() {
+            for metric in scope.metrics() {
+                let t = match metric.data() {
+                    AggregatedMetrics::F64(data) => collect(metric.name(), data, &mut row),
+                    AggregatedMetrics::U64(data) => collect(metric.name(), data, &mut row),
+                    AggregatedMetrics::I64(data) => collect(metric.name(), data, &mut row),
+                };
+                time = time.max(Some(t));
+            }
+        }
+        if row.is_empty() {
+            return Ok(());
+        }
+        let step = self.step.fetch_add(1, Ordering::Relaxed);
+        self.client
+            .log(Value::Object(row), Some(step), time.map(format_iso8601));
+        self.client
+            .flush()
+            .map(|_| ())
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+    }
+}
+
+impl PushMetricExporter for TrackioExporter {
+    async fn export(&self, metrics: &ResourceMetrics) -> OTelSdkResult {
+        if self.shut_down.load(Ordering::Relaxed) {
+            return Err(OTelSdkError::AlreadyShutdown);
+        }
+        self.log(metrics)
+    }
+
+    fn force_flush(&self) -> OTelSdkResult {
+        self.client
+            .flush()
+            .map(|_| ())
+            .map_err(|e| OTelSdkError::InternalFailure(e.to_string()))
+    }
+
+    fn shutdown_with_timeout(&self, _timeout: Duration) -> OTelSdkResult {
+        if self.shut_down.swap(true, Ordering::Relaxed) {
+            return Err(OTelSdkError::AlreadyShutdown);
+        }
+        self.force_flush()
+    }
+
+    fn temporality(&self) -> Temporality {
+        self.temporality
+    }
+}
+
+trait Number: Copy {
+    fn to_f64(self) -> f64;
+}
+
+impl Number for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Number for u64 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Number for i64 {
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+/// Adds the data points of one instrument to `row` and returns their collection time.
+fn collect<T: Number>(
+    name: &str,
+    data: &MetricData<T>,
+    row: &mut Map<String, Value>,
+) -> SystemTime {
+    let mut put = |key: String, value: f64| {
+        if value.is_finite() {
+            row.insert(key, Value::from(value));
+        }
+    };
+    match data {
+        MetricData::Gauge(gauge) => {
+            for point in gauge.data_points() {
+                put(series(name, point.attributes()), point.value().to_f64());
+            }
+            gauge.time()
+        }
+        MetricData::Sum(sum) => {
+            for point in sum.data_points() {
+                put(series(name, point.attributes()), point.value().to_f64());
+            }
+            sum.time()
+        }
+        MetricData::Histogram(hist) => {
+            for point in hist.data_points() {
+                let base = series(name, point.attributes());
+                let (count, total) = (point.count(), point.sum().to_f64());
+                put(format!("{base}/count"), count as f64);
+                put(format!("{base}/sum"), total);
+                if count > 0 {
+                    put(format!("{base}/mean"), total / count as f64);
+                }
+                if let Some(min) = point.min() {
+                    put(format!("{base}/min"), min.to_f64());
+                }
+                if let Some(max) = point.max() {
+                    put(format!("{base}/max"), max.to_f64());
+                }
+            }
+            hist.time()
+        }
+        MetricData::ExponentialHistogram(hist) => {
+            for point in hist.data_points() {
+                let base = series(name, point.attributes());
+                let (count, total) = (point.count(), point.sum().to_f64());
+                put(format!("{base}/count"), count as f64);
+                put(format!("{base}/sum"), total);
+                if count > 0 {
+                    put(format!("{base}/mean"), total / count as f64);
+                }
+                if let Some(min) = point.min() {
+                    put(format!("{base}/min"), min.to_f64());
+                }
+                if let Some(max) = point.max() {
+                    put(format!("{base}/max"), max.to_f64());
+                }
+            }
+            hist.time()
+        }
+    }
+}
+
+fn series<'a>(name: &str, attributes: impl Iterator<Item = &'a KeyValue>) -> String {
+    let mut attrs: Vec<String> = attributes
+        .map(|kv| format!("{}={}", kv.key, kv.value))
+        .collect();
+    if attrs.is_empty() {
+        return name.to_string();
+    }
+    attrs.sort();
+    format!("{name}{{{}}}", attrs.join(","))
+}
@@ -0,0 +1,155 @@
+use parking_lot::{Condvar, Mutex};
+use std::time::Duration;
+
+/// Longest wait while nothing is being logged.
+const IDLE_MAX: Duration = Duration::from_secs(5);
+/// Longest wait while the server is slow to answer.
+const SLOW_MAX: Duration = Duration::from_secs(30);
+/// Longest wait after a failed flush.
+const FAILED_MAX: Duration = Duration::from_secs(60);
+
+/// How a background flush went.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum FlushOutcome {
+    Sent { rows: usize, took: Duration },
+    Failed { retry_after: Option<Duration> },
+}
+
+/// Picks the wait before a background flusher's next flush from how the last one went:
+/// right away while rows pile up, the base interval under steady logging, doubling up
+/// to [`IDLE_MAX`] while idle, and long enough to coalesce rows into fewer requests
+/// while the server is slow.
+#[derive(Debug, Clone)]
+pub(crate) struct FlushPacer {
+    base: Duration,
+    hot: usize,
+    delay: Duration,
+}
+
+impl FlushPacer {
+    /// Wait `base` between flushes of up to `hot` rows.
+    pub(crate) fn new(base: Duration, hot: usize) -> Self {
+        Self {
+            base,
+            hot: hot.max(1),
+            delay: base,
+        }
+    }
+
+    /// The wait before the next flush, after one that went as `outcome`.
+    pub(crate) fn next(&mut self, outcome: FlushOutcome) -> Duration {
+        self.delay = match outcome {
+            FlushOutcome::Failed { retry_after } => retry_after
+                .unwrap_or_else(|| self.delay.saturating_mul(2))
+                .clamp(self.base, FAILED_MAX.max(self.base)),
+            FlushOutcome::Sent { took, .. } if took > self.base => {
+                took.saturating_mul(2).clamp(self.base, SLOW_MAX.max(self.base))
+            }
+            FlushOutcome::Sent { rows: 0, .. } => {
+                self.delay.saturating_mul(2).clamp(self.base, IDLE_MAX.max(self.base))
+            }
+            FlushOutcome::Sent { rows, .. } if rows >= self.hot => Duration::ZERO,
+            FlushOutcome::Sent { .. } => self.base,
+        };
+        self.delay
+    }
+}
+
+/// Lets the thread that logs cut a background flusher's wait short.
+#[derive(Debug, Default)]
+pub(crate) struct Wakeup {
+    woken: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl Wakeup {
+    pub(crate) fn wake(&self) {
+        *self.woken.lock() = true;
+        self.condvar.notify_one();
+    }
+
+    /// Wait until woken or for `timeout`, whichever comes first.
+    pub(crate) fn wait(&self, timeout: Duration) {
+        let mut woken = self.woken.lock();
+        if !*woken {
+            self.condvar.wait_for(&mut woken, timeout);
+        }
+        *woken = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Duration = Duration::from_secs(1);
+
+    fn sent(rows: usize, took_ms: u64) -> FlushOutcome {
+        FlushOutcome::Sent {
+            rows,
+            took: Duration::from_millis(took_ms),
+        }
+    }
+
+    #[test]
+    fn steady_logging_waits_the_base_interval() {
+        let mut pacer = FlushPacer::new(BASE, 100);
+        assert_eq!(pacer.next(sent(10, 5)), BASE);
+        assert_eq!(pacer.next(sent(99, 5)), BASE);
+    }
+
+    #[test]
+    fn a_full_batch_flushes_again_right_away() {
+        let mut pacer = FlushPacer::new(BASE, 100);
+        assert_eq!(pacer.next(sent(100, 5)), Duration::ZERO);
+        assert_eq!(pacer.next(sent(250, 5)), Duration::ZERO);
+        assert_eq!(pacer.next(sent(3, 5)), BASE);
+    }
+
+    #[test]
+    fn idle_waits_double_up_to_the_idle_cap() {
+        let mut pacer = FlushPacer::new(BASE, 100);
+        let waits: Vec<_> = (0..5).map(|_| pacer.next(sent(0, 1)).as_secs()).collect();
+        assert_eq!(waits, [2, 4, 5, 5, 5]);
+        assert_eq!(pacer.next(sent(1, 1)), BASE);
+    }
+
+    #[test]
+    fn a_slow_server_gets_twice_the_time_it_took() {
+        let mut pacer = FlushPacer::new(BASE, 100);
+        assert_eq!(pacer.next(sent(10, 4_000)), Duration::from_secs(8));
+        assert_eq!(pacer.next(sent(10, 60_000)), SLOW_MAX);
+    }
+
+    #[test]
+    fn failures_back_off_or_wait_as_asked() {
+        let mut pacer = FlushPacer::new(BASE, 100);
+        let failed = FlushOutcome::Failed { retry_after: None };
+        assert_eq!(pacer.next(failed), Duration::from_secs(2));
+        assert_eq!(pacer.next(failed), Duration::from_secs(4));
+        for _ in 0..10 {
+            pacer.next(failed);
+        }
+        assert_eq!(pacer.next(failed), FAILED_MAX);
+        let asked = FlushOutcome::Failed {
+            retry_after: Some(Duration::from_secs(7)),
+        };
+        assert_eq!(pacer.next(asked), Duration::from_secs(7));
+        let too_soon = FlushOutcome::Failed {
+            retry_after: Some(Duration::ZERO),
+        };
+        assert_eq!(pacer.next(too_soon), BASE);
+    }
+
+    #[test]
+    fn a_wakeup_cuts_the_wait_short_once() {
+        let wakeup = Wakeup::default();
+        wakeup.wake();
+        let started = std::time::Instant::now();
+        wakeup.wait(Duration::from_secs(10));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        let started = std::time::Instant::now();
+        wakeup.wait(Duration::from_millis(50));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::client::Client;
+use serde_json::{Map, Value};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub(crate) struct PhaseState {
+    name: String,
+    next: AtomicI64,
+}
+
+/// A phase of a run (`train`, `eval`, `test`) with its own step counter, from
+/// [`Client::phase`].
+///
+/// Metric names are prefixed with the phase name, so the dashboard groups them. The
+/// first phase created on a client drives the run's x-axis: its rows are logged at its
+/// own step count. Rows of the other phases are logged at the driving phase's latest
+/// step, so an eval every N train steps lines up with training, and carry their own
+/// count as `<phase>/step`.
+///
+/// ```no_run
+/// # let client = trackio::Client::new().with_project("p").with_run("r");
+/// let train = client.phase("train");
+/// let eval = client.phase("eval");
+/// for batch in 0..10_000 {
+///     train.log(serde_json::json!({ "loss": 0.1 }));
+///     if batch % 500 == 0 {
+///         eval.log(serde_json::json!({ "acc": 0.9 }));
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Phase<'a> {
+    client: &'a Client,
+    state: Arc<PhaseState>,
+    driver: Arc<PhaseState>,
+}
+
+impl Client {
+    /// The phase `name` of this run, created on first use; see [`Phase`].
+    pub fn phase(&self, name: &str) -> Phase<'_> {
+        let mut phases = self.phases().lock();
+        let state = match phases.iter().find(|p| p.name == name) {
+            Some(state) => state.clone(),
+            None => {
+                let state = Arc::new(PhaseState {
+                    name: name.into(),
+                    next: AtomicI64::new(0),
+                });
+                phases.push(state.clone());
+                state
+            }
+        };
+        Phase {
+            client: self,
+            driver: phases[0].clone(),
+            state,
+        }
+    }
+}
+
+impl Phase<'_> {
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    /// Rows logged in this phase so far, which is the step of its next row.
+    pub fn step(&self) -> i64 {
+        self.state.next.load(Ordering::Relaxed)
+    }
+
+    /// Continue counting from `step`, e.g. in a resumed run.
+    pub fn set_step(&self, step: i64) {
+        self.state.next.store(step, Ordering::Relaxed);
+    }
+
+    /// Whether this phase drives the run's x-axis.
+    pub fn is_driver(&self) -> bool {
+        Arc::ptr_eq(&self.state, &self.driver)
+    }
+
+    /// Log a row of this phase: names get the `<phase>/` prefix, and the step is this
+    /// phase's count if it drives the x-axis, the driving phase's latest step otherwise.
+    pub fn log(&self, metrics: Value) {
+        let own = self.state.next.fetch_add(1, Ordering::Relaxed);
+        let Value::Object(row) = metrics else {
+            self.client.log(metrics, Some(own), None);
+            return;
+        };
+        let prefix = format!("{}/", self.state.name);
+        let mut out: Map<String, Value> = row
+            .into_iter()
+            .map(|(k, v)| {
+                if k.starts_with(&prefix) {
+                    (k, v)
+                } else {
+                    (format!("{prefix}{k}"), v)
+                }
+            })
+            .collect();
+        let step = if self.is_driver() {
+            own
+        } else {
+            out.insert(format!("{prefix}step"), Value::from(own));
+            (self.driver.next.load(Ordering::Relaxed) - 1).max(0)
+        };
+        self.client.log(Value::Object(out), Some(step), None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Utilization of a CPU thread pool, such as the rayon pool doing preprocessing, whose
+//! saturation otherwise only shows as GPUs waiting for data.
+//!
+//! A [`PoolMonitor`] counts the jobs it wraps: [`job`](PoolMonitor::job) at submission,
+//! so the job is pending until a thread picks it up, and [`run`](PoolMonitor::run) inside
+//! the work itself, such as the closure of a parallel iterator. It works with any pool,
+//! so the crate doesn't depend on rayon. A background thread logs, every interval and at
+//! the run's latest step:
+//!
+//! | key | value |
+//! |-----|-------|
+//! | `system/<name>/threads` | threads of the pool, as given |
+//! | `system/<name>/active_threads` | jobs running when sampled |
+//! | `system/<name>/pending_jobs` | jobs submitted and not started when sampled |
+//! | `system/<name>/utilization` | time spent in the jobs that finished in the interval, over the threads' time |
+//! | `system/<name>/jobs_per_sec` | jobs finished per second over the interval |
+//!
+//! ```no_run
+//! # mod rayon { pub fn current_num_threads() -> usize { 8 } pub fn spawn(f: impl FnOnce() + Send + 'static) { f() } }
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! let client = Arc::new(trackio::Client::new().with_project("p").with_run("r"));
+//! let pool = client.spawn_pool_monitor("rayon", rayon::current_num_threads(), Duration::from_secs(10));
+//! for shard in 0..64 {
+//!     rayon::spawn(pool.job(move || {
+//!         let _decoded = shard * 2;
+//!     }));
+//! }
+//! ```
+
+use crate::client::{Client, LogItem};
+use serde_json::{Map, Number, Value};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+struct Counters {
+    active: AtomicUsize,
+    pending: AtomicUsize,
+    busy_nanos: AtomicU64,
+    completed: AtomicU64,
+}
+
+/// Counts the jobs of a thread pool for [`Client::spawn_pool_monitor`]; see the
+/// [module docs](crate::pool). Clones count into the same totals.
+#[derive(Debug, Clone)]
+pub struct PoolMonitor {
+    counters: Arc<Counters>,
+}
+
+/// Takes a submitted job off the pending count when it starts or is dropped unrun.
+struct Pending(Option<Arc<Counters>>);
+
+impl Pending {
+    fn start(mut self) -> Arc<Counters> {
+        let counters = self.0.take().expect("job started once");
+        counters.pending.fetch_sub(1, Ordering::Relaxed);
+        counters
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if let Some(counters) = &self.0 {
+            counters.pending.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Client {
+    /// Log the utilization of the pool `name` of `threads` threads every `interval`, from
+    /// the jobs wrapped with the returned monitor; see the [module docs](crate::pool).
+    /// Sampling stops when the client is dropped. The rows bypass hooks, filters and the
+    /// schema.
+    pub fn spawn_pool_monitor(self: &Arc<Self>, name: &str, threads: usize, interval: Duration) -> PoolMonitor {
+        let monitor = PoolMonitor {
+            counters: Arc::default(),
+        };
+        let counters = monitor.counters.clone();
+        let client = Arc::downgrade(self);
+        let prefix = format!("system/{name}");
+        thread::spawn(move || {
+            let mut last = (Instant::now(), 0, 0);
+            loop {
+                thread::sleep(interval);
+                let Some(client) = client.upgrade() else {
+                    return;
+                };
+                let now = (
+                    Instant::now(),
+                    counters.busy_nanos.load(Ordering::Relaxed),
+                    counters.completed.load(Ordering::Relaxed),
+                );
+                let elapsed = now.0.duration_since(last.0).as_secs_f64();
+                let busy = now.1.saturating_sub(last.1) as f64 / 1e9;
+                let mut row = Map::new();
+                row.insert(format!("{prefix}/threads"), threads.into());
+                row.insert(
+                    format!("{prefix}/active_threads"),
+                    counters.active.load(Ordering::Relaxed).into(),
+                );
+                row.insert(
+                    format!("{prefix}/pending_jobs"),
+                    counters.pending.load(Ordering::Relaxed).into(),
+                );
+                if elapsed > 0.0 && threads > 0 {
+                    row.insert(
+                        format!("{prefix}/utilization"),
+                        float((busy / (elapsed * threads as f64)).min(1.0)),
+                    );
+                    row.insert(
+                        format!("{prefix}/jobs_per_sec"),
+                        float(now.2.saturating_sub(last.2) as f64 / elapsed),
+                    );
+                }
+                client.buffer_report(LogItem {
+                    metrics: Value::Object(row),
+                    step: client.last_step(),
+                    timestamp: Some(client.timestamp()),
+                });
+                last = now;
+            }
+        });
+        monitor
+    }
+}
+
+impl PoolMonitor {
+    /// Wrap `f` where it is submitted to the pool, e.g. `rayon::spawn(monitor.job(f))`:
+    /// it counts as pending until a thread runs it, then as active.
+    pub fn job<F, R>(&self, f: F) -> impl FnOnce() -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.counters.pending.fetch_add(1, Ordering::Relaxed);
+        let pending = Pending(Some(self.counters.clone()));
+        move || {
+            let counters = pending.start();
+            Self::timed(&counters, f)
+        }
+    }
+
+    /// Run `f` on the current pool thread as an active job, for work the pool schedules
+    /// itself, such as the closures of a parallel iterator.
+    pub fn run<R>(&self, f: impl FnOnce() -> R) -> R {
+        Self::timed(&self.counters, f)
+    }
+
+    fn timed<R>(counters: &Counters, f: impl FnOnce() -> R) -> R {
+        counters.active.fetch_add(1, Ordering::Relaxed);
+        let _running = Running {
+            counters,
+            started: Instant::now(),
+        };
+        f()
+    }
+}
+
+/// Counts a job as finished when it returns or unwinds.
+struct Running<'a> {
+    counters: &'a Counters,
+    started: Instant,
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        let busy = self.started.elapsed().as_nanos() as u64;
+        self.counters.busy_nanos.fetch_add(busy, Ordering::Relaxed);
+        self.counters.completed.fetch_add(1, Ordering::Relaxed);
+        self.counters.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,99 @@
+use crate::client::Client;
+use crate::report::format_number;
+use indicatif::{ProgressBar, ProgressStyle};
+use serde_json::Value;
+
+/// An indicatif progress bar driven by the rows logged to a run, so terminal feedback
+/// and remote tracking come from one call per step.
+///
+/// Each [`log`](Progress::log) forwards the row to the client, moves the bar to the
+/// row's step (or one position on, without a step) and shows the selected metrics as
+/// the bar's message.
+///
+/// ```no_run
+/// let client = trackio::Client::new().with_project("p").with_run("r");
+/// let progress = client.progress(1_000).show(["loss", "lr"]);
+/// for step in 0..1_000 {
+///     progress.log(serde_json::json!({ "loss": 1.0 / (step as f64 + 1.0), "lr": 3e-4 }), Some(step));
+/// }
+/// progress.finish();
+/// ```
+#[derive(Debug)]
+pub struct Progress<'a> {
+    client: &'a Client,
+    bar: ProgressBar,
+    show: Vec<String>,
+}
+
+impl Client {
+    /// A progress bar of `total` steps that logs to this client.
+    pub fn progress(&self, total: u64) -> Progress<'_> {
+        let style = ProgressStyle::with_template(
+            "{spinner} [{elapsed_precise}] {wide_bar} {pos}/{len} ({eta}) {msg}",
+        )
+        .expect("valid progress template");
+        Progress::new(self, ProgressBar::new(total).with_style(style))
+    }
+}
+
+impl<'a> Progress<'a> {
+    /// Drive an existing bar, e.g. one added to a `MultiProgress` or with a custom style.
+    pub fn new(client: &'a Client, bar: ProgressBar) -> Self {
+        Self {
+            client,
+            bar,
+            show: Vec::new(),
+        }
+    }
+
+    /// Metrics shown in the bar's message, in this order (default: every numeric metric
+    /// of the row).
+    pub fn show<I, S>(mut self, metrics: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.show = metrics.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Log `metrics` to the run and update the bar.
+    pub fn log(&self, metrics: Value, step: Option<i64>) {
+        self.bar.set_message(self.message(&metrics));
+        match step {
+            Some(step) => self.bar.set_position(step.max(0) as u64),
+            None => self.bar.inc(1),
+        }
+        self.client.log(metrics, step, None);
+    }
+
+    /// The underlying bar, for printing above it or changing its length.
+    pub fn bar(&self) -> &ProgressBar {
+        &self.bar
+    }
+
+    /// Complete the bar, keeping the last message, and flush the run.
+    pub fn finish(&self) {
+        self.bar.finish();
+        if let Err(e) = self.client.flush() {
+            self.bar.println(format!("trackio: flush failed: {e}"));
+        }
+    }
+
+    fn message(&self, metrics: &Value) -> String {
+        let Some(row) = metrics.as_object() else {
+            return String::new();
+        };
+        let shown: Vec<String> = if self.show.is_empty() {
+            row.iter()
+                .filter_map(|(k, v)| Some(format!("{k}={}", format_number(v.as_f64()?))))
+                .collect()
+        } else {
+            self.show
+                .iter()
+                .filter_map(|k| Some(format!("{k}={}", format_number(row.get(k)?.as_f64()?))))
+                .collect()
+        };
+        shown.join(" ")
+    }
+}
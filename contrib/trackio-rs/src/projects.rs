@@ -0,0 +1,95 @@
+use crate::client::{Client, TrackioError};
+use crate::runs::RunSummary;
+use serde_json::{json, Value};
+
+/// Settings a project is created with.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectSettings {
+    pub description: Option<String>,
+    /// Metric the dashboard plots against by default, such as `step` or `epoch`.
+    pub x_axis: Option<String>,
+}
+
+/// A project's settings and the summary of each of its runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectDescription {
+    pub project: String,
+    pub settings: ProjectSettings,
+    pub archived: bool,
+    /// In creation order.
+    pub runs: Vec<RunSummary>,
+}
+
+impl Client {
+    /// Create `project` with `settings`, or update the settings of an existing one.
+    /// Needs the write token and a server with the `create_project` endpoint, such as
+    /// the one in `trackio::server`; with others it fails with
+    /// [`TrackioError::Unsupported`].
+    pub fn create_project(
+        &self,
+        project: &str,
+        settings: &ProjectSettings,
+    ) -> Result<(), TrackioError> {
+        self.call_extension(
+            "create_project",
+            &json!({
+                "project": project,
+                "description": settings.description,
+                "x_axis": settings.x_axis,
+            }),
+        )?;
+        Ok(())
+    }
+
+    /// Fetch a project's settings and a summary of every run. Servers without project
+    /// settings report the defaults.
+    pub fn describe_project(&self, project: &str) -> Result<ProjectDescription, TrackioError> {
+        let settings = match self.call_extension("get_project_settings", &json!({ "project": project }))
+        {
+            Ok(settings) => settings,
+            Err(TrackioError::Unsupported(_)) => Value::Null,
+            Err(e) => return Err(e),
+        };
+        let text = |key| settings.get(key).and_then(Value::as_str).map(String::from);
+        let records = self.call_api("get_runs_for_project", &json!({ "project": project }))?;
+        let mut runs: Vec<RunSummary> = Vec::new();
+        for record in records.as_array().into_iter().flatten() {
+            let Some(name) = record.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            if !runs.iter().any(|r| r.run == name) {
+                runs.push(self.get_summary(project, name)?);
+            }
+        }
+        Ok(ProjectDescription {
+            project: project.to_string(),
+            settings: ProjectSettings {
+                description: text("description"),
+                x_axis: text("x_axis"),
+            },
+            archived: settings.get("archived").and_then(Value::as_bool) == Some(true),
+            runs,
+        })
+    }
+
+    /// Archive `project`: its data is kept but it is no longer listed by
+    /// `get_all_projects`. Needs the write token and a server with the
+    /// `archive_project` endpoint, such as the one in `trackio::server`; with others it
+    /// fails with [`TrackioError::Unsupported`].
+    pub fn archive_project(&self, project: &str) -> Result<(), TrackioError> {
+        self.set_archived(project, true)
+    }
+
+    /// Undo [`archive_project`](Client::archive_project).
+    pub fn unarchive_project(&self, project: &str) -> Result<(), TrackioError> {
+        self.set_archived(project, false)
+    }
+
+    fn set_archived(&self, project: &str, archived: bool) -> Result<(), TrackioError> {
+        self.call_extension(
+            "archive_project",
+            &json!({ "project": project, "archived": archived }),
+        )?;
+        Ok(())
+    }
+}
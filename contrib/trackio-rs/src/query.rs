@@ -0,0 +1,144 @@
+use crate::runs::MetricPoint;
+use std::ops::RangeInclusive;
+
+/// Filters applied when fetching a run's history.
+///
+/// ```no_run
+/// use trackio::query::HistoryQuery;
+///
+/// let query = HistoryQuery::new()
+///     .metrics(["train/*"])
+///     .steps(0..=10_000)
+///     .max_points(500);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistoryQuery {
+    pub metrics: Vec<String>,
+    pub steps: Option<RangeInclusive<i64>>,
+    pub max_points: Option<usize>,
+}
+
+impl HistoryQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only keep metrics matching one of these glob patterns (`*` and `?` wildcards).
+    pub fn metrics<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.metrics = globs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Only keep points whose step falls inside `range`.
+    pub fn steps(mut self, range: RangeInclusive<i64>) -> Self {
+        self.steps = Some(range);
+        self
+    }
+
+    /// Downsample every metric to at most `n` points using LTTB.
+    pub fn max_points(mut self, n: usize) -> Self {
+        self.max_points = Some(n);
+        self
+    }
+
+    pub(crate) fn matches_metric(&self, name: &str) -> bool {
+        self.metrics.is_empty() || self.metrics.iter().any(|g| glob_match(g, name))
+    }
+
+    pub(crate) fn matches_step(&self, step: i64) -> bool {
+        self.steps.as_ref().is_none_or(|r| r.contains(&step))
+    }
+}
+
+/// Matches `name` against a glob pattern where `*` matches any run of characters
+/// and `?` matches exactly one.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ni));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ni = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+/// Largest-Triangle-Three-Buckets downsampling of a series to `threshold` points.
+///
+/// Series containing non-numeric values are downsampled with a uniform stride instead.
+pub fn lttb(points: &[MetricPoint], threshold: usize) -> Vec<MetricPoint> {
+    if threshold >= points.len() || threshold == 0 {
+        return points.to_vec();
+    }
+    if threshold < 3 {
+        return [points.first(), points.last()]
+            .into_iter()
+            .flatten()
+            .take(threshold)
+            .cloned()
+            .collect();
+    }
+
+    let ys: Option<Vec<f64>> = points.iter().map(|p| p.value.as_f64()).collect();
+    let Some(ys) = ys else {
+        let stride = points.len() as f64 / threshold as f64;
+        return (0..threshold)
+            .map(|i| points[((i as f64 * stride) as usize).min(points.len() - 1)].clone())
+            .collect();
+    };
+    let xs: Vec<f64> = points.iter().map(|p| p.step as f64).collect();
+
+    let mut out = Vec::with_capacity(threshold);
+    out.push(points[0].clone());
+    let bucket = (points.len() - 2) as f64 / (threshold - 2) as f64;
+    let mut a = 0usize;
+
+    for i in 0..threshold - 2 {
+        let start = (i as f64 * bucket) as usize + 1;
+        let end = (((i + 1) as f64 * bucket) as usize + 1).min(points.len() - 1);
+        let next_start = end;
+        let next_end = ((((i + 2) as f64) * bucket) as usize + 1).min(points.len());
+        let next_len = (next_end - next_start).max(1) as f64;
+        let avg_x = xs[next_start..next_end.max(next_start + 1)]
+            .iter()
+            .sum::<f64>()
+            / next_len;
+        let avg_y = ys[next_start..next_end.max(next_start + 1)]
+            .iter()
+            .sum::<f64>()
+            / next_len;
+
+        let mut best = start;
+        let mut best_area = -1.0;
+        for j in start..end.max(start + 1) {
+            let area =
+                ((xs[a] - avg_x) * (ys[j] - ys[a]) - (xs[a] - xs[j]) * (avg_y - ys[a])).abs();
+            if area > best_area {
+                best_area = area;
+                best = j;
+            }
+        }
+        out.push(points[best].clone());
+        a = best;
+    }
+
+    out.push(points[points.len() - 1].clone());
+    out
+}
@@ -0,0 +1,203 @@
+//! Read throughput of the input pipeline, logged next to the rest of the run so a data
+//! loader starving the GPUs shows up as a dip in `data/<name>/read_bytes_per_sec`.
+//!
+//! A [`TrackedReader`] wraps a [`Read`] (or, with the `tokio` feature, a tokio
+//! `AsyncRead`), counts the bytes and records going through it, and every interval adds a
+//! row to the run at its latest step:
+//!
+//! | key | value |
+//! |-----|-------|
+//! | `data/<name>/read_bytes_per_sec` | bytes read per second since the last row |
+//! | `data/<name>/records_per_sec` | records read per second since the last row |
+//! | `data/<name>/read_bytes` | bytes read so far |
+//! | `data/<name>/records` | records read so far |
+//!
+//! The record keys are only logged once records are counted, by
+//! [`with_record_delimiter`](TrackedReader::with_record_delimiter) for line-based formats
+//! or [`add_records`](TrackedReader::add_records) from the parser. Wrap the raw file, not
+//! a `BufReader`, so the counts follow the reads that hit the disk:
+//!
+//! ```no_run
+//! use std::fs::File;
+//! use std::io::{BufRead, BufReader};
+//!
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! let file = File::open("data/train.jsonl")?;
+//! let reader = client.tracked_reader("train", file).with_record_delimiter(b'\n');
+//! for line in BufReader::new(reader).lines() {
+//!     let _example = line?;
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::client::{Client, LogItem};
+use serde_json::{Map, Number, Value};
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+
+/// A reader counting what goes through it and logging the throughput to a run; see the
+/// [module docs](crate::reader). The window still open is logged when it is dropped.
+#[derive(Debug)]
+pub struct TrackedReader<'a, R> {
+    inner: R,
+    client: &'a Client,
+    prefix: String,
+    interval: Duration,
+    delimiter: Option<u8>,
+    bytes: u64,
+    records: u64,
+    window: Option<Window>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    started: Instant,
+    bytes: u64,
+    records: u64,
+}
+
+impl Client {
+    /// Count the reads of `inner`, a source of the dataset `name`, and log their
+    /// throughput every 10 seconds; see [`TrackedReader`].
+    pub fn tracked_reader<R>(&self, name: &str, inner: R) -> TrackedReader<'_, R> {
+        let prefix = format!("data/{name}");
+        self.set_unit(&format!("{prefix}/read_bytes_per_sec"), "bytes/s");
+        self.set_unit(&format!("{prefix}/read_bytes"), "bytes");
+        TrackedReader {
+            inner,
+            client: self,
+            prefix,
+            interval: Duration::from_secs(10),
+            delimiter: None,
+            bytes: 0,
+            records: 0,
+            window: None,
+        }
+    }
+}
+
+impl<R> TrackedReader<'_, R> {
+    /// How often the throughput is logged (default: 10 seconds).
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Count a record for each `delimiter` byte read, such as `b'\n'` for JSON lines or
+    /// CSV.
+    pub fn with_record_delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = Some(delimiter);
+        self
+    }
+
+    /// Count `n` records parsed from the data read, for formats without a delimiter.
+    pub fn add_records(&mut self, n: u64) {
+        self.records += n;
+        if let Some(window) = &mut self.window {
+            window.records += n;
+        }
+    }
+
+    /// Bytes read so far.
+    pub fn bytes(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Records counted so far.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Log the throughput since the last row now, rather than at the end of the interval.
+    pub fn report(&mut self) {
+        let Some(window) = self.window.take() else {
+            return;
+        };
+        let secs = window.started.elapsed().as_secs_f64();
+        if secs <= 0.0 {
+            return;
+        }
+        let mut row = Map::new();
+        row.insert(
+            format!("{}/read_bytes_per_sec", self.prefix),
+            float(window.bytes as f64 / secs),
+        );
+        row.insert(format!("{}/read_bytes", self.prefix), self.bytes.into());
+        if self.records > 0 {
+            row.insert(
+                format!("{}/records_per_sec", self.prefix),
+                float(window.records as f64 / secs),
+            );
+            row.insert(format!("{}/records", self.prefix), self.records.into());
+        }
+        self.client.buffer_report(LogItem {
+            metrics: Value::Object(row),
+            step: self.client.last_step(),
+            timestamp: Some(self.client.timestamp()),
+        });
+    }
+
+    fn counted(&mut self, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        let records = self
+            .delimiter
+            .map_or(0, |d| data.iter().filter(|&&b| b == d).count() as u64);
+        self.bytes += data.len() as u64;
+        self.records += records;
+        let window = self.window.get_or_insert(Window {
+            started: Instant::now(),
+            bytes: 0,
+            records: 0,
+        });
+        window.bytes += data.len() as u64;
+        window.records += records;
+        if window.started.elapsed() >= self.interval {
+            self.report();
+        }
+    }
+}
+
+impl<R: Read> Read for TrackedReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.counted(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for TrackedReader<'_, R> {
+    fn poll_read(
+        self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> std::task::Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let before = buf.filled().len();
+        let poll = std::pin::Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let std::task::Poll::Ready(Ok(())) = poll {
+            this.counted(&buf.filled()[before..]);
+        }
+        poll
+    }
+}
+
+impl<R> Drop for TrackedReader<'_, R> {
+    fn drop(&mut self) {
+        self.report();
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
@@ -0,0 +1,152 @@
+//! A model registry in the project, so an evaluation gate can promote the best
+//! checkpoint to staging or production with lineage back to the run that produced it.
+//!
+//! Needs a server with the registry endpoints, such as the one in `trackio::server`.
+//! The Python dashboard has none, so against it every call fails with
+//! [`TrackioError::Unsupported`].
+//!
+//! ```no_run
+//! use serde_json::json;
+//! use trackio::registry::ModelStage;
+//!
+//! # let client = trackio::Client::new().with_project("llm").with_run("sft-7");
+//! client.register_model("chat", "7", "s3://ckpt/sft-7/step-12000", json!({"eval/acc": 0.81}))?;
+//! let best = client
+//!     .models("llm", Some("chat"))?
+//!     .into_iter()
+//!     .max_by(|a, b| a.metric("eval/acc").total_cmp(&b.metric("eval/acc")))
+//!     .expect("a registered version");
+//! client.promote_model("chat", &best.version, ModelStage::Production)?;
+//! # Ok::<(), trackio::client::TrackioError>(())
+//! ```
+
+use crate::client::{Client, TrackioError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Where a model version is in its lifecycle. `Staging` and `Production` hold one
+/// version each: promoting another archives the one there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelStage {
+    #[default]
+    None,
+    Staging,
+    Production,
+    Archived,
+}
+
+impl ModelStage {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModelStage::None => "none",
+            ModelStage::Staging => "staging",
+            ModelStage::Production => "production",
+            ModelStage::Archived => "archived",
+        }
+    }
+}
+
+/// A registered version of a model.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ModelVersion {
+    pub name: String,
+    pub version: String,
+    /// The run of the project that produced it.
+    pub run: String,
+    /// Where the weights are, e.g. a URL or a path.
+    pub artifact: String,
+    /// The metrics it was registered with.
+    pub metrics: Value,
+    pub stage: ModelStage,
+    /// ISO 8601, as are the other times.
+    pub registered_at: String,
+    pub promoted_at: Option<String>,
+}
+
+impl ModelVersion {
+    /// The registered value of `metric`, or NaN if it has none.
+    pub fn metric(&self, metric: &str) -> f64 {
+        self.metrics
+            .get(metric)
+            .and_then(Value::as_f64)
+            .unwrap_or(f64::NAN)
+    }
+}
+
+impl Client {
+    /// Register `version` of the model `name` as produced by this client's run, stored
+    /// at `artifact_ref`, with the metrics it should be compared on. Needs the write
+    /// token; registering a version twice is an error.
+    pub fn register_model(
+        &self,
+        name: &str,
+        version: &str,
+        artifact_ref: &str,
+        metrics: Value,
+    ) -> Result<(), TrackioError> {
+        self.call_extension(
+            "register_model",
+            &json!({
+                "project": self.project(),
+                "run": self.run_name(),
+                "name": name,
+                "version": version,
+                "artifact": artifact_ref,
+                "metrics": metrics,
+            }),
+        )?;
+        Ok(())
+    }
+
+    /// Move `version` of the model `name` in this client's project to `stage`. Needs
+    /// the write token.
+    pub fn promote_model(
+        &self,
+        name: &str,
+        version: &str,
+        stage: ModelStage,
+    ) -> Result<(), TrackioError> {
+        self.call_extension(
+            "promote_model",
+            &json!({
+                "project": self.project(),
+                "name": name,
+                "version": version,
+                "stage": stage.as_str(),
+            }),
+        )?;
+        Ok(())
+    }
+
+    /// The registered versions of the models of `project`, or only of the model `name`,
+    /// in registration order.
+    pub fn models(
+        &self,
+        project: &str,
+        name: Option<&str>,
+    ) -> Result<Vec<ModelVersion>, TrackioError> {
+        let models = self.call_extension("get_models", &json!({ "project": project, "name": name }))?;
+        Ok(match models {
+            Value::Array(models) => models
+                .into_iter()
+                .filter_map(|m| serde_json::from_value(m).ok())
+                .collect(),
+            _ => Vec::new(),
+        })
+    }
+
+    /// The version of the model `name` in `stage`, the latest registered if several.
+    pub fn model_in_stage(
+        &self,
+        project: &str,
+        name: &str,
+        stage: ModelStage,
+    ) -> Result<Option<ModelVersion>, TrackioError> {
+        Ok(self
+            .models(project, Some(name))?
+            .into_iter()
+            .rev()
+            .find(|m| m.stage == stage))
+    }
+}
@@ -0,0 +1,406 @@
+use crate::client::{Client, TrackioError};
+use crate::diff::flatten_config;
+use crate::query::HistoryQuery;
+use crate::runs::MetricPoint;
+use crate::time::now_iso8601;
+use base64::Engine;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+const PALETTE: [&str; 8] = [
+    "#f97316", "#3b82f6", "#10b981", "#ef4444", "#8b5cf6", "#eab308", "#06b6d4", "#ec4899",
+];
+
+/// Output format of a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A single HTML page with inline SVG charts.
+    Html,
+    /// Markdown with tables and the charts embedded as SVG data URIs.
+    Markdown,
+}
+
+/// What goes into a report rendered by [`Client::render_report`].
+///
+/// ```no_run
+/// use trackio::report::ReportTemplate;
+///
+/// let client = trackio::Client::new();
+/// let report = client.render_report(
+///     "my-project",
+///     &["baseline", "bigger-lr"],
+///     &ReportTemplate::html().title("LR sweep").metrics(["train/loss", "val/*"]),
+/// )?;
+/// std::fs::write("report.html", report)?;
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportTemplate {
+    pub format: ReportFormat,
+    pub title: Option<String>,
+    pub metrics: Vec<String>,
+    pub max_points: usize,
+    pub config: bool,
+}
+
+impl Default for ReportTemplate {
+    fn default() -> Self {
+        Self::new(ReportFormat::Html)
+    }
+}
+
+impl ReportTemplate {
+    pub fn new(format: ReportFormat) -> Self {
+        Self {
+            format,
+            title: None,
+            metrics: Vec::new(),
+            max_points: 500,
+            config: true,
+        }
+    }
+
+    pub fn html() -> Self {
+        Self::new(ReportFormat::Html)
+    }
+
+    pub fn markdown() -> Self {
+        Self::new(ReportFormat::Markdown)
+    }
+
+    /// Heading of the report (default: the project name).
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Only chart and summarize metrics matching one of these glob patterns.
+    pub fn metrics<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.metrics = globs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Downsample every chart line to at most `n` points (default: 500).
+    pub fn max_points(mut self, n: usize) -> Self {
+        self.max_points = n;
+        self
+    }
+
+    /// Whether to include a table of the runs' configs (default: true).
+    pub fn config(mut self, include: bool) -> Self {
+        self.config = include;
+        self
+    }
+}
+
+type Table = (&'static str, fn(&RunData) -> &Map<String, Value>);
+
+const TABLES: [Table; 2] = [("Final metrics", |r| &r.summary), ("Config", |r| &r.config)];
+
+struct RunData {
+    name: String,
+    config: Map<String, Value>,
+    summary: Map<String, Value>,
+    history: BTreeMap<String, Vec<MetricPoint>>,
+}
+
+impl Client {
+    /// Fetch the configs, final values and histories of `runs` and render them as a
+    /// self-contained report, for sharing results with people who won't open the
+    /// dashboard.
+    pub fn render_report(
+        &self,
+        project: &str,
+        runs: &[&str],
+        template: &ReportTemplate,
+    ) -> Result<String, TrackioError> {
+        let query = HistoryQuery::new()
+            .metrics(template.metrics.clone())
+            .max_points(template.max_points);
+        let mut data = Vec::with_capacity(runs.len());
+        for run in runs {
+            let mut summary = self.get_summary(project, run)?.metrics;
+            summary.retain(|name, _| query.matches_metric(name));
+            data.push(RunData {
+                name: run.to_string(),
+                config: if template.config {
+                    flatten_config(self.get_config(project, run)?)
+                } else {
+                    Map::new()
+                },
+                summary,
+                history: self.history(project, run, &query)?,
+            });
+        }
+        let title = template.title.as_deref().unwrap_or(project);
+        Ok(match template.format {
+            ReportFormat::Html => render_html(title, project, &data),
+            ReportFormat::Markdown => render_markdown(title, project, &data),
+        })
+    }
+}
+
+/// Render the numeric points of one metric for several runs as an SVG line chart.
+pub fn line_chart_svg(title: &str, series: &[(&str, &[MetricPoint])]) -> String {
+    let (width, height) = (640.0, 300.0);
+    let (left, right, top, bottom) = (64.0, 16.0, 32.0, 56.0);
+    let lines: Vec<(&str, Vec<(f64, f64)>)> = series
+        .iter()
+        .map(|(name, points)| {
+            let xy = points
+                .iter()
+                .filter_map(|p| Some((p.step as f64, p.value.as_f64()?)))
+                .filter(|(_, y)| y.is_finite())
+                .collect();
+            (*name, xy)
+        })
+        .collect();
+    let all = lines.iter().flat_map(|(_, xy)| xy.iter());
+    let (mut x0, mut x1, mut y0, mut y1) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+    for &(x, y) in all {
+        x0 = x0.min(x);
+        x1 = x1.max(x);
+        y0 = y0.min(y);
+        y1 = y1.max(y);
+    }
+    if x0 > x1 {
+        (x0, x1, y0, y1) = (0.0, 1.0, 0.0, 1.0);
+    }
+    if x1 == x0 {
+        x1 = x0 + 1.0;
+    }
+    if y1 == y0 {
+        (y0, y1) = (y0 - 0.5, y1 + 0.5);
+    }
+    let sx = |x: f64| left + (x - x0) / (x1 - x0) * (width - left - right);
+    let sy = |y: f64| top + (y1 - y) / (y1 - y0) * (height - top - bottom);
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {width} {height}" width="{width}" height="{height}" font-family="sans-serif" font-size="11">"##
+    );
+    let _ = write!(
+        svg,
+        r##"<rect width="100%" height="100%" fill="#fff"/><text x="{left}" y="20" font-size="13" font-weight="bold">{}</text>"##,
+        escape(title)
+    );
+    let (plot_bottom, plot_right) = (height - bottom, width - right);
+    let _ = write!(
+        svg,
+        r##"<path d="M{left} {top}V{plot_bottom}H{plot_right}" fill="none" stroke="#9ca3af"/>"##
+    );
+    for (y, label) in [(y1, top), (y0, plot_bottom)] {
+        let _ = write!(
+            svg,
+            r##"<text x="{}" y="{}" text-anchor="end" fill="#4b5563">{}</text>"##,
+            left - 6.0,
+            label + 4.0,
+            format_number(y)
+        );
+    }
+    for (x, anchor) in [(x0, "start"), (x1, "end")] {
+        let _ = write!(
+            svg,
+            r##"<text x="{}" y="{}" text-anchor="{anchor}" fill="#4b5563">{}</text>"##,
+            sx(x),
+            plot_bottom + 16.0,
+            format_number(x)
+        );
+    }
+    for (i, (name, xy)) in lines.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let points: Vec<String> = xy
+            .iter()
+            .map(|&(x, y)| format!("{:.1},{:.1}", sx(x), sy(y)))
+            .collect();
+        if points.len() == 1 {
+            let _ = write!(
+                svg,
+                r##"<circle cx="{:.1}" cy="{:.1}" r="3" fill="{color}"/>"##,
+                sx(xy[0].0),
+                sy(xy[0].1)
+            );
+        } else if !points.is_empty() {
+            let _ = write!(
+                svg,
+                r##"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"/>"##,
+                points.join(" ")
+            );
+        }
+        let lx = left + (i % 4) as f64 * 140.0;
+        let ly = plot_bottom + 32.0 + (i / 4) as f64 * 14.0;
+        let _ = write!(
+            svg,
+            r##"<rect x="{lx}" y="{}" width="10" height="10" fill="{color}"/><text x="{}" y="{ly}">{}</text>"##,
+            ly - 9.0,
+            lx + 14.0,
+            escape(name)
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn chart_for(metric: &str, runs: &[RunData]) -> String {
+    let series: Vec<(&str, &[MetricPoint])> = runs
+        .iter()
+        .map(|r| {
+            let points = r.history.get(metric).map_or(&[][..], Vec::as_slice);
+            (r.name.as_str(), points)
+        })
+        .collect();
+    line_chart_svg(metric, &series)
+}
+
+fn charted_metrics(runs: &[RunData]) -> BTreeSet<&String> {
+    runs.iter()
+        .flat_map(|r| r.history.iter())
+        .filter(|(_, points)| points.iter().any(|p| p.value.is_number()))
+        .map(|(metric, _)| metric)
+        .collect()
+}
+
+/// Rows of `key, value per run` for the keys of `pick`.
+fn table_rows<'a>(
+    runs: &'a [RunData],
+    pick: impl Fn(&'a RunData) -> &'a Map<String, Value>,
+) -> Vec<(&'a String, Vec<String>)> {
+    let keys: BTreeSet<&String> = runs.iter().flat_map(|r| pick(r).keys()).collect();
+    keys.into_iter()
+        .map(|key| {
+            let cells = runs
+                .iter()
+                .map(|r| pick(r).get(key).map(format_value).unwrap_or_default())
+                .collect();
+            (key, cells)
+        })
+        .collect()
+}
+
+fn render_markdown(title: &str, project: &str, runs: &[RunData]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "# {title}\n");
+    let _ = writeln!(
+        out,
+        "Project `{project}`, {} run(s). Generated {}.\n",
+        runs.len(),
+        now_iso8601()
+    );
+    for (heading, pick) in TABLES {
+        let rows = table_rows(runs, pick);
+        if rows.is_empty() {
+            continue;
+        }
+        let _ = writeln!(out, "## {heading}\n");
+        let header: Vec<String> = runs.iter().map(|r| md_cell(&r.name)).collect();
+        let _ = writeln!(out, "| | {} |", header.join(" | "));
+        let _ = writeln!(out, "|---|{}", "---|".repeat(runs.len()));
+        for (key, cells) in rows {
+            let cells: Vec<String> = cells.iter().map(|c| md_cell(c)).collect();
+            let _ = writeln!(out, "| {} | {} |", md_cell(key), cells.join(" | "));
+        }
+        out.push('\n');
+    }
+    let metrics = charted_metrics(runs);
+    if !metrics.is_empty() {
+        let _ = writeln!(out, "## Charts\n");
+    }
+    for metric in metrics {
+        let svg = base64::engine::general_purpose::STANDARD.encode(chart_for(metric, runs));
+        let _ = writeln!(
+            out,
+            "![{}](data:image/svg+xml;base64,{svg})\n",
+            md_cell(metric)
+        );
+    }
+    out
+}
+
+fn render_html(title: &str, project: &str, runs: &[RunData]) -> String {
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title><style>\
+         body{{font-family:sans-serif;margin:2em auto;max-width:1000px;color:#111827}}\
+         table{{border-collapse:collapse;margin-bottom:1.5em}}\
+         th,td{{border:1px solid #e5e7eb;padding:4px 10px;text-align:left}}\
+         th{{background:#f9fafb}}svg{{margin:0 1em 1em 0}}</style></head><body>\n",
+        escape(title)
+    );
+    let _ = writeln!(
+        out,
+        "<h1>{}</h1>\n<p>Project <code>{}</code>, {} run(s). Generated {}.</p>",
+        escape(title),
+        escape(project),
+        runs.len(),
+        now_iso8601()
+    );
+    for (heading, pick) in TABLES {
+        let rows = table_rows(runs, pick);
+        if rows.is_empty() {
+            continue;
+        }
+        let _ = write!(out, "<h2>{heading}</h2>\n<table><tr><th></th>");
+        for run in runs {
+            let _ = write!(out, "<th>{}</th>", escape(&run.name));
+        }
+        out.push_str("</tr>\n");
+        for (key, cells) in rows {
+            let _ = write!(out, "<tr><th>{}</th>", escape(key));
+            for cell in cells {
+                let _ = write!(out, "<td>{}</td>", escape(&cell));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+    let metrics = charted_metrics(runs);
+    if !metrics.is_empty() {
+        out.push_str("<h2>Charts</h2>\n");
+    }
+    for metric in metrics {
+        out.push_str(&chart_for(metric, runs));
+        out.push('\n');
+    }
+    out.push_str("</body></html>\n");
+    out
+}
+
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.to_string(),
+            None => n.as_f64().map(format_number).unwrap_or_default(),
+        },
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn format_number(v: f64) -> String {
+    if v.fract() == 0.0 && v.abs() < 1e15 {
+        return format!("{v:.0}");
+    }
+    if v.abs() >= 1e5 || v.abs() < 1e-3 {
+        return format!("{v:.3e}");
+    }
+    let s = format!("{v:.4}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn md_cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
@@ -0,0 +1,352 @@
+use crate::client::{Client, TrackioError};
+use crate::query::{lttb, HistoryQuery};
+use crate::time::now_iso8601;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Final state of a run as reported by the Trackio server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary {
+    pub project: String,
+    pub run: String,
+    pub num_logs: u64,
+    pub last_step: Option<i64>,
+    /// Last logged value of every metric in the run.
+    pub metrics: Map<String, Value>,
+}
+
+/// A single logged value of one metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricPoint {
+    pub step: i64,
+    pub timestamp: Option<String>,
+    pub value: Value,
+}
+
+/// A note appended to a run with [`Client::append_note`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub text: String,
+    /// The step of the last row logged before the note, if any.
+    pub step: Option<i64>,
+    pub timestamp: String,
+}
+
+impl Note {
+    fn from_json(json: &Value) -> Option<Self> {
+        Some(Self {
+            text: json.get("text")?.as_str()?.into(),
+            step: json.get("step").and_then(Value::as_i64),
+            timestamp: json
+                .get("timestamp")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .into(),
+        })
+    }
+}
+
+impl Client {
+    /// Fetch the config (hyperparameters) a run was initialized with.
+    ///
+    /// Returns `Ok(None)` when the run does not exist or was logged without a config.
+    pub fn get_config(&self, project: &str, run: &str) -> Result<Option<Value>, TrackioError> {
+        let summary = self.call_api(
+            "get_run_summary",
+            &json!({ "project": project, "run": run }),
+        )?;
+        Ok(match summary.get("config") {
+            None | Some(Value::Null) => None,
+            Some(config) => Some(config.clone()),
+        })
+    }
+
+    /// Names of the runs of `project` whose parent is `run`, in creation order.
+    pub fn children(&self, project: &str, run: &str) -> Result<Vec<String>, TrackioError> {
+        let configs = self.call_api("get_run_configs", &json!({ "project": project }))?;
+        let records = self.call_api("get_runs_for_project", &json!({ "project": project }))?;
+        let is_child = |id: &str| {
+            configs
+                .get(id)
+                .and_then(|c| c.get("_Parent"))
+                .and_then(Value::as_str)
+                == Some(run)
+        };
+        let mut children = Vec::new();
+        for record in records.as_array().into_iter().flatten() {
+            let name = record.get("name").and_then(Value::as_str);
+            let id = record.get("id").and_then(Value::as_str).or(name);
+            if let (Some(id), Some(name)) = (id, name) {
+                if (is_child(id) || is_child(name)) && !children.iter().any(|c| c == name) {
+                    children.push(name.to_string());
+                }
+            }
+        }
+        Ok(children)
+    }
+
+    /// Rename the run `old` of `project` to `new`. Needs the write token.
+    pub fn rename_run(&self, project: &str, old: &str, new: &str) -> Result<(), TrackioError> {
+        self.call_api(
+            "rename_run",
+            &json!({ "project": project, "old_name": old, "new_name": new }),
+        )?;
+        Ok(())
+    }
+
+    /// Move `run` from this client's project to `new_project`. Needs the write token
+    /// and a server with the `move_run` endpoint, such as the one in `trackio::server`;
+    /// with the Python dashboard, which has none, it fails with
+    /// [`TrackioError::Unsupported`].
+    pub fn move_run(&self, run: &str, new_project: &str) -> Result<(), TrackioError> {
+        self.call_extension(
+            "move_run",
+            &json!({ "project": self.project(), "run": run, "new_project": new_project }),
+        )?;
+        Ok(())
+    }
+
+    /// Set `key` in the metadata of this client's run, such as a dataset version, a
+    /// docker image digest or a reviewer, for automation reading runs back with
+    /// [`get_meta`](Client::get_meta). Metadata is separate from the config and can
+    /// change at any time; a `null` value removes the key. Needs the write token and a
+    /// server with the run metadata endpoints, such as the one in `trackio::server`; with
+    /// the Python dashboard, which has none, it fails with [`TrackioError::Unsupported`].
+    pub fn set_meta(&self, key: &str, value: Value) -> Result<(), TrackioError> {
+        let mut meta = Map::new();
+        meta.insert(key.into(), value);
+        self.call_extension(
+            "set_run_meta",
+            &json!({ "project": self.project(), "run": self.run_name(), "meta": meta }),
+        )?;
+        Ok(())
+    }
+
+    /// The metadata of `run` set with [`set_meta`](Client::set_meta). Needs the run
+    /// metadata endpoints too.
+    pub fn get_meta(&self, project: &str, run: &str) -> Result<Map<String, Value>, TrackioError> {
+        let meta = self.call_extension("get_run_meta", &json!({ "project": project, "run": run }))?;
+        match meta {
+            Value::Object(meta) => Ok(meta),
+            _ => Ok(Map::new()),
+        }
+    }
+
+    /// Append a timestamped note to this client's run, such as "lowered the LR by hand
+    /// at step 12k", kept with the run like a lab notebook. The note also records the
+    /// step of the last row logged. Notes are stored in the run metadata under `notes`,
+    /// so they need the write token and a server with the run metadata endpoints, such
+    /// as the one in `trackio::server`; with the Python dashboard it fails with
+    /// [`TrackioError::Unsupported`].
+    pub fn append_note(&self, text: &str) -> Result<Note, TrackioError> {
+        let note = Note {
+            text: text.into(),
+            step: self.last_step(),
+            timestamp: now_iso8601(),
+        };
+        self.call_extension(
+            "append_note",
+            &json!({
+                "project": self.project(),
+                "run": self.run_name(),
+                "note": { "text": note.text, "step": note.step, "timestamp": note.timestamp },
+            }),
+        )?;
+        Ok(note)
+    }
+
+    /// The notes of `run` appended with [`append_note`](Client::append_note), oldest
+    /// first.
+    pub fn notes(&self, project: &str, run: &str) -> Result<Vec<Note>, TrackioError> {
+        let meta = self.get_meta(project, run)?;
+        Ok(meta
+            .get("notes")
+            .and_then(Value::as_array)
+            .map(|notes| notes.iter().filter_map(Note::from_json).collect())
+            .unwrap_or_default())
+    }
+
+    /// The metadata of every run of `project` that has some, by run name. Needs the run
+    /// metadata endpoints too.
+    pub fn get_all_meta(
+        &self,
+        project: &str,
+    ) -> Result<BTreeMap<String, Map<String, Value>>, TrackioError> {
+        let all = self.call_extension("get_run_metas", &json!({ "project": project }))?;
+        Ok(match all {
+            Value::Object(all) => all
+                .into_iter()
+                .filter_map(|(run, meta)| match meta {
+                    Value::Object(meta) => Some((run, meta)),
+                    _ => None,
+                })
+                .collect(),
+            _ => BTreeMap::new(),
+        })
+    }
+
+    /// Delete a run with all its metrics, config and metadata, e.g. a failed sweep trial. Needs
+    /// the write token. Returns whether the run existed.
+    pub fn delete_run(&self, project: &str, run: &str) -> Result<bool, TrackioError> {
+        let deleted = self.call_api("delete_run", &json!({ "project": project, "run": run }))?;
+        Ok(deleted.as_bool().unwrap_or(false))
+    }
+
+    /// Delete the rows of a run logged at a step in `steps`, such as garbage from a
+    /// diverged stretch before a restart. Needs the write token and a server with the
+    /// `prune_run` endpoint, such as the one in `trackio::server`; with others it fails
+    /// with [`TrackioError::Unsupported`]. Returns how many rows were deleted.
+    pub fn prune(
+        &self,
+        project: &str,
+        run: &str,
+        steps: RangeInclusive<i64>,
+    ) -> Result<u64, TrackioError> {
+        let deleted = self.call_extension(
+            "prune_run",
+            &json!({
+                "project": project,
+                "run": run,
+                "min_step": steps.start(),
+                "max_step": steps.end(),
+            }),
+        )?;
+        Ok(deleted.as_u64().unwrap_or(0))
+    }
+
+    /// Fetch the summary of a run: log count, last step and the last value of each metric.
+    pub fn get_summary(&self, project: &str, run: &str) -> Result<RunSummary, TrackioError> {
+        let info = self.call_api(
+            "get_run_summary",
+            &json!({ "project": project, "run": run }),
+        )?;
+        let logs = self.call_api("get_logs", &json!({ "project": project, "run": run }))?;
+
+        let mut metrics = Map::new();
+        for row in logs.as_array().into_iter().flatten() {
+            for (key, value) in row.as_object().into_iter().flatten() {
+                if key == "step" || key == "timestamp" || value.is_null() {
+                    continue;
+                }
+                metrics.insert(key.clone(), value.clone());
+            }
+        }
+
+        Ok(RunSummary {
+            project: project.to_string(),
+            run: run.to_string(),
+            num_logs: info.get("num_logs").and_then(Value::as_u64).unwrap_or(0),
+            last_step: info.get("last_step").and_then(Value::as_i64),
+            metrics,
+        })
+    }
+
+    /// Fetch the logged history of a run, keyed by metric name and ordered by step.
+    ///
+    /// Without a step range, the server downsamples to `query.max_points` before sending;
+    /// with one, only the matching metrics are fetched, restricted to that range server-side.
+    /// Either way the result is then reduced to `max_points` per metric with LTTB.
+    pub fn history(
+        &self,
+        project: &str,
+        run: &str,
+        query: &HistoryQuery,
+    ) -> Result<BTreeMap<String, Vec<MetricPoint>>, TrackioError> {
+        let mut history: BTreeMap<String, Vec<MetricPoint>> = BTreeMap::new();
+
+        match &query.steps {
+            None => {
+                let batch = self.call_api(
+                    "get_logs_batch",
+                    &json!({
+                        "project": project,
+                        "runs": [{ "run": run }],
+                        "max_points": query.max_points,
+                    }),
+                )?;
+                let logs = batch
+                    .get(0)
+                    .and_then(|entry| entry.get("logs"))
+                    .and_then(Value::as_array);
+                for row in logs.into_iter().flatten() {
+                    let Some(row) = row.as_object() else {
+                        continue;
+                    };
+                    let step = row.get("step").and_then(Value::as_i64).unwrap_or(-1);
+                    let timestamp = row
+                        .get("timestamp")
+                        .and_then(Value::as_str)
+                        .map(String::from);
+                    for (key, value) in row {
+                        if key == "step"
+                            || key == "timestamp"
+                            || value.is_null()
+                            || !query.matches_metric(key)
+                        {
+                            continue;
+                        }
+                        history.entry(key.clone()).or_default().push(MetricPoint {
+                            step,
+                            timestamp: timestamp.clone(),
+                            value: value.clone(),
+                        });
+                    }
+                }
+            }
+            Some(range) => {
+                let names = self.call_api(
+                    "get_metrics_for_run",
+                    &json!({ "project": project, "run": run }),
+                )?;
+                let half = (range.end() - range.start()) / 2;
+                let center = range.start() + half;
+                for name in names
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                {
+                    if !query.matches_metric(name) {
+                        continue;
+                    }
+                    let rows = self.call_api(
+                        "get_metric_values",
+                        &json!({
+                            "project": project,
+                            "run": run,
+                            "metric_name": name,
+                            "around_step": center,
+                            "window": half + 1,
+                        }),
+                    )?;
+                    let points = rows
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|row| {
+                            Some(MetricPoint {
+                                step: row.get("step")?.as_i64()?,
+                                timestamp: row
+                                    .get("timestamp")
+                                    .and_then(Value::as_str)
+                                    .map(String::from),
+                                value: row.get("value").filter(|v| !v.is_null())?.clone(),
+                            })
+                        })
+                        .filter(|p| query.matches_step(p.step));
+                    history.entry(name.to_string()).or_default().extend(points);
+                }
+            }
+        }
+
+        for points in history.values_mut() {
+            points.sort_by_key(|p| p.step);
+            if let Some(max_points) = query.max_points {
+                *points = lttb(points, max_points);
+            }
+        }
+        history.retain(|_, points| !points.is_empty());
+        Ok(history)
+    }
+}
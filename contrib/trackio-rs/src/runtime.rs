@@ -0,0 +1,25 @@
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// A boxed future, `Send` except on `wasm32`, where futures stay on the page's thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+/// A boxed future, `Send` except on `wasm32`, where futures stay on the page's thread.
+#[cfg(target_arch = "wasm32")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// The executor services [`WebClient`](crate::web::WebClient) uses to wait between
+/// retries and to flush in the background, so the crate works on tokio, smol,
+/// async-std or in the browser without depending on any of them.
+///
+/// Each is a line or two over the executor's own `spawn` and timer; see the README for
+/// tokio and smol.
+pub trait Runtime: Debug + Send + Sync {
+    /// Run `task` to completion in the background.
+    fn spawn(&self, task: BoxFuture<'static, ()>);
+
+    /// A future that completes after `duration`.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
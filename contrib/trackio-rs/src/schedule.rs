@@ -0,0 +1,63 @@
+use crate::client::{Client, LogItem};
+use serde_json::{Map, Number, Value};
+
+impl Client {
+    /// Log the whole curve of a schedule, such as the learning rate, before training
+    /// gets there, to check the shape of warmup and decay at a glance.
+    ///
+    /// `schedule` is evaluated at `samples` evenly spaced steps of `0..total_steps`,
+    /// first and last included, and logged as `schedule/<name>` at those steps. The rows
+    /// are low priority: they are sent with the next flush, after the rows already
+    /// logged, without triggering one. Hooks, filters and the schema apply; only the
+    /// ranks that send the config log it.
+    ///
+    /// ```no_run
+    /// # let client = trackio::Client::new().with_project("p").with_run("r");
+    /// let (warmup, total) = (2_000, 100_000);
+    /// client.log_schedule(
+    ///     "lr",
+    ///     |step| {
+    ///         let step = step as f64;
+    ///         if step < warmup as f64 {
+    ///             3e-4 * step / warmup as f64
+    ///         } else {
+    ///             3e-4 * 0.5 * (1.0 + (std::f64::consts::PI * step / total as f64).cos())
+    ///         }
+    ///     },
+    ///     total,
+    ///     500,
+    /// );
+    /// ```
+    pub fn log_schedule<F>(&self, name: &str, schedule: F, total_steps: i64, samples: usize)
+    where
+        F: Fn(i64) -> f64,
+    {
+        if !self.sends_config() {
+            return;
+        }
+        let key = format!("schedule/{name}");
+        let items = sample_steps(total_steps, samples)
+            .filter_map(|step| {
+                let value = Number::from_f64(schedule(step)).map_or(Value::Null, Value::Number);
+                let mut row = Map::new();
+                row.insert(key.clone(), value);
+                let metrics = self.prepare_metrics(Value::Object(row))?;
+                Some(LogItem {
+                    metrics: self.apply_schema(metrics),
+                    step: self.map_step(Some(step)),
+                    timestamp: Some(self.timestamp()),
+                })
+            })
+            .collect();
+        self.buffer_background(items);
+    }
+}
+
+/// At most `samples` evenly spaced steps of `0..total`, including the first and last.
+fn sample_steps(total: i64, samples: usize) -> impl Iterator<Item = i64> {
+    let n = i64::try_from(samples).unwrap_or(i64::MAX).min(total.max(0));
+    (0..n).map(move |i| match n {
+        1 => 0,
+        _ => (i128::from(i) * i128::from(total - 1) / i128::from(n - 1)) as i64,
+    })
+}
@@ -0,0 +1,248 @@
+use crate::query::glob_match;
+use parking_lot::Mutex;
+use serde_json::{Map, Number, Value};
+use std::collections::HashSet;
+
+/// The type a metric's values must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    /// A number; `null` (how NaN and infinities are logged) is accepted too.
+    Float,
+    Int,
+    String,
+    Bool,
+}
+
+impl MetricType {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            MetricType::Float => value.is_number() || value.is_null(),
+            MetricType::Int => value.is_i64() || value.is_u64(),
+            MetricType::String => value.is_string(),
+            MetricType::Bool => value.is_boolean(),
+        }
+    }
+
+    /// Converts `value` when that loses nothing a chart needs: integers and numeric
+    /// strings to floats, whole floats to integers, `tensor(0.5, ...)` debug strings
+    /// to their number, `"true"`/`0`/`1` to booleans, and any scalar to a string.
+    fn coerce(self, value: &Value) -> Option<Value> {
+        match (self, value) {
+            (MetricType::Float, Value::String(s)) => number_in(s).map(float),
+            (MetricType::Int, Value::Number(n)) => n
+                .as_f64()
+                .filter(|f| f.fract() == 0.0 && f.abs() < 9.0e15)
+                .map(|f| Value::from(f as i64)),
+            (MetricType::Int, Value::String(s)) => number_in(s)
+                .filter(|f| f.fract() == 0.0 && f.abs() < 9.0e15)
+                .map(|f| Value::from(f as i64)),
+            (MetricType::String, Value::Number(_) | Value::Bool(_)) => {
+                Some(Value::String(value.to_string()))
+            }
+            (MetricType::Bool, Value::Number(n)) => match n.as_f64() {
+                Some(0.0) => Some(Value::Bool(false)),
+                Some(1.0) => Some(Value::Bool(true)),
+                _ => None,
+            },
+            (MetricType::Bool, Value::String(s)) => match s.trim().to_lowercase().as_str() {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// What to do with a value whose type does not match its declaration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnMismatch {
+    /// Convert it if that is lossless (see [`MetricType`]), otherwise drop it.
+    #[default]
+    Coerce,
+    /// Drop it.
+    Reject,
+}
+
+/// Declared types for metrics, checked by the client before rows are sent so a stray
+/// string or tensor debug repr can't turn a chart into garbage.
+///
+/// Values that don't match are coerced or dropped from their row, with a warning
+/// through the `log` crate the first time each metric misbehaves. Metrics without a
+/// declaration are left alone unless [`deny_unknown`](MetricSchema::deny_unknown) is
+/// set.
+///
+/// ```no_run
+/// use trackio::schema::{MetricSchema, MetricType, OnMismatch};
+///
+/// let schema = MetricSchema::new()
+///     .metric("train/loss", MetricType::Float)
+///     .metric("eval/*", MetricType::Float)
+///     .metric("epoch", MetricType::Int)
+///     .on_mismatch(OnMismatch::Reject);
+/// let client = trackio::Client::new()
+///     .with_project("p")
+///     .with_run("r")
+///     .with_schema(schema);
+/// ```
+#[derive(Debug, Default)]
+pub struct MetricSchema {
+    metrics: Vec<(String, MetricType)>,
+    on_mismatch: OnMismatch,
+    deny_unknown: bool,
+    warned: Mutex<HashSet<String>>,
+}
+
+impl MetricSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare the type of the metrics matching `pattern`, a name or a glob where `*`
+    /// matches any run of characters. The first matching declaration wins.
+    pub fn metric(mut self, pattern: &str, ty: MetricType) -> Self {
+        self.metrics.push((pattern.into(), ty));
+        self
+    }
+
+    /// What to do with mismatched values (default: [`OnMismatch::Coerce`]).
+    pub fn on_mismatch(mut self, policy: OnMismatch) -> Self {
+        self.on_mismatch = policy;
+        self
+    }
+
+    /// Drop metrics that match no declaration instead of sending them as they are.
+    pub fn deny_unknown(mut self, deny: bool) -> Self {
+        self.deny_unknown = deny;
+        self
+    }
+
+    /// The declared type of `name`, if any.
+    pub fn type_of(&self, name: &str) -> Option<MetricType> {
+        self.metrics
+            .iter()
+            .find(|(pattern, _)| glob_match(pattern, name))
+            .map(|(_, ty)| *ty)
+    }
+
+    /// Check the row `metrics` against the schema, coercing or dropping the values that
+    /// don't match. Anything other than an object is returned unchanged.
+    pub fn apply(&self, metrics: Value) -> Value {
+        let Value::Object(row) = metrics else {
+            return metrics;
+        };
+        let mut out = Map::with_capacity(row.len());
+        for (name, value) in row {
+            let Some(ty) = self.type_of(&name) else {
+                if self.deny_unknown {
+                    self.warn(&name, "is not declared in the schema; dropped");
+                } else {
+                    out.insert(name, value);
+                }
+                continue;
+            };
+            if ty.matches(&value) {
+                out.insert(name, value);
+                continue;
+            }
+            let coerced = match self.on_mismatch {
+                OnMismatch::Coerce => ty.coerce(&value),
+                OnMismatch::Reject => None,
+            };
+            match coerced {
+                Some(coerced) => {
+                    out.insert(name, coerced);
+                }
+                None => self.warn(&name, &format!("expected {ty:?}, got {value}; dropped")),
+            }
+        }
+        Value::Object(out)
+    }
+
+    fn warn(&self, name: &str, message: &str) {
+        if self.warned.lock().insert(name.to_string()) {
+            log::warn!("trackio: metric {name:?} {message}");
+        }
+    }
+}
+
+/// The number in `s`, either the whole string or the first argument of a tensor-like
+/// repr such as `tensor(0.5012, device='cuda:0')`.
+fn number_in(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if let Ok(f) = s.parse() {
+        return Some(f);
+    }
+    let (_, args) = s.split_once('(')?;
+    let end = args.find([',', ')'])?;
+    args[..end].trim().parse().ok()
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> MetricSchema {
+        MetricSchema::new()
+            .metric("train/loss", MetricType::Float)
+            .metric("eval/*", MetricType::Float)
+            .metric("epoch", MetricType::Int)
+            .metric("tag", MetricType::String)
+            .metric("done", MetricType::Bool)
+    }
+
+    #[test]
+    fn matching_values_pass_unchanged() {
+        let row = json!({ "train/loss": 0.5, "eval/acc": null, "epoch": 3, "tag": "a", "done": true, "other": [1] });
+        assert_eq!(schema().apply(row.clone()), row);
+    }
+
+    #[test]
+    fn mismatches_are_coerced_when_lossless() {
+        let row = json!({
+            "train/loss": "tensor(0.5012, device='cuda:0')",
+            "eval/acc": "0.75",
+            "epoch": 3.0,
+            "tag": 7,
+            "done": "True",
+        });
+        assert_eq!(
+            schema().apply(row),
+            json!({ "train/loss": 0.5012, "eval/acc": 0.75, "epoch": 3, "tag": "7", "done": true })
+        );
+    }
+
+    #[test]
+    fn mismatches_that_would_lose_information_are_dropped() {
+        let row = json!({ "train/loss": "nan-ish", "epoch": 3.5, "done": 2, "tag": [1] });
+        assert_eq!(schema().apply(row), json!({}));
+    }
+
+    #[test]
+    fn reject_drops_every_mismatch() {
+        let schema = schema().on_mismatch(OnMismatch::Reject);
+        assert_eq!(schema.apply(json!({ "epoch": 3.0, "train/loss": "0.5" })), json!({}));
+    }
+
+    #[test]
+    fn unknown_metrics_are_dropped_only_when_denied() {
+        let row = json!({ "lr": 0.1, "epoch": 1 });
+        assert_eq!(schema().apply(row.clone()), row);
+        assert_eq!(schema().deny_unknown(true).apply(row), json!({ "epoch": 1 }));
+    }
+
+    #[test]
+    fn the_first_matching_declaration_wins() {
+        let schema = MetricSchema::new()
+            .metric("eval/step", MetricType::Int)
+            .metric("eval/*", MetricType::Float);
+        assert_eq!(schema.type_of("eval/step"), Some(MetricType::Int));
+        assert_eq!(schema.type_of("eval/acc"), Some(MetricType::Float));
+        assert_eq!(schema.type_of("loss"), None);
+    }
+}
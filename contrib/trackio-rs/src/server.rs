@@ -0,0 +1,565 @@
+use crate::checksum;
+use crate::client::{LogItem, TrackioError};
+use crate::events::Event;
+use crate::sqlite::SqliteBackend;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const DEFAULT_MAX_POINTS: usize = 3000;
+
+/// A pure-Rust Trackio server storing runs in the local SQLite databases.
+///
+/// It speaks the same `/api/<name>` protocol as the Python server for the write path
+/// (`log`, `bulk_log`) and a read subset (`get_all_projects`, `get_runs_for_project`,
+/// `get_run_configs`, `get_metrics_for_run`, `get_run_summary`, `get_logs`,
+/// `get_logs_batch`, `get_metric_values`, `get_snapshot`), so both this crate's
+/// [`Client`](crate::Client) and the Python dashboard can be pointed at the same data.
+/// It also has the run and project management endpoints the client uses (`rename_run`,
+/// `move_run`, `delete_run`, `prune_run`, `get_run_meta`, `get_run_metas`,
+/// `set_run_meta`, `append_note`, `create_project`, `get_project_settings`,
+/// `archive_project`), run events (`log_events`, `get_events`) and a model registry
+/// (`register_model`, `promote_model`, `get_models`).
+#[derive(Debug, Clone)]
+pub struct Server {
+    store: Arc<SqliteBackend>,
+    write_token: Option<String>,
+}
+
+impl Server {
+    /// Serve the project databases stored in `db_dir`.
+    pub fn new<P: Into<PathBuf>>(db_dir: P) -> Self {
+        Self {
+            store: Arc::new(SqliteBackend::with_dir(db_dir)),
+            write_token: None,
+        }
+    }
+
+    /// Require `X-Trackio-Write-Token: <token>` on write endpoints.
+    pub fn with_write_token(mut self, token: &str) -> Self {
+        self.write_token = Some(token.into());
+        self
+    }
+
+    /// The axum router, for embedding the API into an existing application.
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/version", get(version))
+            .route("/api/healthz", get(healthz))
+            .route("/api/{name}", post(api))
+            .with_state(Arc::new(self))
+    }
+
+    /// Bind `addr` and serve until the process exits.
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), TrackioError> {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        axum::serve(listener, self.router()).await?;
+        Ok(())
+    }
+}
+
+/// Serve the SQLite databases in `db_path` on `addr` without write authentication.
+pub async fn serve<P: Into<PathBuf>>(addr: SocketAddr, db_path: P) -> Result<(), TrackioError> {
+    Server::new(db_path).serve(addr).await
+}
+
+async fn version() -> Json<Value> {
+    Json(json!({ "version": env!("CARGO_PKG_VERSION"), "server": "trackio-rs" }))
+}
+
+async fn healthz() -> Json<Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+async fn api(
+    State(server): State<Arc<Server>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    #[cfg(feature = "gzip")]
+    let body = match headers.get("content-encoding") {
+        Some(encoding) if encoding.as_bytes().eq_ignore_ascii_case(b"gzip") => {
+            let mut decoded = Vec::new();
+            let mut decoder = flate2::read::GzDecoder::new(&body[..]);
+            if std::io::Read::read_to_end(&mut decoder, &mut decoded).is_err() {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error": "invalid gzip body" })),
+                )
+                    .into_response();
+            }
+            Bytes::from(decoded)
+        }
+        _ => body,
+    };
+    let checksum = headers
+        .get("x-trackio-checksum")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    if let Some(checksum) = &checksum {
+        if !checksum::matches(checksum, &body) {
+            let error = format!("{}: the body received does not match {checksum}", checksum::MISMATCH);
+            return (StatusCode::BAD_REQUEST, Json(json!({ "error": error }))).into_response();
+        }
+    }
+    let kwargs: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    let authorized = match &server.write_token {
+        None => true,
+        Some(expected) => headers
+            .get("x-trackio-write-token")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v == expected),
+    };
+
+    let result =
+        tokio::task::spawn_blocking(move || dispatch(&server.store, &name, kwargs, authorized))
+            .await
+            .unwrap_or_else(|e| Err(ApiError::Internal(e.to_string())));
+
+    match result {
+        Ok(data) => {
+            let mut response = json!({ "data": data });
+            if let Some(checksum) = checksum {
+                response["checksum"] = checksum.into();
+            }
+            Json(response).into_response()
+        }
+        Err(e) => {
+            let status = match e {
+                ApiError::UnknownApi(_) => StatusCode::NOT_FOUND,
+                ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+                ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+                ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(json!({ "error": e.to_string() }))).into_response()
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ApiError {
+    #[error("Unknown API: {0}")]
+    UnknownApi(String),
+    #[error("A write_token is required to log metrics to this server. Send header X-Trackio-Write-Token.")]
+    Unauthorized,
+    #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl From<TrackioError> for ApiError {
+    fn from(e: TrackioError) -> Self {
+        match e {
+            TrackioError::InvalidArgument(message) => ApiError::BadRequest(message),
+            e => ApiError::Internal(e.to_string()),
+        }
+    }
+}
+
+fn dispatch(
+    store: &SqliteBackend,
+    name: &str,
+    kwargs: Value,
+    authorized: bool,
+) -> Result<Value, ApiError> {
+    let project = || str_arg(&kwargs, "project");
+    let run = || str_arg(&kwargs, "run");
+
+    match name {
+        "log" | "bulk_log" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let logs = parse_logs(&kwargs)?;
+            let rows = logs.values().map(|(items, _)| items.len()).sum();
+            let mut offsets = vec![Value::Null; rows];
+            let mut rejected = Vec::new();
+            let mut accepted = 0;
+            for ((project, run), (items, config)) in logs {
+                if let Some(config) = config {
+                    store.set_config(&project, &run, &config)?;
+                }
+                let (indices, items): (Vec<usize>, Vec<LogItem>) = items
+                    .into_iter()
+                    .filter(|(index, item)| {
+                        let valid = item.metrics.is_object();
+                        if !valid {
+                            rejected.push(json!({
+                                "index": index,
+                                "reason": "metrics must be a JSON object",
+                            }));
+                        }
+                        valid
+                    })
+                    .unzip();
+                for (index, offset) in indices.into_iter().zip(store.insert(&project, &run, &items)?) {
+                    accepted += usize::from(offset.is_some());
+                    offsets[index] = json!(offset);
+                }
+            }
+            Ok(json!({ "accepted": accepted, "offsets": offsets, "rejected": rejected }))
+        }
+        "get_all_projects" => {
+            let include_archived = kwargs.get("include_archived") == Some(&Value::Bool(true));
+            let mut projects = Vec::new();
+            for project in store.projects()? {
+                let archived = store.project_settings(&project)?.get("archived")
+                    == Some(&Value::Bool(true));
+                if include_archived || !archived {
+                    projects.push(project);
+                }
+            }
+            Ok(json!(projects))
+        }
+        "get_project_settings" => Ok(Value::Object(store.project_settings(&project()?)?)),
+        "create_project" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let mut settings = Map::new();
+            for key in ["description", "x_axis"] {
+                settings.insert(key.into(), kwargs.get(key).cloned().unwrap_or(Value::Null));
+            }
+            store.update_project_settings(&project()?, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "archive_project" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let project = project()?;
+            if !store.db_path(&project).exists() {
+                return Err(ApiError::BadRequest(format!("project {project:?} not found")));
+            }
+            let archived = kwargs.get("archived").and_then(Value::as_bool).unwrap_or(true);
+            let mut settings = Map::new();
+            settings.insert("archived".into(), Value::Bool(archived));
+            store.update_project_settings(&project, &settings)?;
+            Ok(Value::Bool(true))
+        }
+        "get_runs_for_project" => {
+            let runs = store.runs(&project()?)?;
+            Ok(runs
+                .into_iter()
+                .map(|r| json!({ "id": r, "name": r }))
+                .collect())
+        }
+        "get_run_configs" => {
+            let project = project()?;
+            let mut configs = Map::new();
+            for run in store.runs(&project)? {
+                if let Some(config) = store.config(&project, &run)? {
+                    configs.insert(run, config);
+                }
+            }
+            Ok(Value::Object(configs))
+        }
+        "get_metrics_for_run" => Ok(json!(metric_names(&store.logs(&project()?, &run()?)?))),
+        "get_run_summary" => {
+            let (project, run) = (project()?, run()?);
+            let logs = store.logs(&project, &run)?;
+            Ok(json!({
+                "project": project,
+                "run": run,
+                "run_id": run,
+                "num_logs": logs.len(),
+                "metrics": metric_names(&logs),
+                "config": store.config(&project, &run)?,
+                "last_step": logs.iter().filter_map(|r| r.get("step")?.as_i64()).max(),
+            }))
+        }
+        "get_logs" => {
+            let logs = store.logs(&project()?, &run()?)?;
+            Ok(json!(downsample(logs, DEFAULT_MAX_POINTS)))
+        }
+        "get_logs_batch" => {
+            let project = project()?;
+            let max_points = kwargs
+                .get("max_points")
+                .and_then(Value::as_u64)
+                .map_or(DEFAULT_MAX_POINTS, |n| n as usize);
+            let runs = kwargs
+                .get("runs")
+                .and_then(Value::as_array)
+                .ok_or_else(|| ApiError::BadRequest("runs must be a list".into()))?;
+            let mut out = Vec::with_capacity(runs.len());
+            for entry in runs {
+                let run = entry.get("run").and_then(Value::as_str).unwrap_or_default();
+                let logs = store.logs(&project, run)?;
+                out.push(json!({
+                    "run": run,
+                    "run_id": run,
+                    "logs": downsample(logs, max_points),
+                }));
+            }
+            Ok(Value::Array(out))
+        }
+        "get_metric_values" => {
+            let metric = str_arg(&kwargs, "metric_name")?;
+            let logs = store.logs(&project()?, &run()?)?;
+            Ok(logs
+                .iter()
+                .filter(|row| step_selected(&kwargs, row))
+                .filter_map(|row| {
+                    Some(json!({
+                        "timestamp": row.get("timestamp"),
+                        "step": row.get("step"),
+                        "value": row.get(&metric)?,
+                    }))
+                })
+                .collect())
+        }
+        "get_snapshot" => {
+            let logs = store.logs(&project()?, &run()?)?;
+            let mut snapshot: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+            for row in logs.iter().filter(|row| step_selected(&kwargs, row)) {
+                for (key, value) in row {
+                    if key == "step" || key == "timestamp" {
+                        continue;
+                    }
+                    snapshot.entry(key.clone()).or_default().push(json!({
+                        "timestamp": row.get("timestamp"),
+                        "step": row.get("step"),
+                        "value": value,
+                    }));
+                }
+            }
+            Ok(json!(snapshot))
+        }
+        "get_run_meta" => Ok(Value::Object(store.run_meta(&project()?, &run()?)?)),
+        "get_run_metas" => {
+            let project = project()?;
+            let mut metas = Map::new();
+            for run in store.meta_runs(&project)? {
+                let meta = store.run_meta(&project, &run)?;
+                metas.insert(run, Value::Object(meta));
+            }
+            Ok(Value::Object(metas))
+        }
+        "set_run_meta" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let meta = kwargs
+                .get("meta")
+                .and_then(Value::as_object)
+                .ok_or_else(|| ApiError::BadRequest("meta must be an object".into()))?;
+            store.update_run_meta(&project()?, &run()?, meta)?;
+            Ok(Value::Bool(true))
+        }
+        "get_events" => {
+            let events = store.events(&project()?, &run()?)?;
+            Ok(Value::Array(events.iter().map(Event::to_json).collect()))
+        }
+        "log_events" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let events = kwargs
+                .get("events")
+                .and_then(Value::as_array)
+                .ok_or_else(|| ApiError::BadRequest("events must be an array".into()))?
+                .iter()
+                .map(|event| {
+                    Event::from_json(event)
+                        .ok_or_else(|| ApiError::BadRequest("every event needs a name".into()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            store.insert_events(&project()?, &run()?, &events)?;
+            Ok(Value::Bool(true))
+        }
+        "append_note" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let note = kwargs
+                .get("note")
+                .filter(|note| note.get("text").is_some_and(Value::is_string))
+                .ok_or_else(|| ApiError::BadRequest("note must have a text".into()))?;
+            store.append_note(&project()?, &run()?, note.clone())?;
+            Ok(Value::Bool(true))
+        }
+        "get_models" => {
+            let name = kwargs.get("name").and_then(Value::as_str);
+            Ok(Value::Array(store.models(&project()?, name)?))
+        }
+        "register_model" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let (name, version) = (str_arg(&kwargs, "name")?, str_arg(&kwargs, "version")?);
+            let artifact = str_arg(&kwargs, "artifact")?;
+            let metrics = kwargs.get("metrics").cloned().unwrap_or(json!({}));
+            store.register_model(&project()?, &run()?, &name, &version, &artifact, &metrics)?;
+            Ok(Value::Bool(true))
+        }
+        "promote_model" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let (name, version) = (str_arg(&kwargs, "name")?, str_arg(&kwargs, "version")?);
+            let stage = str_arg(&kwargs, "stage")?;
+            if !["none", "staging", "production", "archived"].contains(&stage.as_str()) {
+                return Err(ApiError::BadRequest(format!("unknown stage {stage:?}")));
+            }
+            store.promote_model(&project()?, &name, &version, &stage)?;
+            Ok(Value::Bool(true))
+        }
+        "rename_run" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let (old, new) = (str_arg(&kwargs, "old_name")?, str_arg(&kwargs, "new_name")?);
+            store.rename_run(&project()?, &old, &new)?;
+            Ok(Value::Bool(true))
+        }
+        "move_run" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let new_project = str_arg(&kwargs, "new_project")?;
+            store.move_run(&project()?, &run()?, &new_project)?;
+            Ok(Value::Bool(true))
+        }
+        "delete_run" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            Ok(Value::Bool(store.delete_run(&project()?, &run()?)?))
+        }
+        "prune_run" => {
+            if !authorized {
+                return Err(ApiError::Unauthorized);
+            }
+            let step = |name| {
+                kwargs.get(name).and_then(Value::as_i64).ok_or_else(|| {
+                    ApiError::BadRequest(format!("Missing required parameter: {name}"))
+                })
+            };
+            let steps = step("min_step")?..=step("max_step")?;
+            Ok(json!(store.prune(&project()?, &run()?, steps)?))
+        }
+        other => Err(ApiError::UnknownApi(other.into())),
+    }
+}
+
+/// Rows grouped by project and run, each with its position in the request.
+type ParsedLogs = BTreeMap<(String, String), (Vec<(usize, LogItem)>, Option<Value>)>;
+
+/// Accepts both the Python client's `{"logs": [{project, run, metrics, step}]}` shape and
+/// this crate's columnar `{project, run, metrics_list, steps, timestamps}` shape.
+fn parse_logs(kwargs: &Value) -> Result<ParsedLogs, ApiError> {
+    let mut out = ParsedLogs::new();
+
+    if let Some(entries) = kwargs.get("logs").and_then(Value::as_array) {
+        for (index, entry) in entries.iter().enumerate() {
+            let key = (str_arg(entry, "project")?, str_arg(entry, "run")?);
+            let (items, config) = out.entry(key).or_default();
+            let item = LogItem {
+                metrics: entry.get("metrics").cloned().unwrap_or(json!({})),
+                step: entry.get("step").and_then(Value::as_i64),
+                timestamp: entry
+                    .get("timestamp")
+                    .and_then(Value::as_str)
+                    .map(String::from),
+            };
+            items.push((index, item));
+            if config.is_none() {
+                *config = entry.get("config").filter(|c| c.is_object()).cloned();
+            }
+        }
+        return Ok(out);
+    }
+
+    let key = (str_arg(kwargs, "project")?, str_arg(kwargs, "run")?);
+    let metrics_list = match (kwargs.get("metrics_list"), kwargs.get("metrics")) {
+        (Some(Value::Array(list)), _) => list.clone(),
+        (None, Some(metrics)) => vec![metrics.clone()],
+        _ => {
+            return Err(ApiError::BadRequest(
+                "Missing required parameter: logs".into(),
+            ))
+        }
+    };
+    let steps = kwargs.get("steps").and_then(Value::as_array);
+    let timestamps = kwargs.get("timestamps").and_then(Value::as_array);
+    let (items, config) = out.entry(key).or_default();
+    for (i, metrics) in metrics_list.into_iter().enumerate() {
+        let item = LogItem {
+            metrics,
+            step: steps
+                .and_then(|s| s.get(i))
+                .or(kwargs.get("step"))
+                .and_then(Value::as_i64),
+            timestamp: timestamps
+                .and_then(|t| t.get(i))
+                .and_then(Value::as_str)
+                .map(String::from),
+        };
+        items.push((i, item));
+    }
+    *config = kwargs.get("config").filter(|c| c.is_object()).cloned();
+    Ok(out)
+}
+
+fn str_arg(kwargs: &Value, name: &str) -> Result<String, ApiError> {
+    kwargs
+        .get(name)
+        .and_then(Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| ApiError::BadRequest(format!("Missing required parameter: {name}")))
+}
+
+fn step_selected(kwargs: &Value, row: &Map<String, Value>) -> bool {
+    let Some(step) = row.get("step").and_then(Value::as_i64) else {
+        return false;
+    };
+    if let Some(exact) = kwargs.get("step").and_then(Value::as_i64) {
+        return step == exact;
+    }
+    match (
+        kwargs.get("around_step").and_then(Value::as_i64),
+        kwargs.get("window").and_then(Value::as_f64),
+    ) {
+        (Some(center), Some(window)) => {
+            let window = window as i64;
+            step >= center - window && step <= center + window
+        }
+        _ => true,
+    }
+}
+
+fn metric_names(logs: &[Map<String, Value>]) -> Vec<String> {
+    let names: BTreeSet<&String> = logs
+        .iter()
+        .flat_map(|row| row.keys())
+        .filter(|k| *k != "step" && *k != "timestamp")
+        .collect();
+    names.into_iter().cloned().collect()
+}
+
+fn downsample<T>(rows: Vec<T>, max_points: usize) -> Vec<T> {
+    if max_points == 0 || rows.len() <= max_points {
+        return rows;
+    }
+    let last = rows.len() - 1;
+    let stride = last as f64 / (max_points - 1).max(1) as f64;
+    let keep: BTreeSet<usize> = (0..max_points)
+        .map(|i| ((i as f64 * stride).round() as usize).min(last))
+        .collect();
+    rows.into_iter()
+        .enumerate()
+        .filter(|(i, _)| keep.contains(i))
+        .map(|(_, row)| row)
+        .collect()
+}
@@ -0,0 +1,287 @@
+use crate::client::{Client, FailedBatch, TrackioError};
+use crate::time::format_iso8601;
+use memmap2::MmapMut;
+use serde_json::{Map, Value};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MAGIC: u64 = u64::from_le_bytes(*b"TRKRING1");
+const CAPACITY: usize = 8;
+const TAIL: usize = 64;
+const HEAD: usize = 128;
+const DROPPED: usize = 192;
+const DATA: usize = 256;
+const WRAP: u32 = u32::MAX;
+const NO_STEP: i64 = i64::MIN;
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A single-producer, single-consumer byte ring in a memory-mapped file.
+///
+/// Positions only grow; the producer publishes `TAIL` and the drainer publishes `HEAD`,
+/// each on its own cache line. Records are 8-byte aligned and never straddle the end of
+/// the ring: a `WRAP` marker sends the reader back to the start instead.
+#[derive(Debug)]
+struct Ring {
+    map: MmapMut,
+    capacity: u64,
+}
+
+impl Ring {
+    fn create(path: &Path, capacity: usize) -> Result<Self, TrackioError> {
+        let capacity = (capacity.max(64) + 7) & !7;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.set_len((DATA + capacity) as u64)?;
+        let mut map = unsafe { MmapMut::map_mut(&file)? };
+        map[CAPACITY..CAPACITY + 8].copy_from_slice(&(capacity as u64).to_le_bytes());
+        map[..8].copy_from_slice(&MAGIC.to_le_bytes());
+        map.flush()?;
+        Ok(Self {
+            map,
+            capacity: capacity as u64,
+        })
+    }
+
+    fn open(path: &Path) -> Result<Self, TrackioError> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        let word = |at: usize| {
+            map.get(at..at + 8)
+                .map(|b| u64::from_le_bytes(b.try_into().expect("8 bytes")))
+        };
+        if word(0) != Some(MAGIC) {
+            return Err(TrackioError::InvalidArgument(format!(
+                "{} is not a trackio ring",
+                path.display()
+            )));
+        }
+        let capacity = word(CAPACITY).unwrap_or(0);
+        if map.len() as u64 != DATA as u64 + capacity {
+            return Err(TrackioError::InvalidArgument(format!(
+                "{} has the wrong size for its capacity",
+                path.display()
+            )));
+        }
+        Ok(Self { map, capacity })
+    }
+
+    fn counter(&self, at: usize) -> &AtomicU64 {
+        unsafe { &*(self.map.as_ptr().add(at) as *const AtomicU64) }
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.map[DATA..]
+    }
+
+    fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.map[DATA..]
+    }
+}
+
+/// The logging side of a shared-memory ring, for training loops where even a mutex is
+/// too much. [`log`](ShmProducer::log) copies the row into the ring without allocating,
+/// locking or syscalls; an [`ShmDrainer`] in another thread or process turns the rows
+/// into JSON and sends them.
+///
+/// A ring has exactly one producer. When it is full, rows are dropped and counted
+/// rather than blocking the loop.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use trackio::shm::{ShmDrainer, ShmProducer};
+///
+/// let mut ring = ShmProducer::create("/dev/shm/trackio-run", 1 << 20)?;
+/// let client = trackio::Client::new().with_project("p").with_run("r");
+/// let drainer = ShmDrainer::open("/dev/shm/trackio-run", client)?;
+/// std::thread::spawn(move || drainer.run(Duration::from_millis(200)));
+///
+/// for step in 0..1_000_000 {
+///     ring.log(Some(step), &[("loss", 0.5), ("lr", 1e-4)]);
+/// }
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug)]
+pub struct ShmProducer {
+    ring: Ring,
+    tail: u64,
+}
+
+impl ShmProducer {
+    /// Create (or reset) the ring file at `path` with room for `capacity` bytes of rows.
+    /// Put it on a memory-backed filesystem such as `/dev/shm` to avoid disk writeback.
+    pub fn create<P: AsRef<Path>>(path: P, capacity: usize) -> Result<Self, TrackioError> {
+        Ok(Self {
+            ring: Ring::create(path.as_ref(), capacity)?,
+            tail: 0,
+        })
+    }
+
+    /// Append one row. Returns `false`, and counts the row as dropped, if the ring is
+    /// full or the row does not fit in it.
+    pub fn log(&mut self, step: Option<i64>, metrics: &[(&str, f64)]) -> bool {
+        let body: usize = 18 + metrics.iter().map(|(k, _)| 10 + k.len()).sum::<usize>();
+        let len = ((4 + body + 7) & !7) as u64;
+        let cap = self.ring.capacity;
+        let fits = len <= cap
+            && len < WRAP as u64
+            && metrics.len() <= u16::MAX as usize
+            && metrics.iter().all(|(k, _)| k.len() <= u16::MAX as usize);
+        let head = self.ring.counter(HEAD).load(Ordering::Acquire);
+        let off = self.tail % cap;
+        let pad = if off + len > cap { cap - off } else { 0 };
+        if !fits || self.tail + pad + len - head > cap {
+            self.ring.counter(DROPPED).fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        let data = self.ring.data_mut();
+        if pad > 0 {
+            data[off as usize..off as usize + 4].copy_from_slice(&WRAP.to_le_bytes());
+        }
+        let pos = self.tail + pad;
+        let mut at = (pos % cap) as usize;
+        let mut put = |bytes: &[u8]| {
+            data[at..at + bytes.len()].copy_from_slice(bytes);
+            at += bytes.len();
+        };
+        put(&(len as u32).to_le_bytes());
+        put(&step.unwrap_or(NO_STEP).to_le_bytes());
+        put(&ts.to_le_bytes());
+        put(&(metrics.len() as u16).to_le_bytes());
+        for (key, value) in metrics {
+            put(&(key.len() as u16).to_le_bytes());
+            put(key.as_bytes());
+            put(&value.to_le_bytes());
+        }
+
+        self.tail = pos + len;
+        self.ring.counter(TAIL).store(self.tail, Ordering::Release);
+        true
+    }
+
+    /// Rows dropped because the ring was full.
+    pub fn dropped(&self) -> u64 {
+        self.ring.counter(DROPPED).load(Ordering::Relaxed)
+    }
+}
+
+/// The sending side of a shared-memory ring: reads the rows written by a
+/// [`ShmProducer`] and logs them to a run.
+#[derive(Debug)]
+pub struct ShmDrainer {
+    ring: Ring,
+    client: Client,
+    requeued: bool,
+}
+
+impl ShmDrainer {
+    /// Attach to the ring a producer created at `path`.
+    pub fn open<P: AsRef<Path>>(path: P, client: Client) -> Result<Self, TrackioError> {
+        Ok(Self {
+            ring: Ring::open(path.as_ref())?,
+            client,
+            requeued: false,
+        })
+    }
+
+    /// Log every row written since the last call and flush them, in one request however
+    /// many there are. Returns the number of rows logged. The rows leave the ring once
+    /// queued on the client, so if the flush fails they are only in the error's
+    /// [`batch`](TrackioError::into_batch), which [`requeue`](ShmDrainer::requeue) puts
+    /// back.
+    pub fn drain(&mut self) -> Result<usize, TrackioError> {
+        let cap = self.ring.capacity;
+        let tail = self.ring.counter(TAIL).load(Ordering::Acquire);
+        let mut head = self.ring.counter(HEAD).load(Ordering::Relaxed);
+        let mut logged = 0;
+        while head < tail {
+            let off = (head % cap) as usize;
+            let data = self.ring.data();
+            let len = u32::from_le_bytes(data[off..off + 4].try_into().expect("4 bytes"));
+            if len == WRAP {
+                head += cap - off as u64;
+                continue;
+            }
+            let (metrics, step, ts) = read_row(&data[off + 4..off + len as usize]);
+            self.client.stage(metrics, step, Some(ts));
+            head += len as u64;
+            logged += 1;
+        }
+        self.ring.counter(HEAD).store(head, Ordering::Release);
+        if logged > 0 || self.requeued {
+            self.client.flush()?;
+            self.requeued = false;
+        }
+        Ok(logged)
+    }
+
+    /// Put the rows of a failed [`drain`](ShmDrainer::drain) back in front of the
+    /// client's buffer, to be sent with the next one.
+    pub fn requeue(&mut self, batch: FailedBatch) {
+        self.client.requeue(batch);
+        self.requeued = true;
+    }
+
+    /// Drain every `interval`. When sending fails with a retryable error, such as the
+    /// server being down, the rows are requeued and draining goes on after a backoff
+    /// starting at `interval` and doubling up to a minute, or as long as the server asks;
+    /// the ring keeps taking rows meanwhile. Returns on the first error that retrying
+    /// won't fix, such as a refused token.
+    pub fn run(mut self, interval: Duration) -> Result<(), TrackioError> {
+        let mut backoff = interval;
+        loop {
+            match self.drain() {
+                Ok(_) => {
+                    backoff = interval;
+                    thread::sleep(interval);
+                }
+                Err(e) if e.is_retryable() => {
+                    log::warn!("trackio: draining the shared-memory ring failed, retrying: {e}");
+                    let delay = e.retry_after().unwrap_or(backoff);
+                    if let Some(batch) = e.into_batch() {
+                        self.requeue(batch);
+                    }
+                    thread::sleep(delay);
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Rows the producer dropped because the ring was full.
+    pub fn dropped(&self) -> u64 {
+        self.ring.counter(DROPPED).load(Ordering::Relaxed)
+    }
+}
+
+fn read_row(mut buf: &[u8]) -> (Value, Option<i64>, String) {
+    let mut take = |n: usize| {
+        let (head, rest) = buf.split_at(n);
+        buf = rest;
+        head
+    };
+    let step = i64::from_le_bytes(take(8).try_into().expect("8 bytes"));
+    let ts = u64::from_le_bytes(take(8).try_into().expect("8 bytes"));
+    let count = u16::from_le_bytes(take(2).try_into().expect("2 bytes"));
+    let mut metrics = Map::new();
+    for _ in 0..count {
+        let key_len = u16::from_le_bytes(take(2).try_into().expect("2 bytes")) as usize;
+        let key = String::from_utf8_lossy(take(key_len)).into_owned();
+        let value = f64::from_le_bytes(take(8).try_into().expect("8 bytes"));
+        metrics.insert(key, Value::from(value));
+    }
+    let step = (step != NO_STEP).then_some(step);
+    let ts = format_iso8601(UNIX_EPOCH + Duration::from_nanos(ts));
+    (Value::Object(metrics), step, ts)
+}
@@ -0,0 +1,27 @@
+use crate::transport::Request;
+use std::fmt;
+use std::sync::Arc;
+
+type SignFn = Arc<dyn Fn(&Request<'_>) -> Vec<(String, String)> + Send + Sync>;
+
+/// The callback installed with
+/// [`Client::with_request_signer`](crate::Client::with_request_signer), giving the
+/// extra headers of each request.
+#[derive(Clone)]
+pub(crate) struct RequestSigner(SignFn);
+
+impl fmt::Debug for RequestSigner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RequestSigner")
+    }
+}
+
+impl RequestSigner {
+    pub(crate) fn new(sign: SignFn) -> Self {
+        Self(sign)
+    }
+
+    pub(crate) fn headers(&self, request: &Request<'_>) -> Vec<(String, String)> {
+        (self.0)(request)
+    }
+}
@@ -0,0 +1,302 @@
+use crate::alerts::{deliver, Alert};
+use crate::backend::Backend;
+use crate::client::{Client, ErrorKind, LogItem, TrackioError};
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Sends every batch to several backends, each with its own failure isolation, so a
+/// network outage never costs the local copy.
+///
+/// A batch a sink fails to take is queued for that sink alone and retried, oldest first,
+/// with the next batch; the other sinks are not held up. Queues are capped at
+/// [`with_max_pending`](FanOut::with_max_pending) rows per sink, dropping the oldest
+/// rows beyond that. A send only fails when every sink failed, and even then the batch
+/// stays queued. `FanOut` is cheap to clone, so keep a clone to inspect
+/// [`status`](FanOut::status) after handing it to the client.
+///
+/// ```no_run
+/// use trackio::sinks::{FanOut, JsonlBackend, ServerBackend};
+///
+/// let fanout = FanOut::new()
+///     .sink("space", ServerBackend::new("https://me-trackio.hf.space"))
+///     .sink("jsonl", JsonlBackend::new("metrics.jsonl"));
+/// let client = trackio::Client::new()
+///     .with_project("p")
+///     .with_run("r")
+///     .with_backend(fanout.clone());
+/// client.log(serde_json::json!({ "loss": 0.5 }), Some(1), None);
+/// client.flush()?;
+/// for sink in fanout.status() {
+///     println!("{}: {} rows pending", sink.name, sink.pending_rows);
+/// }
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct FanOut {
+    sinks: Vec<Arc<Sink>>,
+    max_pending: usize,
+}
+
+/// Health of one sink of a [`FanOut`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinkStatus {
+    pub name: String,
+    /// Rows waiting to be retried.
+    pub pending_rows: usize,
+    /// Rows dropped because the queue was full or the sink rejected them.
+    pub dropped_rows: u64,
+    /// Error of the last failed attempt, cleared once the sink catches up.
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Sink {
+    name: String,
+    backend: Box<dyn Backend>,
+    queue: Mutex<Queue>,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    batches: VecDeque<(String, String, Vec<LogItem>)>,
+    configs: Vec<(String, String, Value)>,
+    rows: usize,
+    dropped: u64,
+    last_error: Option<String>,
+}
+
+impl Default for FanOut {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FanOut {
+    pub fn new() -> Self {
+        Self {
+            sinks: Vec::new(),
+            max_pending: 100_000,
+        }
+    }
+
+    /// Add a sink called `name`.
+    pub fn sink<B: Backend + 'static>(mut self, name: &str, backend: B) -> Self {
+        self.sinks.push(Arc::new(Sink {
+            name: name.into(),
+            backend: Box::new(backend),
+            queue: Mutex::new(Queue::default()),
+        }));
+        self
+    }
+
+    /// Rows kept per sink for retrying while it is failing (default: 100 000).
+    pub fn with_max_pending(mut self, rows: usize) -> Self {
+        self.max_pending = rows;
+        self
+    }
+
+    /// Pending rows and last error of every sink, in the order they were added.
+    pub fn status(&self) -> Vec<SinkStatus> {
+        self.sinks
+            .iter()
+            .map(|sink| {
+                let queue = sink.queue.lock();
+                SinkStatus {
+                    name: sink.name.clone(),
+                    pending_rows: queue.rows,
+                    dropped_rows: queue.dropped,
+                    last_error: queue.last_error.clone(),
+                }
+            })
+            .collect()
+    }
+
+    /// Runs `op` on every sink, returning the first error only if all of them failed.
+    fn each(
+        &self,
+        mut op: impl FnMut(&Sink, &mut Queue) -> Result<(), TrackioError>,
+    ) -> Result<(), TrackioError> {
+        let mut first_error = None;
+        let mut any_ok = self.sinks.is_empty();
+        for sink in &self.sinks {
+            let mut queue = sink.queue.lock();
+            match op(sink, &mut queue) {
+                Ok(()) => any_ok = true,
+                Err(e) => {
+                    queue.last_error = Some(e.to_string());
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) if !any_ok => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Sink {
+    /// Sends queued configs and batches, oldest first, stopping at the first failure. A
+    /// batch the sink rejects outright is dropped so it can't block the ones after it.
+    fn drain(&self, queue: &mut Queue) -> Result<(), TrackioError> {
+        while let Some((project, run, config)) = queue.configs.first() {
+            if let Err(e) = self.backend.set_config(project, run, config) {
+                if e.kind() != ErrorKind::Rejected {
+                    return Err(e);
+                }
+                log::warn!("trackio: sink {} rejected config: {e}", self.name);
+            }
+            queue.configs.remove(0);
+        }
+        while let Some((project, run, items)) = queue.batches.front() {
+            if let Err(e) = self.backend.send(project, run, items) {
+                if e.kind() != ErrorKind::Rejected {
+                    return Err(e);
+                }
+                log::warn!(
+                    "trackio: sink {} dropped {} rejected rows: {e}",
+                    self.name,
+                    items.len()
+                );
+                queue.dropped += items.len() as u64;
+            }
+            queue.rows -= items.len();
+            queue.batches.pop_front();
+        }
+        queue.last_error = None;
+        Ok(())
+    }
+}
+
+impl Queue {
+    fn push(&mut self, project: &str, run: &str, items: &[LogItem], max_rows: usize) {
+        self.rows += items.len();
+        self.batches
+            .push_back((project.into(), run.into(), items.to_vec()));
+        while self.rows > max_rows {
+            let Some((_, _, oldest)) = self.batches.pop_front() else {
+                break;
+            };
+            self.rows -= oldest.len();
+            self.dropped += oldest.len() as u64;
+        }
+    }
+}
+
+impl Backend for FanOut {
+    fn send(&self, project: &str, run: &str, items: &[LogItem]) -> Result<(), TrackioError> {
+        self.each(|sink, queue| {
+            queue.push(project, run, items, self.max_pending);
+            sink.drain(queue)
+        })
+    }
+
+    fn set_config(&self, project: &str, run: &str, config: &Value) -> Result<(), TrackioError> {
+        self.each(|sink, queue| {
+            queue.configs.retain(|(p, r, _)| p != project || r != run);
+            queue
+                .configs
+                .push((project.into(), run.into(), config.clone()));
+            sink.drain(queue)
+        })
+    }
+
+    fn send_alerts(&self, project: &str, run: &str, alerts: &[Alert]) -> Result<(), TrackioError> {
+        self.each(|sink, _| sink.backend.send_alerts(project, run, alerts))
+    }
+}
+
+/// A [`Backend`] that posts to a Trackio server, for use as one sink of a [`FanOut`].
+#[derive(Debug)]
+pub struct ServerBackend {
+    client: Client,
+}
+
+impl ServerBackend {
+    /// Post to the server at `base_url`, with the write token from `TRACKIO_WRITE_TOKEN`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::new().with_base_url(base_url),
+        }
+    }
+
+    pub fn with_write_token(mut self, token: &str) -> Self {
+        self.client = self.client.with_write_token(token);
+        self
+    }
+}
+
+impl Backend for ServerBackend {
+    fn send(&self, project: &str, run: &str, items: &[LogItem]) -> Result<(), TrackioError> {
+        self.client.post_bulk(project, run, items, None).map(|_| ())
+    }
+
+    fn set_config(&self, project: &str, run: &str, config: &Value) -> Result<(), TrackioError> {
+        self.client
+            .post_bulk(project, run, &[], Some(config))
+            .map(|_| ())
+    }
+
+    fn send_alerts(&self, project: &str, run: &str, alerts: &[Alert]) -> Result<(), TrackioError> {
+        deliver(&self.client.for_run(project, run), alerts)
+    }
+}
+
+/// A [`Backend`] that appends every row to a JSON-lines file, one object per line with
+/// `project`, `run`, `step`, `timestamp` and `metrics` (configs as `config` lines).
+#[derive(Debug)]
+pub struct JsonlBackend {
+    path: PathBuf,
+    file: Mutex<Option<BufWriter<File>>>,
+}
+
+impl JsonlBackend {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            file: Mutex::new(None),
+        }
+    }
+
+    fn append(&self, lines: impl Iterator<Item = Value>) -> Result<(), TrackioError> {
+        let mut file = self.file.lock();
+        if file.is_none() {
+            let opened = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            *file = Some(BufWriter::new(opened));
+        }
+        let out = file.as_mut().expect("file was just opened");
+        for line in lines {
+            writeln!(out, "{line}")?;
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl Backend for JsonlBackend {
+    fn send(&self, project: &str, run: &str, items: &[LogItem]) -> Result<(), TrackioError> {
+        self.append(items.iter().map(|item| {
+            json!({
+                "project": project,
+                "run": run,
+                "step": item.step,
+                "timestamp": item.timestamp,
+                "metrics": item.metrics,
+            })
+        }))
+    }
+
+    fn set_config(&self, project: &str, run: &str, config: &Value) -> Result<(), TrackioError> {
+        self.append(std::iter::once(
+            json!({ "project": project, "run": run, "config": config }),
+        ))
+    }
+}
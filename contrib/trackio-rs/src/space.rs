@@ -0,0 +1,62 @@
+use crate::client::{Client, TrackioError};
+use crate::hf::{file_op, HfApi, RepoType};
+
+const SPACE_README: &str = "---
+emoji: 🎯
+sdk: gradio
+app_file: app.py
+tags:
+ - trackio
+hf_oauth: true
+hf_oauth_scopes:
+ - write-repos
+---
+";
+
+const SPACE_APP: &str = "import trackio\ntrackio.show()\n";
+
+const SPACE_REQUIREMENTS: &str = "trackio[spaces,mcp]\n";
+
+impl Client {
+    /// Create the Trackio dashboard Space `space_id` (`user/name`) if it does not exist.
+    ///
+    /// The Space gets the same app, README and requirements as one deployed by the Python
+    /// client, the Hugging Face token as its `HF_TOKEN` secret and, if this client has one,
+    /// its write token as `TRACKIO_WRITE_TOKEN` so it can log there. Visibility is always
+    /// set explicitly from `private` rather than inherited from the organization.
+    ///
+    /// Uses `$HF_TOKEN` or the token saved by the `hf` CLI. Returns `true` if the Space was
+    /// created; an existing Space is left untouched. The Space still has to build, so call
+    /// [`Client::wait_until_ready`] before logging to it.
+    pub fn ensure_space(&self, space_id: &str, private: bool) -> Result<bool, TrackioError> {
+        if !space_id.contains('/') {
+            return Err(TrackioError::Hub(format!(
+                "invalid Space id {space_id:?}; expected user/name or org/name"
+            )));
+        }
+        let api = HfApi::new();
+        if api.repo_exists(RepoType::Space, space_id)? {
+            return Ok(false);
+        }
+
+        api.create_repo(RepoType::Space, space_id, private, Some("gradio"))?;
+        api.commit(
+            RepoType::Space,
+            space_id,
+            "Create Trackio dashboard",
+            &[
+                file_op("README.md", SPACE_README.as_bytes()),
+                file_op("requirements.txt", SPACE_REQUIREMENTS.as_bytes()),
+                file_op("app.py", SPACE_APP.as_bytes()),
+            ],
+        )?;
+        if let Some(token) = api.token_value() {
+            api.add_space_secret(space_id, "HF_TOKEN", token)?;
+        }
+        if let Some(token) = self.write_token() {
+            api.add_space_secret(space_id, "TRACKIO_WRITE_TOKEN", token)?;
+        }
+        api.add_space_variable(space_id, "GRADIO_MCP_SERVER", "True")?;
+        Ok(true)
+    }
+}
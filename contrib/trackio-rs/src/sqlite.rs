@@ -0,0 +1,793 @@
+use crate::backend::Backend;
+use crate::client::{LogItem, TrackioError};
+use crate::events::Event;
+use crate::time::now_iso8601;
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
+use std::env;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS metrics (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        run_name TEXT NOT NULL,
+        step INTEGER NOT NULL,
+        metrics TEXT NOT NULL,
+        log_id TEXT,
+        space_id TEXT
+    );
+    CREATE TABLE IF NOT EXISTS configs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id TEXT NOT NULL,
+        run_name TEXT NOT NULL,
+        config TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        UNIQUE(run_id)
+    );
+    CREATE INDEX IF NOT EXISTS idx_metrics_run_step ON metrics(run_id, step);
+    CREATE INDEX IF NOT EXISTS idx_metrics_run_timestamp ON metrics(run_id, timestamp);
+    CREATE UNIQUE INDEX IF NOT EXISTS idx_metrics_log_id ON metrics(log_id) WHERE log_id IS NOT NULL;
+    CREATE INDEX IF NOT EXISTS idx_configs_run_name ON configs(run_name);
+    CREATE TABLE IF NOT EXISTS project_metadata (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS models (
+        name TEXT NOT NULL,
+        version TEXT NOT NULL,
+        run_id TEXT NOT NULL,
+        artifact TEXT NOT NULL,
+        metrics TEXT NOT NULL,
+        stage TEXT NOT NULL,
+        registered_at TEXT NOT NULL,
+        promoted_at TEXT,
+        PRIMARY KEY (name, version)
+    );
+    CREATE TABLE IF NOT EXISTS run_meta (
+        run_id TEXT NOT NULL,
+        key TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY (run_id, key)
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id TEXT NOT NULL,
+        step INTEGER,
+        timestamp TEXT NOT NULL,
+        level TEXT NOT NULL,
+        name TEXT NOT NULL,
+        fields TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS idx_events_run ON events(run_id);
+";
+
+/// Writes metrics straight into the per-project SQLite databases read by the Python
+/// trackio dashboard, so runs can be recorded without a server.
+///
+/// Databases live in `$TRACKIO_DIR`, or `$HF_HOME/trackio` (default
+/// `~/.cache/huggingface/trackio`), one `<project>.db` file per project.
+#[derive(Debug)]
+pub struct SqliteBackend {
+    dir: PathBuf,
+    file: Option<PathBuf>,
+    conns: Mutex<HashMap<PathBuf, Connection>>,
+}
+
+impl Default for SqliteBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqliteBackend {
+    /// Use the same directory as the Python trackio package.
+    pub fn new() -> Self {
+        Self::with_dir(default_trackio_dir())
+    }
+
+    /// Store project databases in `dir` instead of the default trackio directory.
+    pub fn with_dir<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            file: None,
+            conns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Store every project in the database file `path`, such as a project bundle.
+    pub(crate) fn single_file<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        Self {
+            dir: path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            file: Some(path),
+            conns: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Path of the database file backing `project`.
+    pub fn db_path(&self, project: &str) -> PathBuf {
+        if let Some(file) = &self.file {
+            return file.clone();
+        }
+        self.dir
+            .join(format!("{}.db", canonical_project_name(project)))
+    }
+
+    pub(crate) fn with_conn<T>(
+        &self,
+        project: &str,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, TrackioError> {
+        let path = self.db_path(project);
+        let mut conns = self.conns.lock();
+        if !conns.contains_key(&path) {
+            conns.insert(path.clone(), open(&path).map_err(sqlite_err)?);
+        }
+        let conn = conns.get_mut(&path).expect("connection was just inserted");
+        f(conn).map_err(sqlite_err)
+    }
+
+    fn read<T: Default>(
+        &self,
+        project: &str,
+        f: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+    ) -> Result<T, TrackioError> {
+        if !self.db_path(project).exists() {
+            return Ok(T::default());
+        }
+        self.with_conn(project, f)
+    }
+
+    /// Names of all projects with a database in the storage directory.
+    pub fn projects(&self) -> Result<Vec<String>, TrackioError> {
+        let mut projects = Vec::new();
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(projects),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "db") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    projects.push(stem.to_string());
+                }
+            }
+        }
+        projects.sort();
+        Ok(projects)
+    }
+
+    /// Names of the runs in `project`, in creation order.
+    pub fn runs(&self, project: &str) -> Result<Vec<String>, TrackioError> {
+        self.read(project, |conn| {
+            let mut stmt =
+                conn.prepare("SELECT run_name FROM metrics GROUP BY run_name ORDER BY MIN(id)")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect()
+        })
+    }
+
+    /// The config stored for a run, if any.
+    pub fn config(
+        &self,
+        project: &str,
+        run: &str,
+    ) -> Result<Option<serde_json::Value>, TrackioError> {
+        let raw: Option<String> = self.read(project, |conn| {
+            conn.query_row(
+                "SELECT config FROM configs WHERE run_id = ?1",
+                params![run],
+                |row| row.get(0),
+            )
+            .optional()
+        })?;
+        Ok(raw.and_then(|raw| serde_json::from_str(&raw).ok()))
+    }
+
+    /// All logged rows of a run ordered by step, each holding `step`, `timestamp`
+    /// and the metric values, in the shape returned by the server's `get_logs`.
+    pub fn logs(
+        &self,
+        project: &str,
+        run: &str,
+    ) -> Result<Vec<serde_json::Map<String, serde_json::Value>>, TrackioError> {
+        let rows: Vec<(i64, String, String)> = self.read(project, |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT step, timestamp, metrics FROM metrics WHERE run_id = ?1 ORDER BY step, id",
+            )?;
+            let rows = stmt.query_map(params![run], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })?;
+            rows.collect()
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|(step, timestamp, metrics)| {
+                let mut row = match serde_json::from_str(&metrics) {
+                    Ok(serde_json::Value::Object(map)) => map,
+                    _ => serde_json::Map::new(),
+                };
+                row.insert("step".into(), step.into());
+                row.insert("timestamp".into(), timestamp.into());
+                row
+            })
+            .collect())
+    }
+
+    /// Store `config` for a run, replacing any previous one.
+    pub fn set_config(
+        &self,
+        project: &str,
+        run: &str,
+        config: &serde_json::Value,
+    ) -> Result<(), TrackioError> {
+        self.with_conn(project, |conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO configs (run_id, run_name, config, created_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![run, run, config.to_string(), now_iso8601()],
+            )
+            .map(|_| ())
+        })
+    }
+
+    /// Settings stored for `project`, such as its description and whether it is
+    /// archived. They share the `project_metadata` table with the Python package, whose
+    /// plain-text values such as `space_id` are read as strings.
+    pub fn project_settings(
+        &self,
+        project: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, TrackioError> {
+        let rows: Vec<(String, String)> = self.read(project, |conn| {
+            let mut stmt = conn.prepare("SELECT key, value FROM project_metadata ORDER BY key")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect()
+        })?;
+        Ok(rows
+            .into_iter()
+            .map(|(key, value)| {
+                let value = serde_json::from_str(&value).unwrap_or(serde_json::Value::String(value));
+                (key, value)
+            })
+            .collect())
+    }
+
+    /// Store `settings` for `project`, creating it if needed. Keys not in `settings`
+    /// keep their value; a `null` value removes the key.
+    pub fn update_project_settings(
+        &self,
+        project: &str,
+        settings: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), TrackioError> {
+        self.with_conn(project, |conn| {
+            let tx = conn.transaction()?;
+            for (key, value) in settings {
+                if value.is_null() {
+                    tx.execute("DELETE FROM project_metadata WHERE key = ?1", params![key])?;
+                } else {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO project_metadata (key, value) VALUES (?1, ?2)",
+                        params![key, value.to_string()],
+                    )?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// Metadata stored for `run` of `project`.
+    pub fn run_meta(
+        &self,
+        project: &str,
+        run: &str,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, TrackioError> {
+        let rows: Vec<(String, String)> = self.read(project, |conn| {
+            let mut stmt =
+                conn.prepare("SELECT key, value FROM run_meta WHERE run_id = ?1 ORDER BY key")?;
+            let rows = stmt.query_map(params![run], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect()
+        })?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(key, value)| Some((key, serde_json::from_str(&value).ok()?)))
+            .collect())
+    }
+
+    /// Runs of `project` with metadata, sorted by name.
+    pub fn meta_runs(&self, project: &str) -> Result<Vec<String>, TrackioError> {
+        self.read(project, |conn| {
+            let mut stmt =
+                conn.prepare("SELECT DISTINCT run_id FROM run_meta ORDER BY run_id")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect()
+        })
+    }
+
+    /// Store `meta` for `run` of `project`. Keys not in `meta` keep their value; a
+    /// `null` value removes the key.
+    pub fn update_run_meta(
+        &self,
+        project: &str,
+        run: &str,
+        meta: &serde_json::Map<String, serde_json::Value>,
+    ) -> Result<(), TrackioError> {
+        self.with_conn(project, |conn| {
+            let tx = conn.transaction()?;
+            for (key, value) in meta {
+                if value.is_null() {
+                    tx.execute(
+                        "DELETE FROM run_meta WHERE run_id = ?1 AND key = ?2",
+                        params![run, key],
+                    )?;
+                } else {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO run_meta (run_id, key, value) VALUES (?1, ?2, ?3)",
+                        params![run, key, value.to_string()],
+                    )?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// The events logged on `run` of `project`, oldest first.
+    pub fn events(&self, project: &str, run: &str) -> Result<Vec<Event>, TrackioError> {
+        let rows: Vec<(Option<i64>, String, String, String, String)> =
+            self.read(project, |conn| {
+                let mut stmt = conn.prepare(
+                    "SELECT step, timestamp, level, name, fields FROM events
+                     WHERE run_id = ?1 ORDER BY id",
+                )?;
+                let rows = stmt.query_map(params![run], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+                })?;
+                rows.collect()
+            })?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(step, timestamp, level, name, fields)| {
+                Event::from_json(&serde_json::json!({
+                    "step": step,
+                    "timestamp": timestamp,
+                    "level": level,
+                    "name": name,
+                    "fields": serde_json::from_str::<serde_json::Value>(&fields).ok()?,
+                }))
+            })
+            .collect())
+    }
+
+    /// Append `events` to those of `run` of `project`.
+    pub fn insert_events(
+        &self,
+        project: &str,
+        run: &str,
+        events: &[Event],
+    ) -> Result<(), TrackioError> {
+        self.with_conn(project, |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut insert = tx.prepare_cached(
+                    "INSERT INTO events (run_id, step, timestamp, level, name, fields)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+                for event in events {
+                    insert.execute(params![
+                        run,
+                        event.step,
+                        event.timestamp,
+                        event.level.as_str(),
+                        event.name,
+                        serde_json::Value::Object(event.fields.clone()).to_string(),
+                    ])?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// Append `note` to the `notes` array in the metadata of `run` of `project`.
+    pub fn append_note(
+        &self,
+        project: &str,
+        run: &str,
+        note: serde_json::Value,
+    ) -> Result<(), TrackioError> {
+        self.with_conn(project, |conn| {
+            let tx = conn.transaction()?;
+            let notes: Option<String> = tx
+                .query_row(
+                    "SELECT value FROM run_meta WHERE run_id = ?1 AND key = 'notes'",
+                    params![run],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let mut notes = match notes.and_then(|n| serde_json::from_str(&n).ok()) {
+                Some(serde_json::Value::Array(notes)) => notes,
+                _ => Vec::new(),
+            };
+            notes.push(note);
+            tx.execute(
+                "INSERT OR REPLACE INTO run_meta (run_id, key, value) VALUES (?1, 'notes', ?2)",
+                params![run, serde_json::Value::Array(notes).to_string()],
+            )?;
+            tx.commit()
+        })
+    }
+
+    /// The registered versions of the models of `project`, or of the model `name`, in
+    /// registration order.
+    pub fn models(
+        &self,
+        project: &str,
+        name: Option<&str>,
+    ) -> Result<Vec<serde_json::Value>, TrackioError> {
+        self.read(project, |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT name, version, run_id, artifact, metrics, stage, registered_at, promoted_at
+                 FROM models WHERE ?1 IS NULL OR name = ?1 ORDER BY rowid",
+            )?;
+            let rows = stmt.query_map(params![name], |row| {
+                let metrics: String = row.get(4)?;
+                Ok(serde_json::json!({
+                    "name": row.get::<_, String>(0)?,
+                    "version": row.get::<_, String>(1)?,
+                    "run": row.get::<_, String>(2)?,
+                    "artifact": row.get::<_, String>(3)?,
+                    "metrics": serde_json::from_str::<serde_json::Value>(&metrics)
+                        .unwrap_or_default(),
+                    "stage": row.get::<_, String>(5)?,
+                    "registered_at": row.get::<_, String>(6)?,
+                    "promoted_at": row.get::<_, Option<String>>(7)?,
+                }))
+            })?;
+            rows.collect()
+        })
+    }
+
+    /// Register `version` of the model `name`, produced by `run` and stored at
+    /// `artifact`, with the metrics it was selected on. Versions can't be registered
+    /// twice.
+    pub fn register_model(
+        &self,
+        project: &str,
+        run: &str,
+        name: &str,
+        version: &str,
+        artifact: &str,
+        metrics: &serde_json::Value,
+    ) -> Result<(), TrackioError> {
+        if name.trim().is_empty() || version.trim().is_empty() {
+            return Err(TrackioError::InvalidArgument(
+                "model name and version must not be empty".into(),
+            ));
+        }
+        let inserted = self.with_conn(project, |conn| {
+            conn.execute(
+                "INSERT OR IGNORE INTO models
+                 (name, version, run_id, artifact, metrics, stage, registered_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, 'none', ?6)",
+                params![name, version, run, artifact, metrics.to_string(), now_iso8601()],
+            )
+        })?;
+        if inserted == 0 {
+            return Err(TrackioError::InvalidArgument(format!(
+                "version {version:?} of model {name:?} is already registered in project {project:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Move `version` of the model `name` to `stage`. The version previously in
+    /// `staging` or `production` is archived, so each of those stages holds one version.
+    pub fn promote_model(
+        &self,
+        project: &str,
+        name: &str,
+        version: &str,
+        stage: &str,
+    ) -> Result<(), TrackioError> {
+        let updated = self.read(project, |conn| {
+            let tx = conn.transaction()?;
+            if matches!(stage, "staging" | "production") {
+                tx.execute(
+                    "UPDATE models SET stage = 'archived'
+                     WHERE name = ?1 AND stage = ?2 AND version != ?3",
+                    params![name, stage, version],
+                )?;
+            }
+            let updated = tx.execute(
+                "UPDATE models SET stage = ?3, promoted_at = ?4 WHERE name = ?1 AND version = ?2",
+                params![name, version, stage, now_iso8601()],
+            )?;
+            if updated > 0 {
+                tx.commit()?;
+            }
+            Ok(updated)
+        })?;
+        if updated == 0 {
+            return Err(TrackioError::InvalidArgument(format!(
+                "version {version:?} of model {name:?} not found in project {project:?}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rename `old` in `project` to `new`, keeping its rows, config and metadata.
+    pub fn rename_run(&self, project: &str, old: &str, new: &str) -> Result<(), TrackioError> {
+        let new = new.trim();
+        if new.is_empty() {
+            return Err(TrackioError::InvalidArgument("new run name is empty".into()));
+        }
+        let runs = self.runs(project)?;
+        if !runs.iter().any(|r| r == old) && self.config(project, old)?.is_none() {
+            return Err(TrackioError::InvalidArgument(format!(
+                "run {old:?} not found in project {project:?}"
+            )));
+        }
+        if runs.iter().any(|r| r == new) || self.config(project, new)?.is_some() {
+            return Err(TrackioError::InvalidArgument(format!(
+                "run {new:?} already exists in project {project:?}"
+            )));
+        }
+        self.with_conn(project, |conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "UPDATE metrics SET run_id = ?2, run_name = ?2 WHERE run_id = ?1",
+                params![old, new],
+            )?;
+            tx.execute(
+                "UPDATE configs SET run_id = ?2, run_name = ?2 WHERE run_id = ?1",
+                params![old, new],
+            )?;
+            tx.execute("UPDATE run_meta SET run_id = ?2 WHERE run_id = ?1", params![old, new])?;
+            tx.execute("UPDATE events SET run_id = ?2 WHERE run_id = ?1", params![old, new])?;
+            tx.commit()
+        })
+    }
+
+    /// Move `run` from `project` to `new_project`, which must not have a run of that
+    /// name. The rows are written to the new project before they are deleted from the
+    /// old one, so a failure part-way leaves a copy rather than losing data.
+    pub fn move_run(&self, project: &str, run: &str, new_project: &str) -> Result<(), TrackioError> {
+        if self.db_path(project) == self.db_path(new_project) {
+            return Err(TrackioError::InvalidArgument(format!(
+                "run {run:?} is already in project {new_project:?}"
+            )));
+        }
+        let (rows, config): (Vec<MovedRow>, Option<String>) = self.read(project, |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT timestamp, step, metrics, log_id, space_id FROM metrics
+                 WHERE run_id = ?1 ORDER BY id",
+            )?;
+            let rows = stmt
+                .query_map(params![run], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+                })?
+                .collect::<rusqlite::Result<_>>()?;
+            let config = conn
+                .query_row(
+                    "SELECT config FROM configs WHERE run_id = ?1",
+                    params![run],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok((rows, config))
+        })?;
+        if rows.is_empty() && config.is_none() {
+            return Err(TrackioError::InvalidArgument(format!(
+                "run {run:?} not found in project {project:?}"
+            )));
+        }
+        let meta = self.run_meta(project, run)?;
+        let events = self.events(project, run)?;
+        if self.runs(new_project)?.iter().any(|r| r == run)
+            || self.config(new_project, run)?.is_some()
+        {
+            return Err(TrackioError::InvalidArgument(format!(
+                "run {run:?} already exists in project {new_project:?}"
+            )));
+        }
+        self.with_conn(new_project, |conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut insert = tx.prepare_cached(
+                    "INSERT INTO metrics (timestamp, run_id, run_name, step, metrics, log_id, space_id)
+                     VALUES (?1, ?2, ?2, ?3, ?4, ?5, ?6)",
+                )?;
+                for (timestamp, step, metrics, log_id, space_id) in &rows {
+                    insert.execute(params![timestamp, run, step, metrics, log_id, space_id])?;
+                }
+            }
+            if let Some(config) = &config {
+                tx.execute(
+                    "INSERT INTO configs (run_id, run_name, config, created_at)
+                     VALUES (?1, ?1, ?2, ?3)",
+                    params![run, config, now_iso8601()],
+                )?;
+            }
+            tx.commit()
+        })?;
+        self.update_run_meta(new_project, run, &meta)?;
+        self.insert_events(new_project, run, &events)?;
+        self.with_conn(project, |conn| {
+            let tx = conn.transaction()?;
+            tx.execute("DELETE FROM metrics WHERE run_id = ?1", params![run])?;
+            tx.execute("DELETE FROM configs WHERE run_id = ?1", params![run])?;
+            tx.execute("DELETE FROM run_meta WHERE run_id = ?1", params![run])?;
+            tx.execute("DELETE FROM events WHERE run_id = ?1", params![run])?;
+            tx.commit()
+        })
+    }
+
+    /// Delete `run` with its config, metadata and events from `project`. Returns whether
+    /// it existed.
+    pub fn delete_run(&self, project: &str, run: &str) -> Result<bool, TrackioError> {
+        self.read(project, |conn| {
+            let tx = conn.transaction()?;
+            let rows = tx.execute("DELETE FROM metrics WHERE run_id = ?1", params![run])?;
+            let configs = tx.execute("DELETE FROM configs WHERE run_id = ?1", params![run])?;
+            let meta = tx.execute("DELETE FROM run_meta WHERE run_id = ?1", params![run])?;
+            let events = tx.execute("DELETE FROM events WHERE run_id = ?1", params![run])?;
+            tx.commit()?;
+            Ok(rows + configs + meta + events > 0)
+        })
+    }
+
+    /// Delete the rows of `run` logged at a step in `steps`. Returns how many were
+    /// deleted.
+    pub fn prune(
+        &self,
+        project: &str,
+        run: &str,
+        steps: RangeInclusive<i64>,
+    ) -> Result<usize, TrackioError> {
+        self.read(project, |conn| {
+            conn.execute(
+                "DELETE FROM metrics WHERE run_id = ?1 AND step BETWEEN ?2 AND ?3",
+                params![run, steps.start(), steps.end()],
+            )
+        })
+    }
+
+    /// Store `items` for `run`, returning the row id of each, or `None` for a row
+    /// ignored as a duplicate. Rows without a step get the run's next one.
+    pub fn insert(
+        &self,
+        project: &str,
+        run: &str,
+        items: &[LogItem],
+    ) -> Result<Vec<Option<i64>>, TrackioError> {
+        self.with_conn(project, |conn| {
+            let tx = conn.transaction()?;
+            let last: Option<i64> = tx
+                .query_row(
+                    "SELECT MAX(step) FROM metrics WHERE run_id = ?1",
+                    params![run],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .flatten();
+            let mut next_step = last.map_or(0, |s| s + 1);
+            let mut offsets = Vec::with_capacity(items.len());
+            {
+                let mut insert = tx.prepare_cached(
+                    "INSERT OR IGNORE INTO metrics (timestamp, run_id, run_name, step, metrics)
+                     VALUES (?1, ?2, ?3, ?4, ?5)",
+                )?;
+                for item in items {
+                    let step = match item.step {
+                        Some(step) if step >= 0 => step,
+                        _ => next_step,
+                    };
+                    next_step = next_step.max(step + 1);
+                    let timestamp = item
+                        .timestamp
+                        .clone()
+                        .filter(|t| !t.is_empty())
+                        .unwrap_or_else(now_iso8601);
+                    let inserted = insert
+                        .execute(params![timestamp, run, run, step, item.metrics.to_string()])?;
+                    offsets.push((inserted > 0).then(|| tx.last_insert_rowid()));
+                }
+            }
+            tx.commit()?;
+            Ok(offsets)
+        })
+    }
+}
+
+type MovedRow = (String, i64, String, Option<String>, Option<String>);
+
+impl Backend for SqliteBackend {
+    fn send(&self, project: &str, run: &str, items: &[LogItem]) -> Result<(), TrackioError> {
+        self.insert(project, run, items).map(|_| ())
+    }
+
+    fn set_config(
+        &self,
+        project: &str,
+        run: &str,
+        config: &serde_json::Value,
+    ) -> Result<(), TrackioError> {
+        SqliteBackend::set_config(self, project, run, config)
+    }
+
+    fn send_events(&self, project: &str, run: &str, events: &[Event]) -> Result<(), TrackioError> {
+        self.insert_events(project, run, events)
+    }
+}
+
+fn open(path: &Path) -> rusqlite::Result<Connection> {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(Duration::from_secs(30))?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    add_missing_columns(&conn)?;
+    conn.execute_batch(SCHEMA)?;
+    Ok(conn)
+}
+
+/// Add the `metrics` columns the Python package added over time, which databases it
+/// created before then lack, as its own `init_db` does.
+fn add_missing_columns(conn: &Connection) -> rusqlite::Result<()> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('metrics')")?;
+    let columns: Vec<String> = stmt.query_map([], |row| row.get(0))?.collect::<Result<_, _>>()?;
+    if columns.is_empty() {
+        return Ok(());
+    }
+    for column in ["log_id", "space_id"] {
+        if !columns.iter().any(|c| c == column) {
+            conn.execute(&format!("ALTER TABLE metrics ADD COLUMN {column} TEXT"), [])?;
+        }
+    }
+    Ok(())
+}
+
+fn sqlite_err(e: rusqlite::Error) -> TrackioError {
+    TrackioError::Sqlite(e.to_string())
+}
+
+/// On-disk name of a project: only Unicode alphanumerics, `-` and `_` are kept, falling
+/// back to `default`.
+pub fn canonical_project_name(project: &str) -> String {
+    let safe: String = project
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    if safe.is_empty() {
+        "default".into()
+    } else {
+        safe
+    }
+}
+
+/// The directory the Python trackio package stores its databases in.
+pub fn default_trackio_dir() -> PathBuf {
+    if let Ok(dir) = env::var("TRACKIO_DIR") {
+        return PathBuf::from(dir);
+    }
+    let hf_home = env::var("HF_HOME").map(PathBuf::from).unwrap_or_else(|_| {
+        env::var("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| home_dir().join(".cache"))
+            .join("huggingface")
+    });
+    hf_home.join("trackio")
+}
+
+fn home_dir() -> PathBuf {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("."))
+}
@@ -0,0 +1,72 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL: Duration = Duration::from_millis(2);
+
+/// Staggers the bulk posts of the processes on a host that share a lock file, so many
+/// workers flushing on the same interval send one after another instead of in bursts.
+///
+/// A post holds an exclusive lock on the file while it is sent and until `spacing` has
+/// passed since it got the lock, so posts from the host start at least `spacing` apart.
+/// A process waits for the lock at most `max_wait`, then sends anyway, so a hung peer
+/// can't hold up the uploads of the others.
+#[derive(Debug)]
+pub(crate) struct NodeStagger {
+    path: PathBuf,
+    spacing: Duration,
+    max_wait: Duration,
+}
+
+impl NodeStagger {
+    /// Stagger through `trackio-flush.lock` in `dir`, or in the temporary directory.
+    pub(crate) fn new(dir: Option<&Path>, spacing: Duration, max_wait: Duration) -> Self {
+        let dir = dir.map_or_else(std::env::temp_dir, Path::to_path_buf);
+        Self {
+            path: dir.join("trackio-flush.lock"),
+            spacing,
+            max_wait,
+        }
+    }
+
+    /// Runs `send` holding the lock, if it can be had.
+    pub(crate) fn run<T>(&self, send: impl FnOnce() -> T) -> T {
+        let Some(lock) = self.lock() else {
+            return send();
+        };
+        let acquired = Instant::now();
+        let result = send();
+        if let Some(rest) = self.spacing.checked_sub(acquired.elapsed()) {
+            thread::sleep(rest);
+        }
+        drop(lock);
+        result
+    }
+
+    fn lock(&self) -> Option<File> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .or_else(|_| File::open(&self.path));
+        let file = match file {
+            Ok(file) => file,
+            Err(e) => {
+                log::debug!("trackio: sending unstaggered, can't open {}: {e}", self.path.display());
+                return None;
+            }
+        };
+        let deadline = Instant::now() + self.max_wait;
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Some(file),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => thread::sleep(POLL),
+                Err(e) => {
+                    log::debug!("trackio: sending unstaggered, no lock on {}: {e}", self.path.display());
+                    return None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use crate::client::{Client, TrackioError};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Accepts StatsD packets over UDP and logs them to a run, so shell scripts and
+/// processes in other languages can report into the same run as the training job:
+///
+/// ```sh
+/// echo "queue/depth:42|g" | nc -u -w0 127.0.0.1 8125
+/// ```
+///
+/// Packets are aggregated over the flush interval and each interval becomes one row at
+/// the next step. Counters (`c`, honouring `@rate`) are summed, gauges (`g`, including
+/// `+`/`-` deltas) keep their last value, sets (`s`) log the number of distinct values and
+/// timers (`ms`, `h`, `d`) are logged as `<name>/count`, `/sum`, `/mean`, `/min` and
+/// `/max`. DogStatsD tags (`|#k:v,...`) are appended as `<name>{k=v,...}`.
+///
+/// ```no_run
+/// use trackio::statsd::StatsdListener;
+///
+/// let client = trackio::Client::new().with_project("p").with_run("r");
+/// let listener = StatsdListener::bind(client, "127.0.0.1:8125")?.spawn();
+/// // ... train ...
+/// listener.stop()?;
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug)]
+pub struct StatsdListener {
+    client: Client,
+    socket: UdpSocket,
+    flush_interval: Duration,
+    step: i64,
+    window: Window,
+    gauges: HashMap<String, f64>,
+}
+
+#[derive(Debug, Default)]
+struct Window {
+    counters: BTreeMap<String, f64>,
+    gauges: BTreeMap<String, f64>,
+    timers: BTreeMap<String, Vec<f64>>,
+    sets: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Window {
+    fn is_empty(&self) -> bool {
+        self.counters.is_empty()
+            && self.gauges.is_empty()
+            && self.timers.is_empty()
+            && self.sets.is_empty()
+    }
+}
+
+impl StatsdListener {
+    /// Listen on `addr` (StatsD's conventional port is 8125) and log to `client`'s run.
+    pub fn bind<A: ToSocketAddrs>(client: Client, addr: A) -> Result<Self, TrackioError> {
+        Ok(Self {
+            client,
+            socket: UdpSocket::bind(addr)?,
+            flush_interval: Duration::from_secs(10),
+            step: 0,
+            window: Window::default(),
+            gauges: HashMap::new(),
+        })
+    }
+
+    /// How long packets are aggregated before being logged as a row (default: 10s).
+    pub fn with_flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// First step logged (default: 0).
+    pub fn with_start_step(mut self, step: i64) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// The address the listener is bound to, useful after binding to port 0.
+    pub fn local_addr(&self) -> Result<SocketAddr, TrackioError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// Receive and log packets until an error occurs.
+    pub fn run(mut self) -> Result<(), TrackioError> {
+        self.serve(&AtomicBool::new(false))
+    }
+
+    /// Receive and log packets on a background thread.
+    pub fn spawn(mut self) -> StatsdHandle {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = stop.clone();
+        let thread = thread::spawn(move || self.serve(&flag));
+        StatsdHandle { stop, thread }
+    }
+
+    fn serve(&mut self, stop: &AtomicBool) -> Result<(), TrackioError> {
+        let mut buf = vec![0u8; 65536];
+        let mut deadline = Instant::now() + self.flush_interval;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            self.socket.set_read_timeout(Some(
+                wait.clamp(Duration::from_millis(1), Duration::from_millis(250)),
+            ))?;
+            match self.socket.recv(&mut buf) {
+                Ok(n) => {
+                    for line in String::from_utf8_lossy(&buf[..n]).lines() {
+                        self.ingest(line);
+                    }
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(e) => return Err(e.into()),
+            }
+            let stopping = stop.load(Ordering::Relaxed);
+            if stopping || Instant::now() >= deadline {
+                self.flush()?;
+                deadline = Instant::now() + self.flush_interval;
+            }
+            if stopping {
+                return Ok(());
+            }
+        }
+    }
+
+    fn ingest(&mut self, line: &str) {
+        let Some(packet) = Packet::parse(line) else {
+            return;
+        };
+        let window = &mut self.window;
+        match packet.kind {
+            "c" => {
+                let Ok(v) = packet.value.parse::<f64>() else {
+                    return;
+                };
+                *window.counters.entry(packet.key).or_default() += v / packet.rate;
+            }
+            "g" => {
+                let Ok(v) = packet.value.parse::<f64>() else {
+                    return;
+                };
+                let last = self.gauges.entry(packet.key.clone()).or_default();
+                if packet.value.starts_with(['+', '-']) {
+                    *last += v;
+                } else {
+                    *last = v;
+                }
+                window.gauges.insert(packet.key, *last);
+            }
+            "ms" | "h" | "d" => {
+                let Ok(v) = packet.value.parse::<f64>() else {
+                    return;
+                };
+                window.timers.entry(packet.key).or_default().push(v);
+            }
+            "s" => {
+                window
+                    .sets
+                    .entry(packet.key)
+                    .or_default()
+                    .insert(packet.value.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), TrackioError> {
+        let window = std::mem::take(&mut self.window);
+        if window.is_empty() {
+            return Ok(());
+        }
+        let mut row = Map::new();
+        let mut put = |key: String, value: f64| {
+            if value.is_finite() {
+                row.insert(key, Value::from(value));
+            }
+        };
+        for (key, v) in window.counters.into_iter().chain(window.gauges) {
+            put(key, v);
+        }
+        for (key, values) in window.sets {
+            put(key, values.len() as f64);
+        }
+        for (key, values) in window.timers {
+            let sum: f64 = values.iter().sum();
+            let count = values.len() as f64;
+            let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            put(format!("{key}/count"), count);
+            put(format!("{key}/sum"), sum);
+            put(format!("{key}/mean"), sum / count);
+            put(format!("{key}/min"), min);
+            put(format!("{key}/max"), max);
+        }
+        self.client.log(Value::Object(row), Some(self.step), None);
+        self.step += 1;
+        self.client.flush().map(|_| ())
+    }
+}
+
+/// A listener running on a background thread, returned by [`StatsdListener::spawn`].
+#[derive(Debug)]
+pub struct StatsdHandle {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<Result<(), TrackioError>>,
+}
+
+impl StatsdHandle {
+    /// Log the current interval, stop listening and return the listener's first error.
+    pub fn stop(self) -> Result<(), TrackioError> {
+        self.stop.store(true, Ordering::Relaxed);
+        self.thread
+            .join()
+            .unwrap_or_else(|_| Err(TrackioError::Import("statsd listener panicked".into())))
+    }
+}
+
+/// One `name:value|type[|@rate][|#tags]` line.
+struct Packet<'a> {
+    key: String,
+    value: &'a str,
+    kind: &'a str,
+    rate: f64,
+}
+
+impl<'a> Packet<'a> {
+    fn parse(line: &'a str) -> Option<Self> {
+        let (name, rest) = line.trim().split_once(':')?;
+        let mut parts = rest.split('|');
+        let value = parts.next()?.trim();
+        let kind = parts.next()?.trim();
+        let mut rate = 1.0;
+        let mut tags = Vec::new();
+        for part in parts {
+            if let Some(r) = part.strip_prefix('@') {
+                rate = r.parse().ok().filter(|r| *r > 0.0 && *r <= 1.0)?;
+            } else if let Some(t) = part.strip_prefix('#') {
+                tags.extend(
+                    t.split(',')
+                        .filter(|t| !t.is_empty())
+                        .map(|t| t.replacen(':', "=", 1)),
+                );
+            }
+        }
+        if name.is_empty() || value.is_empty() {
+            return None;
+        }
+        let key = if tags.is_empty() {
+            name.to_string()
+        } else {
+            tags.sort();
+            format!("{name}{{{}}}", tags.join(","))
+        };
+        Some(Self {
+            key,
+            value,
+            kind,
+            rate,
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use crate::log_item::LogItem;
+use parking_lot::Mutex;
+use serde_json::{json, Number, Value};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Relative accuracy of the quantiles.
+const ACCURACY: f64 = 0.01;
+
+pub(crate) const QUANTILES: [(&str, f64); 3] = [
+    ("trackio/step_time/p50", 0.5),
+    ("trackio/step_time/p95", 0.95),
+    ("trackio/step_time/p99", 0.99),
+];
+
+/// Wall time between the steps a client logs, summarized once per flush; see
+/// [`Client::with_step_timer`](crate::Client::with_step_timer).
+#[derive(Debug, Default)]
+pub(crate) struct StepTimer {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    last: Option<(Option<i64>, Instant)>,
+    last_step: Option<i64>,
+    sketch: Sketch,
+}
+
+impl StepTimer {
+    /// Notes a row logged at `step`. A higher step than the last records the time since
+    /// it, divided by the steps advanced; rows without a step each count as one.
+    pub(crate) fn observe(&self, step: Option<i64>) {
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        let steps = match (state.last, step) {
+            (Some((Some(last), _)), Some(step)) if step <= last => return,
+            (Some((Some(last), _)), Some(step)) => (step - last) as f64,
+            _ => 1.0,
+        };
+        if let Some((_, at)) = state.last {
+            state.sketch.add(now.duration_since(at).as_secs_f64() / steps);
+        }
+        state.last = Some((step, now));
+        if step.is_some() {
+            state.last_step = step;
+        }
+    }
+
+    /// The quantiles of the step times since the last report, at the latest step
+    /// logged; `None` if no step was timed since.
+    pub(crate) fn report(&self) -> Option<LogItem> {
+        let mut state = self.state.lock();
+        let sketch = std::mem::take(&mut state.sketch);
+        if sketch.count == 0 {
+            return None;
+        }
+        let mut metrics = serde_json::Map::new();
+        for (key, q) in QUANTILES {
+            metrics.insert(key.into(), float(sketch.quantile(q)));
+        }
+        metrics.insert("trackio/step_time/count".into(), json!(sketch.count));
+        Some(LogItem {
+            metrics: Value::Object(metrics),
+            step: state.last_step,
+            timestamp: None,
+        })
+    }
+}
+
+/// A histogram with logarithmic buckets, so quantiles of any magnitude are within
+/// [`ACCURACY`] of the true value while memory grows only with the range of values.
+#[derive(Debug, Default)]
+struct Sketch {
+    buckets: BTreeMap<i32, u64>,
+    count: u64,
+}
+
+impl Sketch {
+    fn gamma() -> f64 {
+        (1.0 + ACCURACY) / (1.0 - ACCURACY)
+    }
+
+    fn add(&mut self, value: f64) {
+        if !value.is_finite() || value <= 0.0 {
+            return;
+        }
+        let index = (value.ln() / Self::gamma().ln()).ceil() as i32;
+        *self.buckets.entry(index).or_default() += 1;
+        self.count += 1;
+    }
+
+    fn quantile(&self, q: f64) -> f64 {
+        let rank = (q * (self.count - 1) as f64).floor() as u64;
+        let mut seen = 0;
+        for (&index, &n) in &self.buckets {
+            seen += n;
+            if seen > rank {
+                let gamma = Self::gamma();
+                return 2.0 * gamma.powi(index) / (gamma + 1.0);
+            }
+        }
+        0.0
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
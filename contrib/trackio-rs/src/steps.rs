@@ -0,0 +1,31 @@
+use std::fmt;
+use std::sync::Arc;
+
+type StepFn = Arc<dyn Fn(i64) -> i64 + Send + Sync>;
+
+/// The step rewrites installed with [`Client::with_step_offset`](crate::Client::with_step_offset)
+/// and [`Client::with_step_transform`](crate::Client::with_step_transform), applied in
+/// the order they were added.
+#[derive(Clone, Default)]
+pub(crate) struct StepTransforms {
+    transforms: Vec<StepFn>,
+}
+
+impl fmt::Debug for StepTransforms {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StepTransforms")
+            .field("transforms", &self.transforms.len())
+            .finish()
+    }
+}
+
+impl StepTransforms {
+    pub(crate) fn push(&mut self, transform: StepFn) {
+        self.transforms.push(transform);
+    }
+
+    pub(crate) fn apply(&self, step: Option<i64>) -> Option<i64> {
+        let step = step?;
+        Some(self.transforms.iter().fold(step, |step, f| f(step)))
+    }
+}
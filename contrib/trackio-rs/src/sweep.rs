@@ -0,0 +1,498 @@
+use crate::client::{Client, TrackioError};
+use crate::groups::GroupAggregate;
+use parking_lot::Mutex;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The values one hyperparameter can take.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    /// One of a fixed list of values.
+    Choice(Vec<Value>),
+    /// A float drawn uniformly from `[low, high]`.
+    Uniform { low: f64, high: f64 },
+    /// A float whose logarithm is uniform in `[ln low, ln high]`, e.g. a learning rate.
+    LogUniform { low: f64, high: f64 },
+    /// An integer in `[low, high]`.
+    Int { low: i64, high: i64 },
+}
+
+/// The hyperparameters a sweep explores.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchSpace {
+    pub params: BTreeMap<String, Param>,
+}
+
+impl SearchSpace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn choice<I, V>(mut self, name: &str, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        let values = values.into_iter().map(Into::into).collect();
+        self.params.insert(name.into(), Param::Choice(values));
+        self
+    }
+
+    pub fn uniform(mut self, name: &str, low: f64, high: f64) -> Self {
+        self.params
+            .insert(name.into(), Param::Uniform { low, high });
+        self
+    }
+
+    pub fn log_uniform(mut self, name: &str, low: f64, high: f64) -> Self {
+        self.params
+            .insert(name.into(), Param::LogUniform { low, high });
+        self
+    }
+
+    pub fn int(mut self, name: &str, low: i64, high: i64) -> Self {
+        self.params.insert(name.into(), Param::Int { low, high });
+        self
+    }
+}
+
+/// How a sweep proposes the next configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Every combination of the values of choice and integer parameters, in order.
+    Grid,
+    /// Independent random draws.
+    Random,
+    /// Tree-structured Parzen estimator: random draws at first, then values that were
+    /// more common among the best trials than among the rest.
+    Tpe,
+}
+
+/// Whether lower or higher values of the sweep metric are better.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    Minimize,
+    Maximize,
+}
+
+/// One trial of a sweep, handed to the trial callback.
+///
+/// Its [`client`](Trial::client) logs to a run named `<sweep id>-<number>`, grouped
+/// under the sweep id and configured with the trial's parameters.
+#[derive(Debug)]
+pub struct Trial {
+    pub number: usize,
+    pub params: Map<String, Value>,
+    client: Client,
+    metric: String,
+    objective: Mutex<Option<f64>>,
+    aggregate: Option<Arc<GroupAggregate>>,
+}
+
+impl Trial {
+    /// The value of parameter `name`.
+    pub fn param(&self, name: &str) -> Option<&Value> {
+        self.params.get(name)
+    }
+
+    /// A client logging to this trial's run.
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Log a row to the trial's run. The last logged value of the sweep metric is the
+    /// trial's result. Rows with a step also feed the
+    /// [group aggregate](Sweep::with_group_aggregate), if the sweep has one.
+    pub fn log(&self, metrics: Value, step: Option<i64>) {
+        if let Some(v) = metrics.get(&self.metric).and_then(Value::as_f64) {
+            *self.objective.lock() = Some(v);
+        }
+        if let (Some(aggregate), Some(step)) = (&self.aggregate, step) {
+            aggregate.observe(self.client.run(), &metrics, step);
+        }
+        self.client.log(metrics, step, None);
+    }
+}
+
+/// Outcome of one trial.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrialResult {
+    pub number: usize,
+    pub run: String,
+    pub params: Map<String, Value>,
+    /// Last value of the sweep metric, if the trial logged it.
+    pub objective: Option<f64>,
+    /// The error the trial callback returned, if it failed.
+    pub error: Option<String>,
+}
+
+/// All trials of a finished sweep.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepResult {
+    pub trials: Vec<TrialResult>,
+    pub goal: Goal,
+}
+
+impl SweepResult {
+    /// The trial with the best objective.
+    pub fn best(&self) -> Option<&TrialResult> {
+        self.trials
+            .iter()
+            .filter(|t| t.objective.is_some_and(f64::is_finite))
+            .min_by(|a, b| {
+                let (a, b) = (a.objective.unwrap_or(0.0), b.objective.unwrap_or(0.0));
+                match self.goal {
+                    Goal::Minimize => a.total_cmp(&b),
+                    Goal::Maximize => b.total_cmp(&a),
+                }
+            })
+    }
+}
+
+/// A hyperparameter sweep run in-process, the equivalent of a W&B sweep agent.
+///
+/// Each trial is logged as its own run in the client's project, grouped under the
+/// sweep id, with the proposed parameters as its config.
+///
+/// ```no_run
+/// use trackio::sweep::{Method, SearchSpace, Sweep};
+///
+/// let client = trackio::Client::new().with_project("mnist");
+/// let space = SearchSpace::new()
+///     .log_uniform("lr", 1e-5, 1e-1)
+///     .choice("batch_size", [32, 64, 128]);
+/// let result = Sweep::new("lr-sweep", space)
+///     .with_method(Method::Tpe)
+///     .minimize("val_loss")
+///     .with_max_trials(30)
+///     .run(&client, |trial| {
+///         let lr = trial.param("lr").and_then(|v| v.as_f64()).unwrap();
+///         for epoch in 0..10 {
+///             trial.log(serde_json::json!({"val_loss": 1.0 / (epoch as f64 + 1.0) + lr}), Some(epoch));
+///         }
+///         Ok(())
+///     })?;
+/// println!("best: {:?}", result.best());
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sweep {
+    id: String,
+    space: SearchSpace,
+    method: Method,
+    metric: String,
+    goal: Goal,
+    max_trials: Option<usize>,
+    seed: u64,
+    aggregate: bool,
+}
+
+impl Sweep {
+    pub fn new(id: &str, space: SearchSpace) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self {
+            id: id.into(),
+            space,
+            method: Method::Random,
+            metric: "loss".into(),
+            goal: Goal::Minimize,
+            max_trials: None,
+            seed,
+            aggregate: false,
+        }
+    }
+
+    /// Search method (default: random).
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Optimize for low values of `metric` (the default, with `loss`).
+    pub fn minimize(mut self, metric: &str) -> Self {
+        self.metric = metric.into();
+        self.goal = Goal::Minimize;
+        self
+    }
+
+    /// Optimize for high values of `metric`.
+    pub fn maximize(mut self, metric: &str) -> Self {
+        self.metric = metric.into();
+        self.goal = Goal::Maximize;
+        self
+    }
+
+    /// Stop after this many trials. Required for random and TPE sweeps; a grid sweep
+    /// stops by itself once every combination has been tried.
+    pub fn with_max_trials(mut self, n: usize) -> Self {
+        self.max_trials = Some(n);
+        self
+    }
+
+    /// Seed for the random draws, to make a sweep reproducible.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Log the mean, spread and range over the trials of every metric they log with
+    /// [`Trial::log`] at each step to a run `<sweep id>-aggregate`, once the sweep is
+    /// done; see [`GroupAggregate`].
+    pub fn with_group_aggregate(mut self) -> Self {
+        self.aggregate = true;
+        self
+    }
+
+    /// Run the trials one after another, calling `trial_fn` for each and flushing its
+    /// run afterwards. A trial whose callback fails is recorded and the sweep goes on.
+    pub fn run<F>(&self, client: &Client, mut trial_fn: F) -> Result<SweepResult, TrackioError>
+    where
+        F: FnMut(&Trial) -> Result<(), TrackioError>,
+    {
+        let grid = match self.method {
+            Method::Grid => Some(grid(&self.space)?),
+            _ => None,
+        };
+        let limit = match (&grid, self.max_trials) {
+            (Some(grid), Some(n)) => n.min(grid.len()),
+            (Some(grid), None) => grid.len(),
+            (None, Some(n)) => n,
+            (None, None) => {
+                return Err(TrackioError::InvalidArgument(
+                    "random and TPE sweeps need with_max_trials".into(),
+                ))
+            }
+        };
+
+        let aggregate = self
+            .aggregate
+            .then(|| Arc::new(client.group_aggregate(&self.id)));
+        let mut rng = Rng(self.seed);
+        let mut result = SweepResult {
+            trials: Vec::new(),
+            goal: self.goal,
+        };
+        for number in 0..limit {
+            let params = match &grid {
+                Some(grid) => grid[number].clone(),
+                None if self.method == Method::Tpe => self.suggest_tpe(&result, &mut rng),
+                None => sample(&self.space, &mut rng),
+            };
+            let run = format!("{}-{number}", self.id);
+            let trial = Trial {
+                number,
+                params: params.clone(),
+                client: client.for_run(client.project(), &run).with_group(&self.id),
+                metric: self.metric.clone(),
+                objective: Mutex::new(None),
+                aggregate: aggregate.clone(),
+            };
+            let mut config = params.clone();
+            config.insert("_SweepId".into(), json!(self.id));
+            config.insert("_SweepTrial".into(), json!(number));
+            trial.client.set_config(Value::Object(config));
+
+            let outcome = trial_fn(&trial).and_then(|()| trial.client.flush());
+            result.trials.push(TrialResult {
+                number,
+                run,
+                params,
+                objective: *trial.objective.lock(),
+                error: outcome.err().map(|e| e.to_string()),
+            });
+        }
+        if let Some(aggregate) = aggregate {
+            if let Err(e) = aggregate.finish() {
+                log::warn!("trackio: could not log the aggregate of sweep {}: {e}", self.id);
+            }
+        }
+        Ok(result)
+    }
+
+    fn suggest_tpe(&self, history: &SweepResult, rng: &mut Rng) -> Map<String, Value> {
+        const STARTUP: usize = 10;
+        const CANDIDATES: usize = 24;
+        let mut done: Vec<(f64, &Map<String, Value>)> = history
+            .trials
+            .iter()
+            .filter_map(|t| Some((t.objective.filter(|v| v.is_finite())?, &t.params)))
+            .collect();
+        if done.len() < STARTUP {
+            return sample(&self.space, rng);
+        }
+        done.sort_by(|a, b| match self.goal {
+            Goal::Minimize => a.0.total_cmp(&b.0),
+            Goal::Maximize => b.0.total_cmp(&a.0),
+        });
+        let n_good = (done.len() / 4).max(1);
+        let (good, bad) = done.split_at(n_good);
+
+        let mut out = Map::new();
+        for (name, param) in &self.space.params {
+            let observed = |trials: &[(f64, &Map<String, Value>)]| -> Vec<f64> {
+                trials
+                    .iter()
+                    .filter_map(|(_, p)| encode(param, p.get(name)?))
+                    .collect()
+            };
+            let (good, bad) = (observed(good), observed(bad));
+            let mut best = (f64::NEG_INFINITY, sample_one(param, rng));
+            for _ in 0..CANDIDATES {
+                let x = match good.get(rng.below(good.len().max(1))) {
+                    Some(&center) => perturb(param, center, good.len(), rng),
+                    None => encode(param, &sample_one(param, rng)).unwrap_or(0.0),
+                };
+                let score = density(param, &good, x).ln() - density(param, &bad, x).ln();
+                if score > best.0 {
+                    best = (score, decode(param, x));
+                }
+            }
+            out.insert(name.clone(), best.1);
+        }
+        out
+    }
+}
+
+/// Maps a parameter value into the space the TPE works in: the index of a choice, the
+/// logarithm of a log-uniform value, the value itself otherwise.
+fn encode(param: &Param, value: &Value) -> Option<f64> {
+    match param {
+        Param::Choice(values) => values.iter().position(|v| v == value).map(|i| i as f64),
+        Param::LogUniform { .. } => value.as_f64().filter(|v| *v > 0.0).map(f64::ln),
+        Param::Uniform { .. } | Param::Int { .. } => value.as_f64(),
+    }
+}
+
+fn decode(param: &Param, x: f64) -> Value {
+    match param {
+        Param::Choice(values) => {
+            let i = (x.round().max(0.0) as usize).min(values.len().saturating_sub(1));
+            values.get(i).cloned().unwrap_or(Value::Null)
+        }
+        Param::Uniform { low, high } => json!(x.clamp(*low, *high)),
+        Param::LogUniform { low, high } => json!(x.exp().clamp(*low, *high)),
+        Param::Int { low, high } => json!((x.round() as i64).clamp(*low, *high)),
+    }
+}
+
+/// Bounds of the encoded space.
+fn bounds(param: &Param) -> (f64, f64) {
+    match param {
+        Param::Choice(values) => (0.0, values.len().saturating_sub(1) as f64),
+        Param::Uniform { low, high } => (*low, *high),
+        Param::LogUniform { low, high } => (low.ln(), high.ln()),
+        Param::Int { low, high } => (*low as f64, *high as f64),
+    }
+}
+
+fn bandwidth(param: &Param, n: usize) -> f64 {
+    let (low, high) = bounds(param);
+    ((high - low) / (n as f64 + 1.0).sqrt()).max(1e-12)
+}
+
+fn perturb(param: &Param, center: f64, n: usize, rng: &mut Rng) -> f64 {
+    let (low, high) = bounds(param);
+    match param {
+        Param::Choice(values) if rng.f64() < 0.2 => rng.below(values.len().max(1)) as f64,
+        Param::Choice(_) => center,
+        _ => (center + rng.normal() * bandwidth(param, n)).clamp(low, high),
+    }
+}
+
+/// Parzen density of `x` given the observations, mixed with a uniform prior so that
+/// unobserved regions keep a non-zero density.
+fn density(param: &Param, observed: &[f64], x: f64) -> f64 {
+    let (low, high) = bounds(param);
+    let prior = 1.0 / (high - low).max(1.0);
+    if let Param::Choice(values) = param {
+        let hits = observed.iter().filter(|v| (**v - x).abs() < 0.5).count();
+        return (hits as f64 + 1.0) / (observed.len() + values.len().max(1)) as f64;
+    }
+    let h = bandwidth(param, observed.len());
+    let kernel: f64 = observed
+        .iter()
+        .map(|v| (-0.5 * ((x - v) / h).powi(2)).exp() / (h * (2.0 * std::f64::consts::PI).sqrt()))
+        .sum();
+    (kernel + prior) / (observed.len() as f64 + 1.0)
+}
+
+fn sample(space: &SearchSpace, rng: &mut Rng) -> Map<String, Value> {
+    space
+        .params
+        .iter()
+        .map(|(name, param)| (name.clone(), sample_one(param, rng)))
+        .collect()
+}
+
+fn sample_one(param: &Param, rng: &mut Rng) -> Value {
+    match param {
+        Param::Choice(values) => values
+            .get(rng.below(values.len().max(1)))
+            .cloned()
+            .unwrap_or(Value::Null),
+        Param::Uniform { low, high } => json!(low + rng.f64() * (high - low)),
+        Param::LogUniform { low, high } => {
+            json!((low.ln() + rng.f64() * (high.ln() - low.ln())).exp())
+        }
+        Param::Int { low, high } => {
+            let span = (high - low).max(0) as usize + 1;
+            json!(low + rng.below(span) as i64)
+        }
+    }
+}
+
+fn grid(space: &SearchSpace) -> Result<Vec<Map<String, Value>>, TrackioError> {
+    let mut combos = vec![Map::new()];
+    for (name, param) in &space.params {
+        let values: Vec<Value> = match param {
+            Param::Choice(values) => values.clone(),
+            Param::Int { low, high } => (*low..=*high).map(Value::from).collect(),
+            _ => {
+                return Err(TrackioError::InvalidArgument(format!(
+                    "grid sweeps need choice or integer parameters, {name} is continuous"
+                )))
+            }
+        };
+        combos = combos
+            .into_iter()
+            .flat_map(|combo| {
+                values.iter().map(move |v| {
+                    let mut combo = combo.clone();
+                    combo.insert(name.clone(), v.clone());
+                    combo
+                })
+            })
+            .collect();
+    }
+    Ok(combos)
+}
+
+/// SplitMix64, enough for proposing hyperparameters without a `rand` dependency.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn f64(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+
+    fn normal(&mut self) -> f64 {
+        let u = self.f64().max(f64::MIN_POSITIVE);
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * self.f64()).cos()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::log_item::LogItem;
+use parking_lot::Mutex;
+use serde_json::json;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often the client's health is logged.
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// The client's own health, logged to its run as `trackio/*` metrics once a minute; see
+/// [`Client::with_telemetry`](crate::Client::with_telemetry).
+#[derive(Debug)]
+pub(crate) struct Telemetry {
+    since: Mutex<Instant>,
+    last_step: AtomicI64,
+    flushes: AtomicU64,
+    flush_micros: AtomicU64,
+    max_flush_micros: AtomicU64,
+    failed_flushes: AtomicU64,
+    retries: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self {
+            since: Mutex::new(Instant::now()),
+            last_step: AtomicI64::new(i64::MIN),
+            flushes: AtomicU64::new(0),
+            flush_micros: AtomicU64::new(0),
+            max_flush_micros: AtomicU64::new(0),
+            failed_flushes: AtomicU64::new(0),
+            retries: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Telemetry {
+    /// Notes the step of a logged row, where the next report goes.
+    pub(crate) fn observe_step(&self, step: Option<i64>) {
+        if let Some(step) = step {
+            self.last_step.fetch_max(step, Ordering::Relaxed);
+        }
+    }
+
+    /// Records a flush that took `took`, failed or not.
+    pub(crate) fn flushed(&self, took: Duration, ok: bool) {
+        let micros = took.as_micros() as u64;
+        self.flushes.fetch_add(1, Ordering::Relaxed);
+        self.flush_micros.fetch_add(micros, Ordering::Relaxed);
+        self.max_flush_micros.fetch_max(micros, Ordering::Relaxed);
+        if !ok {
+            self.failed_flushes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn retried(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dropped(&self, rows: usize) {
+        self.dropped.fetch_add(rows as u64, Ordering::Relaxed);
+    }
+
+    /// The report row once a minute has passed since the last, with the counts of that
+    /// minute and the `queue_depth` rows waiting now; `None` before then.
+    pub(crate) fn report(&self, queue_depth: usize) -> Option<LogItem> {
+        {
+            let mut since = self.since.lock();
+            if since.elapsed() < INTERVAL {
+                return None;
+            }
+            *since = Instant::now();
+        }
+        let flushes = self.flushes.swap(0, Ordering::Relaxed);
+        let total_micros = self.flush_micros.swap(0, Ordering::Relaxed);
+        let max_micros = self.max_flush_micros.swap(0, Ordering::Relaxed);
+        let step = self.last_step.load(Ordering::Relaxed);
+        Some(LogItem {
+            metrics: json!({
+                "trackio/queue_depth": queue_depth,
+                "trackio/flushes": flushes,
+                "trackio/failed_flushes": self.failed_flushes.swap(0, Ordering::Relaxed),
+                "trackio/flush_latency_ms": millis(total_micros.checked_div(flushes).unwrap_or(0)),
+                "trackio/max_flush_latency_ms": millis(max_micros),
+                "trackio/retries": self.retries.swap(0, Ordering::Relaxed),
+                "trackio/dropped": self.dropped.swap(0, Ordering::Relaxed),
+            }),
+            step: (step != i64::MIN).then_some(step),
+            timestamp: None,
+        })
+    }
+}
+
+fn millis(micros: u64) -> f64 {
+    micros as f64 / 1000.0
+}
@@ -0,0 +1,203 @@
+use crate::alerts::{Above, Below, Condition, IsNaN, Trigger};
+use crate::client::{Client, TrackioError};
+use crate::schema::{MetricSchema, MetricType};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+
+/// A preset that runs across a team start from: project, naming, tags, config keys,
+/// metric types and alert rules, shared as a TOML file and applied when the client is
+/// set up.
+///
+/// ```toml
+/// project = "vision"
+/// run_prefix = "rn50-"          # run names get this prefix
+/// group = "resnet"
+/// tags = ["vision", "baseline"]
+///
+/// [config]                      # defaults under the run's own config
+/// optimizer = "adamw"
+/// precision = "bf16"
+///
+/// [metrics]                     # float, int, string or bool; names or globs
+/// "train/*" = "float"
+/// epoch = "int"
+///
+/// [[alerts]]
+/// metric = "train/loss"
+/// condition = "nan"             # nan, above or below
+///
+/// [[alerts]]
+/// metric = "val/acc"
+/// condition = "below"
+/// value = 0.1
+/// for_steps = 500
+/// ```
+///
+/// ```no_run
+/// use trackio::RunTemplate;
+///
+/// let template = RunTemplate::from_file("templates/vision.toml")?;
+/// let client = trackio::Client::new().with_run("lr-3e-4").with_template(&template);
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunTemplate {
+    pub project: Option<String>,
+    /// Put in front of run names that don't already start with it.
+    pub run_prefix: Option<String>,
+    pub group: Option<String>,
+    pub tags: Vec<String>,
+    /// Config keys the run's config starts from; see
+    /// [`Client::with_default_config`].
+    pub config: Map<String, Value>,
+    /// Metric types, by name or glob, checked as by [`MetricSchema`].
+    pub metrics: Vec<(String, MetricType)>,
+    /// Alert rules, as set by [`Client::alert_if`].
+    pub alerts: Vec<(String, Trigger)>,
+}
+
+impl RunTemplate {
+    /// Parse a template from TOML, in the format shown above.
+    pub fn from_toml(text: &str) -> Result<Self, TrackioError> {
+        Self::parse(text).map_err(TrackioError::Config)
+    }
+
+    /// Read a template from the TOML file at `path`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, TrackioError> {
+        let path = path.as_ref();
+        fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| Self::parse(&text))
+            .map_err(|e| TrackioError::Config(format!("{}: {e}", path.display())))
+    }
+
+    /// Metric types are declared with exact names first, then globs from the longest,
+    /// so `epoch = "int"` wins over `"*" = "float"` whatever the order in the file.
+    fn parse(text: &str) -> Result<Self, String> {
+        let file: TemplateFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut metrics = file
+            .metrics
+            .into_iter()
+            .map(|(name, ty)| Ok((name, metric_type(&ty)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        metrics.sort_by_key(|(name, _)| (name.contains('*'), Reverse(name.len())));
+        let alerts = file
+            .alerts
+            .into_iter()
+            .map(|rule| Ok((rule.metric.clone(), rule.trigger()?)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            project: file.project,
+            run_prefix: file.run_prefix,
+            group: file.group,
+            tags: file.tags,
+            config: file.config,
+            metrics,
+            alerts,
+        })
+    }
+
+    fn schema(&self) -> Option<MetricSchema> {
+        if self.metrics.is_empty() {
+            return None;
+        }
+        Some(
+            self.metrics
+                .iter()
+                .fold(MetricSchema::new(), |schema, (name, ty)| schema.metric(name, *ty)),
+        )
+    }
+}
+
+impl Client {
+    /// Set the client up from `template`: its project, if any, the prefix on the run
+    /// name, group, tags, default config keys, metric types and alert rules. Settings
+    /// made on the client afterwards take precedence. A schema set before is replaced
+    /// when the template declares metric types.
+    pub fn with_template(mut self, template: &RunTemplate) -> Self {
+        if let Some(project) = &template.project {
+            self = self.with_project(project);
+        }
+        if let Some(prefix) = &template.run_prefix {
+            if !self.run().starts_with(prefix.as_str()) {
+                let run = format!("{prefix}{}", self.run());
+                self = self.with_run(&run);
+            }
+        }
+        if let Some(group) = &template.group {
+            self = self.with_group(group);
+        }
+        if !template.tags.is_empty() {
+            let tags: Vec<&str> = template.tags.iter().map(String::as_str).collect();
+            self = self.with_tags(&tags);
+        }
+        if let Some(schema) = template.schema() {
+            self = self.with_schema(schema);
+        }
+        for (metric, trigger) in &template.alerts {
+            self = self.alert_if(metric, *trigger);
+        }
+        self.with_default_config(template.config.clone())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateFile {
+    project: Option<String>,
+    run_prefix: Option<String>,
+    group: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    config: Map<String, Value>,
+    #[serde(default)]
+    metrics: Map<String, Value>,
+    #[serde(default)]
+    alerts: Vec<AlertRule>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AlertRule {
+    metric: String,
+    condition: String,
+    value: Option<f64>,
+    #[serde(default)]
+    for_steps: i64,
+}
+
+impl AlertRule {
+    fn trigger(&self) -> Result<Trigger, String> {
+        let value = || {
+            self.value.ok_or_else(|| {
+                format!("alert on {:?}: {:?} needs a value", self.metric, self.condition)
+            })
+        };
+        let condition: Condition = match self.condition.to_ascii_lowercase().as_str() {
+            "nan" => IsNaN,
+            "above" => Above(value()?),
+            "below" => Below(value()?),
+            other => {
+                return Err(format!(
+                    "alert on {:?}: unknown condition {other:?}",
+                    self.metric
+                ))
+            }
+        };
+        Ok(condition.for_steps(self.for_steps))
+    }
+}
+
+fn metric_type(ty: &Value) -> Result<MetricType, String> {
+    match ty.as_str().map(str::to_ascii_lowercase).as_deref() {
+        Some("float") => Ok(MetricType::Float),
+        Some("int") => Ok(MetricType::Int),
+        Some("string") => Ok(MetricType::String),
+        Some("bool") => Ok(MetricType::Bool),
+        _ => Err(format!("unknown metric type {ty}")),
+    }
+}
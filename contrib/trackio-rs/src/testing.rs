@@ -0,0 +1,609 @@
+use crate::client::{Client, TrackioError};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    /// The JSON body, or `Null` for requests without one.
+    pub body: Value,
+    /// The status the mock answered with.
+    pub status: u16,
+}
+
+/// One row of a bulk log received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedRow {
+    pub project: String,
+    pub run: String,
+    /// The row's step; rows logged without one (sent as `-1`) have `None`.
+    pub step: Option<i64>,
+    pub timestamp: Option<String>,
+    pub metrics: Map<String, Value>,
+}
+
+#[derive(Debug, Default)]
+struct Mock {
+    requests: Vec<RecordedRequest>,
+    failures: VecDeque<(u16, String)>,
+    responses: HashMap<String, Value>,
+    missing: Vec<String>,
+}
+
+/// An in-process stand-in for a Trackio server that records what clients send, so
+/// code that logs to Trackio can be unit-tested without a real dashboard.
+///
+/// It speaks just enough HTTP for [`Client`]: bulk logging on either endpoint flavor,
+/// `/version`, and every `/api/<name>` call, which answers `{"data": null}` unless a
+/// response was set with [`respond`](MockServer::respond).
+///
+/// ```no_run
+/// use trackio::testing::MockServer;
+///
+/// let server = MockServer::start();
+/// let client = server.client().with_project("p").with_run("r");
+/// client.log(serde_json::json!({ "loss": 0.5 }), Some(3), None);
+/// client.flush().unwrap();
+/// server.assert_logged("loss", 3);
+/// server.assert_logged_value("loss", 3, 0.5);
+/// ```
+#[derive(Debug)]
+pub struct MockServer {
+    listener: Listener,
+    state: Arc<Mutex<Mock>>,
+}
+
+impl MockServer {
+    /// Listen on a free localhost port.
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(Mock::default()));
+        let shared = state.clone();
+        let listener = Listener::spawn(Arc::new(move |request| handle(request, &shared)));
+        Self { listener, state }
+    }
+
+    /// Base URL of the server, e.g. `http://127.0.0.1:53211`.
+    pub fn url(&self) -> String {
+        self.listener.url()
+    }
+
+    /// A client pointed at this server. Project and run still come from the environment.
+    pub fn client(&self) -> Client {
+        Client::new().with_base_url(&self.url())
+    }
+
+    /// Answer 404 on `path`, e.g. `/api/bulk_log` to emulate a server that only has the
+    /// `/gradio_api` flavor.
+    pub fn without_path(self, path: &str) -> Self {
+        self.state.lock().missing.push(path.into());
+        self
+    }
+
+    /// Answer the next `n` requests with `status` instead of handling them, to exercise
+    /// retries and error handling.
+    pub fn fail_next(&self, n: usize, status: u16) {
+        let mut state = self.state.lock();
+        for _ in 0..n {
+            state
+                .failures
+                .push_back((status, format!("mock failure {status}")));
+        }
+    }
+
+    /// Answer `/api/<name>` with `{"data": data}`.
+    pub fn respond(&self, name: &str, data: Value) {
+        self.state.lock().responses.insert(name.into(), data);
+    }
+
+    /// Every request received so far, failed ones included.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().requests.clone()
+    }
+
+    /// Bodies of the bulk logs accepted so far.
+    pub fn payloads(&self) -> Vec<Value> {
+        self.state
+            .lock()
+            .requests
+            .iter()
+            .filter(|r| r.status == 200 && is_bulk(&r.path))
+            .map(|r| r.body.clone())
+            .collect()
+    }
+
+    /// Every logged row accepted so far, in order.
+    pub fn rows(&self) -> Vec<LoggedRow> {
+        self.payloads().iter().flat_map(rows_of).collect()
+    }
+
+    /// The last config received for `run`.
+    pub fn config(&self, run: &str) -> Option<Value> {
+        self.payloads()
+            .into_iter()
+            .rev()
+            .filter(|p| p.get("run").and_then(Value::as_str) == Some(run))
+            .find_map(|mut p| p.get_mut("config").map(Value::take))
+            .filter(|c| !c.is_null())
+    }
+
+    /// Titles of the alerts accepted so far.
+    pub fn alerts(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .filter(|r| r.status == 200 && r.path.ends_with("/bulk_alert"))
+            .flat_map(|r| r.body["alerts"].as_array().cloned().unwrap_or_default())
+            .filter_map(|a| a["title"].as_str().map(String::from))
+            .collect()
+    }
+
+    /// Names of the events accepted so far.
+    pub fn events(&self) -> Vec<String> {
+        self.requests()
+            .iter()
+            .filter(|r| r.status == 200 && r.path.ends_with("/log_events"))
+            .flat_map(|r| r.body["events"].as_array().cloned().unwrap_or_default())
+            .filter_map(|e| e["name"].as_str().map(String::from))
+            .collect()
+    }
+
+    /// Panics unless `metric` was logged at `step`.
+    #[track_caller]
+    pub fn assert_logged(&self, metric: &str, step: i64) {
+        if self.value_at(metric, step).is_none() {
+            panic!(
+                "expected {metric:?} to be logged at step {step}; logged rows:\n{}",
+                self.describe_rows()
+            );
+        }
+    }
+
+    /// Panics unless `metric` was logged at `step` with `value`.
+    #[track_caller]
+    pub fn assert_logged_value(&self, metric: &str, step: i64, value: impl Into<Value>) {
+        let value = value.into();
+        let found = self.value_at(metric, step);
+        let matches = match (&found, value.as_f64()) {
+            (Some(found), Some(expected)) => found.as_f64() == Some(expected),
+            (Some(found), None) => *found == value,
+            (None, _) => false,
+        };
+        if !matches {
+            panic!(
+                "expected {metric:?} = {value} at step {step}, found {}; logged rows:\n{}",
+                found.map_or("nothing".into(), |v| v.to_string()),
+                self.describe_rows()
+            );
+        }
+    }
+
+    /// Panics if `metric` was logged at any step.
+    #[track_caller]
+    pub fn assert_not_logged(&self, metric: &str) {
+        if let Some(row) = self.rows().iter().find(|r| r.metrics.contains_key(metric)) {
+            panic!(
+                "expected {metric:?} not to be logged, but it was at {:?}",
+                row.step
+            );
+        }
+    }
+
+    /// Panics unless an alert with `title` was received.
+    #[track_caller]
+    pub fn assert_alerted(&self, title: &str) {
+        let alerts = self.alerts();
+        if !alerts.iter().any(|a| a == title) {
+            panic!("expected an alert {title:?}; received {alerts:?}");
+        }
+    }
+
+    /// Forget every request received so far.
+    pub fn reset(&self) {
+        self.state.lock().requests.clear();
+    }
+
+    fn value_at(&self, metric: &str, step: i64) -> Option<Value> {
+        self.rows()
+            .into_iter()
+            .rev()
+            .filter(|r| r.step == Some(step))
+            .find_map(|mut r| r.metrics.remove(metric))
+    }
+
+    fn describe_rows(&self) -> String {
+        let rows = self.rows();
+        if rows.is_empty() {
+            return "  (none)".into();
+        }
+        rows.iter()
+            .map(|r| {
+                let step = r.step.map_or("-".into(), |s| s.to_string());
+                format!(
+                    "  {}/{} step {step}: {}",
+                    r.project,
+                    r.run,
+                    json!(r.metrics)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+type Handler = Arc<dyn Fn(RecordedRequest) -> (u16, String) + Send + Sync>;
+
+/// A localhost HTTP/1.1 listener answering every request with a handler, stopped on drop.
+#[derive(Debug)]
+struct Listener {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+}
+
+impl Listener {
+    fn spawn(handler: Handler) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let addr = listener.local_addr().expect("mock server address");
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Relaxed) {
+                    return;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let handler = handler.clone();
+                thread::spawn(move || serve(stream, &*handler));
+            }
+        });
+        Self { addr, stop }
+    }
+
+    fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+fn is_bulk(path: &str) -> bool {
+    path == "/api/bulk_log" || path == "/gradio_api/bulk_log"
+}
+
+fn rows_of(payload: &Value) -> Vec<LoggedRow> {
+    let text = |v: &Value| v.as_str().unwrap_or_default().to_string();
+    let list = |key: &str| payload[key].as_array().cloned().unwrap_or_default();
+    let (steps, timestamps) = (list("steps"), list("timestamps"));
+    list("metrics_list")
+        .into_iter()
+        .enumerate()
+        .map(|(i, metrics)| LoggedRow {
+            project: text(&payload["project"]),
+            run: text(&payload["run"]),
+            step: steps.get(i).and_then(Value::as_i64).filter(|s| *s >= 0),
+            timestamp: timestamps
+                .get(i)
+                .and_then(Value::as_str)
+                .filter(|t| !t.is_empty())
+                .map(String::from),
+            metrics: match metrics {
+                Value::Object(map) => map,
+                _ => Map::new(),
+            },
+        })
+        .collect()
+}
+
+fn serve(stream: TcpStream, handler: &(dyn Fn(RecordedRequest) -> (u16, String) + Send + Sync)) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    while let Some(request) = read_request(&mut reader) {
+        let (status, body) = handler(request);
+        let reason = match status {
+            200 => "OK",
+            404 => "Not Found",
+            _ => "Error",
+        };
+        let response = format!(
+            "HTTP/1.1 {status} {reason}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        if writer.write_all(response.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn handle(mut request: RecordedRequest, state: &Mutex<Mock>) -> (u16, String) {
+    let mut state = state.lock();
+    let (status, body) = respond(&request.path, &mut state);
+    request.status = status;
+    state.requests.push(request);
+    (status, body)
+}
+
+fn respond(path: &str, state: &mut Mock) -> (u16, String) {
+    if let Some((status, message)) = state.failures.pop_front() {
+        return (status, json!({ "error": message }).to_string());
+    }
+    if state.missing.iter().any(|m| m == path) {
+        return (404, json!({ "detail": "Not Found" }).to_string());
+    }
+    if path == "/version" {
+        return (200, json!({ "version": "mock" }).to_string());
+    }
+    let name = path
+        .strip_prefix("/api/")
+        .or_else(|| path.strip_prefix("/gradio_api/"));
+    match name {
+        Some(name) => {
+            let data = state.responses.get(name).cloned().unwrap_or(Value::Null);
+            (200, json!({ "data": data }).to_string())
+        }
+        None => (404, json!({ "detail": "Not Found" }).to_string()),
+    }
+}
+
+fn read_request(reader: &mut BufReader<TcpStream>) -> Option<RecordedRequest> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).ok()? == 0 {
+        return None;
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let path = parts.next()?.split('?').next()?.to_string();
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+    let len = headers
+        .get("content-length")
+        .and_then(|l| l.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; len];
+    reader.read_exact(&mut body).ok()?;
+    Some(RecordedRequest {
+        method,
+        path,
+        headers,
+        body: serde_json::from_slice(&body).unwrap_or(Value::Null),
+        status: 0,
+    })
+}
+
+/// One request and the response it got, as stored in a cassette file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    pub method: String,
+    pub path: String,
+    pub request: Value,
+    pub status: u16,
+    pub response: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Tape {
+    interactions: Vec<Interaction>,
+    #[serde(skip)]
+    played: usize,
+    #[serde(skip)]
+    mismatches: Vec<String>,
+}
+
+/// A VCR-style stand-in for a Trackio server: in record mode it forwards every request
+/// to a real server and stores the exchange in a JSON cassette file; in replay mode it
+/// answers from the cassette, in order, and checks that the client sends the same
+/// requests again.
+///
+/// This lets tests of endpoint-flavor detection, retries and error handling run
+/// hermetically in CI against responses captured once from a real server. Request
+/// fields that change from run to run (`timestamps`, `timestamp`, `alert_id` by default)
+/// are ignored when comparing, and the write token is never stored.
+///
+/// ```no_run
+/// use trackio::testing::Cassette;
+///
+/// let cassette = if std::env::var("RECORD").is_ok() {
+///     Cassette::record("tests/cassettes/flush.json", "http://127.0.0.1:7860")
+/// } else {
+///     Cassette::replay("tests/cassettes/flush.json")?
+/// };
+/// let client = cassette.client().with_project("p").with_run("r");
+/// client.log(serde_json::json!({ "loss": 0.5 }), Some(1), None);
+/// client.flush()?;
+/// cassette.finish()?;
+/// # Ok::<(), trackio::client::TrackioError>(())
+/// ```
+#[derive(Debug)]
+pub struct Cassette {
+    listener: Listener,
+    path: PathBuf,
+    recording: bool,
+    tape: Arc<Mutex<Tape>>,
+}
+
+impl Cassette {
+    /// Forward requests to `upstream` and record them to `path` on [`finish`](Cassette::finish).
+    pub fn record<P: Into<PathBuf>>(path: P, upstream: &str) -> Self {
+        let tape = Arc::new(Mutex::new(Tape::default()));
+        let (shared, upstream) = (tape.clone(), upstream.trim_end_matches('/').to_string());
+        let http = reqwest::blocking::Client::new();
+        let listener = Listener::spawn(Arc::new(move |request| {
+            let (status, response) = forward(&http, &upstream, &request);
+            shared.lock().interactions.push(Interaction {
+                method: request.method,
+                path: request.path,
+                request: request.body,
+                status,
+                response: response.clone(),
+            });
+            (status, response)
+        }));
+        Self {
+            listener,
+            path: path.into(),
+            recording: true,
+            tape,
+        }
+    }
+
+    /// Answer from the cassette at `path`, recorded earlier with [`record`](Cassette::record).
+    pub fn replay<P: Into<PathBuf>>(path: P) -> Result<Self, TrackioError> {
+        Self::replay_ignoring(path, &["timestamps", "timestamp", "alert_id"])
+    }
+
+    /// Like [`replay`](Cassette::replay), with the request fields to ignore when comparing.
+    pub fn replay_ignoring<P: Into<PathBuf>>(
+        path: P,
+        ignored: &[&str],
+    ) -> Result<Self, TrackioError> {
+        let path = path.into();
+        let tape: Tape = serde_json::from_slice(&fs::read(&path)?)
+            .map_err(|e| TrackioError::Cassette(format!("{}: {e}", path.display())))?;
+        let tape = Arc::new(Mutex::new(tape));
+        let shared = tape.clone();
+        let ignored: Vec<String> = ignored.iter().map(|f| f.to_string()).collect();
+        let listener = Listener::spawn(Arc::new(move |request| {
+            play(&mut shared.lock(), request, &ignored)
+        }));
+        Ok(Self {
+            listener,
+            path,
+            recording: false,
+            tape,
+        })
+    }
+
+    /// Base URL to point clients at.
+    pub fn url(&self) -> String {
+        self.listener.url()
+    }
+
+    /// A client pointed at this cassette. Project and run still come from the environment.
+    pub fn client(&self) -> Client {
+        Client::new().with_base_url(&self.url())
+    }
+
+    /// The interactions recorded or loaded so far.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.tape.lock().interactions.clone()
+    }
+
+    /// When recording, write the cassette file. When replaying, fail if a request did not
+    /// match the cassette or if recorded interactions were never requested.
+    pub fn finish(&self) -> Result<(), TrackioError> {
+        let tape = self.tape.lock();
+        if self.recording {
+            if let Some(dir) = self.path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let json = serde_json::to_vec_pretty(&*tape)
+                .map_err(|e| TrackioError::Cassette(e.to_string()))?;
+            fs::write(&self.path, json)?;
+            return Ok(());
+        }
+        let mut problems = tape.mismatches.clone();
+        for unplayed in &tape.interactions[tape.played.min(tape.interactions.len())..] {
+            problems.push(format!(
+                "not requested: {} {}",
+                unplayed.method, unplayed.path
+            ));
+        }
+        if problems.is_empty() {
+            return Ok(());
+        }
+        Err(TrackioError::Cassette(format!(
+            "{} did not replay cleanly:\n  {}",
+            self.path.display(),
+            problems.join("\n  ")
+        )))
+    }
+}
+
+fn forward(
+    http: &reqwest::blocking::Client,
+    upstream: &str,
+    request: &RecordedRequest,
+) -> (u16, String) {
+    let url = format!("{upstream}{}", request.path);
+    let mut req = match request.method.as_str() {
+        "GET" => http.get(url),
+        _ => http.post(url).json(&request.body),
+    };
+    if let Some(token) = request.headers.get("x-trackio-write-token") {
+        req = req.header("X-Trackio-Write-Token", token);
+    }
+    match req.send() {
+        Ok(resp) => (resp.status().as_u16(), resp.text().unwrap_or_default()),
+        Err(e) => (502, json!({ "error": e.to_string() }).to_string()),
+    }
+}
+
+fn play(tape: &mut Tape, request: RecordedRequest, ignored: &[String]) -> (u16, String) {
+    let Some(expected) = tape.interactions.get(tape.played).cloned() else {
+        let problem = format!("unexpected request: {} {}", request.method, request.path);
+        tape.mismatches.push(problem.clone());
+        return (500, json!({ "error": problem }).to_string());
+    };
+    tape.played += 1;
+    let same = expected.method == request.method
+        && expected.path == request.path
+        && without(&expected.request, ignored) == without(&request.body, ignored);
+    if !same {
+        let problem = format!(
+            "request {}: expected {} {} {}, got {} {} {}",
+            tape.played,
+            expected.method,
+            expected.path,
+            without(&expected.request, ignored),
+            request.method,
+            request.path,
+            without(&request.body, ignored)
+        );
+        tape.mismatches.push(problem.clone());
+        return (500, json!({ "error": problem }).to_string());
+    }
+    (expected.status, expected.response)
+}
+
+fn without(value: &Value, ignored: &[String]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .filter(|(k, _)| !ignored.contains(k))
+                .map(|(k, v)| (k.clone(), without(v, ignored)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(|v| without(v, ignored)).collect()),
+        other => other.clone(),
+    }
+}
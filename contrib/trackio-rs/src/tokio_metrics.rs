@@ -0,0 +1,115 @@
+use crate::client::{Client, LogItem};
+use serde_json::{Map, Number, Value};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, RuntimeMetrics};
+
+impl Client {
+    /// Sample the metrics of the tokio runtime behind `handle` from a background thread
+    /// every `interval`, and log them under `system/tokio/*` at the latest step, for
+    /// async data pipelines whose stalls otherwise only show as step-time spikes:
+    ///
+    /// | key | value |
+    /// |-----|-------|
+    /// | `system/tokio/workers` | worker threads |
+    /// | `system/tokio/alive_tasks` | tasks spawned and not finished |
+    /// | `system/tokio/global_queue_depth` | tasks waiting in the shared queue |
+    /// | `system/tokio/busy_ratio` | share of the interval the workers spent running tasks |
+    /// | `system/tokio/max_worker_busy_ratio` | the same for the busiest worker |
+    /// | `system/tokio/parks` | times workers went idle in the interval |
+    ///
+    /// Sampling runs on its own thread, so a blocked runtime is still reported, and stops
+    /// when the client is dropped. Tokio adds to a worker's busy time when it parks, so a
+    /// long poll shows as a busy interval at its end, capped at 1. The rows bypass hooks,
+    /// filters and the schema.
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// # #[tokio::main] async fn main() {
+    /// let client = Arc::new(trackio::Client::new().with_project("p").with_run("r"));
+    /// client.spawn_tokio_metrics(&tokio::runtime::Handle::current(), Duration::from_secs(10));
+    /// client.spawn_flusher();
+    /// # }
+    /// ```
+    pub fn spawn_tokio_metrics(self: &Arc<Self>, handle: &Handle, interval: Duration) {
+        let client = Arc::downgrade(self);
+        let metrics = handle.metrics();
+        thread::spawn(move || {
+            let mut last = Sample::take(&metrics);
+            loop {
+                thread::sleep(interval);
+                let Some(client) = client.upgrade() else {
+                    return;
+                };
+                let sample = Sample::take(&metrics);
+                client.buffer_report(LogItem {
+                    metrics: Value::Object(sample.row(&metrics, &last)),
+                    step: client.last_step(),
+                    timestamp: Some(client.timestamp()),
+                });
+                last = sample;
+            }
+        });
+    }
+}
+
+/// The cumulative counters of the workers at one point in time.
+struct Sample {
+    at: Instant,
+    busy: Vec<Duration>,
+    parks: u64,
+}
+
+impl Sample {
+    fn take(metrics: &RuntimeMetrics) -> Self {
+        let workers = 0..metrics.num_workers();
+        Self {
+            at: Instant::now(),
+            busy: workers
+                .clone()
+                .map(|w| metrics.worker_total_busy_duration(w))
+                .collect(),
+            parks: workers.map(|w| metrics.worker_park_count(w)).sum(),
+        }
+    }
+
+    /// The row for the interval since `last`.
+    fn row(&self, metrics: &RuntimeMetrics, last: &Sample) -> Map<String, Value> {
+        let elapsed = self.at.duration_since(last.at).as_secs_f64();
+        let ratios: Vec<f64> = self
+            .busy
+            .iter()
+            .zip(&last.busy)
+            .map(|(now, then)| (now.saturating_sub(*then).as_secs_f64() / elapsed).min(1.0))
+            .collect();
+        let mut row = Map::new();
+        row.insert("system/tokio/workers".into(), metrics.num_workers().into());
+        row.insert("system/tokio/alive_tasks".into(), metrics.num_alive_tasks().into());
+        row.insert(
+            "system/tokio/global_queue_depth".into(),
+            metrics.global_queue_depth().into(),
+        );
+        if !ratios.is_empty() && elapsed > 0.0 {
+            row.insert(
+                "system/tokio/busy_ratio".into(),
+                float(ratios.iter().sum::<f64>() / ratios.len() as f64),
+            );
+            row.insert(
+                "system/tokio/max_worker_busy_ratio".into(),
+                float(ratios.iter().copied().fold(0.0, f64::max)),
+            );
+        }
+        row.insert(
+            "system/tokio/parks".into(),
+            self.parks.saturating_sub(last.parks).into(),
+        );
+        row
+    }
+}
+
+fn float(f: f64) -> Value {
+    Number::from_f64(f).map_or(Value::Null, Value::Number)
+}
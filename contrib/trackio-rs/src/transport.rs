@@ -0,0 +1,246 @@
+use crate::error::TrackioError;
+use crate::runtime::BoxFuture;
+use std::fmt::Debug;
+#[cfg(not(target_arch = "wasm32"))]
+use std::net::SocketAddr;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+
+/// HTTP method of a [`Request`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// One request from the client: the body is already serialized and, if configured,
+/// compressed, with `headers` saying so.
+#[derive(Debug)]
+pub struct Request<'a> {
+    pub method: Method,
+    pub url: &'a str,
+    pub headers: Vec<(&'a str, &'a str)>,
+    pub body: Vec<u8>,
+}
+
+/// A successful response.
+#[derive(Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// How the client reaches a Trackio server or webhook.
+///
+/// With the default `http` feature the client uses [`HttpTransport`], built on
+/// `reqwest`. Without it the crate has no network or TLS dependencies, and a transport
+/// wrapping whatever HTTP stack the build allows can be installed with
+/// [`Client::with_transport`](crate::Client::with_transport). Retries, endpoint
+/// discovery, compression and payload dumps stay in the client.
+pub trait Transport: Debug + Send + Sync {
+    /// Send `request`. Failure statuses are errors, classified with
+    /// [`TrackioError::from_status`] so the client knows which to retry.
+    fn send(&self, request: Request<'_>) -> Result<Response, TrackioError>;
+
+    /// Called in a process forked from the one that created the transport, before it
+    /// is used again. Does nothing by default.
+    fn after_fork(&self) {}
+}
+
+/// [`Transport`] for async code, used by [`WebClient`](crate::web::WebClient).
+///
+/// The default, [`ReqwestTransport`], runs in the browser and on tokio. Natively,
+/// reqwest needs a tokio reactor, so under smol or async-std install one over an HTTP
+/// client for that runtime instead.
+pub trait AsyncTransport: Debug + Send + Sync {
+    /// Send `request`, classifying failure statuses as [`Transport::send`] does.
+    fn send<'a>(&'a self, request: Request<'a>) -> BoxFuture<'a, Result<Response, TrackioError>>;
+}
+
+/// `trackio-rs/<version> (rustc <version>; <os> <arch>)`, followed by `app` if given,
+/// e.g. `trackio-rs/0.1.0 (rustc 1.86.0; linux x86_64) my-trainer/2.3`.
+pub fn user_agent(app: Option<&str>) -> String {
+    let mut agent = format!(
+        "trackio-rs/{} (rustc {}; {} {})",
+        env!("CARGO_PKG_VERSION"),
+        env!("TRACKIO_RUSTC_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+    );
+    if let Some(app) = app.filter(|app| !app.is_empty()) {
+        agent.push(' ');
+        agent.push_str(app);
+    }
+    agent
+}
+
+/// The transport a client gets unless another is set: [`HttpTransport`] with the
+/// `http` feature, none without.
+/// `resolve` pins host names to addresses, as in
+/// [`HttpTransport::with_resolve`].
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn default_transport(
+    timeout: Duration,
+    resolve: &[(String, SocketAddr)],
+) -> Option<Arc<dyn Transport>> {
+    #[cfg(feature = "http")]
+    return Some(Arc::new(
+        resolve
+            .iter()
+            .fold(HttpTransport::new(timeout), |transport, (host, addr)| {
+                transport.with_resolve(host, *addr)
+            }),
+    ));
+    #[cfg(not(feature = "http"))]
+    {
+        let _ = (timeout, resolve);
+        None
+    }
+}
+
+/// The async transport a [`WebClient`](crate::web::WebClient) gets unless another is
+/// set: [`ReqwestTransport`] with the `http` feature, none without.
+pub(crate) fn default_async_transport() -> Option<Arc<dyn AsyncTransport>> {
+    #[cfg(feature = "http")]
+    return Some(Arc::new(ReqwestTransport::default()));
+    #[cfg(not(feature = "http"))]
+    None
+}
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+pub use self::http::HttpTransport;
+
+#[cfg(feature = "http")]
+pub use self::reqwest_async::ReqwestTransport;
+
+#[cfg(feature = "http")]
+mod reqwest_async {
+    use super::{AsyncTransport, Method, Request, Response};
+    use crate::error::{retry_after, TrackioError};
+    use crate::runtime::BoxFuture;
+
+    /// [`AsyncTransport`] over an async `reqwest` client: `fetch` in the browser, tokio
+    /// natively.
+    #[derive(Debug, Default)]
+    pub struct ReqwestTransport {
+        http: reqwest::Client,
+    }
+
+    impl ReqwestTransport {
+        /// Send through `http`, e.g. one with a timeout or a proxy.
+        pub fn new(http: reqwest::Client) -> Self {
+            Self { http }
+        }
+    }
+
+    impl AsyncTransport for ReqwestTransport {
+        fn send<'a>(
+            &'a self,
+            request: Request<'a>,
+        ) -> BoxFuture<'a, Result<Response, TrackioError>> {
+            Box::pin(async move {
+                let mut req = match request.method {
+                    Method::Get => self.http.get(request.url),
+                    Method::Post => self.http.post(request.url).body(request.body),
+                };
+                for (name, value) in request.headers {
+                    req = req.header(name, value);
+                }
+                let resp = req.send().await?;
+                let status = resp.status().as_u16();
+                if !resp.status().is_success() {
+                    let retry_after = retry_after(resp.headers());
+                    let body = resp.text().await.unwrap_or_default();
+                    return Err(TrackioError::from_status(status, retry_after, body));
+                }
+                Ok(Response {
+                    status,
+                    body: resp.bytes().await?.to_vec(),
+                })
+            })
+        }
+    }
+}
+
+#[cfg(all(feature = "http", not(target_arch = "wasm32")))]
+mod http {
+    use super::{Method, Request, Response, Transport};
+    use crate::error::TrackioError;
+    use parking_lot::Mutex;
+    use reqwest::blocking::Client as Http;
+    use std::net::SocketAddr;
+    use std::time::Duration;
+
+    /// [`Transport`] over a blocking `reqwest` client.
+    #[derive(Debug)]
+    pub struct HttpTransport {
+        timeout: Duration,
+        resolve: Vec<(String, SocketAddr)>,
+        http: Mutex<Http>,
+    }
+
+    impl HttpTransport {
+        /// Requests time out after `timeout`.
+        pub fn new(timeout: Duration) -> Self {
+            Self {
+                timeout,
+                resolve: Vec::new(),
+                http: Mutex::new(build_http(timeout, &[])),
+            }
+        }
+
+        /// Connect to `addr`, an IPv4 or IPv6 address, for URLs on `host` instead of
+        /// resolving it through DNS. A port in the URL takes precedence over the one of
+        /// `addr`, and port 0 stands for the default port of the scheme.
+        pub fn with_resolve(mut self, host: &str, addr: SocketAddr) -> Self {
+            self.resolve.push((host.into(), addr));
+            self.http = Mutex::new(build_http(self.timeout, &self.resolve));
+            self
+        }
+    }
+
+    impl Transport for HttpTransport {
+        fn send(&self, request: Request<'_>) -> Result<Response, TrackioError> {
+            let http = self.http.lock().clone();
+            let mut req = match request.method {
+                Method::Get => http.get(request.url),
+                Method::Post => http.post(request.url).body(request.body),
+            };
+            for (name, value) in request.headers {
+                req = req.header(name, value);
+            }
+            let resp = req.send()?;
+            let status = resp.status();
+            if !status.is_success() {
+                return Err(TrackioError::from_response(resp));
+            }
+            Ok(Response {
+                status: status.as_u16(),
+                body: resp.bytes()?.to_vec(),
+            })
+        }
+
+        /// The connection pool's thread does not survive `fork()`, so the child gets a
+        /// new client. A lock held at the time of the fork belongs to a thread that
+        /// does not exist in the child, so it is released first.
+        fn after_fork(&self) {
+            unsafe {
+                if self.http.is_locked() {
+                    self.http.force_unlock();
+                }
+            }
+            *self.http.lock() = build_http(self.timeout, &self.resolve);
+        }
+    }
+
+    fn build_http(timeout: Duration, resolve: &[(String, SocketAddr)]) -> Http {
+        resolve
+            .iter()
+            .fold(Http::builder().timeout(timeout), |builder, (host, addr)| {
+                builder.resolve(host, *addr)
+            })
+            .build()
+            .expect("failed to build HTTP client")
+    }
+}
@@ -0,0 +1,295 @@
+use crate::client::{Client, TrackioError};
+use crate::query::HistoryQuery;
+use crate::report::format_number;
+use crate::runs::MetricPoint;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{Axis, Block, Cell, Chart, Dataset, GraphType, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const COLORS: [Color; 8] = [
+    Color::Yellow,
+    Color::Cyan,
+    Color::Magenta,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+    Color::LightYellow,
+    Color::LightCyan,
+];
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const SPARK_WIDTH: usize = 16;
+
+type Histories = Vec<BTreeMap<String, Vec<(f64, f64)>>>;
+
+/// A live terminal dashboard of one or more runs, created by [`Client::dashboard`].
+///
+/// Shows a table with the last value and a sparkline of every metric per run, and a
+/// chart of the selected metric across runs. For SSH-only machines where the Gradio UI
+/// can't be opened. `↑`/`↓` (or `k`/`j`) select a metric, `q` quits.
+pub struct Dashboard<'a> {
+    client: &'a Client,
+    project: String,
+    runs: Vec<String>,
+    query: HistoryQuery,
+    poll_interval: Duration,
+}
+
+impl Client {
+    /// A terminal dashboard of `runs` in `project`, refreshed every second.
+    pub fn dashboard(&self, project: &str, runs: &[&str]) -> Dashboard<'_> {
+        Dashboard {
+            client: self,
+            project: project.to_string(),
+            runs: runs.iter().map(|r| r.to_string()).collect(),
+            query: HistoryQuery::new().max_points(200),
+            poll_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+#[derive(Default)]
+struct State {
+    histories: Histories,
+    error: Option<String>,
+    updated: Option<Instant>,
+    table: TableState,
+}
+
+impl Dashboard<'_> {
+    /// Only show metrics matching one of these glob patterns.
+    pub fn with_metrics<I, S>(mut self, globs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.query = self.query.metrics(globs);
+        self
+    }
+
+    /// How often the runs are fetched again (default: 1s).
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Take over the terminal until the user quits.
+    pub fn run(self) -> Result<(), TrackioError> {
+        let (tx, rx) = mpsc::channel();
+        let stop = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| {
+                while !stop.load(Ordering::Relaxed) {
+                    if tx.send(self.fetch()).is_err() {
+                        return;
+                    }
+                    let deadline = Instant::now() + self.poll_interval;
+                    while Instant::now() < deadline && !stop.load(Ordering::Relaxed) {
+                        thread::sleep(Duration::from_millis(50));
+                    }
+                }
+            });
+            let mut terminal = ratatui::init();
+            let result = self.event_loop(&mut terminal, &rx);
+            ratatui::restore();
+            stop.store(true, Ordering::Relaxed);
+            result
+        })
+    }
+
+    fn fetch(&self) -> Result<Histories, TrackioError> {
+        self.runs
+            .iter()
+            .map(|run| {
+                let history = self.client.history(&self.project, run, &self.query)?;
+                Ok(history
+                    .into_iter()
+                    .map(|(metric, points)| (metric, numeric(&points)))
+                    .filter(|(_, points)| !points.is_empty())
+                    .collect())
+            })
+            .collect()
+    }
+
+    fn event_loop(
+        &self,
+        terminal: &mut DefaultTerminal,
+        updates: &Receiver<Result<Histories, TrackioError>>,
+    ) -> Result<(), TrackioError> {
+        let mut state = State::default();
+        state.table.select(Some(0));
+        loop {
+            while let Ok(update) = updates.try_recv() {
+                match update {
+                    Ok(histories) => {
+                        state.histories = histories;
+                        state.error = None;
+                        state.updated = Some(Instant::now());
+                    }
+                    Err(e) => state.error = Some(e.to_string()),
+                }
+            }
+            terminal.draw(|frame| self.draw(frame, &mut state))?;
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => state.table.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => state.table.select_previous(),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame, state: &mut State) {
+        let metrics: Vec<&String> = state
+            .histories
+            .iter()
+            .flat_map(|h| h.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if let Some(i) = state.table.selected() {
+            state
+                .table
+                .select(Some(i.min(metrics.len().saturating_sub(1))));
+        }
+        let [header, table_area, chart_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Percentage(45),
+            Constraint::Min(6),
+        ])
+        .areas(frame.area());
+
+        let status = match (&state.error, state.updated) {
+            (Some(e), _) => format!("error: {e}"),
+            (None, Some(at)) => format!("updated {:.0}s ago", at.elapsed().as_secs_f64()),
+            (None, None) => "loading…".into(),
+        };
+        frame.render_widget(
+            Line::from(format!(
+                " {} · {} · {status} · ↑↓ select, q quit",
+                self.project,
+                self.runs.join(", ")
+            ))
+            .style(Style::new().add_modifier(Modifier::REVERSED)),
+            header,
+        );
+
+        let rows = metrics.iter().map(|metric| {
+            let mut cells = vec![Cell::from(metric.as_str())];
+            for (i, history) in state.histories.iter().enumerate() {
+                let text = match history.get(*metric) {
+                    Some(points) => {
+                        let last = points.last().map_or(0.0, |p| p.1);
+                        format!("{:>10} {}", format_number(last), sparkline(points))
+                    }
+                    None => String::new(),
+                };
+                cells.push(Cell::from(text).style(Style::new().fg(COLORS[i % COLORS.len()])));
+            }
+            Row::new(cells)
+        });
+        let mut widths = vec![Constraint::Fill(1)];
+        widths.extend(self.runs.iter().map(|_| Constraint::Length(28)));
+        let mut head = vec![Cell::from("metric")];
+        head.extend(self.runs.iter().map(|r| Cell::from(r.as_str())));
+        let table = Table::new(rows, widths)
+            .header(Row::new(head).style(Style::new().add_modifier(Modifier::BOLD)))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .block(Block::bordered().title(" metrics "));
+        frame.render_stateful_widget(table, table_area, &mut state.table);
+
+        let selected = state.table.selected().and_then(|i| metrics.get(i));
+        if let Some(metric) = selected {
+            self.draw_chart(frame, chart_area, metric, &state.histories);
+        } else {
+            frame.render_widget(Block::bordered().title(" no metrics yet "), chart_area);
+        }
+    }
+
+    fn draw_chart(&self, frame: &mut Frame, area: Rect, metric: &str, histories: &Histories) {
+        let series: Vec<(usize, &[(f64, f64)])> = histories
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| Some((i, h.get(metric)?.as_slice())))
+            .collect();
+        let points = series.iter().flat_map(|(_, s)| s.iter());
+        let (mut x0, mut x1, mut y0, mut y1) = (f64::MAX, f64::MIN, f64::MAX, f64::MIN);
+        for &(x, y) in points {
+            x0 = x0.min(x);
+            x1 = x1.max(x);
+            y0 = y0.min(y);
+            y1 = y1.max(y);
+        }
+        if x1 <= x0 {
+            x1 = x0 + 1.0;
+        }
+        if y1 <= y0 {
+            (y0, y1) = (y0 - 0.5, y1 + 0.5);
+        }
+        let datasets = series
+            .iter()
+            .map(|(i, data)| {
+                Dataset::default()
+                    .name(self.runs[*i].as_str())
+                    .marker(Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::new().fg(COLORS[i % COLORS.len()]))
+                    .data(data)
+            })
+            .collect();
+        let chart = Chart::new(datasets)
+            .block(Block::bordered().title(format!(" {metric} ")))
+            .x_axis(
+                Axis::default()
+                    .bounds([x0, x1])
+                    .labels([format_number(x0), format_number(x1)]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([y0, y1])
+                    .labels([format_number(y0), format_number(y1)]),
+            );
+        frame.render_widget(chart, area);
+    }
+}
+
+fn numeric(points: &[MetricPoint]) -> Vec<(f64, f64)> {
+    points
+        .iter()
+        .filter_map(|p| Some((p.step as f64, p.value.as_f64()?)))
+        .filter(|(_, y)| y.is_finite())
+        .collect()
+}
+
+fn sparkline(points: &[(f64, f64)]) -> String {
+    let tail = &points[points.len().saturating_sub(SPARK_WIDTH)..];
+    let (lo, hi) = tail.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p.1), hi.max(p.1))
+    });
+    tail.iter()
+        .map(|p| {
+            if hi > lo {
+                SPARK[(((p.1 - lo) / (hi - lo)) * 7.0).round() as usize]
+            } else {
+                SPARK[3]
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,139 @@
+//! Conversion of domain types to the values of a logged row.
+//!
+//! [`log!`](crate::log) takes anything implementing [`ToMetricValue`]: every type
+//! `serde_json::Value` converts from (numbers, `bool`, strings, `Option`s of those), and
+//! types of the application that implement it, so a fixed-point number or a custom
+//! tensor is logged as it is instead of through an `as f64` at every call site.
+//!
+//! ```no_run
+//! use trackio::ToMetricValue;
+//!
+//! /// Money in thousandths of a cent.
+//! #[derive(Clone, Copy)]
+//! struct Milli(i64);
+//!
+//! impl ToMetricValue for Milli {
+//!     fn to_metric_value(self) -> serde_json::Value {
+//!         (self.0 as f64 / 1000.0).into()
+//!     }
+//! }
+//!
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! trackio::log!(client, step = 10, "cost" => Milli(12_345), "tokens" => 2048);
+//! ```
+//!
+//! Timings and sizes are logged as a [`MetricValue`], a plain number in seconds or bytes
+//! whose unit is recorded with the run, so a `Duration` doesn't show up as a giant count
+//! of nanoseconds:
+//!
+//! ```no_run
+//! use std::time::Instant;
+//! use trackio::value::MetricValue;
+//!
+//! # let client = trackio::Client::new().with_project("p").with_run("r");
+//! let started = Instant::now();
+//! trackio::log!(
+//!     client,
+//!     step = 10,
+//!     "step_time" => MetricValue::Duration(started.elapsed()),
+//!     "checkpoint_size" => MetricValue::Bytes(812 << 20),
+//! );
+//! ```
+
+use parking_lot::Mutex;
+use serde_json::{Map, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A value that can be logged as a metric, usually by turning it into a number.
+///
+/// Implemented for every type `serde_json::Value` converts from. Non-finite floats
+/// become `null`, which the dashboard skips.
+pub trait ToMetricValue {
+    fn to_metric_value(self) -> Value;
+
+    /// The unit of the value, such as `s`, recorded by [`log!`](crate::log) with
+    /// [`Client::set_unit`](crate::Client::set_unit).
+    fn unit(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl<T: Into<Value>> ToMetricValue for T {
+    fn to_metric_value(self) -> Value {
+        self.into()
+    }
+}
+
+/// A measurement with a unit. It is logged as a number in the unit, and the unit is
+/// recorded in the run's config under `_Units`, keyed by metric name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MetricValue {
+    /// A time span, logged in seconds.
+    Duration(Duration),
+    /// A size, logged in bytes.
+    Bytes(u64),
+}
+
+impl MetricValue {
+    pub fn as_f64(&self) -> f64 {
+        match self {
+            Self::Duration(d) => d.as_secs_f64(),
+            Self::Bytes(b) => *b as f64,
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Duration(_) => "s",
+            Self::Bytes(_) => "bytes",
+        }
+    }
+}
+
+impl From<Duration> for MetricValue {
+    fn from(d: Duration) -> Self {
+        Self::Duration(d)
+    }
+}
+
+impl ToMetricValue for MetricValue {
+    fn to_metric_value(self) -> Value {
+        match self {
+            Self::Duration(d) => d.as_secs_f64().into(),
+            Self::Bytes(b) => b.into(),
+        }
+    }
+
+    fn unit(&self) -> Option<&'static str> {
+        Some(MetricValue::unit(self))
+    }
+}
+
+/// The units of a run's metrics, sent in its config as `_Units`.
+#[derive(Debug, Default)]
+pub(crate) struct Units {
+    units: Mutex<Map<String, Value>>,
+    changed: AtomicBool,
+}
+
+impl Units {
+    pub(crate) fn set(&self, metric: &str, unit: &str) {
+        let mut units = self.units.lock();
+        if units.get(metric).and_then(Value::as_str) != Some(unit) {
+            units.insert(metric.into(), unit.into());
+            self.changed.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether a unit was set since the last call.
+    pub(crate) fn take_changed(&self) -> bool {
+        self.changed.swap(false, Ordering::Relaxed)
+    }
+
+    pub(crate) fn to_json(&self) -> Option<Value> {
+        let units = self.units.lock();
+        (!units.is_empty()).then(|| Value::Object(units.clone()))
+    }
+}
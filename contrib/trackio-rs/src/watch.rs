@@ -0,0 +1,142 @@
+use crate::client::{Client, TrackioError};
+use crate::runs::MetricPoint;
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+/// A metric point observed while watching a live run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedPoint {
+    pub metric: String,
+    pub point: MetricPoint,
+}
+
+/// Blocking iterator over new points of a live run, created by [`Client::watch`].
+///
+/// The Trackio server has no push channel for metrics, so the iterator polls the run's
+/// last step at a fixed interval, [`with_poll_interval`](Watch::with_poll_interval), and
+/// fetches the points logged since the previous poll. Points arrive up to one interval
+/// after they are logged.
+pub struct Watch<'a> {
+    client: &'a Client,
+    project: String,
+    run: String,
+    last_step: Option<i64>,
+    started: bool,
+    poll_interval: Duration,
+    pending: VecDeque<WatchedPoint>,
+}
+
+impl Client {
+    /// Watch a run and yield every metric point logged after this call.
+    pub fn watch(&self, project: &str, run: &str) -> Watch<'_> {
+        Watch {
+            client: self,
+            project: project.to_string(),
+            run: run.to_string(),
+            last_step: None,
+            started: false,
+            poll_interval: Duration::from_secs(1),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl Watch<'_> {
+    /// How long to wait between polls when no new points are available (default: 1s).
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    fn current_last_step(&self) -> Result<Option<i64>, TrackioError> {
+        let summary = self.client.call_api(
+            "get_run_summary",
+            &json!({ "project": self.project, "run": self.run }),
+        )?;
+        Ok(summary.get("last_step").and_then(Value::as_i64))
+    }
+
+    fn poll(&mut self) -> Result<(), TrackioError> {
+        let latest = self.current_last_step()?;
+        if !self.started {
+            self.started = true;
+            self.last_step = latest;
+            return Ok(());
+        }
+        let Some(latest) = latest else {
+            return Ok(());
+        };
+        let from = self.last_step.map_or(i64::MIN / 2, |s| s + 1);
+        if latest < from {
+            return Ok(());
+        }
+
+        let half = (latest - from) / 2;
+        let snapshot = self.client.call_api(
+            "get_snapshot",
+            &json!({
+                "project": self.project,
+                "run": self.run,
+                "around_step": from + half,
+                "window": half + 1,
+            }),
+        )?;
+
+        let mut points = Vec::new();
+        for (metric, rows) in snapshot.as_object().into_iter().flatten() {
+            for row in rows.as_array().into_iter().flatten() {
+                let Some(step) = row.get("step").and_then(Value::as_i64) else {
+                    continue;
+                };
+                if step < from || step > latest {
+                    continue;
+                }
+                let Some(value) = row.get("value").filter(|v| !v.is_null()) else {
+                    continue;
+                };
+                points.push(WatchedPoint {
+                    metric: metric.clone(),
+                    point: MetricPoint {
+                        step,
+                        timestamp: row
+                            .get("timestamp")
+                            .and_then(Value::as_str)
+                            .map(String::from),
+                        value: value.clone(),
+                    },
+                });
+            }
+        }
+        points.sort_by(|a, b| {
+            a.point
+                .step
+                .cmp(&b.point.step)
+                .then(a.metric.cmp(&b.metric))
+        });
+        self.pending.extend(points);
+        self.last_step = Some(latest);
+        Ok(())
+    }
+}
+
+impl Iterator for Watch<'_> {
+    type Item = Result<WatchedPoint, TrackioError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(point) = self.pending.pop_front() {
+                return Some(Ok(point));
+            }
+            let first = !self.started;
+            if let Err(e) = self.poll() {
+                thread::sleep(self.poll_interval);
+                return Some(Err(e));
+            }
+            if self.pending.is_empty() && !first {
+                thread::sleep(self.poll_interval);
+            }
+        }
+    }
+}
@@ -0,0 +1,248 @@
+use crate::checksum;
+pub use crate::error::{ErrorKind, FailedBatch, TrackioError};
+use crate::log_item::BulkPayload;
+pub use crate::log_item::LogItem;
+use crate::runtime::Runtime;
+use crate::time::now_iso8601;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transport::user_agent;
+use crate::transport::{default_async_transport, AsyncTransport, Method, Request};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use serde_json::Value;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+/// An async client that sends only when you await [`flush`](WebClient::flush): no
+/// threads, timers or blocking I/O, so it runs in the browser on `wasm32-unknown-unknown`
+/// (through `fetch`) as well as on any async runtime natively.
+///
+/// For in-browser demos and WebGPU training loops logging straight to a Trackio Space.
+/// The Space has to allow the page's origin (Gradio Spaces allow any), and the write
+/// token ends up in the page, so use one that can be rotated.
+///
+/// Natively it runs on any executor: retries and [`spawn_flusher`](WebClient::spawn_flusher)
+/// go through the [`Runtime`] set with [`with_runtime`](WebClient::with_runtime), and
+/// requests through an [`AsyncTransport`], so smol and async-std programs need no tokio
+/// runtime once they set a transport for their HTTP client.
+///
+/// ```no_run
+/// # async fn train() -> Result<(), trackio::web::TrackioError> {
+/// use trackio::web::WebClient;
+///
+/// let client = WebClient::new("https://me-trackio.hf.space")
+///     .with_project("webgpu-demo")
+///     .with_run("run-1");
+/// for step in 0..100 {
+///     client.log(serde_json::json!({ "loss": 1.0 / (step as f64 + 1.0) }), Some(step));
+///     if step % 10 == 9 {
+///         client.flush().await?;
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct WebClient {
+    base_url: String,
+    project: String,
+    run: String,
+    write_token: Option<String>,
+    transport: Option<Arc<dyn AsyncTransport>>,
+    runtime: Option<Arc<dyn Runtime>>,
+    max_retries: u32,
+    retry_backoff: Duration,
+    bulk_path: OnceCell<&'static str>,
+    config: Mutex<Option<Value>>,
+    buf: Mutex<Vec<LogItem>>,
+}
+
+impl WebClient {
+    /// Log to the Trackio server at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').into(),
+            project: String::new(),
+            run: String::new(),
+            write_token: None,
+            transport: default_async_transport(),
+            runtime: None,
+            max_retries: 0,
+            retry_backoff: Duration::from_millis(500),
+            bulk_path: OnceCell::new(),
+            config: Mutex::new(None),
+            buf: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_project(mut self, project: &str) -> Self {
+        self.project = project.into();
+        self
+    }
+
+    pub fn with_run(mut self, run: &str) -> Self {
+        self.run = run.into();
+        self
+    }
+
+    pub fn with_write_token(mut self, token: &str) -> Self {
+        self.write_token = Some(token.into());
+        self
+    }
+
+    /// Send requests through `transport` instead of the default one.
+    pub fn with_transport<T: AsyncTransport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Wait between retries and run [`spawn_flusher`](WebClient::spawn_flusher) on
+    /// `runtime`.
+    pub fn with_runtime<R: Runtime + 'static>(mut self, runtime: R) -> Self {
+        self.runtime = Some(Arc::new(runtime));
+        self
+    }
+
+    /// Retry a flush that failed with a retryable error up to `retries` times, waiting
+    /// `backoff`, doubled on each attempt, or as long as the server asks. Needs a
+    /// [`Runtime`] to wait on; without one, failures are returned at once.
+    pub fn with_max_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.max_retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    /// Flush every `interval` on the client's runtime until the client is dropped.
+    /// Failed batches are put back when the error is retryable and dropped with a
+    /// warning otherwise. Fails without a [`Runtime`].
+    pub fn spawn_flusher(self: &Arc<Self>, interval: Duration) -> Result<(), TrackioError> {
+        let runtime = self.runtime.clone().ok_or_else(|| {
+            TrackioError::InvalidArgument("spawn_flusher needs WebClient::with_runtime".into())
+        })?;
+        let client: Weak<Self> = Arc::downgrade(self);
+        let sleeper = runtime.clone();
+        runtime.spawn(Box::pin(async move {
+            loop {
+                sleeper.sleep(interval).await;
+                let Some(client) = client.upgrade() else {
+                    return;
+                };
+                if let Err(e) = client.flush().await {
+                    if !e.is_retryable() {
+                        log::warn!("trackio: dropping batch: {e}");
+                    } else if let Some(batch) = e.into_batch() {
+                        client.requeue(batch);
+                    }
+                }
+            }
+        }));
+        Ok(())
+    }
+
+    /// Buffer a row of metrics; nothing is sent until [`flush`](WebClient::flush).
+    pub fn log(&self, metrics: Value, step: Option<i64>) {
+        self.buf.lock().push(LogItem {
+            metrics,
+            step,
+            timestamp: Some(now_iso8601()),
+        });
+    }
+
+    /// Set the run's config, sent with the next flush.
+    pub fn set_config(&self, config: Value) {
+        *self.config.lock() = Some(config);
+    }
+
+    /// Rows logged but not yet flushed.
+    pub fn pending(&self) -> usize {
+        self.buf.lock().len()
+    }
+
+    /// Send everything buffered in one request. On failure the error carries the batch,
+    /// which can be put back with [`requeue`](WebClient::requeue).
+    pub async fn flush(&self) -> Result<(), TrackioError> {
+        let items = std::mem::take(&mut *self.buf.lock());
+        let config = self.config.lock().take();
+        if items.is_empty() && config.is_none() {
+            return Ok(());
+        }
+        let payload = BulkPayload::new(&self.project, &self.run, &items, config.as_ref());
+        let result = self.post_bulk(&payload).await;
+        result.map_err(|error| {
+            TrackioError::Batch(Box::new(FailedBatch {
+                project: self.project.clone(),
+                run: self.run.clone(),
+                items,
+                config,
+                error,
+            }))
+        })
+    }
+
+    /// Put the rows and config of a failed flush back in front of the buffer.
+    pub fn requeue(&self, batch: FailedBatch) {
+        self.buf.lock().splice(0..0, batch.items);
+        if let Some(config) = batch.config {
+            self.config.lock().get_or_insert(config);
+        }
+    }
+
+    async fn post_bulk(&self, payload: &BulkPayload<'_>) -> Result<(), TrackioError> {
+        if let Some(path) = self.bulk_path.get() {
+            return self.post(path, payload).await;
+        }
+        for path in ["/api/bulk_log", "/gradio_api/bulk_log"] {
+            match self.post(path, payload).await {
+                Ok(()) => {
+                    let _ = self.bulk_path.set(path);
+                    return Ok(());
+                }
+                Err(TrackioError::NotFound(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Err(TrackioError::NoBulkEndpoint)
+    }
+
+    async fn post(&self, path: &str, payload: &BulkPayload<'_>) -> Result<(), TrackioError> {
+        let transport = self.transport.as_deref().ok_or(TrackioError::NoTransport)?;
+        let url = format!("{}{path}", self.base_url);
+        let body = serde_json::to_vec(payload)
+            .map_err(|e| TrackioError::InvalidArgument(e.to_string()))?;
+        let checksum = checksum::of(&body);
+        #[cfg(not(target_arch = "wasm32"))]
+        let agent = user_agent(None);
+        let mut attempt = 0;
+        loop {
+            let mut headers = vec![
+                ("Content-Type", "application/json"),
+                (checksum::HEADER, checksum.as_str()),
+            ];
+            #[cfg(not(target_arch = "wasm32"))]
+            headers.push(("User-Agent", &agent));
+            if let Some(token) = &self.write_token {
+                headers.push(("X-Trackio-Write-Token", token));
+            }
+            let request = Request {
+                method: Method::Post,
+                url: &url,
+                headers,
+                body: body.clone(),
+            };
+            let sent = transport.send(request).await;
+            match (
+                sent.and_then(|resp| checksum::verify_echo(&checksum, resp)),
+                &self.runtime,
+            ) {
+                (Ok(_), _) => return Ok(()),
+                (Err(e), Some(runtime)) if e.is_retryable() && attempt < self.max_retries => {
+                    let backoff = self.retry_backoff.saturating_mul(1 << attempt.min(16));
+                    let delay = e.retry_after().unwrap_or(backoff);
+                    runtime.sleep(delay.min(Duration::from_secs(60))).await;
+                    attempt += 1;
+                }
+                (Err(e), _) => return Err(e),
+            }
+        }
+    }
+}
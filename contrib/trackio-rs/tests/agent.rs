@@ -0,0 +1,74 @@
+#![cfg(unix)]
+
+mod common;
+
+use serde_json::json;
+use std::fs;
+use std::os::unix::net::UnixListener;
+use std::thread;
+use std::time::Duration;
+use trackio::agent::{Agent, IpcClient};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(&server.url())
+        .max_retries(0)
+        .build()
+}
+
+#[test]
+fn a_restarted_agent_replays_its_log() {
+    let dir = common::temp_dir("agent-replay");
+    let wal = dir.join("agent.wal");
+    let socket = dir.join("agent.sock");
+    let lines = [
+        json!({ "op": "config", "project": "p", "run": "a", "config": { "lr": 0.1 } }),
+        json!({ "op": "log", "project": "p", "run": "a", "metrics": { "loss": 1.0 }, "step": 0, "timestamp": "2024-01-01T00:00:00Z" }),
+        json!({ "op": "log", "project": "p", "run": "a", "metrics": { "loss": 0.5 }, "step": 1, "timestamp": "2024-01-01T00:00:01Z" }),
+        json!({ "op": "log", "project": "p", "run": "b", "metrics": { "acc": 0.9 }, "step": 0 }),
+    ];
+    let mut log: String = lines.iter().map(|line| format!("{line}\n")).collect();
+    log.push_str(r#"{"op":"log","project":"p","run":"a","metr"#);
+    fs::write(&wal, log).unwrap();
+    drop(UnixListener::bind(&socket).unwrap());
+
+    let server = MockServer::start();
+    let agent = Agent::new(client(&server))
+        .with_socket(&socket)
+        .with_wal(&wal)
+        .with_flush_interval(Duration::from_millis(20));
+    thread::spawn(move || agent.run());
+
+    common::wait_for("the replayed rows", || server.rows().len() == 3);
+    let rows = server.rows();
+    let a: Vec<_> = rows.iter().filter(|row| row.run == "a").collect();
+    assert_eq!(a.len(), 2);
+    assert_eq!(
+        (a[0].step, a[0].timestamp.as_deref()),
+        (Some(0), Some("2024-01-01T00:00:00Z"))
+    );
+    assert_eq!(a[1].metrics["loss"], 0.5);
+    assert_eq!(server.config("a"), Some(json!({ "lr": 0.1 })));
+    assert!(rows
+        .iter()
+        .any(|row| row.run == "b" && row.metrics["acc"] == 0.9));
+    common::wait_for("the log to be emptied", || {
+        fs::read_to_string(&wal).unwrap().is_empty()
+    });
+
+    let worker = IpcClient::connect(&socket)
+        .unwrap()
+        .with_project("p")
+        .with_run("a");
+    worker.log(json!({ "loss": 0.25 }), Some(2), None);
+    worker.flush().unwrap();
+    server.assert_logged_value("loss", 2, 0.25);
+
+    let second = Agent::new(client(&server))
+        .with_socket(&socket)
+        .with_wal(dir.join("other.wal"));
+    assert!(second.run().is_err());
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use parking_lot::Mutex;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use trackio::alerts::{Above, AlertLevel, Below, IsNaN};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(&server.url())
+        .project("p")
+        .run("r")
+        .max_retries(0)
+        .build()
+}
+
+#[test]
+fn rules_fire_once_per_breach() {
+    let server = MockServer::start();
+    let raised = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&raised);
+    let client = client(&server)
+        .alert_if("loss", Above(2.0))
+        .alert_if("loss", IsNaN)
+        .alert_if("acc", Below(0.1).for_steps(2))
+        .on_alert(move |alert| {
+            seen.lock()
+                .push((alert.title.clone(), alert.level, alert.step))
+        });
+
+    let rows = [
+        json!({ "loss": 3.0, "acc": 0.05 }),
+        json!({ "loss": 4.0, "acc": 0.05 }),
+        json!({ "loss": 1.0, "acc": 0.05 }),
+        json!({ "loss": 2.5, "acc": 0.5 }),
+        json!({ "loss": null, "acc": 0.05 }),
+    ];
+    for (step, row) in rows.into_iter().enumerate() {
+        client.log(row, Some(step as i64), None);
+    }
+    client.flush().unwrap();
+
+    let expected = [
+        ("loss is above 2".to_string(), AlertLevel::Warn, Some(0)),
+        ("acc is below 0.1".to_string(), AlertLevel::Warn, Some(2)),
+        ("loss is above 2".to_string(), AlertLevel::Warn, Some(3)),
+        ("loss is NaN".to_string(), AlertLevel::Error, Some(4)),
+    ];
+    assert_eq!(*raised.lock(), expected);
+    let titles: Vec<_> = expected.into_iter().map(|(title, ..)| title).collect();
+    assert_eq!(server.alerts(), titles);
+}
+
+#[test]
+fn explicit_alerts_are_sent_with_the_next_flush() {
+    let server = MockServer::start();
+    let client = client(&server);
+    client.alert(
+        "checkpoint saved",
+        Some("step 100"),
+        AlertLevel::Info,
+        Some(100),
+    );
+    assert!(server.alerts().is_empty());
+    client.flush().unwrap();
+    server.assert_alerted("checkpoint saved");
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path == "/api/bulk_alert")
+        .unwrap();
+    let alert = &request.body["alerts"][0];
+    assert_eq!(alert["level"], "info");
+    assert_eq!(alert["text"], "step 100");
+    assert_eq!(alert["step"], 100);
+}
+
+#[test]
+fn the_watchdog_reports_a_stall_and_its_end() {
+    let server = MockServer::start();
+    let client = client(&server).with_watchdog(Duration::from_millis(200));
+    client.log(json!({ "loss": 0.5 }), Some(5), None);
+    client.flush().unwrap();
+
+    common::wait_for("the stall alert", || {
+        server.alerts() == ["No metrics logged"]
+    });
+    server.assert_logged_value("system/stalled", 5, 1);
+
+    client.log(json!({ "loss": 0.25 }), Some(6), None);
+    common::wait_for("the end of the stall", || {
+        server
+            .rows()
+            .iter()
+            .any(|row| row.step == Some(6) && row.metrics.contains_key("system/stalled"))
+    });
+    server.assert_logged_value("system/stalled", 6, 0);
+}
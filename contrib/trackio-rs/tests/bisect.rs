@@ -0,0 +1,130 @@
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use trackio::client::TrackioError;
+use trackio::transport::{Request, Response, Transport};
+use trackio::Client;
+
+/// A server that refuses any bulk log holding a `bad` metric, naming the row when
+/// `names_rows` is set, acknowledges the others with their steps as offsets, and goes
+/// down after `up_for` requests.
+#[derive(Debug)]
+struct Picky {
+    sizes: Arc<Mutex<Vec<usize>>>,
+    up_for: usize,
+    names_rows: bool,
+}
+
+impl Transport for Picky {
+    fn send(&self, request: Request<'_>) -> Result<Response, TrackioError> {
+        let payload: Value = serde_json::from_slice(&request.body).unwrap();
+        let rows = payload["metrics_list"].as_array().unwrap();
+        let mut sizes = self.sizes.lock().unwrap();
+        sizes.push(rows.len());
+        if sizes.len() > self.up_for {
+            return Err(TrackioError::from_status(503, None, "down".into()));
+        }
+        if let Some(row) = rows.iter().position(|row| row.get("bad").is_some()) {
+            let body = if self.names_rows {
+                json!({ "error": format!("row {row}: bad metric"), "rejected": [row] })
+            } else {
+                json!({ "error": "bad metric" })
+            };
+            return Err(TrackioError::from_status(422, None, body.to_string()));
+        }
+        let data = json!({ "accepted": rows.len(), "offsets": payload["steps"] });
+        Ok(Response {
+            status: 200,
+            body: json!({ "data": data }).to_string().into_bytes(),
+        })
+    }
+}
+
+fn picky(up_for: usize) -> (Client, Arc<Mutex<Vec<usize>>>) {
+    picky_with(up_for, true, 8, |step| step == 5)
+}
+
+fn picky_with(
+    up_for: usize,
+    names_rows: bool,
+    rows: i64,
+    bad: fn(i64) -> bool,
+) -> (Client, Arc<Mutex<Vec<usize>>>) {
+    let sizes = Arc::default();
+    let client = Client::builder()
+        .base_url("http://picky")
+        .project("p")
+        .run("r")
+        .max_retries(0)
+        .max_batch(rows as usize + 1)
+        .build()
+        .with_transport(Picky {
+            sizes: Arc::clone(&sizes),
+            up_for,
+            names_rows,
+        });
+    for step in 0..rows {
+        let metrics = if bad(step) {
+            json!({ "bad": 1 })
+        } else {
+            json!({ "loss": 1.0 })
+        };
+        client.log(metrics, Some(step), None);
+    }
+    (client, sizes)
+}
+
+#[test]
+fn a_rejected_batch_is_bisected_down_to_the_bad_row() {
+    let (client, sizes) = picky(usize::MAX);
+    let receipt = client.flush().unwrap();
+    assert_eq!(*sizes.lock().unwrap(), [8, 4, 4, 2, 1, 1, 2]);
+    assert_eq!(receipt.sent, 8);
+    assert_eq!(receipt.accepted, Some(7));
+    assert_eq!(
+        receipt.offsets,
+        [
+            Some(0),
+            Some(1),
+            Some(2),
+            Some(3),
+            Some(4),
+            None,
+            Some(6),
+            Some(7)
+        ]
+    );
+    assert_eq!(receipt.rejected.len(), 1);
+    assert_eq!(receipt.rejected[0].index, 5);
+    assert_eq!(receipt.rejected[0].item.metrics, json!({ "bad": 1 }));
+    assert!(receipt.rejected[0].reason.contains("bad metric"));
+}
+
+#[test]
+fn a_server_going_down_while_bisecting_returns_the_unsent_rows() {
+    let (client, sizes) = picky(2);
+    let err = client.flush().unwrap_err();
+    assert_eq!(*sizes.lock().unwrap(), [8, 4, 4]);
+    assert!(err.is_retryable());
+    let batch = err.into_batch().unwrap();
+    let steps: Vec<_> = batch.items.iter().map(|item| item.step).collect();
+    assert_eq!(steps, [Some(4), Some(5), Some(6), Some(7)]);
+}
+
+#[test]
+fn a_rejection_naming_no_row_fails_the_batch_once() {
+    let (client, sizes) = picky_with(usize::MAX, false, 8, |step| step == 5);
+    let err = client.flush().unwrap_err();
+    assert_eq!(*sizes.lock().unwrap(), [8]);
+    assert!(!err.is_retryable());
+    assert_eq!(err.into_batch().unwrap().items.len(), 8);
+}
+
+#[test]
+fn bisecting_stops_at_the_depth_cap() {
+    let (client, sizes) = picky_with(usize::MAX, true, 1024, |_| true);
+    let receipt = client.flush().unwrap();
+    assert_eq!(sizes.lock().unwrap().len(), 511);
+    assert_eq!(receipt.sent, 1024);
+    assert_eq!(receipt.rejected.len(), 1024);
+    assert!(receipt.offsets.iter().all(Option::is_none));
+}
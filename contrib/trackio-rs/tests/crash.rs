@@ -0,0 +1,51 @@
+use serde_json::json;
+use std::sync::Arc;
+use std::thread;
+use trackio::testing::MockServer;
+use trackio::Client;
+
+#[test]
+fn a_panic_logs_a_crash_event_and_flushes() {
+    let server = MockServer::start();
+    let client = Arc::new(
+        Client::builder()
+            .base_url(&server.url())
+            .project("p")
+            .run("r")
+            .max_retries(0)
+            .build()
+            .with_crash_breadcrumbs(2),
+    );
+    client.install_panic_hook();
+    for step in 0..3 {
+        client.log(json!({ "loss": step }), Some(step), None);
+    }
+
+    assert!(thread::spawn(|| panic!("loss diverged")).join().is_err());
+
+    assert_eq!(server.rows().len(), 3);
+    assert_eq!(server.events(), ["crash"]);
+    assert!(server.alerts().is_empty());
+    let request = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path == "/api/log_events")
+        .unwrap();
+    let event = &request.body["events"][0];
+    assert_eq!(event["level"], "error");
+    assert_eq!(event["step"], 2);
+    let fields = &event["fields"];
+    assert_eq!(fields["message"], "loss diverged");
+    assert!(fields["location"]
+        .as_str()
+        .unwrap()
+        .starts_with("tests/crash.rs:"));
+    assert_eq!(
+        fields["rows"],
+        json!([
+            { "step": 1, "metrics": { "loss": 1 } },
+            { "step": 2, "metrics": { "loss": 2 } },
+        ])
+    );
+    assert!(fields.get("backtrace").is_some());
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use serde_json::json;
+use std::time::{Duration, Instant};
+use trackio::distributed::{Reducer, Reduction, TcpReducer};
+
+fn reducers(timeout: Duration) -> (TcpReducer, TcpReducer) {
+    let addr = format!("127.0.0.1:{}", common::free_port());
+    let primary = TcpReducer::new(0, 2, &addr).unwrap().with_timeout(timeout);
+    let other = TcpReducer::new(1, 2, &addr).unwrap();
+    (primary, other)
+}
+
+#[test]
+fn rows_logged_at_one_step_are_combined_separately() {
+    let (primary, other) = reducers(Duration::from_secs(5));
+    other.reduce(Some(1), json!({ "loss": 3.0 }), Reduction::Mean);
+    other.reduce(Some(1), json!({ "lr": 1.5 }), Reduction::Mean);
+    assert_eq!(
+        primary.reduce(Some(1), json!({ "loss": 1.0 }), Reduction::Mean),
+        None
+    );
+    assert_eq!(
+        primary.reduce(Some(1), json!({ "lr": 0.5 }), Reduction::Mean),
+        None
+    );
+    primary.finish();
+
+    let ready = primary.ready();
+    assert_eq!(ready.len(), 2);
+    assert_eq!(ready[0].metrics, json!({ "loss": 2.0 }));
+    assert_eq!(ready[1].metrics, json!({ "lr": 1.0 }));
+    assert!(ready
+        .iter()
+        .all(|item| item.step == Some(1) && item.timestamp.is_some()));
+    assert!(primary.ready().is_empty());
+}
+
+#[test]
+fn a_missing_rank_times_out_without_blocking_rank_0() {
+    let (primary, _other) = reducers(Duration::from_millis(200));
+    let start = Instant::now();
+    primary.reduce(Some(0), json!({ "loss": 1.0 }), Reduction::Mean);
+    assert!(start.elapsed() < Duration::from_millis(100));
+    assert!(primary.ready().is_empty());
+
+    primary.finish();
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    let ready = primary.ready();
+    assert_eq!(ready.len(), 1);
+    assert_eq!(ready[0].metrics, json!({ "loss": 1.0 }));
+    assert_eq!(ready[0].step, Some(0));
+}
+
+#[test]
+fn a_row_arriving_after_its_timeout_is_dropped() {
+    let (primary, other) = reducers(Duration::from_millis(500));
+    primary.reduce(Some(0), json!({ "loss": 1.0 }), Reduction::Mean);
+    primary.finish();
+    other.reduce(Some(0), json!({ "loss": 100.0 }), Reduction::Mean);
+    other.reduce(Some(1), json!({ "loss": 3.0 }), Reduction::Mean);
+    primary.reduce(Some(1), json!({ "loss": 1.0 }), Reduction::Mean);
+    primary.finish();
+
+    let ready = primary.ready();
+    assert_eq!(ready.len(), 2);
+    assert_eq!(
+        (ready[0].step, &ready[0].metrics),
+        (Some(0), &json!({ "loss": 1.0 }))
+    );
+    assert_eq!(
+        (ready[1].step, &ready[1].metrics),
+        (Some(1), &json!({ "loss": 2.0 }))
+    );
+}
+
+#[test]
+fn sum_adds_every_rank() {
+    let (primary, other) = reducers(Duration::from_secs(5));
+    other.reduce(None, json!({ "tokens": 7, "tag": "b" }), Reduction::Sum);
+    primary.reduce(None, json!({ "tokens": 5, "tag": "a" }), Reduction::Sum);
+    primary.finish();
+    let ready = primary.ready();
+    assert_eq!(ready[0].metrics, json!({ "tokens": 12.0, "tag": "a" }));
+    assert_eq!(ready[0].step, None);
+}
+
+#[test]
+fn a_row_only_one_rank_logs_does_not_hold_up_the_others() {
+    let (primary, other) = reducers(Duration::from_secs(30));
+    other.reduce(Some(0), json!({ "loss": 3.0 }), Reduction::Mean);
+    other.reduce(Some(0), json!({ "grad": 1.0 }), Reduction::Mean);
+    other.reduce(Some(1), json!({ "loss": 3.0 }), Reduction::Mean);
+    let start = Instant::now();
+    primary.reduce(Some(0), json!({ "loss": 1.0 }), Reduction::Mean);
+    primary.reduce(Some(1), json!({ "loss": 1.0 }), Reduction::Mean);
+    primary.finish();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let ready = primary.ready();
+    assert_eq!(ready.len(), 2);
+    assert_eq!(
+        (ready[0].step, &ready[0].metrics),
+        (Some(0), &json!({ "loss": 2.0 }))
+    );
+    assert_eq!(
+        (ready[1].step, &ready[1].metrics),
+        (Some(1), &json!({ "loss": 2.0 }))
+    );
+}
+
+#[test]
+fn an_unreachable_rank_0_does_not_block_the_other_ranks() {
+    let other = TcpReducer::new(1, 2, "10.255.255.1:29500")
+        .unwrap()
+        .with_timeout(Duration::from_millis(200));
+    let start = Instant::now();
+    for step in 0..1000 {
+        other.reduce(Some(step), json!({ "loss": 1.0 }), Reduction::Mean);
+    }
+    assert!(start.elapsed() < Duration::from_secs(1));
+    other.finish();
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
@@ -0,0 +1,96 @@
+use serde_json::json;
+use trackio::alerts::AlertLevel;
+use trackio::client::TrackioError;
+use trackio::testing::{Cassette, MockServer};
+use trackio::Client;
+
+#[test]
+fn the_python_server_lacks_the_extension_endpoints() {
+    let path = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/cassettes/python_server.json"
+    );
+    let cassette = Cassette::replay(path).unwrap();
+    let client = Client::builder()
+        .base_url(&cassette.url())
+        .project("p")
+        .run("r")
+        .max_retries(0)
+        .build();
+    client.validate().unwrap();
+    client.log(json!({ "loss": 0.5 }), Some(0), None);
+    client.log(json!({ "loss": 0.25 }), Some(1), None);
+    client.flush().unwrap();
+
+    for _ in 0..2 {
+        let err = client.prune("p", "r", 0..=1).unwrap_err();
+        assert!(matches!(err, TrackioError::Unsupported(ref name) if name == "prune_run"));
+    }
+    cassette.finish().unwrap();
+}
+
+fn client_with(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(&server.url())
+        .project("p")
+        .run("r")
+        .max_retries(0)
+        .build()
+}
+
+#[test]
+fn run_metadata_needs_the_extension_endpoints() {
+    let server = MockServer::start()
+        .without_path("/api/set_run_meta")
+        .without_path("/api/get_run_meta");
+    let client = client_with(&server);
+    for _ in 0..2 {
+        let err = client.set_meta("k", json!(1)).unwrap_err();
+        assert!(matches!(err, TrackioError::Unsupported(ref name) if name == "set_run_meta"));
+    }
+    let err = client.get_meta("p", "r").unwrap_err();
+    assert!(matches!(err, TrackioError::Unsupported(_)));
+    assert_eq!(server.requests().len(), 2);
+
+    let server = MockServer::start();
+    server.respond("get_run_meta", json!({ "k": 1 }));
+    let meta = client_with(&server).get_meta("p", "r").unwrap();
+    assert_eq!(meta["k"], 1);
+}
+
+#[test]
+fn moving_a_run_needs_the_extension_endpoint() {
+    let server = MockServer::start().without_path("/api/move_run");
+    let client = client_with(&server);
+    for _ in 0..2 {
+        let err = client.move_run("r", "archive").unwrap_err();
+        assert!(matches!(err, TrackioError::Unsupported(ref name) if name == "move_run"));
+    }
+    assert_eq!(server.requests().len(), 1);
+
+    let server = MockServer::start();
+    client_with(&server).move_run("r", "archive").unwrap();
+    let request = &server.requests()[0];
+    assert_eq!(request.path, "/api/move_run");
+    assert_eq!(request.body["new_project"], "archive");
+}
+
+#[test]
+fn events_are_dropped_once_the_server_turns_out_to_lack_them() {
+    let server = MockServer::start().without_path("/api/log_events");
+    let client = client_with(&server);
+    client.log(json!({ "loss": 0.5 }), Some(0), None);
+    client.log_event(AlertLevel::Info, "checkpoint", json!({ "path": "a.pt" }));
+    client.flush().unwrap();
+    client.annotate(0, "restart", "");
+    client.log(json!({ "loss": 0.25 }), Some(1), None);
+    client.flush().unwrap();
+
+    let events = server
+        .requests()
+        .iter()
+        .filter(|request| request.path == "/api/log_events")
+        .count();
+    assert_eq!(events, 1);
+    server.assert_logged_value("loss", 1, 0.25);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use serde_json::json;
+use std::fs;
+use trackio::client::TrackioError;
+use trackio::media::{DirStore, Media};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(&server.url())
+        .project("my project")
+        .run("r")
+        .max_retries(0)
+        .build()
+}
+
+#[test]
+fn media_is_stored_by_content_and_logged_as_a_reference() {
+    let dir = common::temp_dir("media");
+    let server = MockServer::start();
+    let client =
+        client(&server).with_media_store(DirStore::new(&dir).with_base_url("s3://ml-media/"));
+
+    let png = b"\x89PNG not really";
+    let url = client
+        .log_media(
+            "eval/samples",
+            Media::image(png, ".png").with_caption("step 100"),
+            Some(100),
+        )
+        .unwrap();
+    let again = client
+        .log_media("eval/samples", Media::image(png, "png"), Some(101))
+        .unwrap();
+    client.flush().unwrap();
+
+    let key = url.strip_prefix("s3://ml-media/").unwrap();
+    assert!(key.starts_with("my_project/r/eval/samples/"), "{key}");
+    assert!(key.ends_with(".png"), "{key}");
+    assert_eq!(again, url);
+    assert_eq!(fs::read(dir.join(key)).unwrap(), png);
+    server.assert_logged_value(
+        "eval/samples",
+        100,
+        json!({ "_type": "trackio.image", "url": url, "size": png.len(), "caption": "step 100" }),
+    );
+}
+
+#[test]
+fn without_a_base_url_files_are_referenced_by_path() {
+    let dir = common::temp_dir("media-path");
+    let server = MockServer::start();
+    let client = client(&server).with_media_store(DirStore::new(&dir));
+
+    let url = client
+        .log_media("notes", Media::file(b"hello", "txt"), None)
+        .unwrap();
+
+    let path = url.strip_prefix("file://").unwrap();
+    assert_eq!(fs::read_to_string(path).unwrap(), "hello");
+}
+
+#[test]
+fn logging_media_needs_a_store() {
+    let server = MockServer::start();
+    let err = client(&server)
+        .log_media("samples", Media::image(b"", "png"), Some(0))
+        .unwrap_err();
+    assert!(matches!(err, TrackioError::InvalidArgument(_)));
+    assert!(server.requests().is_empty());
+}
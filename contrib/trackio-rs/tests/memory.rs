@@ -0,0 +1,57 @@
+use serde_json::json;
+use std::alloc::System;
+use std::hint::black_box;
+use std::time::Duration;
+use trackio::memory::{allocator_stats, resident_memory, TrackingAllocator};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+#[global_allocator]
+static ALLOC: TrackingAllocator<System> = TrackingAllocator::new(System);
+
+const BIG: usize = 64 << 20;
+
+#[test]
+fn the_tracking_allocator_counts_live_bytes_and_the_peak() {
+    let before = allocator_stats().unwrap();
+    let buffer = black_box(vec![1u8; BIG]);
+    let during = allocator_stats().unwrap();
+    assert!(during.allocated >= before.allocated + BIG - (1 << 20));
+    assert!(during.peak_allocated >= during.allocated);
+    drop(buffer);
+    let after = allocator_stats().unwrap();
+    assert!(after.allocated + BIG - (1 << 20) <= during.allocated);
+    assert!(after.peak_allocated >= during.allocated);
+}
+
+#[test]
+fn memory_rows_are_added_to_flushes_once_per_interval() {
+    let server = MockServer::start();
+    let client = Client::builder()
+        .base_url(&server.url())
+        .project("p")
+        .run("r")
+        .max_retries(0)
+        .build()
+        .with_memory_tracking(Duration::from_secs(3600));
+    client.log(json!({ "loss": 0.5 }), Some(7), None);
+    client.flush().unwrap();
+    client.log(json!({ "loss": 0.25 }), Some(8), None);
+    client.flush().unwrap();
+
+    let memory: Vec<_> = server
+        .rows()
+        .into_iter()
+        .filter(|row| row.metrics.contains_key("system/memory/allocated"))
+        .collect();
+    assert_eq!(memory.len(), 1);
+    assert_eq!(memory[0].step, Some(7));
+    let metrics = &memory[0].metrics;
+    assert!(metrics["system/memory/allocations"].as_u64().unwrap() > 0);
+    if resident_memory().is_some() {
+        assert!(metrics["system/memory/rss"].as_u64().unwrap() > 0);
+        assert!(
+            metrics["system/memory/peak_rss"].as_u64() >= metrics["system/memory/rss"].as_u64()
+        );
+    }
+}
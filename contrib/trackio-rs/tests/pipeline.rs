@@ -0,0 +1,42 @@
+use serde_json::{json, Value};
+use trackio::schema::{MetricSchema, MetricType};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+#[test]
+fn hooks_run_before_the_filter_and_the_schema() {
+    let server = MockServer::start();
+    let schema = MetricSchema::new()
+        .metric("epoch", MetricType::Int)
+        .metric("eval/*", MetricType::Float);
+    let client = Client::builder()
+        .base_url(&server.url())
+        .project("p")
+        .run("r")
+        .build()
+        .with_metrics_hook(|row| {
+            if let Some(acc) = row.remove("acc") {
+                row.insert("eval/acc".into(), acc);
+            }
+        })
+        .with_metric_filter(&[], &["debug/*"])
+        .with_schema(schema);
+    client.log(
+        json!({ "acc": "0.9", "epoch": 2.0, "debug/grad": 1.0 }),
+        Some(0),
+        None,
+    );
+    client.log(json!({ "debug/grad": 1.0 }), Some(1), None);
+    client.log(json!({ "epoch": "three" }), Some(2), None);
+    client.flush().unwrap();
+
+    let rows = server.rows();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].step, Some(0));
+    assert_eq!(
+        Value::Object(rows[0].metrics.clone()),
+        json!({ "eval/acc": 0.9, "epoch": 2 })
+    );
+    assert_eq!(rows[1].step, Some(2));
+    assert!(rows[1].metrics.is_empty());
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use serde_json::json;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use trackio::testing::{LoggedRow, MockServer};
+use trackio::Client;
+
+fn sampled(server: &MockServer, client: &Client, done: impl Fn(&LoggedRow) -> bool) -> LoggedRow {
+    let mut found = None;
+    common::wait_for("a pool sample", || {
+        client.flush().unwrap();
+        found = server
+            .rows()
+            .into_iter()
+            .filter(|row| row.metrics.contains_key("system/decode/threads"))
+            .find(&done);
+        found.is_some()
+    });
+    found.unwrap()
+}
+
+#[test]
+fn the_monitor_samples_active_and_pending_jobs() {
+    let server = MockServer::start();
+    let client = Arc::new(
+        Client::builder()
+            .base_url(&server.url())
+            .project("p")
+            .run("r")
+            .max_retries(0)
+            .build(),
+    );
+    client.log(json!({ "loss": 0.5 }), Some(3), None);
+    let monitor = client.spawn_pool_monitor("decode", 2, Duration::from_millis(50));
+
+    let (release, wait) = mpsc::channel::<()>();
+    let running = thread::spawn(monitor.job(move || wait.recv().unwrap()));
+    let queued = monitor.job(|| ());
+    let busy = sampled(&server, &client, |row| {
+        row.metrics["system/decode/active_threads"] == 1
+    });
+    assert_eq!(busy.step, Some(3));
+    assert_eq!(busy.metrics["system/decode/threads"], 2);
+    assert_eq!(busy.metrics["system/decode/pending_jobs"], 1);
+
+    release.send(()).unwrap();
+    running.join().unwrap();
+    drop(queued);
+    monitor.run(|| thread::sleep(Duration::from_millis(10)));
+    let idle = sampled(&server, &client, |row| {
+        row.metrics["system/decode/active_threads"] == 0
+            && row.metrics["system/decode/pending_jobs"] == 0
+            && row.metrics["system/decode/jobs_per_sec"].as_f64() > Some(0.0)
+    });
+    let utilization = idle.metrics["system/decode/utilization"].as_f64().unwrap();
+    assert!((0.0..=1.0).contains(&utilization), "{utilization}");
+}
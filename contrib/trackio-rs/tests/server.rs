@@ -0,0 +1,337 @@
+mod common;
+
+use serde_json::json;
+use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use trackio::alerts::AlertLevel;
+use trackio::bundle::BundleStats;
+use trackio::client::{ErrorKind, TrackioError};
+use trackio::media::{DirStore, Media};
+use trackio::query::HistoryQuery;
+use trackio::report::ReportTemplate;
+use trackio::sqlite::SqliteBackend;
+use trackio::transport::{HttpTransport, Request, Response, Transport};
+use trackio::Client;
+
+fn start(dir: &Path) -> String {
+    let addr = SocketAddr::from(([127, 0, 0, 1], common::free_port()));
+    let dir = dir.to_path_buf();
+    thread::spawn(move || {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(trackio::server::serve(addr, dir))
+    });
+    common::wait_for("the server", || TcpStream::connect(addr).is_ok());
+    format!("http://{addr}")
+}
+
+fn client(url: &str) -> Client {
+    Client::builder()
+        .base_url(url)
+        .project("p")
+        .run("r")
+        .max_retries(0)
+        .build()
+}
+
+/// Changes a logged value after the client has checksummed the body, as a faulty proxy
+/// would.
+#[derive(Debug)]
+struct Mangling(HttpTransport);
+
+impl Transport for Mangling {
+    fn send(&self, mut request: Request<'_>) -> Result<Response, TrackioError> {
+        if let Some(at) = request.body.windows(3).position(|w| w == b"0.5") {
+            request.body[at + 2] = b'6';
+        }
+        self.0.send(request)
+    }
+}
+
+#[test]
+fn checksummed_rows_are_stored_and_acknowledged() {
+    let dir = common::temp_dir("server-receipt");
+    let url = start(&dir);
+    let client = client(&url);
+    let store = SqliteBackend::with_dir(&dir);
+
+    client.validate().unwrap();
+    assert!(store.runs("p").unwrap().is_empty());
+
+    client.log(json!({ "loss": 0.5 }), Some(0), None);
+    client.log(json!({ "loss": 0.25 }), Some(1), None);
+    let receipt = client.flush().unwrap();
+    assert_eq!(receipt.sent, 2);
+    assert_eq!(receipt.accepted, Some(2));
+    assert_eq!(receipt.offsets.len(), 2);
+    assert!(receipt.offsets[0].unwrap() < receipt.offsets[1].unwrap());
+    assert!(receipt.rejected.is_empty());
+
+    let logs = store.logs("p", "r").unwrap();
+    assert_eq!(
+        (logs[0]["loss"].clone(), logs[1]["loss"].clone()),
+        (json!(0.5), json!(0.25))
+    );
+    assert_eq!(client.prune("p", "r", 0..=0).unwrap(), 1);
+    assert_eq!(store.logs("p", "r").unwrap().len(), 1);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_body_changed_on_the_way_is_refused_as_corrupted() {
+    let dir = common::temp_dir("server-corrupted");
+    let url = start(&dir);
+    let client = client(&url).with_transport(Mangling(HttpTransport::new(Duration::from_secs(5))));
+
+    client.log(json!({ "loss": 0.5 }), Some(0), None);
+    let err = client.flush().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Corrupted);
+    assert!(err.to_string().contains("checksum"));
+    assert!(err.is_retryable());
+    assert_eq!(err.into_batch().unwrap().items.len(), 1);
+    assert!(SqliteBackend::with_dir(&dir).runs("p").unwrap().is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn history_queries_filter_metrics_and_steps_and_downsample() {
+    let dir = common::temp_dir("server-query");
+    let url = start(&dir);
+    let client = client(&url);
+    for step in 0..100 {
+        client.log(
+            json!({ "train/loss": 1.0 / (step + 1) as f64, "train/acc": step, "lr": 3e-4 }),
+            Some(step),
+            None,
+        );
+    }
+    client.flush().unwrap();
+
+    let window = client
+        .history(
+            "p",
+            "r",
+            &HistoryQuery::new().metrics(["train/l*"]).steps(10..=19),
+        )
+        .unwrap();
+    assert_eq!(window.keys().collect::<Vec<_>>(), ["train/loss"]);
+    let steps: Vec<_> = window["train/loss"].iter().map(|p| p.step).collect();
+    assert_eq!(steps, (10..=19).collect::<Vec<_>>());
+
+    let coarse = client
+        .history(
+            "p",
+            "r",
+            &HistoryQuery::new().metrics(["train/*"]).max_points(10),
+        )
+        .unwrap();
+    assert_eq!(
+        coarse.keys().collect::<Vec<_>>(),
+        ["train/acc", "train/loss"]
+    );
+    for points in coarse.values() {
+        assert!(points.len() <= 10, "{}", points.len());
+        assert_eq!(points.first().unwrap().step, 0);
+        assert_eq!(points.last().unwrap().step, 99);
+    }
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn two_runs_are_diffed_by_config_summary_and_history() {
+    let dir = common::temp_dir("server-diff");
+    let url = start(&dir);
+    for (run, lr, losses) in [("a", 1e-3, [0.5, 0.25]), ("b", 3e-4, [0.5, 0.125])] {
+        let client = client(&url).with_run(run);
+        client.set_config(json!({ "lr": lr, "model": { "layers": 2, "act": "gelu" } }));
+        for (step, loss) in losses.into_iter().enumerate() {
+            client.log(json!({ "loss": loss }), Some(step as i64 * 10), None);
+        }
+        client.flush().unwrap();
+    }
+
+    let diff = client(&url).diff_runs("p", "a", "b").unwrap();
+    assert!(!diff.is_empty());
+    let config: Vec<_> = diff
+        .config
+        .iter()
+        .map(|c| (c.key.as_str(), c.a.clone(), c.b.clone()))
+        .collect();
+    assert_eq!(config, [("lr", Some(json!(1e-3)), Some(json!(3e-4)))]);
+    assert_eq!(diff.summary.len(), 1);
+    assert_eq!(diff.summary[0].key, "loss");
+    assert_eq!(diff.summary[0].a, Some(json!(0.25)));
+    assert_eq!(diff.summary[0].b, Some(json!(0.125)));
+    let loss = &diff.histories["loss"];
+    assert_eq!(loss.iter().map(|p| p.step).collect::<Vec<_>>(), [0, 10]);
+    assert_eq!(loss[0].a, loss[0].b);
+    assert_ne!(loss[1].a, loss[1].b);
+
+    assert!(client(&url).diff_runs("p", "a", "a").unwrap().is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn reports_summarize_and_chart_the_chosen_runs() {
+    let dir = common::temp_dir("server-report");
+    let url = start(&dir);
+    for (run, lr) in [("base", 1e-3), ("fast<lr>", 3e-3)] {
+        let client = client(&url).with_run(run);
+        client.set_config(json!({ "lr": lr, "optimizer": { "name": "adamw" } }));
+        for step in 0..3 {
+            client.log(
+                json!({ "train/loss": 1.0 / (step + 1) as f64, "debug/norm": step, "note": "ok" }),
+                Some(step),
+                None,
+            );
+        }
+        client.flush().unwrap();
+    }
+    let client = client(&url);
+
+    let html = client
+        .render_report(
+            "p",
+            &["base", "fast<lr>"],
+            &ReportTemplate::html()
+                .title("LR & friends")
+                .metrics(["train/*", "note"]),
+        )
+        .unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h1>LR &amp; friends</h1>"));
+    assert!(html.contains("<th>fast&lt;lr&gt;</th>"));
+    assert!(html.contains("<tr><th>train/loss</th><td>0.3333</td><td>0.3333</td></tr>"));
+    assert!(html.contains("<tr><th>optimizer.name</th><td>adamw</td><td>adamw</td></tr>"));
+    assert!(html.contains("<tr><th>lr</th><td>0.001</td><td>0.003</td></tr>"));
+    assert!(!html.contains("debug/norm"));
+    assert_eq!(html.matches("<svg").count(), 1);
+
+    let markdown = client
+        .render_report("p", &["base"], &ReportTemplate::markdown().config(false))
+        .unwrap();
+    assert!(markdown.starts_with("# p\n"));
+    assert!(markdown.contains("## Final metrics"));
+    assert!(!markdown.contains("## Config"));
+    assert!(markdown.contains("| debug/norm | 2 |"));
+    assert_eq!(markdown.matches("(data:image/svg+xml;base64,").count(), 2);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+/// Serves a project `p` with a run `a` holding rows, config, metadata, an event and a
+/// local image, and a run `b` with rows only. Returns the server's URL.
+fn serve_project(dir: &Path) -> String {
+    let url = start(&dir.join("db"));
+    let a = client(&url)
+        .with_run("a")
+        .with_media_store(DirStore::new(dir.join("media")));
+    a.set_config(json!({ "lr": 1e-3 }));
+    a.log(json!({ "loss": 0.5 }), Some(0), None);
+    a.log_media("sample", Media::image(b"png bytes", "png"), Some(1))
+        .unwrap();
+    a.log_event(AlertLevel::Info, "checkpoint", json!({ "path": "a.pt" }));
+    a.flush().unwrap();
+    a.set_meta("dataset", json!("v2")).unwrap();
+    let b = client(&url).with_run("b");
+    for step in 0..3 {
+        b.log(json!({ "loss": 1.0 / (step + 1) as f64 }), Some(step), None);
+    }
+    b.flush().unwrap();
+    url
+}
+
+#[test]
+fn a_project_is_exported_to_one_database_file() {
+    let dir = common::temp_dir("server-export");
+    let url = serve_project(&dir);
+    let bundle = dir.join("p.trackio");
+
+    let stats = client(&url).export_project("p", &bundle).unwrap();
+    assert_eq!(
+        stats,
+        BundleStats {
+            runs: 2,
+            rows: 5,
+            events: 1,
+            media: 1
+        }
+    );
+    assert!(!bundle.with_extension("partial").exists());
+
+    std::fs::create_dir_all(dir.join("served")).unwrap();
+    std::fs::copy(&bundle, dir.join("served/p.db")).unwrap();
+    let copy = SqliteBackend::with_dir(dir.join("served"));
+    let source = SqliteBackend::with_dir(dir.join("db"));
+    for run in ["a", "b"] {
+        assert_eq!(copy.logs("p", run).unwrap(), source.logs("p", run).unwrap());
+    }
+    assert_eq!(copy.config("p", "a").unwrap().unwrap()["lr"], 1e-3);
+    assert_eq!(copy.run_meta("p", "a").unwrap()["dataset"], "v2");
+    assert_eq!(copy.events("p", "a").unwrap()[0].name, "checkpoint");
+    let embedded: Vec<u8> = rusqlite::Connection::open(&bundle)
+        .unwrap()
+        .query_row("SELECT data FROM bundle_media", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(embedded, b"png bytes");
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_bundle_is_replayed_under_a_new_project() {
+    let dir = common::temp_dir("server-import");
+    let bundle = dir.join("p.trackio");
+    client(&serve_project(&dir))
+        .export_project("p", &bundle)
+        .unwrap();
+    let url = start(&dir.join("target"));
+    let target = client(&url).with_media_store(DirStore::new(dir.join("target-media")));
+
+    let stats = target.import_project(&bundle, "copy").unwrap();
+    assert_eq!(
+        stats,
+        BundleStats {
+            runs: 2,
+            rows: 5,
+            events: 1,
+            media: 1
+        }
+    );
+
+    let source = SqliteBackend::with_dir(dir.join("db"));
+    let copy = SqliteBackend::with_dir(dir.join("target"));
+    assert_eq!(copy.runs("copy").unwrap(), ["a", "b"]);
+    assert_eq!(
+        copy.logs("copy", "b").unwrap(),
+        source.logs("p", "b").unwrap()
+    );
+    let logs = copy.logs("copy", "a").unwrap();
+    let steps: Vec<_> = logs.iter().map(|row| row["step"].clone()).collect();
+    assert_eq!(steps, [json!(0), json!(1)]);
+    assert_eq!(
+        logs[0]["timestamp"],
+        source.logs("p", "a").unwrap()[0]["timestamp"]
+    );
+    let url = logs[1]["sample"]["url"].as_str().unwrap();
+    let path = url.strip_prefix("file://").unwrap();
+    assert!(
+        Path::new(path).starts_with(dir.join("target-media").canonicalize().unwrap()),
+        "{url}"
+    );
+    assert_eq!(std::fs::read(path).unwrap(), b"png bytes");
+    assert_eq!(copy.config("copy", "a").unwrap().unwrap()["lr"], 1e-3);
+    assert_eq!(copy.run_meta("copy", "a").unwrap()["dataset"], "v2");
+    assert_eq!(copy.events("copy", "a").unwrap()[0].name, "checkpoint");
+
+    let err = target
+        .import_project(dir.join("db/p.db"), "copy")
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("not a trackio project bundle"),
+        "{err}"
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use std::thread;
+use std::time::Duration;
+use trackio::shm::{ShmDrainer, ShmProducer};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(&server.url())
+        .project("p")
+        .run("shm")
+        .max_retries(0)
+        .max_batch(4)
+        .build()
+}
+
+fn steps(server: &MockServer) -> Vec<Option<i64>> {
+    server.rows().iter().map(|row| row.step).collect()
+}
+
+#[test]
+fn rows_wrap_around_the_ring_in_order() {
+    let dir = common::temp_dir("shm-wrap");
+    let server = MockServer::start();
+    let mut producer = ShmProducer::create(dir.join("ring"), 96).unwrap();
+    let mut drainer = ShmDrainer::open(dir.join("ring"), client(&server)).unwrap();
+
+    assert!(producer.log(Some(0), &[("loss", 0.0)]));
+    assert!(producer.log(Some(1), &[("loss", 0.5)]));
+    assert!(!producer.log(Some(2), &[("loss", 1.0)]));
+    assert_eq!(producer.dropped(), 1);
+    assert_eq!(drainer.drain().unwrap(), 2);
+
+    assert!(producer.log(Some(3), &[("loss", 1.5)]));
+    assert!(producer.log(Some(4), &[("loss", 2.0)]));
+    assert!(!producer.log(Some(5), &[("loss", 2.5)]));
+    assert_eq!(drainer.drain().unwrap(), 2);
+    assert!(producer.log(None, &[("lr", 0.1)]));
+    assert_eq!(drainer.drain().unwrap(), 1);
+    assert_eq!(drainer.drain().unwrap(), 0);
+
+    assert_eq!(steps(&server), [Some(0), Some(1), Some(3), Some(4), None]);
+    server.assert_logged_value("loss", 4, 2.0);
+    assert_eq!(server.rows()[4].metrics["lr"], 0.1);
+    assert_eq!(drainer.dropped(), 2);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_failed_drain_can_be_requeued() {
+    let dir = common::temp_dir("shm-requeue");
+    let server = MockServer::start();
+    let mut producer = ShmProducer::create(dir.join("ring"), 4096).unwrap();
+    let mut drainer = ShmDrainer::open(dir.join("ring"), client(&server)).unwrap();
+
+    producer.log(Some(0), &[("loss", 1.0)]);
+    producer.log(Some(1), &[("loss", 0.5)]);
+    server.fail_next(1, 503);
+    let err = drainer.drain().unwrap_err();
+    assert!(err.is_retryable());
+    let batch = err.into_batch().unwrap();
+    assert_eq!(batch.items.len(), 2);
+    assert!(server.rows().is_empty());
+
+    drainer.requeue(batch);
+    assert_eq!(drainer.drain().unwrap(), 0);
+    assert_eq!(steps(&server), [Some(0), Some(1)]);
+
+    producer.log(Some(2), &[("loss", 0.25)]);
+    assert_eq!(drainer.drain().unwrap(), 1);
+    assert_eq!(steps(&server), [Some(0), Some(1), Some(2)]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn rows_beyond_a_batch_are_kept_while_the_server_is_down() {
+    let dir = common::temp_dir("shm-down");
+    let server = MockServer::start();
+    let mut producer = ShmProducer::create(dir.join("ring"), 4096).unwrap();
+    let mut drainer = ShmDrainer::open(dir.join("ring"), client(&server)).unwrap();
+
+    for step in 0..10 {
+        producer.log(Some(step), &[("loss", step as f64)]);
+    }
+    server.fail_next(3, 503);
+    for _ in 0..3 {
+        let batch = drainer.drain().unwrap_err().into_batch().unwrap();
+        assert_eq!(batch.items.len(), 10);
+        drainer.requeue(batch);
+    }
+    assert_eq!(server.requests().len(), 3);
+    assert!(server.rows().is_empty());
+
+    assert_eq!(drainer.drain().unwrap(), 0);
+    assert_eq!(steps(&server), (0..10).map(Some).collect::<Vec<_>>());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn run_retries_until_the_server_is_back_and_stops_on_a_refused_token() {
+    let dir = common::temp_dir("shm-run");
+    let server = MockServer::start();
+    let mut producer = ShmProducer::create(dir.join("ring"), 4096).unwrap();
+    let drainer = ShmDrainer::open(dir.join("ring"), client(&server)).unwrap();
+
+    server.fail_next(2, 503);
+    producer.log(Some(0), &[("loss", 1.0)]);
+    let handle = thread::spawn(move || drainer.run(Duration::from_millis(10)));
+    producer.log(Some(1), &[("loss", 0.5)]);
+    common::wait_for("the rows after two failures", || server.rows().len() == 2);
+    assert_eq!(steps(&server), [Some(0), Some(1)]);
+
+    server.fail_next(1, 401);
+    producer.log(Some(2), &[("loss", 0.25)]);
+    let err = handle.join().unwrap().unwrap_err();
+    assert!(!err.is_retryable());
+    std::fs::remove_dir_all(dir).unwrap();
+}
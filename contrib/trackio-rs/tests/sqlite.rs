@@ -0,0 +1,231 @@
+mod common;
+
+use rusqlite::{params, Connection};
+use serde_json::{json, Map, Value};
+use std::path::Path;
+use trackio::client::LogItem;
+use trackio::sqlite::SqliteBackend;
+
+const PYTHON_SCHEMA: &str = "
+    CREATE TABLE metrics (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        run_name TEXT NOT NULL,
+        step INTEGER NOT NULL,
+        metrics TEXT NOT NULL
+    );
+    CREATE TABLE configs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        run_id TEXT NOT NULL,
+        run_name TEXT NOT NULL,
+        config TEXT NOT NULL,
+        created_at TEXT NOT NULL,
+        UNIQUE(run_id)
+    );
+    CREATE TABLE project_metadata (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE INDEX idx_metrics_run_step ON metrics(run_id, step);
+    CREATE INDEX idx_configs_run_name ON configs(run_name);
+";
+
+const PYTHON_COLUMNS: &str = "
+    ALTER TABLE metrics ADD COLUMN log_id TEXT;
+    ALTER TABLE metrics ADD COLUMN space_id TEXT;
+    CREATE UNIQUE INDEX idx_metrics_log_id ON metrics(log_id) WHERE log_id IS NOT NULL;
+";
+
+fn python_db(dir: &Path, current: bool) -> Connection {
+    let conn = Connection::open(dir.join("p.db")).unwrap();
+    conn.execute_batch(PYTHON_SCHEMA).unwrap();
+    if current {
+        conn.execute_batch(PYTHON_COLUMNS).unwrap();
+    }
+    conn.execute(
+        "INSERT INTO metrics (run_id, timestamp, run_name, step, metrics) VALUES (?1, ?2, ?1, 0, ?3)",
+        params!["r", "2024-01-01T00:00:00", r#"{"loss": 1.0}"#],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO project_metadata (key, value) VALUES ('space_id', 'user/space')",
+        [],
+    )
+    .unwrap();
+    conn
+}
+
+fn item(metrics: Value, step: Option<i64>) -> LogItem {
+    LogItem {
+        metrics,
+        step,
+        timestamp: None,
+    }
+}
+
+fn python_logs(conn: &Connection) -> Vec<(String, i64, Value)> {
+    let mut stmt = conn
+        .prepare("SELECT run_name, step, metrics FROM metrics WHERE run_name = 'r' ORDER BY step")
+        .unwrap();
+    let rows = stmt
+        .query_map([], |row| {
+            let metrics: String = row.get(2)?;
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                serde_json::from_str(&metrics).unwrap(),
+            ))
+        })
+        .unwrap();
+    rows.collect::<Result<_, _>>().unwrap()
+}
+
+fn columns(conn: &Connection, table: &str) -> Vec<String> {
+    let mut stmt = conn
+        .prepare(&format!("SELECT name FROM pragma_table_info('{table}')"))
+        .unwrap();
+    let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+    rows.collect::<Result<_, _>>().unwrap()
+}
+
+#[test]
+fn rows_written_by_either_side_are_read_by_the_other() {
+    let dir = common::temp_dir("sqlite-python");
+    let conn = python_db(&dir, true);
+    let backend = SqliteBackend::with_dir(&dir);
+
+    let offsets = backend
+        .insert(
+            "p",
+            "r",
+            &[
+                item(json!({ "loss": 0.5 }), None),
+                item(json!({ "loss": 0.25 }), Some(5)),
+            ],
+        )
+        .unwrap();
+    assert!(offsets.iter().all(Option::is_some));
+    assert_eq!(backend.runs("p").unwrap(), ["r"]);
+    let logs = backend.logs("p", "r").unwrap();
+    assert_eq!(
+        logs.iter()
+            .map(|row| row["step"].clone())
+            .collect::<Vec<_>>(),
+        [0, 1, 5]
+    );
+    assert_eq!(logs[0]["loss"], 1.0);
+    assert_eq!(logs[0]["timestamp"], "2024-01-01T00:00:00");
+    assert_eq!(
+        python_logs(&conn),
+        [
+            ("r".into(), 0, json!({ "loss": 1.0 })),
+            ("r".into(), 1, json!({ "loss": 0.5 })),
+            ("r".into(), 5, json!({ "loss": 0.25 })),
+        ]
+    );
+
+    backend.set_config("p", "r", &json!({ "lr": 0.1 })).unwrap();
+    backend.set_config("p", "r", &json!({ "lr": 0.2 })).unwrap();
+    let config: String = conn
+        .query_row(
+            "SELECT config FROM configs WHERE run_name = 'r'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(
+        serde_json::from_str::<Value>(&config).unwrap(),
+        json!({ "lr": 0.2 })
+    );
+    assert_eq!(
+        backend.config("p", "r").unwrap(),
+        Some(json!({ "lr": 0.2 }))
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_database_from_before_log_id_gets_the_new_columns() {
+    let dir = common::temp_dir("sqlite-old");
+    let conn = python_db(&dir, false);
+    assert!(!columns(&conn, "metrics").contains(&"log_id".to_string()));
+
+    let backend = SqliteBackend::with_dir(&dir);
+    backend
+        .insert("p", "r", &[item(json!({ "loss": 0.5 }), None)])
+        .unwrap();
+    let metrics = columns(&conn, "metrics");
+    assert!(metrics.ends_with(&["log_id".to_string(), "space_id".to_string()]));
+    assert_eq!(python_logs(&conn).len(), 2);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn a_new_database_has_the_python_layout() {
+    let dir = common::temp_dir("sqlite-new");
+    let backend = SqliteBackend::with_dir(&dir);
+    backend
+        .insert("p", "r", &[item(json!({ "loss": 0.5 }), Some(0))])
+        .unwrap();
+    backend.set_config("p", "r", &json!({})).unwrap();
+
+    let conn = Connection::open(backend.db_path("p")).unwrap();
+    assert_eq!(
+        columns(&conn, "metrics"),
+        [
+            "id",
+            "run_id",
+            "timestamp",
+            "run_name",
+            "step",
+            "metrics",
+            "log_id",
+            "space_id"
+        ]
+    );
+    assert_eq!(
+        columns(&conn, "configs"),
+        ["id", "run_id", "run_name", "config", "created_at"]
+    );
+    assert_eq!(columns(&conn, "project_metadata"), ["key", "value"]);
+    assert_eq!(
+        python_logs(&conn),
+        [("r".into(), 0, json!({ "loss": 0.5 }))]
+    );
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn project_settings_share_the_python_metadata_table() {
+    let dir = common::temp_dir("sqlite-settings");
+    let conn = python_db(&dir, true);
+    let backend = SqliteBackend::with_dir(&dir);
+
+    let mut settings = Map::new();
+    settings.insert("description".into(), json!("a project"));
+    settings.insert("archived".into(), json!(true));
+    backend.update_project_settings("p", &settings).unwrap();
+
+    let read = backend.project_settings("p").unwrap();
+    assert_eq!(read["space_id"], "user/space");
+    assert_eq!(read["description"], "a project");
+    assert_eq!(read["archived"], true);
+    let space: String = conn
+        .query_row(
+            "SELECT value FROM project_metadata WHERE key = 'space_id'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(space, "user/space");
+
+    settings.clear();
+    settings.insert("archived".into(), Value::Null);
+    backend.update_project_settings("p", &settings).unwrap();
+    assert!(!backend
+        .project_settings("p")
+        .unwrap()
+        .contains_key("archived"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,74 @@
+mod common;
+
+use serde_json::{json, Value};
+use std::net::UdpSocket;
+use std::time::Duration;
+use trackio::statsd::StatsdListener;
+use trackio::testing::MockServer;
+use trackio::Client;
+
+#[test]
+fn packets_are_aggregated_into_one_row_per_interval() {
+    let server = MockServer::start();
+    let client = Client::builder()
+        .base_url(&server.url())
+        .project("p")
+        .run("r")
+        .max_retries(0)
+        .build();
+    let listener = StatsdListener::bind(client, "127.0.0.1:0")
+        .unwrap()
+        .with_flush_interval(Duration::from_millis(50))
+        .with_start_step(10);
+    let addr = listener.local_addr().unwrap();
+    let listener = listener.spawn();
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+    let packets = [
+        "jobs:1|c",
+        "jobs:2|c|@0.5",
+        "queue/depth:40|g",
+        "users:alice|s",
+        "users:bob|s",
+        "users:alice|s",
+        "step_time:20|ms",
+        "step_time:40|ms|#host:a,gpu:0",
+        "step_time:60|ms",
+        "not a packet",
+    ];
+    socket.send_to(packets.join("\n").as_bytes(), addr).unwrap();
+    common::wait_for("the first interval", || server.rows().len() == 1);
+    socket.send_to(b"queue/depth:+2|g", addr).unwrap();
+    common::wait_for("the second interval", || server.rows().len() == 2);
+    listener.stop().unwrap();
+
+    let rows: Vec<_> = server
+        .rows()
+        .into_iter()
+        .map(|row| (row.step, Value::Object(row.metrics)))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            (
+                Some(10),
+                json!({
+                    "jobs": 5.0,
+                    "queue/depth": 40.0,
+                    "users": 2.0,
+                    "step_time/count": 2.0,
+                    "step_time/sum": 80.0,
+                    "step_time/mean": 40.0,
+                    "step_time/min": 20.0,
+                    "step_time/max": 60.0,
+                    "step_time{gpu=0,host=a}/count": 1.0,
+                    "step_time{gpu=0,host=a}/sum": 40.0,
+                    "step_time{gpu=0,host=a}/mean": 40.0,
+                    "step_time{gpu=0,host=a}/min": 40.0,
+                    "step_time{gpu=0,host=a}/max": 40.0,
+                })
+            ),
+            (Some(11), json!({ "queue/depth": 42.0 })),
+        ]
+    );
+}
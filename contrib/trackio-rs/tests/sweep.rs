@@ -0,0 +1,156 @@
+use serde_json::{json, Value};
+use trackio::client::TrackioError;
+use trackio::sweep::{Goal, Method, SearchSpace, Sweep};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+fn client(server: &MockServer) -> Client {
+    Client::builder()
+        .base_url(&server.url())
+        .project("p")
+        .max_retries(0)
+        .build()
+}
+
+#[test]
+fn a_grid_sweep_logs_every_combination_as_a_grouped_run() {
+    let server = MockServer::start();
+    let space = SearchSpace::new()
+        .choice("act", ["relu", "gelu"])
+        .int("layers", 1, 2);
+    let result = Sweep::new("grid", space)
+        .with_method(Method::Grid)
+        .minimize("val_loss")
+        .run(&client(&server), |trial| {
+            let layers = trial.param("layers").and_then(Value::as_i64).unwrap();
+            let gelu = trial.param("act") == Some(&json!("gelu"));
+            let loss = 1.0 / layers as f64 - if gelu { 0.1 } else { 0.0 };
+            trial.log(json!({ "val_loss": loss + 1.0 }), Some(0));
+            trial.log(json!({ "val_loss": loss }), Some(1));
+            Ok(())
+        })
+        .unwrap();
+
+    assert_eq!(result.goal, Goal::Minimize);
+    assert_eq!(result.trials.len(), 4);
+    let combos: Vec<_> = result
+        .trials
+        .iter()
+        .map(|t| {
+            (
+                t.run.as_str(),
+                t.params["act"].clone(),
+                t.params["layers"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        combos,
+        [
+            ("grid-0", json!("relu"), json!(1)),
+            ("grid-1", json!("relu"), json!(2)),
+            ("grid-2", json!("gelu"), json!(1)),
+            ("grid-3", json!("gelu"), json!(2)),
+        ]
+    );
+    let best = result.best().unwrap();
+    assert_eq!(best.run, "grid-3");
+    assert_eq!(best.objective, Some(0.4));
+
+    assert_eq!(server.rows().len(), 8);
+    let config = server.config("grid-3").unwrap();
+    assert_eq!(config["act"], "gelu");
+    assert_eq!(config["layers"], 2);
+    assert_eq!(config["_SweepId"], "grid");
+    assert_eq!(config["_SweepTrial"], 3);
+    assert_eq!(config["_Group"], "grid");
+}
+
+#[test]
+fn a_failed_trial_is_recorded_and_the_sweep_goes_on() {
+    let server = MockServer::start();
+    let space = SearchSpace::new()
+        .uniform("dropout", 0.0, 0.5)
+        .log_uniform("lr", 1e-5, 1e-1);
+    let sweep = Sweep::new("random", space)
+        .maximize("acc")
+        .with_max_trials(6)
+        .with_seed(7);
+    let result = sweep
+        .run(&client(&server), |trial| {
+            if trial.number == 2 {
+                return Err(TrackioError::InvalidArgument("diverged".into()));
+            }
+            trial.log(json!({ "acc": trial.number as f64 / 10.0 }), None);
+            Ok(())
+        })
+        .unwrap();
+
+    assert_eq!(result.trials.len(), 6);
+    assert!(result.trials[2]
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("diverged"));
+    assert_eq!(result.trials[2].objective, None);
+    assert_eq!(result.best().unwrap().number, 5);
+    for trial in &result.trials {
+        let dropout = trial.params["dropout"].as_f64().unwrap();
+        let lr = trial.params["lr"].as_f64().unwrap();
+        assert!((0.0..=0.5).contains(&dropout), "{dropout}");
+        assert!((1e-5..=1e-1).contains(&lr), "{lr}");
+    }
+
+    let again = sweep
+        .run(&client(&MockServer::start()), |_| Ok(()))
+        .unwrap();
+    let params = |r: &trackio::sweep::SweepResult| {
+        r.trials
+            .iter()
+            .map(|t| t.params.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(params(&again), params(&result));
+}
+
+#[test]
+fn tpe_proposals_stay_in_the_search_space() {
+    let server = MockServer::start();
+    let space = SearchSpace::new()
+        .uniform("x", -2.0, 2.0)
+        .choice("opt", ["sgd", "adam"])
+        .int("width", 8, 16);
+    let result = Sweep::new("tpe", space)
+        .with_method(Method::Tpe)
+        .with_max_trials(20)
+        .with_seed(1)
+        .run(&client(&server), |trial| {
+            let x = trial.param("x").and_then(Value::as_f64).unwrap();
+            trial.log(json!({ "loss": (x - 1.0).powi(2) }), None);
+            Ok(())
+        })
+        .unwrap();
+
+    assert_eq!(result.trials.len(), 20);
+    for trial in &result.trials {
+        assert!((-2.0..=2.0).contains(&trial.params["x"].as_f64().unwrap()));
+        assert!(["sgd", "adam"].contains(&trial.params["opt"].as_str().unwrap()));
+        assert!((8..=16).contains(&trial.params["width"].as_i64().unwrap()));
+    }
+    assert!(result.best().unwrap().objective.unwrap() < 0.5);
+}
+
+#[test]
+fn sweeps_check_their_settings_before_any_trial() {
+    let server = MockServer::start();
+    let err = Sweep::new("s", SearchSpace::new().uniform("x", 0.0, 1.0))
+        .run(&client(&server), |_| Ok(()))
+        .unwrap_err();
+    assert!(matches!(err, TrackioError::InvalidArgument(_)));
+    let err = Sweep::new("s", SearchSpace::new().uniform("x", 0.0, 1.0))
+        .with_method(Method::Grid)
+        .run(&client(&server), |_| Ok(()))
+        .unwrap_err();
+    assert!(err.to_string().contains("continuous"));
+    assert!(server.requests().is_empty());
+}
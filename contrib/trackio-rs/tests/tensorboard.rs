@@ -0,0 +1,168 @@
+mod common;
+
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use trackio::import::tensorboard::{read_scalars, Scalar};
+use trackio::testing::MockServer;
+use trackio::Client;
+
+fn varint(mut v: u64, out: &mut Vec<u8>) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn bytes(field: u64, b: &[u8], out: &mut Vec<u8>) {
+    varint(field << 3 | 2, out);
+    varint(b.len() as u64, out);
+    out.extend_from_slice(b);
+}
+
+/// A summary value with a legacy `simple_value`.
+fn simple(tag: &str, value: f32) -> Vec<u8> {
+    let mut out = Vec::new();
+    bytes(1, tag.as_bytes(), &mut out);
+    varint(2 << 3 | 5, &mut out);
+    out.extend_from_slice(&value.to_le_bytes());
+    out
+}
+
+/// A summary value holding a tensor of `dtype` with `content`, tagged with `plugin`.
+fn tensor(tag: &str, dtype: u64, content: &[u8], plugin: &str) -> Vec<u8> {
+    let mut tensor = Vec::new();
+    varint(1 << 3, &mut tensor);
+    varint(dtype, &mut tensor);
+    bytes(4, content, &mut tensor);
+    let mut plugin_data = Vec::new();
+    bytes(1, plugin.as_bytes(), &mut plugin_data);
+    let mut metadata = Vec::new();
+    bytes(1, &plugin_data, &mut metadata);
+    let mut out = Vec::new();
+    bytes(1, tag.as_bytes(), &mut out);
+    bytes(8, &tensor, &mut out);
+    bytes(9, &metadata, &mut out);
+    out
+}
+
+fn event(wall_time: f64, step: u64, values: &[Vec<u8>]) -> Vec<u8> {
+    let mut summary = Vec::new();
+    for value in values {
+        bytes(1, value, &mut summary);
+    }
+    let mut out = Vec::new();
+    varint(1 << 3 | 1, &mut out);
+    out.extend_from_slice(&wall_time.to_bits().to_le_bytes());
+    varint(2 << 3, &mut out);
+    varint(step, &mut out);
+    bytes(5, &summary, &mut out);
+    out
+}
+
+/// Frames `events` as TFRecords, with zeroed checksums the reader doesn't check.
+fn write_events(path: &Path, events: &[Vec<u8>]) {
+    let mut out = Vec::new();
+    for event in events {
+        out.extend_from_slice(&(event.len() as u64).to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(event);
+        out.extend_from_slice(&[0; 4]);
+    }
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, out).unwrap();
+}
+
+#[test]
+fn scalars_are_read_from_both_summary_forms() {
+    let dir = common::temp_dir("tensorboard-read");
+    let path = dir.join("events.out.tfevents.1");
+    write_events(
+        &path,
+        &[
+            event(1.5, 0, &[simple("loss", 0.1)]),
+            event(
+                2.5,
+                1,
+                &[
+                    tensor("acc", 2, &0.75f64.to_le_bytes(), "scalars"),
+                    tensor("steps", 9, &7i64.to_le_bytes(), "scalars"),
+                    tensor("sample", 1, &[0; 4], "images"),
+                ],
+            ),
+        ],
+    );
+    let mut data = fs::read(&path).unwrap();
+    data.extend_from_slice(&100u64.to_le_bytes());
+    data.extend_from_slice(&[0; 6]);
+    fs::write(&path, data).unwrap();
+
+    let scalar = |tag: &str, step, wall_time, value| Scalar {
+        tag: tag.into(),
+        step,
+        wall_time,
+        value,
+    };
+    assert_eq!(
+        read_scalars(&path).unwrap(),
+        [
+            scalar("loss", 0, 1.5, 0.1),
+            scalar("acc", 1, 2.5, 0.75),
+            scalar("steps", 1, 2.5, 7.0),
+        ]
+    );
+}
+
+#[test]
+fn each_event_directory_is_imported_as_a_run() {
+    let dir = common::temp_dir("tensorboard-import");
+    write_events(
+        &dir.join("train/events.out.tfevents.1"),
+        &[
+            event(0.0, 0, &[simple("loss", 0.5)]),
+            event(1.0, 1, &[simple("loss", 0.25)]),
+        ],
+    );
+    write_events(
+        &dir.join("train/events.out.tfevents.2"),
+        &[event(2.0, 1, &[simple("acc", 0.5)])],
+    );
+    write_events(
+        &dir.join("eval/events.out.tfevents.1"),
+        &[event(3.0, 1, &[simple("acc", 0.75)])],
+    );
+    fs::write(dir.join("train/notes.txt"), "not events").unwrap();
+    let server = MockServer::start();
+    let client = Client::builder()
+        .base_url(&server.url())
+        .max_retries(0)
+        .build();
+
+    assert_eq!(client.import_tensorboard("tb", &dir).unwrap(), 3);
+
+    let rows: Vec<_> = server
+        .rows()
+        .into_iter()
+        .map(|row| (row.project, row.run, row.step, Value::Object(row.metrics)))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("tb".into(), "eval".into(), Some(1), json!({ "acc": 0.75 })),
+            ("tb".into(), "train".into(), Some(0), json!({ "loss": 0.5 })),
+            (
+                "tb".into(),
+                "train".into(),
+                Some(1),
+                json!({ "loss": 0.25, "acc": 0.5 })
+            ),
+        ]
+    );
+    let eval = server
+        .rows()
+        .into_iter()
+        .find(|row| row.run == "eval")
+        .unwrap();
+    assert!(eval.timestamp.unwrap().starts_with("1970-01-01T00:00:03"));
+}
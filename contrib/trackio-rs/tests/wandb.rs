@@ -0,0 +1,101 @@
+mod common;
+
+use serde_json::{json, Value};
+use std::fs;
+use trackio::client::TrackioError;
+use trackio::import::wandb::read_export_dir;
+use trackio::testing::MockServer;
+use trackio::Client;
+
+fn write(path: &std::path::Path, contents: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn exported_runs_are_replayed_with_their_steps_and_config() {
+    let dir = common::temp_dir("wandb");
+    let local = dir.join("run-20240101_120000-abc123/files");
+    write(
+        &local.join("wandb-history.jsonl"),
+        concat!(
+            r#"{"_step": 0, "_timestamp": 1704110400.0, "_runtime": 1, "loss": 0.5, "lr": 0.001}"#,
+            "\n",
+            r#"{"_step": 1, "_timestamp": 1704110401.5, "loss": 0.25, "samples": {"_type": "table"}}"#,
+            "\n",
+            r#"{"_step": 2, "_wandb": {"runtime": 3}, "grad": null}"#,
+            "\nnot json\n",
+        ),
+    );
+    write(
+        &local.join("config.yaml"),
+        "wandb_version: 1\n_wandb:\n  value:\n    cli_version: 0.16.0\nlr:\n  desc: null\n  value: 0.001\noptimizer:\n  value: adamw\n",
+    );
+    let exported = dir.join("sweep-7");
+    write(
+        &exported.join("history.json"),
+        &json!([{ "_step": 5, "acc": 0.75 }]).to_string(),
+    );
+    write(
+        &exported.join("config.json"),
+        &json!({ "batch_size": { "value": 64 }, "_wandb": { "value": {} } }).to_string(),
+    );
+    write(
+        &exported.join("metadata.json"),
+        r#"{"displayName": "bright-sun-7"}"#,
+    );
+
+    let runs = read_export_dir(&dir).unwrap();
+    let names: Vec<_> = runs.iter().map(|run| run.name.as_str()).collect();
+    assert_eq!(names, ["abc123", "bright-sun-7"]);
+
+    let server = MockServer::start();
+    let client = Client::builder()
+        .base_url(&server.url())
+        .max_retries(0)
+        .build();
+    assert_eq!(
+        client
+            .import_wandb("imported", dir.to_str().unwrap())
+            .unwrap(),
+        3
+    );
+
+    let rows: Vec<_> = server
+        .rows()
+        .into_iter()
+        .map(|row| (row.run, row.step, Value::Object(row.metrics)))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            (
+                "abc123".into(),
+                Some(0),
+                json!({ "loss": 0.5, "lr": 0.001 })
+            ),
+            ("abc123".into(), Some(1), json!({ "loss": 0.25 })),
+            ("bright-sun-7".into(), Some(5), json!({ "acc": 0.75 })),
+        ]
+    );
+    assert!(server.rows()[1]
+        .timestamp
+        .as_deref()
+        .unwrap()
+        .starts_with("2024-01-01T12:00:01"));
+    let config = server.config("abc123").unwrap();
+    assert_eq!(config["lr"], 0.001);
+    assert_eq!(config["optimizer"], "adamw");
+    assert!(config.get("_wandb").is_none());
+    assert_eq!(server.config("bright-sun-7").unwrap()["batch_size"], 64);
+}
+
+#[test]
+fn a_source_that_is_neither_a_directory_nor_a_wandb_path_is_refused() {
+    let client = Client::builder()
+        .base_url("http://127.0.0.1:9")
+        .max_retries(0)
+        .build();
+    let err = client.import_wandb("p", "not/a/wandb/path").unwrap_err();
+    assert!(matches!(err, TrackioError::Import(_)), "{err}");
+}
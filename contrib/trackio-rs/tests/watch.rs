@@ -0,0 +1,62 @@
+use serde_json::json;
+use std::thread;
+use std::time::Duration;
+use trackio::testing::MockServer;
+use trackio::watch::WatchedPoint;
+use trackio::Client;
+
+#[test]
+fn watching_yields_the_points_logged_after_it_started() {
+    let server = MockServer::start();
+    server.respond("get_run_summary", json!({ "last_step": 1 }));
+    server.respond(
+        "get_snapshot",
+        json!({
+            "loss": [
+                { "step": 1, "value": 0.9 },
+                { "step": 2, "value": 0.5, "timestamp": "2024-01-01T00:00:02" },
+                { "step": 3, "value": 0.25 },
+            ],
+            "acc": [{ "step": 3, "value": 0.75 }, { "step": 2, "value": null }],
+        }),
+    );
+    let client = Client::builder()
+        .base_url(&server.url())
+        .max_retries(0)
+        .build();
+    let mut watch = client
+        .watch("p", "r")
+        .with_poll_interval(Duration::from_millis(10));
+
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(100));
+            server.respond("get_run_summary", json!({ "last_step": 3 }));
+        });
+        let points: Vec<WatchedPoint> = watch.by_ref().take(3).map(Result::unwrap).collect();
+        let seen: Vec<_> = points
+            .iter()
+            .map(|p| (p.metric.as_str(), p.point.step, p.point.value.clone()))
+            .collect();
+        assert_eq!(
+            seen,
+            [
+                ("loss", 2, json!(0.5)),
+                ("acc", 3, json!(0.75)),
+                ("loss", 3, json!(0.25)),
+            ]
+        );
+        assert_eq!(
+            points[0].point.timestamp.as_deref(),
+            Some("2024-01-01T00:00:02")
+        );
+    });
+
+    let snapshot = server
+        .requests()
+        .into_iter()
+        .find(|request| request.path == "/api/get_snapshot")
+        .unwrap();
+    assert_eq!(snapshot.body["around_step"], 2);
+    assert_eq!(snapshot.body["window"], 1);
+}